@@ -0,0 +1,186 @@
+use crate::encryption::{StorageKey, StoredContent, ENCRYPTION_RECORD_ADDRESS};
+use holochain_core_types::{
+    cas::{
+        content::{verify_content, Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    error::HolochainError,
+    json::{JsonString, RawString},
+};
+use std::sync::{Arc, RwLock};
+
+use uuid::Uuid;
+
+/// Wraps any content addressable storage and encrypts all content before it gets stored.
+/// Content stays stored under its plain text address (it has to be found by it),
+/// but the wrapped storage only ever sees a JSON string of nonce and cipher text.
+/// The address is used as additional authenticated data, so encrypted content can not
+/// be moved to another address unnoticed.
+#[derive(Clone, Debug)]
+pub struct EncryptedStorage {
+    storage: Arc<RwLock<ContentAddressableStorage>>,
+    key: StorageKey,
+    verify_on_read: bool,
+}
+
+impl PartialEq for EncryptedStorage {
+    fn eq(&self, other: &EncryptedStorage) -> bool {
+        self.get_id() == other.get_id()
+    }
+}
+
+impl EncryptedStorage {
+    pub fn new(
+        storage: Arc<RwLock<ContentAddressableStorage>>,
+        key: StorageKey,
+    ) -> EncryptedStorage {
+        EncryptedStorage {
+            storage,
+            key,
+            verify_on_read: false,
+        }
+    }
+
+    /// Makes fetch() check that decrypted content hashes to the requested address.
+    /// The wrapped storage can't do that itself since it only sees encrypted content.
+    pub fn with_verify_on_read(mut self) -> EncryptedStorage {
+        self.verify_on_read = true;
+        self
+    }
+}
+
+impl ContentAddressableStorage for EncryptedStorage {
+    fn add(&mut self, content: &AddressableContent) -> Result<(), HolochainError> {
+        let address = content.address();
+        let encrypted = self.key.encrypt(
+            content.content().to_string().as_bytes(),
+            address.to_string().as_bytes(),
+        )?;
+        self.storage.write()?.add(&StoredContent::new(
+            address,
+            JsonString::from(RawString::from(encrypted)),
+        ))
+    }
+
+    fn contains(&self, address: &Address) -> Result<bool, HolochainError> {
+        self.storage.read()?.contains(address)
+    }
+
+    fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
+        let encrypted = match self.storage.read()?.fetch(address)? {
+            Some(encrypted) => serde_json::from_str::<String>(&String::from(encrypted))?,
+            None => return Ok(None),
+        };
+        let decrypted = self
+            .key
+            .decrypt(&encrypted, address.to_string().as_bytes())?;
+        let content = Content::from(
+            String::from_utf8(decrypted)
+                .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))?,
+        );
+        if self.verify_on_read {
+            verify_content(address, &content)?;
+        }
+        Ok(Some(content))
+    }
+
+    fn addresses(&self) -> Result<Vec<Address>, HolochainError> {
+        let record_address = Address::from(ENCRYPTION_RECORD_ADDRESS);
+        Ok(self
+            .storage
+            .read()?
+            .addresses()?
+            .into_iter()
+            .filter(|address| *address != record_address)
+            .collect())
+    }
+
+    fn get_id(&self) -> Uuid {
+        self.storage
+            .read()
+            .map(|storage| storage.get_id())
+            .unwrap_or_else(|_| Uuid::nil())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use crate::{
+        cas::{encrypted::EncryptedStorage, file::FilesystemStorage, memory::MemoryStorage},
+        encryption::tests::test_storage_key,
+    };
+    use holochain_core_types::{
+        cas::{
+            content::{
+                AddressableContent, ExampleAddressableContent, OtherExampleAddressableContent,
+            },
+            storage::{ContentAddressableStorage, StorageTestSuite},
+        },
+        json::RawString,
+    };
+    use std::{
+        fs::read_to_string,
+        sync::{Arc, RwLock},
+    };
+
+    pub fn test_encrypted_cas() -> EncryptedStorage {
+        let mut memory = MemoryStorage::new();
+        let key = test_storage_key(&mut memory);
+        EncryptedStorage::new(Arc::new(RwLock::new(memory)), key)
+    }
+
+    #[test]
+    /// show that content of different types can round trip through the same storage
+    fn encrypted_content_round_trip_test() {
+        let test_suite = StorageTestSuite::new(test_encrypted_cas());
+        test_suite.round_trip_test::<ExampleAddressableContent, OtherExampleAddressableContent>(
+            RawString::from("foo").into(),
+            RawString::from("bar").into(),
+        );
+    }
+
+    #[test]
+    /// show that no plain text content ends up on disk
+    fn encrypted_file_content_test() {
+        let dir = tempdir().unwrap();
+        let mut file_storage = FilesystemStorage::new(dir.path()).unwrap();
+        let key = test_storage_key(&mut file_storage);
+        let mut cas = EncryptedStorage::new(Arc::new(RwLock::new(file_storage)), key);
+        let content =
+            ExampleAddressableContent::try_from_content(&RawString::from("private").into())
+                .unwrap();
+        cas.add(&content).unwrap();
+
+        let path = dir
+            .path()
+            .join(content.address().to_string())
+            .with_extension("txt");
+        assert!(!read_to_string(path).unwrap().contains("private"));
+        assert_eq!(
+            cas.fetch(&content.address()).unwrap(),
+            Some(content.content())
+        );
+    }
+
+    #[test]
+    fn encrypted_content_can_not_be_moved() {
+        let mut memory = MemoryStorage::new();
+        let key = test_storage_key(&mut memory);
+        let memory = Arc::new(RwLock::new(memory));
+        let mut cas = EncryptedStorage::new(memory.clone(), key).with_verify_on_read();
+        let foo =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let bar =
+            ExampleAddressableContent::try_from_content(&RawString::from("bar").into()).unwrap();
+        cas.add(&foo).unwrap();
+
+        // store the encrypted foo under the address of bar
+        let encrypted_foo = memory.read().unwrap().fetch(&foo.address()).unwrap();
+        let moved = crate::encryption::StoredContent::new(bar.address(), encrypted_foo.unwrap());
+        memory.write().unwrap().add(&moved).unwrap();
+        assert!(cas.fetch(&bar.address()).is_err());
+    }
+}
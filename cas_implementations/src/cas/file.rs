@@ -1,12 +1,13 @@
 use holochain_core_types::{
     cas::{
-        content::{Address, AddressableContent, Content},
+        content::{verify_content, Address, AddressableContent, Content},
         storage::ContentAddressableStorage,
     },
     error::HolochainError,
 };
 use std::{
-    fs::{create_dir_all, read_to_string, write},
+    ffi::OsStr,
+    fs::{create_dir_all, read_dir, read_to_string, write},
     path::{Path, PathBuf},
     sync::{Arc, RwLock},
 };
@@ -19,6 +20,7 @@ pub struct FilesystemStorage {
     dir_path: PathBuf,
     id: Uuid,
     lock: Arc<RwLock<()>>,
+    verify_on_read: bool,
 }
 
 impl PartialEq for FilesystemStorage {
@@ -35,9 +37,17 @@ impl FilesystemStorage {
             dir_path,
             id: Uuid::new_v4(),
             lock: Arc::new(RwLock::new(())),
+            verify_on_read: false,
         })
     }
 
+    /// Makes fetch() check that content read from disk hashes to the requested address
+    /// and fail instead of returning corrupted or tampered content.
+    pub fn with_verify_on_read(mut self) -> FilesystemStorage {
+        self.verify_on_read = true;
+        self
+    }
+
     /// builds an absolute path for an AddressableContent address
     fn address_to_path(&self, address: &Address) -> PathBuf {
         // using .txt extension because content is arbitrary and controlled by the
@@ -71,12 +81,33 @@ impl ContentAddressableStorage for FilesystemStorage {
     fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
         let _guard = self.lock.read()?;
         if self.contains(&address)? {
-            Ok(Some(read_to_string(self.address_to_path(address))?.into()))
+            let content = read_to_string(self.address_to_path(address))?.into();
+            if self.verify_on_read {
+                verify_content(address, &content)?;
+            }
+            Ok(Some(content))
         } else {
             Ok(None)
         }
     }
 
+    fn addresses(&self) -> Result<Vec<Address>, HolochainError> {
+        let _guard = self.lock.read()?;
+        if !self.dir_path.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut addresses = Vec::new();
+        for entry in read_dir(&self.dir_path)? {
+            let path = entry?.path();
+            if path.is_file() && path.extension() == Some(OsStr::new("txt")) {
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    addresses.push(Address::from(stem));
+                }
+            }
+        }
+        Ok(addresses)
+    }
+
     fn get_id(&self) -> Uuid {
         self.id
     }
@@ -91,11 +122,14 @@ pub mod tests {
     use crate::cas::file::FilesystemStorage;
     use holochain_core_types::{
         cas::{
-            content::{ExampleAddressableContent, OtherExampleAddressableContent},
-            storage::StorageTestSuite,
+            content::{
+                AddressableContent, ExampleAddressableContent, OtherExampleAddressableContent,
+            },
+            storage::{ContentAddressableStorage, StorageTestSuite},
         },
         json::RawString,
     };
+    use std::fs::write;
 
     pub fn test_file_cas() -> (FilesystemStorage, TempDir) {
         let dir = tempdir().expect("Could not create a tempdir for CAS testing");
@@ -114,4 +148,20 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// show that tampered files only get noticed with verify on read
+    fn file_verify_on_read_test() {
+        let (mut cas, dir) = test_file_cas();
+        let content =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        cas.add(&content).unwrap();
+        write(cas.address_to_path(&content.address()), "\"bar\"").unwrap();
+        assert!(cas.fetch(&content.address()).unwrap().is_some());
+
+        let cas = FilesystemStorage::new(dir.path())
+            .unwrap()
+            .with_verify_on_read();
+        assert!(cas.fetch(&content.address()).is_err());
+    }
+
 }
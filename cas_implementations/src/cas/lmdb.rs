@@ -0,0 +1,237 @@
+use holochain_core_types::{
+    cas::{
+        content::{verify_content, Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    error::HolochainError,
+};
+use lmdb::{Cursor, Database, Environment, Error as LmdbError, Transaction, WriteFlags};
+use std::{
+    fs::create_dir_all,
+    path::Path,
+    str::from_utf8,
+    sync::{Arc, RwLock},
+};
+
+use uuid::Uuid;
+
+/// Default for the maximum size the database files can grow to.
+/// LMDB maps the whole file into memory, so this only reserves address space.
+pub const DEFAULT_LMDB_MAP_SIZE: usize = 1024 * 1024 * 1024;
+
+/// Maximum number of named databases an environment can hold besides its main database.
+const LMDB_MAX_DBS: u32 = 8;
+
+/// Opens (and creates if needed) an LMDB environment in the given directory,
+/// whose database files can grow to `map_size` bytes.
+/// LMDB environments must not be opened more than once per process,
+/// so every storage needs its own directory.
+pub fn open_lmdb_environment<P: AsRef<Path>>(
+    dir_path: P,
+    map_size: usize,
+) -> Result<(Environment, Database), HolochainError> {
+    create_dir_all(dir_path.as_ref())?;
+    let environment = Environment::new()
+        .set_map_size(map_size)
+        .set_max_dbs(LMDB_MAX_DBS)
+        .open(dir_path.as_ref())
+        .map_err(lmdb_error)?;
+    let database = environment.open_db(None).map_err(lmdb_error)?;
+    Ok((environment, database))
+}
+
+pub fn lmdb_error(error: LmdbError) -> HolochainError {
+    HolochainError::ErrorGeneric(format!("LMDB error: {}", error))
+}
+
+/// Content addressable storage backed by an LMDB database.
+/// Content gets stored as string under its address as key.
+#[derive(Clone, Debug)]
+pub struct LmdbStorage {
+    environment: Arc<Environment>,
+    database: Database,
+    id: Uuid,
+    lock: Arc<RwLock<()>>,
+    verify_on_read: bool,
+}
+
+impl PartialEq for LmdbStorage {
+    fn eq(&self, other: &LmdbStorage) -> bool {
+        self.id == other.id
+    }
+}
+
+impl LmdbStorage {
+    pub fn new<P: AsRef<Path>>(dir_path: P) -> Result<LmdbStorage, HolochainError> {
+        LmdbStorage::new_with_map_size(dir_path, DEFAULT_LMDB_MAP_SIZE)
+    }
+
+    /// Opens the storage with database files that can grow to `map_size` bytes.
+    pub fn new_with_map_size<P: AsRef<Path>>(
+        dir_path: P,
+        map_size: usize,
+    ) -> Result<LmdbStorage, HolochainError> {
+        let (environment, database) = open_lmdb_environment(dir_path, map_size)?;
+
+        Ok(LmdbStorage {
+            environment: Arc::new(environment),
+            database,
+            id: Uuid::new_v4(),
+            lock: Arc::new(RwLock::new(())),
+            verify_on_read: false,
+        })
+    }
+
+    /// Makes fetch() check that content read from the database hashes to the requested address
+    /// and fail instead of returning corrupted content.
+    pub fn with_verify_on_read(mut self) -> LmdbStorage {
+        self.verify_on_read = true;
+        self
+    }
+}
+
+impl ContentAddressableStorage for LmdbStorage {
+    fn add(&mut self, content: &AddressableContent) -> Result<(), HolochainError> {
+        let _guard = self.lock.write()?;
+        let mut transaction = self.environment.begin_rw_txn().map_err(lmdb_error)?;
+        transaction
+            .put(
+                self.database,
+                &content.address().to_string(),
+                &content.content().to_string(),
+                WriteFlags::empty(),
+            )
+            .map_err(lmdb_error)?;
+        transaction.commit().map_err(lmdb_error)
+    }
+
+    fn contains(&self, address: &Address) -> Result<bool, HolochainError> {
+        Ok(self.fetch(address)?.is_some())
+    }
+
+    fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
+        let _guard = self.lock.read()?;
+        let transaction = self.environment.begin_ro_txn().map_err(lmdb_error)?;
+        let content: Content = match transaction.get(self.database, &address.to_string()) {
+            Ok(bytes) => from_utf8(bytes)
+                .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))?
+                .to_string()
+                .into(),
+            Err(LmdbError::NotFound) => return Ok(None),
+            Err(error) => return Err(lmdb_error(error)),
+        };
+        if self.verify_on_read {
+            verify_content(address, &content)?;
+        }
+        Ok(Some(content))
+    }
+
+    fn addresses(&self) -> Result<Vec<Address>, HolochainError> {
+        let _guard = self.lock.read()?;
+        let transaction = self.environment.begin_ro_txn().map_err(lmdb_error)?;
+        let mut cursor = transaction
+            .open_ro_cursor(self.database)
+            .map_err(lmdb_error)?;
+        let addresses = cursor
+            .iter_start()
+            .map(|(key, _)| {
+                from_utf8(key)
+                    .map(Address::from)
+                    .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))
+            })
+            .collect();
+        addresses
+    }
+
+    fn get_id(&self) -> Uuid {
+        self.id
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::{tempdir, TempDir};
+    use crate::cas::lmdb::LmdbStorage;
+    use holochain_core_types::{
+        cas::{
+            content::{
+                AddressableContent, ExampleAddressableContent, OtherExampleAddressableContent,
+            },
+            storage::{ContentAddressableStorage, StorageTestSuite},
+        },
+        json::RawString,
+    };
+    use lmdb::{Transaction, WriteFlags};
+
+    pub fn test_lmdb_cas() -> (LmdbStorage, TempDir) {
+        let dir = tempdir().expect("Could not create a tempdir for CAS testing");
+        (LmdbStorage::new(dir.path()).unwrap(), dir)
+    }
+
+    #[test]
+    /// show that content of different types can round trip through the same storage
+    fn lmdb_content_round_trip_test() {
+        let (cas, _dir) = test_lmdb_cas();
+        let test_suite = StorageTestSuite::new(cas);
+        test_suite.round_trip_test::<ExampleAddressableContent, OtherExampleAddressableContent>(
+            RawString::from("foo").into(),
+            RawString::from("bar").into(),
+        );
+    }
+
+    #[test]
+    /// show that content survives reopening the database
+    fn lmdb_content_persists_test() {
+        let dir = tempdir().unwrap();
+        let content =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        {
+            let mut cas = LmdbStorage::new(dir.path()).unwrap();
+            cas.add(&content).unwrap();
+        }
+        let cas = LmdbStorage::new(dir.path()).unwrap();
+        assert_eq!(
+            cas.fetch(&content.address()).unwrap(),
+            Some(content.content())
+        );
+    }
+
+    #[test]
+    /// show that the database can not grow beyond its map size
+    fn lmdb_map_size_test() {
+        let dir = tempdir().unwrap();
+        let mut cas = LmdbStorage::new_with_map_size(dir.path(), 64 * 1024).unwrap();
+        let content = ExampleAddressableContent::try_from_content(
+            &RawString::from("x".repeat(1024 * 1024)).into(),
+        )
+        .unwrap();
+        assert!(cas.add(&content).is_err());
+    }
+
+    #[test]
+    fn lmdb_verify_on_read_test() {
+        let (cas, _dir) = test_lmdb_cas();
+        let mut cas = cas.with_verify_on_read();
+        let foo =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let bar =
+            ExampleAddressableContent::try_from_content(&RawString::from("bar").into()).unwrap();
+        cas.add(&foo).unwrap();
+        assert_eq!(cas.fetch(&foo.address()).unwrap(), Some(foo.content()));
+
+        // store foo under the address of bar
+        let mut transaction = cas.environment.begin_rw_txn().unwrap();
+        transaction
+            .put(
+                cas.database,
+                &bar.address().to_string(),
+                &foo.content().to_string(),
+                WriteFlags::empty(),
+            )
+            .unwrap();
+        transaction.commit().unwrap();
+        assert!(cas.fetch(&bar.address()).is_err());
+    }
+}
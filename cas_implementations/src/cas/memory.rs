@@ -1,6 +1,6 @@
 use holochain_core_types::{
     cas::{
-        content::{Address, AddressableContent, Content},
+        content::{verify_content, Address, AddressableContent, Content},
         storage::ContentAddressableStorage,
     },
     error::HolochainError,
@@ -15,6 +15,7 @@ use uuid::Uuid;
 pub struct MemoryStorage {
     storage: Arc<RwLock<HashMap<Address, Content>>>,
     id: Uuid,
+    verify_on_read: bool,
 }
 
 impl PartialEq for MemoryStorage {
@@ -28,8 +29,16 @@ impl MemoryStorage {
         MemoryStorage {
             storage: Arc::new(RwLock::new(HashMap::new())),
             id: Uuid::new_v4(),
+            verify_on_read: false,
         }
     }
+
+    /// Makes fetch() check that content hashes to the requested address
+    /// and fail instead of returning content that does not.
+    pub fn with_verify_on_read(mut self) -> MemoryStorage {
+        self.verify_on_read = true;
+        self
+    }
 }
 
 impl ContentAddressableStorage for MemoryStorage {
@@ -46,7 +55,15 @@ impl ContentAddressableStorage for MemoryStorage {
 
     fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
         let map = self.storage.read()?;
-        Ok(map.get(address).cloned())
+        let content = map.get(address).cloned();
+        if let (true, Some(content)) = (self.verify_on_read, &content) {
+            verify_content(address, content)?;
+        }
+        Ok(content)
+    }
+
+    fn addresses(&self) -> Result<Vec<Address>, HolochainError> {
+        Ok(self.storage.read()?.keys().cloned().collect())
     }
 
     fn get_id(&self) -> Uuid {
@@ -59,8 +76,10 @@ pub mod tests {
     use cas::memory::MemoryStorage;
     use holochain_core_types::{
         cas::{
-            content::{ExampleAddressableContent, OtherExampleAddressableContent},
-            storage::StorageTestSuite,
+            content::{
+                AddressableContent, ExampleAddressableContent, OtherExampleAddressableContent,
+            },
+            storage::{ContentAddressableStorage, StorageTestSuite},
         },
         json::RawString,
     };
@@ -78,4 +97,26 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn memory_verify_on_read() {
+        let storage = test_memory_storage().with_verify_on_read();
+        let test_suite = StorageTestSuite::new(storage.clone());
+        test_suite.round_trip_test::<ExampleAddressableContent, OtherExampleAddressableContent>(
+            RawString::from("foo").into(),
+            RawString::from("bar").into(),
+        );
+
+        // store foo under the address of bar
+        let foo =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let bar =
+            ExampleAddressableContent::try_from_content(&RawString::from("bar").into()).unwrap();
+        storage
+            .storage
+            .write()
+            .unwrap()
+            .insert(bar.address(), foo.content());
+        assert!(storage.fetch(&bar.address()).is_err());
+    }
+
 }
@@ -0,0 +1,269 @@
+use crate::encryption::StorageKey;
+use holochain_core_types::{
+    cas::content::Address,
+    eav::{
+        Attribute, AttributeFilter, Entity, EntityAttributeValueIndex, EntityAttributeValueStorage,
+        IndexQuery, Value,
+    },
+    error::HolochainError,
+};
+use std::{
+    collections::BTreeSet,
+    sync::{Arc, RwLock},
+};
+
+/// Wraps any EAV storage and encrypts entities, attributes and values of all EAVIs.
+/// They get encrypted deterministically (with a nonce derived from the plain text),
+/// so that equal plain texts still result in equal cipher texts and the wrapped storage
+/// can look up EAVIs by entity, attribute or value as before.
+/// Indexes stay as they are, so ranges and limits get pushed down as well.
+/// Only attribute prefixes can't be looked up in cipher texts; these queries
+/// read all EAVIs of the entity and filter them after decrypting.
+#[derive(Clone, Debug)]
+pub struct EavEncryptedStorage {
+    storage: Arc<RwLock<EntityAttributeValueStorage>>,
+    key: StorageKey,
+}
+
+impl EavEncryptedStorage {
+    pub fn new(
+        storage: Arc<RwLock<EntityAttributeValueStorage>>,
+        key: StorageKey,
+    ) -> EavEncryptedStorage {
+        EavEncryptedStorage { storage, key }
+    }
+
+    fn encrypt_address(&self, address: &Address) -> Result<Address, HolochainError> {
+        Ok(Address::from(
+            self.key.encrypt_deterministic(&address.to_string())?,
+        ))
+    }
+
+    fn decrypt_address(&self, address: &Address) -> Result<Address, HolochainError> {
+        Ok(Address::from(
+            self.key.decrypt_deterministic(&address.to_string())?,
+        ))
+    }
+
+    fn encrypt_eavi(
+        &self,
+        eavi: &EntityAttributeValueIndex,
+    ) -> Result<EntityAttributeValueIndex, HolochainError> {
+        EntityAttributeValueIndex::new_with_index(
+            &self.encrypt_address(&eavi.entity())?,
+            &self.key.encrypt_deterministic(&eavi.attribute())?,
+            &self.encrypt_address(&eavi.value())?,
+            eavi.index(),
+        )
+    }
+
+    fn decrypt_eavi(
+        &self,
+        eavi: &EntityAttributeValueIndex,
+    ) -> Result<EntityAttributeValueIndex, HolochainError> {
+        EntityAttributeValueIndex::new_with_index(
+            &self.decrypt_address(&eavi.entity())?,
+            &self.key.decrypt_deterministic(&eavi.attribute())?,
+            &self.decrypt_address(&eavi.value())?,
+            eavi.index(),
+        )
+    }
+
+    fn fetch(
+        &self,
+        entity: Option<Entity>,
+        attribute: AttributeFilter,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        let entity = entity.map(|e| self.encrypt_address(&e)).transpose()?;
+        let value = value.map(|v| self.encrypt_address(&v)).transpose()?;
+        let encrypted = match attribute {
+            AttributeFilter::Any => {
+                self.storage
+                    .read()?
+                    .fetch_eavi(entity, None, value, index_query)?
+            }
+            AttributeFilter::Exact(attribute) => self.storage.read()?.fetch_eavi(
+                entity,
+                Some(self.key.encrypt_deterministic(&attribute)?),
+                value,
+                index_query,
+            )?,
+            AttributeFilter::Prefix(ref prefix) => {
+                let eavis = self.storage.read()?.fetch_eavi(
+                    entity,
+                    None,
+                    value,
+                    index_query.clone().without_limit(),
+                )?;
+                let decrypted = eavis
+                    .iter()
+                    .map(|eavi| self.decrypt_eavi(eavi))
+                    .collect::<Result<BTreeSet<_>, _>>()?
+                    .into_iter()
+                    .filter(|eavi| eavi.attribute().starts_with(prefix.as_str()))
+                    .collect();
+                return Ok(index_query.apply(decrypted));
+            }
+        };
+        encrypted
+            .iter()
+            .map(|eavi| self.decrypt_eavi(eavi))
+            .collect()
+    }
+}
+
+impl EntityAttributeValueStorage for EavEncryptedStorage {
+    fn add_eavi(
+        &mut self,
+        eav: &EntityAttributeValueIndex,
+    ) -> Result<Option<EntityAttributeValueIndex>, HolochainError> {
+        let encrypted = self.encrypt_eavi(eav)?;
+        self.storage
+            .write()?
+            .add_eavi(&encrypted)?
+            .map(|eavi| self.decrypt_eavi(&eavi))
+            .transpose()
+    }
+
+    fn fetch_eavi(
+        &self,
+        entity: Option<Entity>,
+        attribute: Option<Attribute>,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(entity, attribute.into(), value, index_query)
+    }
+
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(
+            entity,
+            AttributeFilter::Prefix(attribute_prefix.to_string()),
+            None,
+            index_query,
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use crate::{
+        cas::memory::MemoryStorage,
+        eav::{encrypted::EavEncryptedStorage, file::EavFileStorage, memory::EavMemoryStorage},
+        encryption::tests::test_storage_key,
+    };
+    use holochain_core_types::{
+        cas::{
+            content::{AddressableContent, ExampleAddressableContent},
+            storage::EavTestSuite,
+        },
+        eav::{EntityAttributeValueIndex, EntityAttributeValueStorage, IndexQuery},
+        json::RawString,
+    };
+    use std::{
+        fs::read_dir,
+        sync::{Arc, RwLock},
+    };
+
+    fn test_encrypted_eav() -> EavEncryptedStorage {
+        let key = test_storage_key(&mut MemoryStorage::new());
+        EavEncryptedStorage::new(Arc::new(RwLock::new(EavMemoryStorage::new())), key)
+    }
+
+    #[test]
+    fn encrypted_eav_round_trip() {
+        let entity_content =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let attribute = "favourite-color".to_string();
+        let value_content =
+            ExampleAddressableContent::try_from_content(&RawString::from("blue").into()).unwrap();
+        EavTestSuite::test_round_trip(
+            test_encrypted_eav(),
+            entity_content,
+            attribute,
+            value_content,
+        )
+    }
+
+    #[test]
+    fn encrypted_eav_one_to_many() {
+        EavTestSuite::test_one_to_many::<ExampleAddressableContent, EavEncryptedStorage>(
+            test_encrypted_eav(),
+        )
+    }
+
+    #[test]
+    fn encrypted_eav_many_to_one() {
+        EavTestSuite::test_many_to_one::<ExampleAddressableContent, EavEncryptedStorage>(
+            test_encrypted_eav(),
+        )
+    }
+
+    #[test]
+    fn encrypted_eav_range() {
+        EavTestSuite::test_range::<ExampleAddressableContent, EavEncryptedStorage>(
+            test_encrypted_eav(),
+        );
+    }
+
+    #[test]
+    fn encrypted_eav_attribute_prefix() {
+        EavTestSuite::test_attribute_prefix::<ExampleAddressableContent, EavEncryptedStorage>(
+            test_encrypted_eav(),
+        );
+    }
+
+    #[test]
+    fn encrypted_eav_limit() {
+        EavTestSuite::test_limit::<ExampleAddressableContent, EavEncryptedStorage>(
+            test_encrypted_eav(),
+        );
+    }
+
+    #[test]
+    /// show that neither addresses nor attributes end up on disk in plain text
+    fn encrypted_eav_file_names() {
+        let dir = tempdir().unwrap();
+        let key = test_storage_key(&mut MemoryStorage::new());
+        let file_storage = EavFileStorage::new(dir.path()).unwrap();
+        let mut eav_storage = EavEncryptedStorage::new(Arc::new(RwLock::new(file_storage)), key);
+        let entity =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let eavi = EntityAttributeValueIndex::new(
+            &entity.address(),
+            &"link__private".to_string(),
+            &entity.address(),
+        )
+        .unwrap();
+        eav_storage.add_eavi(&eavi).unwrap();
+
+        for subscript in vec!["e", "a", "v"] {
+            for key in read_dir(dir.path().join(subscript)).unwrap() {
+                let key = key.unwrap().file_name().to_string_lossy().to_string();
+                assert_ne!(key, entity.address().to_string());
+                assert!(!key.contains("private"));
+            }
+        }
+        assert_eq!(
+            eav_storage
+                .fetch_eavi(
+                    Some(entity.address()),
+                    Some("link__private".to_string()),
+                    None,
+                    IndexQuery::default()
+                )
+                .unwrap(),
+            vec![eavi].into_iter().collect()
+        );
+    }
+}
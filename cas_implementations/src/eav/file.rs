@@ -1,16 +1,15 @@
-use glob::glob;
 use holochain_core_types::{
     cas::content::AddressableContent,
     eav::{
-        get_latest, increment_key_till_no_collision, Attribute, Entity, EntityAttributeValueIndex,
-        EntityAttributeValueStorage, IndexQuery, Value,
+        increment_key_till_no_collision, Attribute, AttributeFilter, Entity,
+        EntityAttributeValueIndex, EntityAttributeValueStorage, Index, IndexQuery, Value,
     },
     error::{HcResult, HolochainError},
     json::JsonString,
 };
 use std::{
     collections::BTreeSet,
-    fs::{create_dir_all, File, OpenOptions},
+    fs::{create_dir_all, read_dir, read_to_string, File},
     io::prelude::*,
     path::{Path, PathBuf},
     sync::{Arc, RwLock},
@@ -35,47 +34,6 @@ impl PartialEq for EavFileStorage {
     }
 }
 
-#[warn(unused_must_use)]
-pub fn read_eav(parent_path: PathBuf) -> HcResult<Vec<String>> {
-    //glob all  files
-    let full_path = parent_path.join("*").join("*.txt");
-
-    let paths = glob(full_path.to_str().unwrap())
-        .map_err(|_| HolochainError::ErrorGeneric("Could not get form path".to_string()))?;
-
-    // let path_result = paths.last().ok_or(HolochainError::ErrorGeneric("Could not get form path".to_string()))?;
-    let (eav, error): (BTreeSet<_>, BTreeSet<_>) = paths
-        .map(|path| {
-            let path_buf: PathBuf = path.unwrap_or_default();
-            OpenOptions::new()
-                .read(true)
-                .open(path_buf.clone())
-                .map(|mut file| {
-                    let mut content = String::new();
-                    file.read_to_string(&mut content)
-                        .map(|_| Ok(content))
-                        .unwrap_or_else(|_| {
-                            Err(HolochainError::ErrorGeneric(
-                                "Could not read from string".to_string(),
-                            ))
-                        })
-                })
-                .unwrap_or_else(|_| {
-                    Err(HolochainError::ErrorGeneric(
-                        "Could not read from string".to_string(),
-                    ))
-                })
-        })
-        .partition(Result::is_ok);
-    if !error.is_empty() {
-        Err(HolochainError::ErrorGeneric(
-            "Could not read from string".to_string(),
-        ))
-    } else {
-        Ok(eav.iter().cloned().map(|s| s.unwrap_or_default()).collect())
-    }
-}
-
 impl EavFileStorage {
     pub fn new<P: AsRef<Path>>(dir_path: P) -> HcResult<EavFileStorage> {
         let dir_path = dir_path.as_ref().into();
@@ -116,52 +74,103 @@ impl EavFileStorage {
         Ok(())
     }
 
-    fn read_from_dir<T>(
+    /// Reads the EAVIs filed in the given directory index under keys matching the filter
+    /// (entity and value keys are matched exactly), skipping the index subdirectories
+    /// that lie outside of the lookup range of the query.
+    /// Only EAVIs for which `matches` holds are kept, and reading stops after the query's
+    /// scan limit.
+    fn read_from_index(
         &self,
-        subscript: String,
-        eav_constraint: Option<T>,
-    ) -> HcResult<BTreeSet<String>>
-    where
-        T: ToString,
-    {
-        let address = eav_constraint
-            .map(|e| e.to_string())
-            .unwrap_or("*".to_string());
-
-        let path = self.dir_path.join(&subscript);
-
-        if path.exists() {
-            let full_path = path.join(&address);
-
-            let paths = glob(full_path.to_str().unwrap())
-                .map_err(|_| HolochainError::ErrorGeneric("Could not get form path".to_string()))?;
-
-            let (eavs, errors): (Vec<_>, Vec<_>) = paths
-                .map(|path_val| {
-                    path_val.map(|walk| read_eav(walk.clone())).unwrap_or(Err(
-                        HolochainError::ErrorGeneric(
-                            "Could not read eavs from directory".to_string(),
-                        ),
-                    ))
+        subscript: &str,
+        key_filter: &AttributeFilter,
+        index_query: &IndexQuery,
+        matches: &Fn(&EntityAttributeValueIndex) -> bool,
+    ) -> HcResult<BTreeSet<EntityAttributeValueIndex>> {
+        let index_path = self.dir_path.join(subscript);
+        if !index_path.is_dir() {
+            return Ok(BTreeSet::new());
+        }
+        let key_paths: Vec<PathBuf> = match key_filter {
+            AttributeFilter::Exact(key) => vec![index_path.join(key)],
+            _ => read_dir(&index_path)?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| {
+                    entry
+                        .file_name()
+                        .to_str()
+                        .map(|key| key_filter.matches(key))
+                        .unwrap_or(false)
                 })
-                .partition(Result::is_ok);
-            if errors.len() > 0 {
-                Err(HolochainError::ErrorGeneric(
-                    "Could not read eavs from directory".to_string(),
-                ))
-            } else {
-                let mut ordmap: BTreeSet<String> = BTreeSet::new();
-                eavs.iter().for_each(|s| {
-                    s.clone().unwrap_or(Vec::new()).iter().for_each(|value| {
-                        ordmap.insert(value.clone());
-                    })
-                });
+                .map(|entry| entry.path())
+                .collect(),
+        };
 
-                Ok(ordmap)
+        let mut index_paths = Vec::new();
+        for key_path in key_paths.into_iter().filter(|path| path.is_dir()) {
+            for index_entry in read_dir(key_path)? {
+                let index_entry = index_entry?;
+                let maybe_index = index_entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|index| index.parse::<Index>().ok())
+                    .filter(|index| index_query.is_in_lookup_range(*index));
+                if let Some(index) = maybe_index {
+                    index_paths.push((index, index_entry.path()));
+                }
             }
-        } else {
-            Ok(BTreeSet::new())
         }
+        // directories read from several keys are not in index order
+        index_paths.sort();
+
+        let limit = index_query.scan_limit().unwrap_or(usize::max_value());
+        let mut eavis = BTreeSet::new();
+        for (_, index_path) in index_paths {
+            for file in read_dir(index_path)? {
+                let content = read_to_string(file?.path())?;
+                let eavi = EntityAttributeValueIndex::try_from_content(&JsonString::from(content))?;
+                if matches(&eavi) {
+                    eavis.insert(eavi);
+                    if eavis.len() >= limit {
+                        return Ok(eavis);
+                    }
+                }
+            }
+        }
+        Ok(eavis)
+    }
+
+    fn fetch(
+        &self,
+        entity: Option<Entity>,
+        attribute: AttributeFilter,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> HcResult<BTreeSet<EntityAttributeValueIndex>> {
+        let _guard = self.lock.read()?;
+        let matches = |e: &EntityAttributeValueIndex| {
+            EntityAttributeValueIndex::filter_on_eav(&e.entity(), entity.as_ref())
+                && attribute.matches(&e.attribute())
+                && EntityAttributeValueIndex::filter_on_eav(&e.value(), value.as_ref())
+        };
+        // only read the most specific directory index
+        let filtered = match (&entity, &value) {
+            (Some(entity), _) => self.read_from_index(
+                ENTITY_DIR,
+                &AttributeFilter::Exact(entity.to_string()),
+                &index_query,
+                &matches,
+            )?,
+            (None, Some(value)) => self.read_from_index(
+                VALUE_DIR,
+                &AttributeFilter::Exact(value.to_string()),
+                &index_query,
+                &matches,
+            )?,
+            (None, None) => {
+                self.read_from_index(ATTRIBUTE_DIR, &attribute, &index_query, &matches)?
+            }
+        };
+        Ok(index_query.apply(filtered))
     }
 }
 
@@ -192,63 +201,21 @@ impl EntityAttributeValueStorage for EavFileStorage {
         value: Option<Value>,
         index_query: IndexQuery,
     ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
-        let _guard = self.lock.read()?;
-        let entity_set = self.read_from_dir::<Entity>(ENTITY_DIR.to_string(), entity.clone())?;
-        let attribute_set = self
-            .read_from_dir::<Attribute>(ATTRIBUTE_DIR.to_string(), attribute)?
-            .clone();
-        let value_set = self.read_from_dir::<Value>(VALUE_DIR.to_string(), value)?;
-
-        let attribute_value_inter: BTreeSet<String> = value_set
-            .intersection(&attribute_set.clone())
-            .cloned()
-            .collect();
-        let entity_attribute_value_inter: BTreeSet<String> = attribute_value_inter
-            .intersection(&entity_set)
-            .cloned()
-            .collect();
+        self.fetch(entity, attribute.into(), value, index_query)
+    }
 
-        let (eav, error): (BTreeSet<_>, BTreeSet<_>) = entity_attribute_value_inter
-            .into_iter()
-            .map(|content| EntityAttributeValueIndex::try_from_content(&JsonString::from(content)))
-            .partition(|c| c.is_ok());
-        if error.len() > 0 {
-            Err(HolochainError::ErrorGeneric(
-                "Error Converting EAVs".to_string(),
-            ))
-        } else {
-            let map: BTreeSet<EntityAttributeValueIndex> = eav
-                .clone()
-                .into_iter()
-                .map(|value: HcResult<EntityAttributeValueIndex>| {
-                    value.unwrap_or(EntityAttributeValueIndex::default())
-                })
-                .collect();
-            Ok(map
-                .clone()
-                .into_iter()
-                .filter(|e| {
-                    index_query
-                        .start()
-                        .map(|start| start <= e.index())
-                        .unwrap_or_else(|| {
-                            let latest = get_latest(e.clone(), map.clone())
-                                .unwrap_or(EntityAttributeValueIndex::default());
-                            latest.index() == e.index()
-                        })
-                })
-                .filter(|e| {
-                    index_query
-                        .end()
-                        .map(|end| end >= e.index())
-                        .unwrap_or_else(|| {
-                            let latest = get_latest(e.clone(), map.clone())
-                                .unwrap_or(EntityAttributeValueIndex::default());
-                            latest.index() == e.index()
-                        })
-                })
-                .collect::<BTreeSet<EntityAttributeValueIndex>>())
-        }
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(
+            entity,
+            AttributeFilter::Prefix(attribute_prefix.to_string()),
+            None,
+            index_query,
+        )
     }
 }
 
@@ -308,4 +275,19 @@ pub mod tests {
         EavTestSuite::test_range::<ExampleAddressableContent, EavFileStorage>(eav_storage);
     }
 
+    #[test]
+    fn file_eav_attribute_prefix() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let eav_storage = EavFileStorage::new(temp.path()).unwrap();
+        EavTestSuite::test_attribute_prefix::<ExampleAddressableContent, EavFileStorage>(
+            eav_storage,
+        );
+    }
+
+    #[test]
+    fn file_eav_limit() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let eav_storage = EavFileStorage::new(temp.path()).unwrap();
+        EavTestSuite::test_limit::<ExampleAddressableContent, EavFileStorage>(eav_storage);
+    }
 }
@@ -0,0 +1,546 @@
+use crate::cas::lmdb::{lmdb_error, open_lmdb_environment, DEFAULT_LMDB_MAP_SIZE};
+use holochain_core_types::{
+    cas::content::AddressableContent,
+    eav::{
+        Attribute, AttributeFilter, Entity, EntityAttributeValueIndex, EntityAttributeValueStorage,
+        Index, IndexQuery, Value,
+    },
+    error::HolochainError,
+    json::JsonString,
+};
+use lmdb::{
+    Cursor, Database, DatabaseFlags, Environment, Error as LmdbError, RoTransaction, RwTransaction,
+    Transaction, WriteFlags,
+};
+use std::{
+    collections::BTreeSet,
+    ops::Bound,
+    path::Path,
+    str::from_utf8,
+    sync::{Arc, RwLock},
+};
+
+use uuid::Uuid;
+
+/// Names of the secondary indexes of the EAVIs by entity, by attribute and by value.
+/// Their names are no valid main database keys, which are 8 bytes long.
+const ENTITY_INDEX: &str = "eav_entity";
+const ATTRIBUTE_INDEX: &str = "eav_attribute";
+const VALUE_INDEX: &str = "eav_value";
+
+/// LMDB keys are limited to 511 bytes, so only this many bytes of an entity, attribute
+/// or value make it into a secondary index key.
+/// Fetching filters the EAVIs it looks up anyway, so this only costs selectivity.
+const MAX_INDEXED_LENGTH: usize = 400;
+
+/// EAV storage backed by an LMDB database.
+/// EAVIs get stored as JSON with their index as key, so that iterating over the database
+/// yields them in the same order as the BTreeSet of the memory implementation.
+/// Secondary indexes by entity, attribute and value let fetches with any of these
+/// constraints only read the EAVIs that might match.
+#[derive(Clone, Debug)]
+pub struct EavLmdbStorage {
+    environment: Arc<Environment>,
+    database: Database,
+    entity_index: Database,
+    attribute_index: Database,
+    value_index: Database,
+    id: Uuid,
+    lock: Arc<RwLock<()>>,
+}
+
+impl PartialEq for EavLmdbStorage {
+    fn eq(&self, other: &EavLmdbStorage) -> bool {
+        self.id == other.id
+    }
+}
+
+/// Big endian bytes of the index with the sign bit flipped,
+/// which makes LMDB's lexicographical key order match the numerical order.
+fn index_to_key(index: Index) -> [u8; 8] {
+    ((index as u64) ^ (1 << 63)).to_be_bytes()
+}
+
+fn key_to_index(key: &[u8]) -> Option<Index> {
+    if key.len() != 8 {
+        return None;
+    }
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(key);
+    Some((u64::from_be_bytes(bytes) ^ (1 << 63)) as Index)
+}
+
+fn indexed_bytes(indexed: &str) -> &[u8] {
+    let bytes = indexed.as_bytes();
+    &bytes[..bytes.len().min(MAX_INDEXED_LENGTH)]
+}
+
+/// Prefix of the keys of all EAVIs with the given entity, attribute or value
+/// in a secondary index.
+fn secondary_key_prefix(indexed: &str) -> Vec<u8> {
+    let mut prefix = indexed_bytes(indexed).to_vec();
+    prefix.push(0);
+    prefix
+}
+
+/// Key of an EAVI in a secondary index: its entity, attribute or value, a zero byte and its
+/// key in the main database, so that the EAVIs sharing a key prefix are ordered by index.
+fn secondary_key(indexed: &str, index: Index) -> Vec<u8> {
+    let mut key = secondary_key_prefix(indexed);
+    key.extend_from_slice(&index_to_key(index));
+    key
+}
+
+/// The main database key a secondary index key ends with.
+fn primary_key(secondary_key: &[u8]) -> &[u8] {
+    &secondary_key[secondary_key.len().saturating_sub(8)..]
+}
+
+impl EavLmdbStorage {
+    pub fn new<P: AsRef<Path>>(dir_path: P) -> Result<EavLmdbStorage, HolochainError> {
+        EavLmdbStorage::new_with_map_size(dir_path, DEFAULT_LMDB_MAP_SIZE)
+    }
+
+    /// Opens the storage with database files that can grow to `map_size` bytes.
+    /// Databases written before there were secondary indexes get indexed on opening.
+    pub fn new_with_map_size<P: AsRef<Path>>(
+        dir_path: P,
+        map_size: usize,
+    ) -> Result<EavLmdbStorage, HolochainError> {
+        let (environment, database) = open_lmdb_environment(dir_path, map_size)?;
+        let open_index = |name| {
+            environment
+                .create_db(Some(name), DatabaseFlags::empty())
+                .map_err(lmdb_error)
+        };
+        let entity_index = open_index(ENTITY_INDEX)?;
+        let attribute_index = open_index(ATTRIBUTE_INDEX)?;
+        let value_index = open_index(VALUE_INDEX)?;
+
+        let storage = EavLmdbStorage {
+            environment: Arc::new(environment),
+            database,
+            entity_index,
+            attribute_index,
+            value_index,
+            id: Uuid::new_v4(),
+            lock: Arc::new(RwLock::new(())),
+        };
+        storage.build_missing_indexes()?;
+        Ok(storage)
+    }
+
+    /// Fills the secondary indexes from the main database if they are empty but it is not.
+    fn build_missing_indexes(&self) -> Result<(), HolochainError> {
+        let mut transaction = self.environment.begin_rw_txn().map_err(lmdb_error)?;
+        let eavis = {
+            let mut index_cursor = transaction
+                .open_ro_cursor(self.entity_index)
+                .map_err(lmdb_error)?;
+            if index_cursor.iter_start().next().is_some() {
+                return Ok(());
+            }
+            let mut cursor = transaction
+                .open_ro_cursor(self.database)
+                .map_err(lmdb_error)?;
+            cursor
+                .iter_start()
+                .filter(|(key, _)| key_to_index(key).is_some())
+                .map(|(_, bytes)| Self::eavi_from_bytes(bytes))
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        if eavis.is_empty() {
+            return Ok(());
+        }
+        for eavi in eavis.iter() {
+            self.put_secondary_keys(&mut transaction, eavi)?;
+        }
+        transaction.commit().map_err(lmdb_error)
+    }
+
+    fn eavi_from_bytes(bytes: &[u8]) -> Result<EntityAttributeValueIndex, HolochainError> {
+        let content =
+            from_utf8(bytes).map_err(|error| HolochainError::ErrorGeneric(error.to_string()))?;
+        EntityAttributeValueIndex::try_from_content(&JsonString::from(content))
+    }
+
+    fn put_secondary_keys(
+        &self,
+        transaction: &mut RwTransaction,
+        eavi: &EntityAttributeValueIndex,
+    ) -> Result<(), HolochainError> {
+        let primary_key = index_to_key(eavi.index());
+        for (database, indexed) in &[
+            (self.entity_index, eavi.entity().to_string()),
+            (self.attribute_index, eavi.attribute()),
+            (self.value_index, eavi.value().to_string()),
+        ] {
+            transaction
+                .put(
+                    *database,
+                    &secondary_key(indexed, eavi.index()),
+                    &primary_key,
+                    WriteFlags::empty(),
+                )
+                .map_err(lmdb_error)?;
+        }
+        Ok(())
+    }
+
+    /// Parses the EAVIs from the given database values, which have to be in index order,
+    /// and keeps the first ones up to the query's scan limit for which `matches` holds.
+    fn collect_matching<'txn, I>(
+        values: I,
+        index_query: &IndexQuery,
+        matches: &Fn(&EntityAttributeValueIndex) -> bool,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError>
+    where
+        I: Iterator<Item = Result<&'txn [u8], HolochainError>>,
+    {
+        values
+            .map(|bytes| bytes.and_then(Self::eavi_from_bytes))
+            .filter(|eavi| eavi.as_ref().map(|eavi| matches(eavi)).unwrap_or(true))
+            .take(index_query.scan_limit().unwrap_or(usize::max_value()))
+            .collect()
+    }
+
+    /// Reads the matching EAVIs within the lookup range of the query.
+    /// Keys are ordered by index, so the range can be read with a single cursor.
+    fn read_range(
+        &self,
+        transaction: &RoTransaction,
+        index_query: &IndexQuery,
+        matches: &Fn(&EntityAttributeValueIndex) -> bool,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        let mut cursor = transaction
+            .open_ro_cursor(self.database)
+            .map_err(lmdb_error)?;
+        let iter = match index_query.lookup_range().0 {
+            Bound::Included(start) => cursor.iter_from(index_to_key(start)),
+            _ => cursor.iter_start(),
+        };
+        let values = iter
+            // skip the names of the secondary indexes
+            .filter_map(|(key, bytes)| key_to_index(key).map(|index| (index, bytes)))
+            .take_while(|(index, _)| index_query.is_in_lookup_range(*index))
+            .map(|(_, bytes)| Ok(bytes));
+        Self::collect_matching(values, index_query, matches)
+    }
+
+    /// Reads the matching EAVIs within the lookup range of the query from the given secondary
+    /// index, whose keys start with `indexed` (if `exact`, followed by the separator).
+    /// Within an entity, attribute or value keys are ordered by index, so with an exact key
+    /// the range can be read with a single cursor.
+    fn read_secondary_index(
+        &self,
+        transaction: &RoTransaction,
+        secondary_index: Database,
+        indexed: &str,
+        exact: bool,
+        index_query: &IndexQuery,
+        matches: &Fn(&EntityAttributeValueIndex) -> bool,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        let prefix = if exact {
+            secondary_key_prefix(indexed)
+        } else {
+            indexed_bytes(indexed).to_vec()
+        };
+        let start_key = match (exact, index_query.lookup_range().0) {
+            (true, Bound::Included(start)) => secondary_key(indexed, start),
+            _ => prefix.clone(),
+        };
+        let mut cursor = transaction
+            .open_ro_cursor(secondary_index)
+            .map_err(lmdb_error)?;
+        let mut indexes = cursor
+            .iter_from(start_key)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .filter_map(|(key, _)| key_to_index(primary_key(key)))
+            .filter(|index| index_query.is_in_lookup_range(*index))
+            .collect::<Vec<_>>();
+        // keys of several attributes starting with the prefix are not in index order
+        if !exact {
+            indexes.sort_unstable();
+        }
+        let values = indexes.into_iter().map(|index| {
+            transaction
+                .get(self.database, &index_to_key(index))
+                .map_err(lmdb_error)
+        });
+        Self::collect_matching(values, index_query, matches)
+    }
+
+    fn fetch(
+        &self,
+        entity: Option<Entity>,
+        attribute: AttributeFilter,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        let _guard = self.lock.read()?;
+        let transaction = self.environment.begin_ro_txn().map_err(lmdb_error)?;
+        let matches = |e: &EntityAttributeValueIndex| {
+            EntityAttributeValueIndex::filter_on_eav(&e.entity(), entity.as_ref())
+                && attribute.matches(&e.attribute())
+                && EntityAttributeValueIndex::filter_on_eav(&e.value(), value.as_ref())
+        };
+        // only read the most specific index
+        let filtered = match (&entity, &value, &attribute) {
+            (Some(entity), _, _) => self.read_secondary_index(
+                &transaction,
+                self.entity_index,
+                &entity.to_string(),
+                true,
+                &index_query,
+                &matches,
+            )?,
+            (None, Some(value), _) => self.read_secondary_index(
+                &transaction,
+                self.value_index,
+                &value.to_string(),
+                true,
+                &index_query,
+                &matches,
+            )?,
+            (None, None, AttributeFilter::Exact(attribute)) => self.read_secondary_index(
+                &transaction,
+                self.attribute_index,
+                attribute,
+                true,
+                &index_query,
+                &matches,
+            )?,
+            (None, None, AttributeFilter::Prefix(prefix)) => self.read_secondary_index(
+                &transaction,
+                self.attribute_index,
+                prefix,
+                false,
+                &index_query,
+                &matches,
+            )?,
+            (None, None, AttributeFilter::Any) => {
+                self.read_range(&transaction, &index_query, &matches)?
+            }
+        };
+        Ok(index_query.apply(filtered))
+    }
+}
+
+impl EntityAttributeValueStorage for EavLmdbStorage {
+    fn add_eavi(
+        &mut self,
+        eav: &EntityAttributeValueIndex,
+    ) -> Result<Option<EntityAttributeValueIndex>, HolochainError> {
+        let _guard = self.lock.write()?;
+        let mut transaction = self.environment.begin_rw_txn().map_err(lmdb_error)?;
+        let mut new_eav = eav.clone();
+        loop {
+            match transaction.get(self.database, &index_to_key(new_eav.index())) {
+                Ok(_) => {
+                    let index = new_eav.index() + 1;
+                    new_eav.set_index(index);
+                }
+                Err(LmdbError::NotFound) => break,
+                Err(error) => return Err(lmdb_error(error)),
+            }
+        }
+        transaction
+            .put(
+                self.database,
+                &index_to_key(new_eav.index()),
+                &new_eav.content().to_string(),
+                WriteFlags::NO_OVERWRITE,
+            )
+            .map_err(lmdb_error)?;
+        self.put_secondary_keys(&mut transaction, &new_eav)?;
+        transaction.commit().map_err(lmdb_error)?;
+        Ok(Some(new_eav))
+    }
+
+    fn fetch_eavi(
+        &self,
+        entity: Option<Entity>,
+        attribute: Option<Attribute>,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(entity, attribute.into(), value, index_query)
+    }
+
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(
+            entity,
+            AttributeFilter::Prefix(attribute_prefix.to_string()),
+            None,
+            index_query,
+        )
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate tempfile;
+
+    use self::tempfile::tempdir;
+    use crate::{
+        cas::lmdb::{open_lmdb_environment, DEFAULT_LMDB_MAP_SIZE},
+        eav::lmdb::{index_to_key, key_to_index, EavLmdbStorage},
+    };
+    use holochain_core_types::{
+        cas::{
+            content::{AddressableContent, ExampleAddressableContent},
+            storage::EavTestSuite,
+        },
+        eav::{EntityAttributeValueIndex, EntityAttributeValueStorage, IndexQuery},
+        json::RawString,
+    };
+    use lmdb::{Transaction, WriteFlags};
+
+    #[test]
+    fn lmdb_eav_round_trip() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let entity_content =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let attribute = "favourite-color".to_string();
+        let value_content =
+            ExampleAddressableContent::try_from_content(&RawString::from("blue").into()).unwrap();
+        EavTestSuite::test_round_trip(
+            EavLmdbStorage::new(temp.path()).unwrap(),
+            entity_content,
+            attribute,
+            value_content,
+        )
+    }
+
+    #[test]
+    fn lmdb_eav_one_to_many() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let eav_storage = EavLmdbStorage::new(temp.path()).unwrap();
+        EavTestSuite::test_one_to_many::<ExampleAddressableContent, EavLmdbStorage>(eav_storage)
+    }
+
+    #[test]
+    fn lmdb_eav_many_to_one() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let eav_storage = EavLmdbStorage::new(temp.path()).unwrap();
+        EavTestSuite::test_many_to_one::<ExampleAddressableContent, EavLmdbStorage>(eav_storage)
+    }
+
+    #[test]
+    fn lmdb_eav_range() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let eav_storage = EavLmdbStorage::new(temp.path()).unwrap();
+        EavTestSuite::test_range::<ExampleAddressableContent, EavLmdbStorage>(eav_storage);
+    }
+
+    #[test]
+    fn lmdb_eav_attribute_prefix() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let eav_storage = EavLmdbStorage::new(temp.path()).unwrap();
+        EavTestSuite::test_attribute_prefix::<ExampleAddressableContent, EavLmdbStorage>(
+            eav_storage,
+        );
+    }
+
+    #[test]
+    fn lmdb_eav_limit() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let eav_storage = EavLmdbStorage::new(temp.path()).unwrap();
+        EavTestSuite::test_limit::<ExampleAddressableContent, EavLmdbStorage>(eav_storage);
+    }
+
+    #[test]
+    fn lmdb_eav_keys_keep_index_order() {
+        assert_eq!(key_to_index(&index_to_key(-42)), Some(-42));
+        assert!(index_to_key(-1) < index_to_key(0));
+        assert!(index_to_key(i64::min_value()) < index_to_key(-1));
+        assert!(index_to_key(1) < index_to_key(i64::max_value()));
+    }
+
+    #[test]
+    fn lmdb_eav_colliding_indexes_get_incremented() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let mut eav_storage = EavLmdbStorage::new(temp.path()).unwrap();
+        let entity =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let eav = EntityAttributeValueIndex::new_with_index(
+            &entity.address(),
+            &"attribute".to_string(),
+            &entity.address(),
+            42,
+        )
+        .unwrap();
+        let first = eav_storage.add_eavi(&eav).unwrap().unwrap();
+        let second = eav_storage.add_eavi(&eav).unwrap().unwrap();
+        assert_eq!(first.index(), 42);
+        assert_eq!(second.index(), 43);
+        assert_eq!(
+            eav_storage
+                .fetch_eavi(None, None, None, IndexQuery::new(0, 100))
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn lmdb_eav_indexes_existing_databases() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let entity =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        let eav = EntityAttributeValueIndex::new_with_index(
+            &entity.address(),
+            &"attribute".to_string(),
+            &entity.address(),
+            42,
+        )
+        .unwrap();
+        // a database written before there were secondary indexes
+        {
+            let (environment, database) =
+                open_lmdb_environment(temp.path(), DEFAULT_LMDB_MAP_SIZE).unwrap();
+            let mut transaction = environment.begin_rw_txn().unwrap();
+            transaction
+                .put(
+                    database,
+                    &index_to_key(42),
+                    &eav.content().to_string(),
+                    WriteFlags::empty(),
+                )
+                .unwrap();
+            transaction.commit().unwrap();
+        }
+
+        let eav_storage = EavLmdbStorage::new(temp.path()).unwrap();
+        let expected = vec![eav.clone()].into_iter().collect();
+        assert_eq!(
+            eav_storage
+                .fetch_eavi(Some(entity.address()), None, None, IndexQuery::default())
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            eav_storage
+                .fetch_eavi(None, None, Some(entity.address()), IndexQuery::default())
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            eav_storage
+                .fetch_eavi_by_attribute_prefix(None, "attr", IndexQuery::default())
+                .unwrap(),
+            expected
+        );
+        assert_eq!(
+            eav_storage
+                .fetch_eavi(None, None, None, IndexQuery::default())
+                .unwrap(),
+            expected
+        );
+    }
+}
@@ -1,20 +1,84 @@
 use holochain_core_types::{
     eav::{
-        get_latest, increment_key_till_no_collision, Attribute, Entity, EntityAttributeValueIndex,
-        EntityAttributeValueStorage, IndexQuery, Value,
+        Attribute, AttributeFilter, Entity, EntityAttributeValueIndex, EntityAttributeValueStorage,
+        Index, IndexQuery, Value,
     },
     error::HolochainError,
 };
 use std::{
-    collections::BTreeSet,
+    collections::{BTreeMap, BTreeSet},
+    ops::Bound,
     sync::{Arc, RwLock},
 };
 
 use uuid::Uuid;
 
+/// All EAVIs by their index, plus indexes by (entity, attribute), by attribute and by value
+/// so fetching EAVIs does not need to filter through all of them.
+#[derive(Debug, Default)]
+struct EavMemoryIndexes {
+    eavis: BTreeMap<Index, EntityAttributeValueIndex>,
+    by_entity_attribute: BTreeMap<(Entity, Attribute), BTreeSet<Index>>,
+    by_attribute: BTreeMap<Attribute, BTreeSet<Index>>,
+    by_value: BTreeMap<Value, BTreeSet<Index>>,
+}
+
+impl EavMemoryIndexes {
+    fn insert(&mut self, eavi: EntityAttributeValueIndex) {
+        let index = eavi.index();
+        self.by_entity_attribute
+            .entry((eavi.entity(), eavi.attribute()))
+            .or_insert_with(BTreeSet::new)
+            .insert(index);
+        self.by_attribute
+            .entry(eavi.attribute())
+            .or_insert_with(BTreeSet::new)
+            .insert(index);
+        self.by_value
+            .entry(eavi.value())
+            .or_insert_with(BTreeSet::new)
+            .insert(index);
+        self.eavis.insert(index, eavi);
+    }
+
+    /// Indexes within the given range of the EAVIs that might match the given constraints,
+    /// taken from the most specific index available.
+    fn candidates(
+        &self,
+        entity: Option<&Entity>,
+        attribute: &AttributeFilter,
+        value: Option<&Value>,
+        range: (Bound<Index>, Bound<Index>),
+    ) -> Vec<Index> {
+        let index_sets: Vec<&BTreeSet<Index>> = match (entity, value, attribute) {
+            (Some(entity), _, _) => self
+                .by_entity_attribute
+                .range((entity.clone(), attribute.range_start())..)
+                .take_while(|((e, a), _)| e == entity && attribute.matches(a))
+                .map(|(_, indexes)| indexes)
+                .collect(),
+            (None, Some(value), _) => self.by_value.get(value).into_iter().collect(),
+            (None, None, AttributeFilter::Any) => {
+                return self.eavis.range(range).map(|(index, _)| *index).collect();
+            }
+            (None, None, _) => self
+                .by_attribute
+                .range(attribute.range_start()..)
+                .take_while(|(a, _)| attribute.matches(a))
+                .map(|(_, indexes)| indexes)
+                .collect(),
+        };
+        index_sets
+            .into_iter()
+            .flat_map(|indexes| indexes.range(range))
+            .cloned()
+            .collect()
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct EavMemoryStorage {
-    storage: Arc<RwLock<BTreeSet<EntityAttributeValueIndex>>>,
+    storage: Arc<RwLock<EavMemoryIndexes>>,
     id: Uuid,
 }
 
@@ -27,10 +91,38 @@ impl PartialEq for EavMemoryStorage {
 impl EavMemoryStorage {
     pub fn new() -> EavMemoryStorage {
         EavMemoryStorage {
-            storage: Arc::new(RwLock::new(BTreeSet::new())),
+            storage: Arc::new(RwLock::new(EavMemoryIndexes::default())),
             id: Uuid::new_v4(),
         }
     }
+
+    fn fetch(
+        &self,
+        entity: Option<Entity>,
+        attribute: AttributeFilter,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        let indexes = self.storage.read()?;
+        let mut candidates = indexes.candidates(
+            entity.as_ref(),
+            &attribute,
+            value.as_ref(),
+            index_query.lookup_range(),
+        );
+        // candidates taken from several index sets are not in index order
+        candidates.sort_unstable();
+        let candidates = candidates
+            .into_iter()
+            .filter_map(|index| indexes.eavis.get(&index))
+            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.entity(), entity.as_ref()))
+            .filter(|e| attribute.matches(&e.attribute()))
+            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.value(), value.as_ref()))
+            .take(index_query.scan_limit().unwrap_or(usize::max_value()))
+            .cloned()
+            .collect::<BTreeSet<EntityAttributeValueIndex>>();
+        Ok(index_query.apply(candidates))
+    }
 }
 
 impl EntityAttributeValueStorage for EavMemoryStorage {
@@ -38,10 +130,14 @@ impl EntityAttributeValueStorage for EavMemoryStorage {
         &mut self,
         eav: &EntityAttributeValueIndex,
     ) -> Result<Option<EntityAttributeValueIndex>, HolochainError> {
-        let mut map = self.storage.write()?;
-        let new_eav = increment_key_till_no_collision(eav.clone(), map.clone())?;
-        map.insert(new_eav.clone());
-        Ok(Some(new_eav.clone()))
+        let mut indexes = self.storage.write()?;
+        let mut new_eav = eav.clone();
+        while indexes.eavis.contains_key(&new_eav.index()) {
+            let index = new_eav.index() + 1;
+            new_eav.set_index(index);
+        }
+        indexes.insert(new_eav.clone());
+        Ok(Some(new_eav))
     }
 
     fn fetch_eavi(
@@ -51,36 +147,21 @@ impl EntityAttributeValueStorage for EavMemoryStorage {
         value: Option<Value>,
         index_query: IndexQuery,
     ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
-        let map = self.storage.read()?;
-        Ok(map
-            .clone()
-            .into_iter()
-            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.entity(), entity.as_ref()))
-            .filter(|e| {
-                EntityAttributeValueIndex::filter_on_eav(&e.attribute(), attribute.as_ref())
-            })
-            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.value(), value.as_ref()))
-            .filter(|e| {
-                index_query
-                    .start()
-                    .map(|start| start <= e.index())
-                    .unwrap_or_else(|| {
-                        let latest = get_latest(e.clone(), map.clone())
-                            .unwrap_or(EntityAttributeValueIndex::default());
-                        latest.index() == e.index()
-                    })
-            })
-            .filter(|e| {
-                index_query
-                    .end()
-                    .map(|end| end >= e.index())
-                    .unwrap_or_else(|| {
-                        let latest = get_latest(e.clone(), map.clone())
-                            .unwrap_or(EntityAttributeValueIndex::default());
-                        latest.index() == e.index()
-                    })
-            })
-            .collect::<BTreeSet<EntityAttributeValueIndex>>())
+        self.fetch(entity, attribute.into(), value, index_query)
+    }
+
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(
+            entity,
+            AttributeFilter::Prefix(attribute_prefix.to_string()),
+            None,
+            index_query,
+        )
     }
 }
 
@@ -128,4 +209,17 @@ pub mod tests {
         EavTestSuite::test_range::<ExampleAddressableContent, EavMemoryStorage>(eav_storage);
     }
 
+    #[test]
+    fn memory_eav_attribute_prefix() {
+        let eav_storage = EavMemoryStorage::new();
+        EavTestSuite::test_attribute_prefix::<ExampleAddressableContent, EavMemoryStorage>(
+            eav_storage,
+        );
+    }
+
+    #[test]
+    fn memory_eav_limit() {
+        let eav_storage = EavMemoryStorage::new();
+        EavTestSuite::test_limit::<ExampleAddressableContent, EavMemoryStorage>(eav_storage);
+    }
 }
@@ -1,2 +1,4 @@
+pub mod encrypted;
 pub mod file;
+pub mod lmdb;
 pub mod memory;
@@ -0,0 +1,309 @@
+//! Keys and helpers for encrypting storages at rest.
+//!
+//! All keys of a storage get derived with `kdf::derive` from a master key,
+//! which is the password hash of the agent's passphrase and a random salt.
+//! The salt gets stored in plain text in the (wrapped) CAS, together with a known
+//! message encrypted with the content key, so that a wrong passphrase can be told
+//! apart from corrupted content right when opening the storage.
+
+use holochain_core_types::{
+    cas::{
+        content::{Address, AddressableContent, Content},
+        storage::ContentAddressableStorage,
+    },
+    error::HolochainError,
+    json::JsonString,
+};
+use holochain_sodium::{aead, hash, kdf, pwhash, random::random_secbuf, secbuf::SecBuf};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+/// Address of the plain text record holding the salt in the wrapped CAS
+pub const ENCRYPTION_RECORD_ADDRESS: &str = "StorageEncryption";
+
+const KEY_BYTES: usize = 32;
+const KDF_CONTEXT: &[u8; kdf::CONTEXTBYTES] = b"HcStore_";
+const CONTENT_KEY_INDEX: u64 = 1;
+const EAV_KEY_INDEX: u64 = 2;
+const EAV_NONCE_KEY_INDEX: u64 = 3;
+const CHECK_MESSAGE: &str = "holochain storage";
+
+#[derive(Serialize, Deserialize)]
+struct EncryptionRecord {
+    salt: String,
+    check: String,
+}
+
+/// Content stored under an address that is not the hash of the content,
+/// i.e. encrypted content, the encryption record or content copied from another storage.
+pub(crate) struct StoredContent {
+    address: Address,
+    content: Content,
+}
+
+impl StoredContent {
+    pub(crate) fn new(address: Address, content: Content) -> StoredContent {
+        StoredContent { address, content }
+    }
+}
+
+impl AddressableContent for StoredContent {
+    fn address(&self) -> Address {
+        self.address.clone()
+    }
+
+    fn content(&self) -> Content {
+        self.content.clone()
+    }
+
+    fn try_from_content(_content: &Content) -> Result<Self, HolochainError> {
+        Err(HolochainError::ErrorGeneric(
+            "The address of stored content can not be restored from its content".to_string(),
+        ))
+    }
+}
+
+fn encode(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn decode(encoded: &str) -> Result<Vec<u8>, HolochainError> {
+    Ok(base64::decode_config(encoded, base64::URL_SAFE_NO_PAD)?)
+}
+
+fn secbuf_from(bytes: &[u8]) -> Result<SecBuf, HolochainError> {
+    let mut buf = SecBuf::with_insecure(bytes.len());
+    buf.write(0, bytes)?;
+    Ok(buf)
+}
+
+/// The keys a storage gets encrypted with.
+/// Cloning is cheap and all clones share the same secure memory.
+#[derive(Clone)]
+pub struct StorageKey {
+    content_key: Arc<Mutex<SecBuf>>,
+    eav_key: Arc<Mutex<SecBuf>>,
+    eav_nonce_key: Arc<Mutex<SecBuf>>,
+}
+
+impl fmt::Debug for StorageKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StorageKey")
+    }
+}
+
+impl StorageKey {
+    /// Derives the keys of the storage that uses the given CAS from the passphrase.
+    /// Creates a new salt in the CAS if there is none yet and fails if the passphrase
+    /// is not the one the storage was created with.
+    pub fn from_passphrase(
+        cas: &mut ContentAddressableStorage,
+        passphrase: &mut SecBuf,
+    ) -> Result<StorageKey, HolochainError> {
+        let record_address = Address::from(ENCRYPTION_RECORD_ADDRESS);
+        match cas.fetch(&record_address)? {
+            Some(content) => {
+                let record: EncryptionRecord = serde_json::from_str(&String::from(content))?;
+                let key = StorageKey::derive(passphrase, &decode(&record.salt)?)?;
+                let check = key
+                    .decrypt(&record.check, record_address.to_string().as_bytes())
+                    .map_err(|_| {
+                        HolochainError::ErrorGeneric(
+                            "Wrong passphrase for encrypted storage".to_string(),
+                        )
+                    })?;
+                if check != CHECK_MESSAGE.as_bytes() {
+                    return Err(HolochainError::ErrorGeneric(
+                        "Wrong passphrase for encrypted storage".to_string(),
+                    ));
+                }
+                Ok(key)
+            }
+            None => {
+                let mut salt = SecBuf::with_insecure(pwhash::SALTBYTES);
+                random_secbuf(&mut salt);
+                let salt = salt.read_lock().to_vec();
+                let key = StorageKey::derive(passphrase, &salt)?;
+                let record = EncryptionRecord {
+                    salt: encode(&salt),
+                    check: key.encrypt(
+                        CHECK_MESSAGE.as_bytes(),
+                        record_address.to_string().as_bytes(),
+                    )?,
+                };
+                cas.add(&StoredContent::new(
+                    record_address,
+                    JsonString::from(serde_json::to_string(&record)?),
+                ))?;
+                Ok(key)
+            }
+        }
+    }
+
+    fn derive(passphrase: &mut SecBuf, salt: &[u8]) -> Result<StorageKey, HolochainError> {
+        let mut salt = secbuf_from(salt)?;
+        let mut master_key = SecBuf::with_secure(pwhash::HASHBYTES);
+        pwhash::hash(
+            passphrase,
+            pwhash::OPSLIMIT_INTERACTIVE,
+            pwhash::MEMLIMIT_INTERACTIVE,
+            pwhash::ALG_ARGON2ID13,
+            &mut salt,
+            &mut master_key,
+        )?;
+        let mut context = secbuf_from(KDF_CONTEXT)?;
+        let mut subkey = |index| -> Result<Arc<Mutex<SecBuf>>, HolochainError> {
+            let mut key = SecBuf::with_secure(KEY_BYTES);
+            kdf::derive(&mut key, index, &mut context, &mut master_key)?;
+            Ok(Arc::new(Mutex::new(key)))
+        };
+        Ok(StorageKey {
+            content_key: subkey(CONTENT_KEY_INDEX)?,
+            eav_key: subkey(EAV_KEY_INDEX)?,
+            eav_nonce_key: subkey(EAV_NONCE_KEY_INDEX)?,
+        })
+    }
+
+    /// Encrypts the message with the content key and a random nonce.
+    /// The additional data (e.g. the address) gets authenticated but not stored.
+    /// Returns nonce and cipher text, base64 encoded.
+    pub(crate) fn encrypt(&self, message: &[u8], adata: &[u8]) -> Result<String, HolochainError> {
+        let mut nonce = SecBuf::with_insecure(aead::NONCEBYTES);
+        random_secbuf(&mut nonce);
+        seal(&self.content_key, message, adata, nonce)
+    }
+
+    /// Reverses `encrypt()`, failing if the cipher text was not created with this key
+    /// and the same additional data.
+    pub(crate) fn decrypt(&self, encrypted: &str, adata: &[u8]) -> Result<Vec<u8>, HolochainError> {
+        open(&self.content_key, encrypted, adata)
+    }
+
+    /// Encrypts the string with the EAV key and a nonce derived from the string itself,
+    /// so that equal strings result in equal cipher texts and can still be looked up.
+    pub(crate) fn encrypt_deterministic(&self, message: &str) -> Result<String, HolochainError> {
+        let mut input = {
+            let mut nonce_key = self.eav_nonce_key.lock()?;
+            let nonce_key = nonce_key.read_lock();
+            secbuf_from(&[&nonce_key[..], message.as_bytes()].concat())?
+        };
+        let mut digest = SecBuf::with_insecure(hash::BYTES256);
+        hash::sha256(&mut input, &mut digest)?;
+        let nonce = secbuf_from(&digest.read_lock()[..aead::NONCEBYTES])?;
+        seal(&self.eav_key, message.as_bytes(), &[], nonce)
+    }
+
+    /// Reverses `encrypt_deterministic()`.
+    pub(crate) fn decrypt_deterministic(&self, encrypted: &str) -> Result<String, HolochainError> {
+        let message = open(&self.eav_key, encrypted, &[])?;
+        String::from_utf8(message).map_err(|error| HolochainError::ErrorGeneric(error.to_string()))
+    }
+}
+
+fn seal(
+    key: &Mutex<SecBuf>,
+    message: &[u8],
+    adata: &[u8],
+    mut nonce: SecBuf,
+) -> Result<String, HolochainError> {
+    let mut message = secbuf_from(message)?;
+    let mut adata = secbuf_from(adata)?;
+    let mut cipher = SecBuf::with_insecure(message.len() + aead::ABYTES);
+    aead::enc(
+        &mut message,
+        &mut *key.lock()?,
+        Some(&mut adata),
+        &mut nonce,
+        &mut cipher,
+    )?;
+    let nonce = nonce.read_lock().to_vec();
+    let cipher = cipher.read_lock().to_vec();
+    Ok(encode(&[nonce, cipher].concat()))
+}
+
+fn open(key: &Mutex<SecBuf>, encrypted: &str, adata: &[u8]) -> Result<Vec<u8>, HolochainError> {
+    let bytes = decode(encrypted)?;
+    if bytes.len() < aead::NONCEBYTES + aead::ABYTES {
+        return Err(HolochainError::ErrorGeneric(
+            "Encrypted data is too short".to_string(),
+        ));
+    }
+    let (nonce, cipher) = bytes.split_at(aead::NONCEBYTES);
+    let mut nonce = secbuf_from(nonce)?;
+    let mut cipher = secbuf_from(cipher)?;
+    let mut adata = secbuf_from(adata)?;
+    let mut message = SecBuf::with_insecure(cipher.len() - aead::ABYTES);
+    aead::dec(
+        &mut message,
+        &mut *key.lock()?,
+        Some(&mut adata),
+        &mut nonce,
+        &mut cipher,
+    )
+    .map_err(|_| {
+        HolochainError::ErrorGeneric(
+            "Could not decrypt data, it was tampered with or encrypted with another key"
+                .to_string(),
+        )
+    })?;
+    let message = message.read_lock().to_vec();
+    Ok(message)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use crate::{
+        cas::memory::MemoryStorage,
+        encryption::{StorageKey, ENCRYPTION_RECORD_ADDRESS},
+    };
+    use holochain_core_types::cas::{content::Address, storage::ContentAddressableStorage};
+    use holochain_sodium::secbuf::SecBuf;
+
+    pub fn test_storage_key(cas: &mut ContentAddressableStorage) -> StorageKey {
+        let mut passphrase = SecBuf::with_insecure_from_string("secret".to_string());
+        StorageKey::from_passphrase(cas, &mut passphrase).unwrap()
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let key = test_storage_key(&mut MemoryStorage::new());
+        let encrypted = key.encrypt(b"private", b"address").unwrap();
+        assert!(!encrypted.contains("private"));
+        assert_eq!(key.decrypt(&encrypted, b"address").unwrap(), b"private");
+        assert!(key.decrypt(&encrypted, b"other address").is_err());
+        // random nonces
+        assert_ne!(encrypted, key.encrypt(b"private", b"address").unwrap());
+    }
+
+    #[test]
+    fn encrypt_deterministic() {
+        let key = test_storage_key(&mut MemoryStorage::new());
+        let encrypted = key.encrypt_deterministic("link__tag").unwrap();
+        assert_eq!(encrypted, key.encrypt_deterministic("link__tag").unwrap());
+        assert_ne!(encrypted, key.encrypt_deterministic("link__other").unwrap());
+        assert_eq!(key.decrypt_deterministic(&encrypted).unwrap(), "link__tag");
+    }
+
+    #[test]
+    fn passphrase_gets_checked() {
+        let mut cas = MemoryStorage::new();
+        let key = test_storage_key(&mut cas);
+        assert!(cas
+            .contains(&Address::from(ENCRYPTION_RECORD_ADDRESS))
+            .unwrap());
+
+        // same salt, same keys
+        let same_key = test_storage_key(&mut cas);
+        let encrypted = key.encrypt(b"private", b"").unwrap();
+        assert_eq!(same_key.decrypt(&encrypted, b"").unwrap(), b"private");
+
+        let mut passphrase = SecBuf::with_insecure_from_string("wrong".to_string());
+        assert!(StorageKey::from_passphrase(&mut cas, &mut passphrase).is_err());
+
+        // a new salt results in other keys
+        let other_key = test_storage_key(&mut MemoryStorage::new());
+        assert!(other_key.decrypt(&encrypted, b"").is_err());
+    }
+}
@@ -6,16 +6,22 @@
 //! which are defined but not implemented in the core_types crate.
 
 extern crate holochain_core_types;
+extern crate holochain_sodium;
 extern crate snowflake;
 
 extern crate uuid;
 
 extern crate serde;
+#[macro_use]
+extern crate serde_derive;
 extern crate serde_json;
 
+extern crate base64;
 extern crate chrono;
-extern crate glob;
+extern crate lmdb;
 
 pub mod cas;
 pub mod eav;
+pub mod encryption;
+pub mod migration;
 pub mod path;
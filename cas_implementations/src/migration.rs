@@ -0,0 +1,729 @@
+//! EAV stands for entity-attribute-value. It is a pattern implemented here
+//! for adding metadata about entries in the DHT, additionally
+//! being used to define relationships between AddressableContent values.
+//! See [wikipedia](https://en.wikipedia.org/wiki/Entity%E2%80%93attribute%E2%80%93value_model) to learn more about this pattern.
+
+use crate::{
+    cas::content::{Address, AddressableContent, Content},
+    entry::{test_entry_a, test_entry_b, Entry},
+    error::{HcResult, HolochainError},
+    json::JsonString,
+};
+use chrono::offset::Utc;
+use objekt;
+use std::{
+    cmp::Ordering,
+    collections::{BTreeSet, HashMap},
+    convert::TryInto,
+    ops::Bound,
+    sync::{Arc, RwLock},
+};
+
+use regex::RegexBuilder;
+use std::fmt::Debug;
+
+/// Address of AddressableContent representing the EAV entity
+pub type Entity = Address;
+
+/// Using String for EAV attributes (not e.g. an enum) keeps it simple and open
+pub type Attribute = String; // BUT we totally could make it an enum with a Custom variant!
+
+/// Address of AddressableContent representing the EAV value
+pub type Value = Address;
+
+// @TODO do we need this?
+// unique (local to the source) monotonically increasing number that can be used for crdt/ordering
+// @see https://papers.radixdlt.com/tempo/#logical-clocks
+pub type Index = i64;
+
+// @TODO do we need this?
+// source agent asserting the meta
+// type Source ...
+/// The basic struct for EntityAttributeValue triple, implemented as AddressableContent
+/// including the necessary serialization inherited.
+#[derive(PartialEq, Eq, Hash, Clone, Debug, Serialize, Deserialize, DefaultJson, Default)]
+pub struct EntityAttributeValueIndex {
+    entity: Entity,
+    attribute: Attribute,
+    value: Value,
+    index: Index,
+    // source: Source,
+}
+
+impl PartialOrd for EntityAttributeValueIndex {
+    fn partial_cmp(&self, other: &EntityAttributeValueIndex) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// EAVIs are ordered by index. EAVIs of different triples can share an index, e.g. in
+/// storages that only keep indexes unique per triple, so these get ordered by their triple
+/// to not collapse them in sets.
+impl Ord for EntityAttributeValueIndex {
+    fn cmp(&self, other: &EntityAttributeValueIndex) -> Ordering {
+        self.index
+            .cmp(&other.index())
+            .then_with(|| self.entity.cmp(&other.entity))
+            .then_with(|| self.attribute.cmp(&other.attribute))
+            .then_with(|| self.value.cmp(&other.value))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IndexQuery {
+    start: Option<i64>,
+    end: Option<i64>,
+    limit: Option<usize>,
+}
+
+impl IndexQuery {
+    pub fn start(&self) -> Option<i64> {
+        self.start.clone()
+    }
+
+    pub fn end(&self) -> Option<i64> {
+        self.end.clone()
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.limit.clone()
+    }
+
+    pub fn new(start: i64, end: i64) -> IndexQuery {
+        IndexQuery {
+            start: Some(start),
+            end: Some(end),
+            limit: None,
+        }
+    }
+
+    /// Restricts the query to the first `limit` matching EAVIs in index order.
+    pub fn with_limit(mut self, limit: usize) -> IndexQuery {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The same query without a limit,
+    /// for storages that have to filter the EAVIs they look up some more.
+    pub fn without_limit(mut self) -> IndexQuery {
+        self.limit = None;
+        self
+    }
+
+    /// The range of indexes a storage has to look at to answer this query.
+    /// Without a start, only the latest EAVI of each entity/attribute/value triple matches.
+    /// Telling if an EAVI is the latest requires looking at the EAVIs after it,
+    /// so in that case the end can not narrow down the lookup.
+    pub fn lookup_range(&self) -> (Bound<Index>, Bound<Index>) {
+        let start = self.start.map(Bound::Included).unwrap_or(Bound::Unbounded);
+        let end = self
+            .start
+            .and(self.end)
+            .map(Bound::Included)
+            .unwrap_or(Bound::Unbounded);
+        (start, end)
+    }
+
+    /// How many of the EAVIs matching the entity, attribute and value constraints a storage
+    /// needs to look up in index order, if it can stop early at all.
+    /// Only with both ends set no later EAVIs are needed to tell which ones are the latest,
+    /// so only then the limit can be applied while looking EAVIs up.
+    pub fn scan_limit(&self) -> Option<usize> {
+        self.start.and(self.end).and(self.limit)
+    }
+
+    /// Same as `lookup_range()` for storages that can't make use of ranges directly.
+    pub fn is_in_lookup_range(&self, index: Index) -> bool {
+        self.start.map(|start| start <= index).unwrap_or(true)
+            && self
+                .start
+                .and(self.end)
+                .map(|end| index <= end)
+                .unwrap_or(true)
+    }
+
+    /// Applies the query to EAVIs that match the entity, attribute and value constraints.
+    /// These have to include all matching EAVIs within `lookup_range()`.
+    /// Open ends of the range only let through the latest EAVI of each triple
+    /// and the result gets cut off at the limit.
+    pub fn apply(
+        &self,
+        eavis: BTreeSet<EntityAttributeValueIndex>,
+    ) -> BTreeSet<EntityAttributeValueIndex> {
+        let mut latest = HashMap::new();
+        for eavi in eavis.iter() {
+            latest.insert(
+                (eavi.entity(), eavi.attribute(), eavi.value()),
+                eavi.index(),
+            );
+        }
+        let is_latest = |eavi: &EntityAttributeValueIndex| {
+            latest.get(&(eavi.entity(), eavi.attribute(), eavi.value())) == Some(&eavi.index())
+        };
+        eavis
+            .iter()
+            .filter(|e| {
+                self.start
+                    .map(|start| start <= e.index())
+                    .unwrap_or_else(|| is_latest(e))
+            })
+            .filter(|e| {
+                self.end
+                    .map(|end| end >= e.index())
+                    .unwrap_or_else(|| is_latest(e))
+            })
+            .take(self.limit.unwrap_or(usize::max_value()))
+            .cloned()
+            .collect()
+    }
+}
+
+impl Default for IndexQuery {
+    fn default() -> IndexQuery {
+        IndexQuery {
+            start: None,
+            end: None,
+            limit: None,
+        }
+    }
+}
+
+/// Constraint on the attribute of the EAVIs a storage looks up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeFilter {
+    Any,
+    Exact(Attribute),
+    Prefix(String),
+}
+
+impl AttributeFilter {
+    pub fn matches(&self, attribute: &str) -> bool {
+        match self {
+            AttributeFilter::Any => true,
+            AttributeFilter::Exact(exact) => attribute == exact,
+            AttributeFilter::Prefix(prefix) => attribute.starts_with(prefix.as_str()),
+        }
+    }
+
+    /// The first matching attribute in lexicographical order.
+    /// All matching attributes follow it without gaps,
+    /// so sorted indexes can look them up by iterating from there while `matches()` holds.
+    pub fn range_start(&self) -> Attribute {
+        match self {
+            AttributeFilter::Any => Attribute::new(),
+            AttributeFilter::Exact(attribute) => attribute.clone(),
+            AttributeFilter::Prefix(prefix) => prefix.clone(),
+        }
+    }
+}
+
+impl From<Option<Attribute>> for AttributeFilter {
+    fn from(attribute: Option<Attribute>) -> AttributeFilter {
+        attribute
+            .map(AttributeFilter::Exact)
+            .unwrap_or(AttributeFilter::Any)
+    }
+}
+
+impl AddressableContent for EntityAttributeValueIndex {
+    fn content(&self) -> Content {
+        self.to_owned().into()
+    }
+
+    fn try_from_content(content: &Content) -> Result<Self, HolochainError> {
+        content.to_owned().try_into()
+    }
+}
+
+fn validate_attribute(attribute: &Attribute) -> HcResult<()> {
+    let regex = RegexBuilder::new(r#"[/:*?<>"'\\|+]"#)
+        .build()
+        .map_err(|_| HolochainError::ErrorGeneric("Could not create regex".to_string()))?;
+    if !regex.is_match(attribute) {
+        Ok(())
+    } else {
+        Err(HolochainError::ErrorGeneric(
+            "Attribute name invalid".to_string(),
+        ))
+    }
+}
+
+impl EntityAttributeValueIndex {
+    pub fn new(
+        entity: &Entity,
+        attribute: &Attribute,
+        value: &Value,
+    ) -> HcResult<EntityAttributeValueIndex> {
+        validate_attribute(attribute)?;
+        Ok(EntityAttributeValueIndex {
+            entity: entity.clone(),
+            attribute: attribute.clone(),
+            value: value.clone(),
+            index: Utc::now().timestamp_nanos(),
+        })
+    }
+
+    pub fn new_with_index(
+        entity: &Entity,
+        attribute: &Attribute,
+        value: &Value,
+        timestamp: i64,
+    ) -> HcResult<EntityAttributeValueIndex> {
+        validate_attribute(attribute)?;
+        Ok(EntityAttributeValueIndex {
+            entity: entity.clone(),
+            attribute: attribute.clone(),
+            value: value.clone(),
+            index: timestamp,
+        })
+    }
+
+    pub fn entity(&self) -> Entity {
+        self.entity.clone()
+    }
+
+    pub fn attribute(&self) -> Attribute {
+        self.attribute.clone()
+    }
+
+    pub fn value(&self) -> Value {
+        self.value.clone()
+    }
+
+    pub fn index(&self) -> Index {
+        self.index.clone()
+    }
+
+    pub fn set_index(&mut self, new_index: i64) {
+        self.index = new_index
+    }
+
+    /// this is a predicate for matching on eav values. Useful for reducing duplicated filtered code.
+    pub fn filter_on_eav<T>(eav: &T, e: Option<&T>) -> bool
+    where
+        T: PartialOrd,
+    {
+        e.map(|a| a == eav).unwrap_or(true)
+    }
+}
+
+/// This provides a simple and flexible interface to define relationships between AddressableContent.
+/// It does NOT provide storage for AddressableContent.
+/// Use cas::storage::ContentAddressableStorage to store AddressableContent.
+pub trait EntityAttributeValueStorage: objekt::Clone + Send + Sync + Debug {
+    /// Adds the given EntityAttributeValue to the EntityAttributeValueStorage
+    /// append only storage.
+    fn add_eavi(
+        &mut self,
+        eav: &EntityAttributeValueIndex,
+    ) -> Result<Option<EntityAttributeValueIndex>, HolochainError>;
+    /// Fetch the set of EntityAttributeValues that match constraints according to the latest hash version
+    /// - None = no constraint
+    /// - Some(Entity) = requires the given entity (e.g. all a/v pairs for the entity)
+    /// - Some(Attribute) = requires the given attribute (e.g. all links)
+    /// - Some(Value) = requires the given value (e.g. all entities referencing an Address)
+    /// The index query restricts the result to a range of indexes and/or a maximum number of EAVIs.
+    fn fetch_eavi(
+        &self,
+        entity: Option<Entity>,
+        attribute: Option<Attribute>,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError>;
+    /// Fetch the set of EntityAttributeValues whose attribute starts with the given prefix,
+    /// optionally restricted to the given entity (e.g. all links of an entry regardless of their tag).
+    /// The index query applies as in `fetch_eavi()`.
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError>;
+}
+
+clone_trait_object!(EntityAttributeValueStorage);
+
+#[derive(Clone, Debug)]
+pub struct ExampleEntityAttributeValueStorage {
+    storage: Arc<RwLock<BTreeSet<EntityAttributeValueIndex>>>,
+}
+impl ExampleEntityAttributeValueStorage {
+    pub fn new() -> ExampleEntityAttributeValueStorage {
+        ExampleEntityAttributeValueStorage {
+            storage: Arc::new(RwLock::new(BTreeSet::new())),
+        }
+    }
+
+    fn fetch(
+        &self,
+        entity: Option<Entity>,
+        attribute: AttributeFilter,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        let map = self.storage.read()?;
+        let filtered = map
+            .iter()
+            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.entity(), entity.as_ref()))
+            .filter(|e| attribute.matches(&e.attribute()))
+            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.value(), value.as_ref()))
+            .cloned()
+            .collect::<BTreeSet<EntityAttributeValueIndex>>();
+
+        Ok(index_query.apply(filtered))
+    }
+}
+
+pub fn increment_key_till_no_collision(
+    mut eav: EntityAttributeValueIndex,
+    map: BTreeSet<EntityAttributeValueIndex>,
+) -> HcResult<EntityAttributeValueIndex> {
+    if map
+        .iter()
+        .filter(|e| e.index == eav.index())
+        .collect::<BTreeSet<&EntityAttributeValueIndex>>()
+        .len()
+        > 0
+    {
+        let timestamp = eav.clone().index + 1;
+        eav.set_index(timestamp);
+        increment_key_till_no_collision(eav, map)
+    } else {
+        Ok(eav)
+    }
+}
+
+impl EntityAttributeValueStorage for ExampleEntityAttributeValueStorage {
+    fn add_eavi(
+        &mut self,
+        eav: &EntityAttributeValueIndex,
+    ) -> Result<Option<EntityAttributeValueIndex>, HolochainError> {
+        let mut map = self.storage.write()?;
+        let new_eav = increment_key_till_no_collision(eav.clone(), map.clone())?;
+        map.insert(new_eav.clone());
+        Ok(Some(new_eav.clone()))
+    }
+
+    fn fetch_eavi(
+        &self,
+        entity: Option<Entity>,
+        attribute: Option<Attribute>,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(entity, attribute.into(), value, index_query)
+    }
+
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(
+            entity,
+            AttributeFilter::Prefix(attribute_prefix.to_string()),
+            None,
+            index_query,
+        )
+    }
+}
+
+pub fn get_latest(
+    eav: EntityAttributeValueIndex,
+    map: BTreeSet<EntityAttributeValueIndex>,
+) -> HcResult<EntityAttributeValueIndex> {
+    let filter = map
+        .clone()
+        .into_iter()
+        .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.entity(), Some(&eav.entity())))
+        .filter(|e| {
+            EntityAttributeValueIndex::filter_on_eav(&e.attribute(), Some(&eav.attribute()))
+        })
+        .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.value(), Some(&eav.value())));
+    filter.last().ok_or(HolochainError::ErrorGeneric(
+        "Could not get last value".to_string(),
+    ))
+}
+
+impl PartialEq for EntityAttributeValueStorage {
+    fn eq(&self, other: &EntityAttributeValueStorage) -> bool {
+        self.fetch_eavi(None, None, None, IndexQuery::default())
+            == other.fetch_eavi(None, None, None, IndexQuery::default())
+    }
+}
+
+pub fn test_eav_entity() -> Entry {
+    test_entry_a()
+}
+
+pub fn test_eav_attribute() -> String {
+    "foo-attribute".to_string()
+}
+
+pub fn test_eav_value() -> Entry {
+    test_entry_b()
+}
+
+pub fn test_eav() -> EntityAttributeValueIndex {
+    EntityAttributeValueIndex::new_with_index(
+        &test_eav_entity().address(),
+        &test_eav_attribute(),
+        &test_eav_value().address(),
+        0,
+    )
+    .expect("Could not create eav")
+}
+
+pub fn test_eav_content() -> Content {
+    test_eav().content()
+}
+
+pub fn test_eav_address() -> Address {
+    test_eav().address()
+}
+
+pub fn eav_round_trip_test_runner(
+    entity_content: impl AddressableContent + Clone,
+    attribute: String,
+    value_content: impl AddressableContent + Clone,
+) {
+    let eav = EntityAttributeValueIndex::new(
+        &entity_content.address(),
+        &attribute,
+        &value_content.address(),
+    )
+    .expect("Could not create EAV");
+    let mut eav_storage = ExampleEntityAttributeValueStorage::new();
+
+    assert_eq!(
+        BTreeSet::new(),
+        eav_storage
+            .fetch_eavi(
+                Some(entity_content.address()),
+                Some(attribute.clone()),
+                Some(value_content.address()),
+                IndexQuery::default()
+            )
+            .expect("could not fetch eav"),
+    );
+
+    eav_storage.add_eavi(&eav).expect("could not add eav");
+
+    let mut expected = BTreeSet::new();
+    expected.insert(eav.clone());
+    // some examples of constraints that should all return the eav
+    for (e, a, v) in vec![
+        // constrain all
+        (
+            Some(entity_content.address()),
+            Some(attribute.clone()),
+            Some(value_content.address()),
+        ),
+        // open entity
+        (None, Some(attribute.clone()), Some(value_content.address())),
+        // open attribute
+        (
+            Some(entity_content.address()),
+            None,
+            Some(value_content.address()),
+        ),
+        // open value
+        (
+            Some(entity_content.address()),
+            Some(attribute.clone()),
+            None,
+        ),
+        // open
+        (None, None, None),
+    ] {
+        assert_eq!(
+            expected,
+            eav_storage
+                .fetch_eavi(e, a, v, IndexQuery::default())
+                .expect("could not fetch eav")
+        );
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        cas::{
+            content::{AddressableContent, AddressableContentTestSuite, ExampleAddressableContent},
+            storage::{
+                test_content_addressable_storage, EavTestSuite, ExampleContentAddressableStorage,
+            },
+        },
+        eav::EntityAttributeValueIndex,
+        json::RawString,
+    };
+
+    pub fn test_eav_storage() -> ExampleEntityAttributeValueStorage {
+        ExampleEntityAttributeValueStorage::new()
+    }
+
+    #[test]
+    fn example_eav_round_trip() {
+        let eav_storage = test_eav_storage();
+        let entity =
+            ExampleAddressableContent::try_from_content(&JsonString::from(RawString::from("foo")))
+                .unwrap();
+        let attribute = "favourite-color".to_string();
+        let value =
+            ExampleAddressableContent::try_from_content(&JsonString::from(RawString::from("blue")))
+                .unwrap();
+
+        EavTestSuite::test_round_trip(eav_storage, entity, attribute, value)
+    }
+
+    #[test]
+    fn example_eav_one_to_many() {
+        EavTestSuite::test_one_to_many::<
+            ExampleAddressableContent,
+            ExampleEntityAttributeValueStorage,
+        >(test_eav_storage());
+    }
+
+    #[test]
+    fn example_eav_many_to_one() {
+        EavTestSuite::test_many_to_one::<
+            ExampleAddressableContent,
+            ExampleEntityAttributeValueStorage,
+        >(test_eav_storage());
+    }
+
+    #[test]
+    fn example_eav_range() {
+        EavTestSuite::test_range::<ExampleAddressableContent, ExampleEntityAttributeValueStorage>(
+            test_eav_storage(),
+        );
+    }
+
+    #[test]
+    fn example_eav_attribute_prefix() {
+        EavTestSuite::test_attribute_prefix::<
+            ExampleAddressableContent,
+            ExampleEntityAttributeValueStorage,
+        >(test_eav_storage());
+    }
+
+    #[test]
+    fn example_eav_limit() {
+        EavTestSuite::test_limit::<ExampleAddressableContent, ExampleEntityAttributeValueStorage>(
+            test_eav_storage(),
+        );
+    }
+
+    #[test]
+    /// show that open ends of an index query can't narrow down the lookup unless there is a start
+    fn index_query_lookup_range() {
+        assert_eq!(
+            IndexQuery::default().lookup_range(),
+            (Bound::Unbounded, Bound::Unbounded)
+        );
+        assert_eq!(
+            IndexQuery::new(1, 5).lookup_range(),
+            (Bound::Included(1), Bound::Included(5))
+        );
+        let end_only = IndexQuery {
+            start: None,
+            end: Some(5),
+            limit: None,
+        };
+        assert_eq!(
+            end_only.lookup_range(),
+            (Bound::Unbounded, Bound::Unbounded)
+        );
+        assert!(end_only.is_in_lookup_range(6));
+        assert!(!IndexQuery::new(1, 5).is_in_lookup_range(6));
+    }
+
+    #[test]
+    /// show that storages can only stop looking up EAVIs at the limit if both ends are set
+    fn index_query_scan_limit() {
+        assert_eq!(IndexQuery::new(1, 5).scan_limit(), None);
+        assert_eq!(IndexQuery::new(1, 5).with_limit(2).scan_limit(), Some(2));
+        assert_eq!(IndexQuery::default().with_limit(2).scan_limit(), None);
+        let start_only = IndexQuery {
+            start: Some(1),
+            end: None,
+            limit: Some(2),
+        };
+        assert_eq!(start_only.scan_limit(), None);
+    }
+
+    #[test]
+    /// show AddressableContent implementation
+    fn addressable_content_test() {
+        // from_content()
+        AddressableContentTestSuite::addressable_content_trait_test::<EntityAttributeValueIndex>(
+            test_eav_content(),
+            test_eav(),
+            test_eav_address(),
+        );
+    }
+
+    #[test]
+    /// show CAS round trip
+    fn cas_round_trip_test() {
+        let addressable_contents = vec![test_eav()];
+        AddressableContentTestSuite::addressable_content_round_trip::<
+            EntityAttributeValueIndex,
+            ExampleContentAddressableStorage,
+        >(addressable_contents, test_content_addressable_storage());
+    }
+
+    #[test]
+    fn validate_attribute_paths() {
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"abc".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_ok());
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"abc123".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_ok());
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"123".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_ok());
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"link_:{}".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_err());
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"link_\"".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_err());
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"link_/".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_err());
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"link_\\".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_err());
+        assert!(EntityAttributeValueIndex::new(
+            &test_eav_entity().address(),
+            &"link_?".to_string(),
+            &test_eav_entity().address()
+        )
+        .is_err());
+    }
+}
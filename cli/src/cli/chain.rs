@@ -0,0 +1,116 @@
+use colored::*;
+use error::DefaultResult;
+use holochain_cas_implementations::{
+    cas::file::FilesystemStorage, encryption::ENCRYPTION_RECORD_ADDRESS,
+};
+use holochain_core::{
+    agent::{chain_archive::ChainArchive, chain_store::ChainStore},
+    persister::SimplePersister,
+};
+use holochain_core_types::json::JsonString;
+use std::{
+    convert::TryFrom,
+    fs,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
+
+const CAS_DIR: &str = "cas";
+
+/// Opens the CAS of an instance's file storage (as configured with `type = "file"`)
+fn open_cas(storage_path: &PathBuf) -> DefaultResult<Arc<RwLock<FilesystemStorage>>> {
+    let cas_path = storage_path.join(CAS_DIR);
+    if cas_path
+        .join(ENCRYPTION_RECORD_ADDRESS)
+        .with_extension("txt")
+        .is_file()
+    {
+        bail!(
+            "{:?} is encrypted, its chain can't be read without the passphrase",
+            storage_path
+        );
+    }
+    Ok(Arc::new(RwLock::new(
+        FilesystemStorage::new(cas_path)?.with_verify_on_read(),
+    )))
+}
+
+/// Writes the source chain persisted in the given file storage to a portable archive.
+pub fn chain_export(storage_path: &PathBuf, archive_path: &PathBuf) -> DefaultResult<()> {
+    let cas = open_cas(storage_path)?;
+    let top_chain_header = SimplePersister::new(cas.clone())
+        .load_snapshot()?
+        .and_then(|snapshot| snapshot.agent)
+        .map(|agent| agent.top_chain_header().clone());
+    if top_chain_header.is_none() {
+        bail!("{:?} does not hold a source chain", storage_path);
+    }
+    let archive = ChainArchive::from_chain(&ChainStore::new(cas), &top_chain_header)?;
+    fs::write(archive_path, JsonString::from(archive.clone()).to_string())?;
+    println!(
+        "{} {} chain items to {:?}",
+        "Exported".green().bold(),
+        archive.items.len(),
+        archive_path
+    );
+    Ok(())
+}
+
+/// Verifies the archived source chain and restores it into the given file storage,
+/// which must not hold a source chain yet. An instance using the storage resumes
+/// with the imported chain.
+pub fn chain_import(archive_path: &PathBuf, storage_path: &PathBuf) -> DefaultResult<()> {
+    let json = fs::read_to_string(archive_path)?;
+    let archive = ChainArchive::try_from(JsonString::from(json))?;
+    let cas = open_cas(storage_path)?;
+    let mut persister = SimplePersister::new(cas.clone());
+    if persister.load_snapshot()?.is_some() {
+        bail!("{:?} already holds a source chain", storage_path);
+    }
+    let snapshot = archive.import(&ChainStore::new(cas), &None)?;
+    persister.save_snapshot(snapshot)?;
+    println!(
+        "{} {} chain items of DNA {} to {:?}",
+        "Imported".green().bold(),
+        archive.items.len(),
+        archive.dna_address,
+        storage_path
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use cli::init::tests::gen_dir;
+    use holochain_core::state::StateSnapshot;
+
+    #[test]
+    fn export_fails_without_chain() {
+        let storage = gen_dir();
+        let archive = gen_dir();
+        assert!(chain_export(
+            &storage.path().to_path_buf(),
+            &archive.path().join("chain.json")
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn import_rejects_invalid_archive() {
+        let storage = gen_dir();
+        let archive = gen_dir();
+        let archive_path = archive.path().join("chain.json");
+        fs::write(
+            &archive_path,
+            "{\"version\":1,\"dna_address\":\"Qm\",\"items\":[]}",
+        )
+        .unwrap();
+        assert!(chain_import(&archive_path, &storage.path().to_path_buf()).is_err());
+
+        // nothing got imported
+        let cas = open_cas(&storage.path().to_path_buf()).unwrap();
+        let snapshot: Option<StateSnapshot> = SimplePersister::new(cas).load_snapshot().unwrap();
+        assert!(snapshot.is_none());
+    }
+}
@@ -0,0 +1,378 @@
+use colored::*;
+use error::DefaultResult;
+use holochain_cas_implementations::encryption::ENCRYPTION_RECORD_ADDRESS;
+use holochain_core_types::{
+    cas::content::{verify_content, Address, AddressableContent, Content},
+    chain_header::ChainHeader,
+    eav::EntityAttributeValueIndex,
+    json::JsonString,
+};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+const CAS_DIR: &str = "cas";
+const EAV_DIR: &str = "eav";
+const QUARANTINE_DIR: &str = "quarantine";
+
+/// A file of the storage that failed one or more checks
+#[derive(Debug)]
+struct BadItem {
+    path: PathBuf,
+    reasons: Vec<String>,
+    /// Whether the file itself is corrupt.
+    /// Intact files that only refer to bad or missing items, like chain headers whose
+    /// predecessor is bad, get reported but never quarantined.
+    corrupt: bool,
+}
+
+impl BadItem {
+    fn new<S: Into<String>>(path: &Path, reason: S) -> Self {
+        BadItem {
+            path: path.to_path_buf(),
+            reasons: vec![reason.into()],
+            corrupt: true,
+        }
+    }
+
+    fn referring<S: Into<String>>(path: &Path, reason: S) -> Self {
+        BadItem {
+            corrupt: false,
+            ..BadItem::new(path, reason)
+        }
+    }
+}
+
+/// Merges the bad items found for the same file, so that every file gets reported
+/// (and quarantined) once
+fn merge_by_path(bad_items: Vec<BadItem>) -> Vec<BadItem> {
+    let mut merged: BTreeMap<PathBuf, BadItem> = BTreeMap::new();
+    for item in bad_items {
+        match merged.remove(&item.path) {
+            Some(mut existing) => {
+                existing.reasons.extend(item.reasons);
+                existing.corrupt |= item.corrupt;
+                merged.insert(existing.path.clone(), existing);
+            }
+            None => {
+                merged.insert(item.path.clone(), item);
+            }
+        }
+    }
+    merged.into_iter().map(|(_, item)| item).collect()
+}
+
+/// Checks the integrity of an instance's file storage (as configured with `type = "file"`).
+/// Every CAS item has to hash to its address, every link of a chain header has to resolve
+/// and every EAVI has to be filed under its own entity, attribute, value and index.
+/// Bad items get reported or, with `quarantine`, moved to a quarantine directory
+/// next to the storage so that they can be inspected without being read by the instance.
+/// Intact items that only refer to bad ones stay in place, they get reported only.
+pub fn fsck(storage_path: &PathBuf, quarantine: bool) -> DefaultResult<()> {
+    let cas_path = storage_path.join(CAS_DIR);
+    let eav_path = storage_path.join(EAV_DIR);
+    if !cas_path.is_dir() && !eav_path.is_dir() {
+        bail!("{:?} does not contain a file storage", storage_path);
+    }
+    if cas_path
+        .join(ENCRYPTION_RECORD_ADDRESS)
+        .with_extension("txt")
+        .is_file()
+    {
+        bail!(
+            "{:?} is encrypted, its content can't be checked without the passphrase",
+            storage_path
+        );
+    }
+
+    let mut bad_items = check_cas(&cas_path)?;
+    bad_items.append(&mut check_eav(&eav_path)?);
+    let bad_items = merge_by_path(bad_items);
+
+    for item in bad_items.iter() {
+        println!(
+            "{} {}: {}",
+            "Bad item".red().bold(),
+            item.path.display(),
+            item.reasons.join(", ")
+        );
+    }
+
+    if bad_items.is_empty() {
+        println!(
+            "{} storage at {:?}",
+            "Verified".green().bold(),
+            storage_path
+        );
+        Ok(())
+    } else if quarantine {
+        let quarantine_path = storage_path.join(QUARANTINE_DIR);
+        let (corrupt_items, referring_items): (Vec<_>, Vec<_>) =
+            bad_items.into_iter().partition(|item| item.corrupt);
+        quarantine_items(storage_path, &quarantine_path, &corrupt_items)?;
+        println!(
+            "{} {} bad items to {:?}",
+            "Quarantined".yellow().bold(),
+            corrupt_items.len(),
+            quarantine_path
+        );
+        if !referring_items.is_empty() {
+            println!(
+                "{} {} intact items that refer to bad or missing items in place",
+                "Left".yellow().bold(),
+                referring_items.len()
+            );
+        }
+        Ok(())
+    } else {
+        bail!(
+            "found {} bad items, run with --quarantine to move them out of the storage",
+            bad_items.len()
+        )
+    }
+}
+
+/// Paths of all files directly in the given directory, empty if it does not exist
+fn files_in(dir: &Path) -> DefaultResult<Vec<PathBuf>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Paths and names of all subdirectories of the given directory, empty if it does not exist
+fn dirs_in(dir: &Path) -> DefaultResult<Vec<(PathBuf, String)>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut dirs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.path().is_dir() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            dirs.push((entry.path(), name));
+        }
+    }
+    Ok(dirs)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Re-hashes all CAS items and checks that the links of all chain headers resolve
+/// to items that are intact themselves.
+fn check_cas(cas_path: &Path) -> DefaultResult<Vec<BadItem>> {
+    let mut bad_items = Vec::new();
+    let mut intact: HashMap<Address, (PathBuf, Content)> = HashMap::new();
+    for path in files_in(cas_path)? {
+        let address = Address::from(file_stem(&path));
+        let content = JsonString::from(fs::read_to_string(&path)?);
+        match verify_content(&address, &content) {
+            Ok(()) => {
+                intact.insert(address, (path, content));
+            }
+            Err(error) => bad_items.push(BadItem::new(&path, error.to_string())),
+        }
+    }
+
+    for (path, content) in intact.values() {
+        if let Ok(header) = ChainHeader::try_from_content(content) {
+            for (name, link) in vec![
+                ("link", header.link()),
+                ("link_same_type", header.link_same_type()),
+            ] {
+                if let Some(link) = link {
+                    if !intact.contains_key(&link) {
+                        bad_items.push(BadItem::referring(
+                            path,
+                            format!("header {} {} does not resolve", name, link),
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    Ok(bad_items)
+}
+
+/// Checks that every EAVI file parses and is filed under its own address and index,
+/// and under the entity, attribute or value of the directory index it is in.
+fn check_eav(eav_path: &Path) -> DefaultResult<Vec<BadItem>> {
+    let mut bad_items = Vec::new();
+    for (subscript_path, subscript) in dirs_in(eav_path)? {
+        for (key_path, key) in dirs_in(&subscript_path)? {
+            for (index_path, index) in dirs_in(&key_path)? {
+                for path in files_in(&index_path)? {
+                    let content = JsonString::from(fs::read_to_string(&path)?);
+                    let eavi = match EntityAttributeValueIndex::try_from_content(&content) {
+                        Ok(eavi) => eavi,
+                        Err(error) => {
+                            bad_items.push(BadItem::new(&path, error.to_string()));
+                            continue;
+                        }
+                    };
+                    let filed_key = match subscript.as_str() {
+                        "e" => eavi.entity().to_string(),
+                        "a" => eavi.attribute(),
+                        "v" => eavi.value().to_string(),
+                        _ => key.clone(),
+                    };
+                    if eavi.address().to_string() != file_stem(&path) {
+                        bad_items.push(BadItem::new(&path, "EAVI does not match its address"));
+                    } else if filed_key != key || eavi.index().to_string() != index {
+                        bad_items.push(BadItem::new(&path, "EAVI is filed in the wrong place"));
+                    }
+                }
+            }
+        }
+    }
+    Ok(bad_items)
+}
+
+/// Moves the bad items to the quarantine directory, keeping their paths relative to the storage
+fn quarantine_items(
+    storage_path: &Path,
+    quarantine_path: &Path,
+    bad_items: &Vec<BadItem>,
+) -> DefaultResult<()> {
+    for item in bad_items {
+        let relative_path = item.path.strip_prefix(storage_path)?;
+        let target = quarantine_path.join(relative_path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::rename(&item.path, &target)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use cli::init::tests::gen_dir;
+    use holochain_cas_implementations::{cas::file::FilesystemStorage, eav::file::EavFileStorage};
+    use holochain_core_types::{
+        cas::storage::ContentAddressableStorage,
+        chain_header::test_chain_header,
+        eav::EntityAttributeValueStorage,
+        entry::{test_entry, test_entry_b},
+    };
+
+    fn file_storage(storage_path: &Path) -> (FilesystemStorage, EavFileStorage) {
+        (
+            FilesystemStorage::new(storage_path.join(CAS_DIR)).unwrap(),
+            EavFileStorage::new(storage_path.join(EAV_DIR)).unwrap(),
+        )
+    }
+
+    fn next_header(previous: &ChainHeader) -> ChainHeader {
+        ChainHeader::new(
+            previous.entry_type(),
+            previous.entry_address(),
+            previous.provenances(),
+            &Some(previous.address()),
+            &Some(previous.address()),
+            &None,
+            previous.timestamp(),
+        )
+    }
+
+    #[test]
+    fn intact_storage_passes() {
+        let dir = gen_dir();
+        let (mut cas, mut eav) = file_storage(dir.path());
+        let header = test_chain_header();
+        cas.add(&test_entry()).unwrap();
+        cas.add(&header).unwrap();
+        cas.add(&next_header(&header)).unwrap();
+        eav.add_eavi(
+            &EntityAttributeValueIndex::new(
+                &test_entry().address(),
+                &"link__tag".to_string(),
+                &test_entry_b().address(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        assert!(check_cas(&dir.path().join(CAS_DIR)).unwrap().is_empty());
+        assert!(check_eav(&dir.path().join(EAV_DIR)).unwrap().is_empty());
+        assert!(fsck(&dir.path().to_path_buf(), false).is_ok());
+    }
+
+    #[test]
+    fn finds_tampered_items_and_dangling_links() {
+        let dir = gen_dir();
+        let (mut cas, _) = file_storage(dir.path());
+        let header = test_chain_header();
+        cas.add(&test_entry()).unwrap();
+        cas.add(&next_header(&header)).unwrap();
+        let entry_path = dir
+            .path()
+            .join(CAS_DIR)
+            .join(test_entry().address().to_string())
+            .with_extension("txt");
+        fs::write(&entry_path, "tampered").unwrap();
+
+        let bad_items = merge_by_path(check_cas(&dir.path().join(CAS_DIR)).unwrap());
+        // the tampered entry and the header with both links to the missing previous header
+        assert_eq!(bad_items.len(), 2);
+        assert!(bad_items
+            .iter()
+            .any(|item| item.path == entry_path && item.corrupt));
+        assert!(bad_items
+            .iter()
+            .any(|item| item.reasons.len() == 2 && !item.corrupt));
+        assert!(fsck(&dir.path().to_path_buf(), false).is_err());
+    }
+
+    #[test]
+    fn leaves_headers_with_bad_predecessors_in_place() {
+        let dir = gen_dir();
+        let (mut cas, _) = file_storage(dir.path());
+        let header = test_chain_header();
+        let next = next_header(&header);
+        cas.add(&header).unwrap();
+        cas.add(&next).unwrap();
+        let header_file = PathBuf::from(CAS_DIR)
+            .join(header.address().to_string())
+            .with_extension("txt");
+        let next_file = PathBuf::from(CAS_DIR)
+            .join(next.address().to_string())
+            .with_extension("txt");
+        fs::write(dir.path().join(&header_file), "tampered").unwrap();
+
+        assert!(fsck(&dir.path().to_path_buf(), true).is_ok());
+        assert!(dir.path().join(QUARANTINE_DIR).join(&header_file).exists());
+        assert!(dir.path().join(&next_file).exists());
+        assert!(!dir.path().join(QUARANTINE_DIR).join(&next_file).exists());
+    }
+
+    #[test]
+    fn quarantines_bad_items() {
+        let dir = gen_dir();
+        let (mut cas, _) = file_storage(dir.path());
+        cas.add(&test_entry()).unwrap();
+        let entry_file = PathBuf::from(CAS_DIR)
+            .join(test_entry().address().to_string())
+            .with_extension("txt");
+        fs::write(dir.path().join(&entry_file), "tampered").unwrap();
+
+        assert!(fsck(&dir.path().to_path_buf(), true).is_ok());
+        assert!(!dir.path().join(&entry_file).exists());
+        assert!(dir.path().join(QUARANTINE_DIR).join(&entry_file).exists());
+        assert!(fsck(&dir.path().to_path_buf(), false).is_ok());
+    }
+}
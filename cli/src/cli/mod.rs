@@ -1,17 +1,23 @@
 mod agent;
+mod chain;
+mod fsck;
 mod generate;
 mod init;
 pub mod package;
 mod run;
 mod scaffold;
+mod storage;
 pub mod test;
 mod test_context;
 
 pub use self::{
     agent::agent,
+    chain::{chain_export, chain_import},
+    fsck::fsck,
     generate::generate,
     init::init,
     package::{package, unpack},
     run::run,
+    storage::{storage_migrate, StorageLocation},
     test::{test, TEST_DIR_NAME},
 };
@@ -35,6 +35,7 @@ pub fn run(
         name: agent.nick,
         public_address: agent.key,
         key_file: "hc_run.key".into(),
+        test_agent: Some(true),
     };
 
     let dna_config = DnaConfiguration {
@@ -48,6 +49,8 @@ pub fn run(
 
         StorageConfiguration::File {
             path: LOCAL_STORAGE_PATH.into(),
+            encrypted: None,
+            verify_on_read: None,
         }
     } else {
         StorageConfiguration::Memory
@@ -58,6 +61,7 @@ pub fn run(
         dna: DNA_CONFIG_ID.into(),
         agent: AGENT_CONFIG_ID.into(),
         storage,
+        membrane_proof: None,
     };
 
     let interface_type = env::var("HC_INTERFACE").ok().unwrap_or_else(|| interface);
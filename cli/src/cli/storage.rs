@@ -0,0 +1,186 @@
+use colored::*;
+use error::DefaultResult;
+use holochain_cas_implementations::{
+    cas::{file::FilesystemStorage, lmdb::LmdbStorage},
+    eav::{file::EavFileStorage, lmdb::EavLmdbStorage},
+    encryption::ENCRYPTION_RECORD_ADDRESS,
+    migration::migrate,
+};
+use holochain_core_types::{
+    cas::{content::Address, storage::ContentAddressableStorage},
+    eav::EntityAttributeValueStorage,
+};
+use std::{fmt, path::PathBuf, str::FromStr};
+
+const CAS_DIR: &str = "cas";
+const EAV_DIR: &str = "eav";
+
+/// A persistent storage given as `<backend>:<path>`, with the backends and directory layout
+/// of the storages an instance can be configured with (`type = "file"` or `type = "lmdb"`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum StorageLocation {
+    File(PathBuf),
+    Lmdb(PathBuf),
+}
+
+impl StorageLocation {
+    fn path(&self) -> &PathBuf {
+        match self {
+            StorageLocation::File(path) | StorageLocation::Lmdb(path) => path,
+        }
+    }
+
+    fn open(
+        &self,
+    ) -> DefaultResult<(
+        Box<ContentAddressableStorage>,
+        Box<EntityAttributeValueStorage>,
+    )> {
+        let cas_path = self.path().join(CAS_DIR);
+        let eav_path = self.path().join(EAV_DIR);
+        let cas: Box<ContentAddressableStorage> = match self {
+            StorageLocation::File(_) => {
+                Box::new(FilesystemStorage::new(cas_path)?.with_verify_on_read())
+            }
+            StorageLocation::Lmdb(_) => Box::new(LmdbStorage::new(cas_path)?.with_verify_on_read()),
+        };
+        let eav: Box<EntityAttributeValueStorage> = match self {
+            StorageLocation::File(_) => Box::new(EavFileStorage::new(eav_path)?),
+            StorageLocation::Lmdb(_) => Box::new(EavLmdbStorage::new(eav_path)?),
+        };
+        Ok((cas, eav))
+    }
+}
+
+impl FromStr for StorageLocation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.splitn(2, ':');
+        let backend = parts.next().unwrap_or_default();
+        let path = match parts.next() {
+            Some(path) if !path.is_empty() => PathBuf::from(path),
+            _ => return Err(format!("expected <backend>:<path> but got \"{}\"", s)),
+        };
+        match backend {
+            "file" => Ok(StorageLocation::File(path)),
+            "lmdb" => Ok(StorageLocation::Lmdb(path)),
+            _ => Err(format!(
+                "unknown storage backend \"{}\", expected file or lmdb",
+                backend
+            )),
+        }
+    }
+}
+
+impl fmt::Display for StorageLocation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageLocation::File(path) => write!(f, "file:{}", path.display()),
+            StorageLocation::Lmdb(path) => write!(f, "lmdb:{}", path.display()),
+        }
+    }
+}
+
+/// Copies all CAS items and EAVIs of an instance's storage to an empty storage
+/// of another (or the same) backend and verifies the copy.
+/// The source storage is left as it is.
+pub fn storage_migrate(from: &StorageLocation, to: &StorageLocation) -> DefaultResult<()> {
+    if !from.path().join(CAS_DIR).is_dir() && !from.path().join(EAV_DIR).is_dir() {
+        bail!("{} does not contain a storage", from);
+    }
+    if from.path() == to.path() {
+        bail!("can't migrate {} onto itself", from);
+    }
+    let (from_cas, from_eav) = from.open()?;
+    if from_cas.contains(&Address::from(ENCRYPTION_RECORD_ADDRESS))? {
+        bail!(
+            "{} is encrypted, its content can't be migrated without the passphrase",
+            from
+        );
+    }
+    let (mut to_cas, mut to_eav) = to.open()?;
+    let report = migrate(&*from_cas, &*from_eav, &mut *to_cas, &mut *to_eav)?;
+    println!(
+        "{} {} CAS items and {} EAVIs from {} to {}",
+        "Migrated".green().bold(),
+        report.cas_items,
+        report.eavis,
+        from,
+        to
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use cli::init::tests::gen_dir;
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        eav::{EntityAttributeValueIndex, IndexQuery},
+        entry::{test_entry, test_entry_b},
+    };
+
+    #[test]
+    fn parses_storage_locations() {
+        assert_eq!(
+            "file:some/path".parse(),
+            Ok(StorageLocation::File(PathBuf::from("some/path")))
+        );
+        assert_eq!(
+            "lmdb:/some/path".parse(),
+            Ok(StorageLocation::Lmdb(PathBuf::from("/some/path")))
+        );
+        assert!("memory:some/path".parse::<StorageLocation>().is_err());
+        assert!("file:".parse::<StorageLocation>().is_err());
+        assert!("some/path".parse::<StorageLocation>().is_err());
+    }
+
+    #[test]
+    fn migrates_file_storage_to_lmdb() {
+        let from_dir = gen_dir();
+        let to_dir = gen_dir();
+        let from = StorageLocation::File(from_dir.path().to_path_buf());
+        let to = StorageLocation::Lmdb(to_dir.path().join("storage"));
+        let eavi = EntityAttributeValueIndex::new(
+            &test_entry().address(),
+            &"link__tag".to_string(),
+            &test_entry_b().address(),
+        )
+        .unwrap();
+        {
+            let (mut cas, mut eav) = from.open().unwrap();
+            cas.add(&test_entry()).unwrap();
+            eav.add_eavi(&eavi).unwrap();
+        }
+
+        storage_migrate(&from, &to).unwrap();
+        {
+            let (cas, eav) = to.open().unwrap();
+            assert_eq!(
+                cas.fetch(&test_entry().address()).unwrap(),
+                Some(test_entry().content())
+            );
+            assert_eq!(
+                eav.fetch_eavi(None, None, None, IndexQuery::default())
+                    .unwrap(),
+                vec![eavi].into_iter().collect()
+            );
+        }
+
+        // the target is not empty anymore
+        assert!(storage_migrate(&from, &to).is_err());
+    }
+
+    #[test]
+    fn migrate_fails_without_storage() {
+        let from_dir = gen_dir();
+        let to_dir = gen_dir();
+        assert!(storage_migrate(
+            &StorageLocation::File(from_dir.path().to_path_buf()),
+            &StorageLocation::Lmdb(to_dir.path().to_path_buf())
+        )
+        .is_err());
+    }
+}
@@ -119,6 +119,38 @@ enum Cli {
         )]
         interface: String,
     },
+    #[structopt(
+        name = "fsck",
+        about = "Checks the integrity of an instance's file storage"
+    )]
+    Fsck {
+        #[structopt(
+            help = "The storage directory of the instance (containing the cas and eav directories)",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+        #[structopt(
+            long,
+            help = "Move bad items to a quarantine directory inside the storage directory"
+        )]
+        quarantine: bool,
+    },
+    #[structopt(
+        name = "chain",
+        about = "Exports or imports the source chain of an instance's file storage"
+    )]
+    Chain {
+        #[structopt(subcommand)]
+        command: ChainCommand,
+    },
+    #[structopt(
+        name = "storage",
+        about = "Moves the storage of an instance between storage backends"
+    )]
+    Storage {
+        #[structopt(subcommand)]
+        command: StorageCommand,
+    },
     #[structopt(
         name = "test",
         alias = "t",
@@ -144,6 +176,56 @@ enum Cli {
     },
 }
 
+#[derive(StructOpt)]
+enum ChainCommand {
+    #[structopt(
+        name = "export",
+        about = "Writes the source chain of a file storage to a portable archive"
+    )]
+    Export {
+        #[structopt(
+            help = "The storage directory of the instance (containing the cas directory)",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+        #[structopt(help = "The archive file to write", parse(from_os_str))]
+        archive: PathBuf,
+    },
+    #[structopt(
+        name = "import",
+        about = "Verifies an archived source chain and restores it into an empty file storage"
+    )]
+    Import {
+        #[structopt(help = "The archive file to read", parse(from_os_str))]
+        archive: PathBuf,
+        #[structopt(
+            help = "The storage directory of the instance (containing the cas directory)",
+            parse(from_os_str)
+        )]
+        path: PathBuf,
+    },
+}
+
+#[derive(StructOpt)]
+enum StorageCommand {
+    #[structopt(
+        name = "migrate",
+        about = "Copies all content of a storage to an empty storage and verifies the copy"
+    )]
+    Migrate {
+        #[structopt(
+            long,
+            help = "The storage to migrate, as <backend>:<path> with backend file or lmdb"
+        )]
+        from: cli::StorageLocation,
+        #[structopt(
+            long,
+            help = "The empty storage to migrate to, as <backend>:<path> with backend file or lmdb"
+        )]
+        to: cli::StorageLocation,
+    },
+}
+
 fn main() {
     run().unwrap_or_else(|err| {
         eprintln!("{}", err);
@@ -173,6 +255,18 @@ fn run() -> HolochainResult<()> {
             interface,
         } => cli::run(package, port, persist, networked, interface)
             .map_err(HolochainError::Default)?,
+        Cli::Fsck { path, quarantine } => {
+            cli::fsck(&path, quarantine).map_err(HolochainError::Default)?
+        }
+        Cli::Chain { command } => match command {
+            ChainCommand::Export { path, archive } => cli::chain_export(&path, &archive),
+            ChainCommand::Import { archive, path } => cli::chain_import(&archive, &path),
+        }
+        .map_err(HolochainError::Default)?,
+        Cli::Storage { command } => match command {
+            StorageCommand::Migrate { from, to } => cli::storage_migrate(&from, &to),
+        }
+        .map_err(HolochainError::Default)?,
         Cli::Test {
             dir,
             testfile,
@@ -6,9 +6,12 @@ use crate::{
     },
     error::HolochainInstanceError,
 };
-use holochain_core_types::{cas::content::AddressableContent, error::HolochainError};
+use holochain_core::agent::chain_archive::ChainArchive;
+use holochain_core_types::{
+    cas::content::AddressableContent, error::HolochainError, json::JsonString,
+};
 use json_patch;
-use std::{fs, path::PathBuf, sync::Arc};
+use std::{convert::TryFrom, fs, path::PathBuf, sync::Arc};
 
 pub trait ConductorAdmin {
     fn install_dna_from_file(
@@ -24,10 +27,21 @@ pub trait ConductorAdmin {
         id: &String,
         dna_id: &String,
         agent_id: &String,
+        membrane_proof: Option<String>,
     ) -> Result<(), HolochainError>;
     fn remove_instance(&mut self, id: &String) -> Result<(), HolochainError>;
     fn start_instance(&mut self, id: &String) -> Result<(), HolochainInstanceError>;
     fn stop_instance(&mut self, id: &String) -> Result<(), HolochainInstanceError>;
+    fn export_instance_chain(
+        &mut self,
+        id: &String,
+        path: PathBuf,
+    ) -> Result<(), HolochainInstanceError>;
+    fn import_instance_chain(
+        &mut self,
+        id: &String,
+        path: PathBuf,
+    ) -> Result<(), HolochainInstanceError>;
     fn add_interface(&mut self, new_instance: InterfaceConfiguration)
         -> Result<(), HolochainError>;
     fn remove_interface(&mut self, id: &String) -> Result<(), HolochainError>;
@@ -160,6 +174,7 @@ impl ConductorAdmin for Conductor {
         id: &String,
         dna_id: &String,
         agent_id: &String,
+        membrane_proof: Option<String>,
     ) -> Result<(), HolochainError> {
         let mut new_config = self.config.clone();
         let storage_path = self.instance_storage_dir_path().join(id.clone());
@@ -175,7 +190,10 @@ impl ConductorAdmin for Conductor {
                         format!("invalid path {:?}", storage_path).into(),
                     ))?
                     .into(),
+                encrypted: None,
+                verify_on_read: None,
             },
+            membrane_proof,
         };
         new_config.instances.push(new_instance);
         new_config.check_consistency()?;
@@ -224,6 +242,39 @@ impl ConductorAdmin for Conductor {
         instance.write().unwrap().stop()
     }
 
+    /// Writes a portable archive of the instance's source chain to the given file.
+    fn export_instance_chain(
+        &mut self,
+        id: &String,
+        path: PathBuf,
+    ) -> Result<(), HolochainInstanceError> {
+        let instance = self.instances.get(id)?;
+        let archive = instance.read().unwrap().export_chain()?;
+        fs::write(&path, JsonString::from(archive).to_string()).map_err(HolochainError::from)?;
+        notify(format!(
+            "Exported source chain of instance \"{}\" to {:?}.",
+            id, path
+        ));
+        Ok(())
+    }
+
+    /// Replaces the source chain of the (stopped) instance with the one archived in the
+    /// given file, after verifying its hash chain, header links and signatures.
+    fn import_instance_chain(
+        &mut self,
+        id: &String,
+        path: PathBuf,
+    ) -> Result<(), HolochainInstanceError> {
+        let instance = self.instances.get(id)?;
+        let json = fs::read_to_string(&path).map_err(HolochainError::from)?;
+        let archive = ChainArchive::try_from(JsonString::from(json))?;
+        notify(format!(
+            "Importing source chain into instance \"{}\"...",
+            id
+        ));
+        instance.write().unwrap().import_chain(&archive)
+    }
+
     fn add_interface(&mut self, interface: InterfaceConfiguration) -> Result<(), HolochainError> {
         let mut new_config = self.config.clone();
         if new_config
@@ -512,8 +563,9 @@ pub mod tests {
         conductor::base::{tests::example_dna_string, DnaLoader},
         config::{load_configuration, Configuration, InterfaceConfiguration, InterfaceDriver},
     };
-    use holochain_core_types::{agent::AgentId, dna::Dna, json::JsonString};
-    use std::{convert::TryFrom, env::current_dir, fs::File, io::Read};
+    use holochain_core_types::{agent::AgentId, dna::Dna};
+    use std::{env::current_dir, fs::File, io::Read};
+    use tempfile::tempdir;
 
     pub fn test_dna_loader() -> DnaLoader {
         let loader = Box::new(|_: &PathBuf| {
@@ -556,7 +608,8 @@ pub mod tests {
 id = 'test-agent-1'
 key_file = 'holo_tester.key'
 name = 'Holo Tester 1'
-public_address = 'HoloTester1-----------------------------------------------------------------------AAACZp4xHB'"#
+public_address = 'HoloTester1-----------------------------------------------------------------------AAACZp4xHB'
+test_agent = true"#
     .to_string()
     }
 
@@ -565,7 +618,8 @@ public_address = 'HoloTester1---------------------------------------------------
 id = 'test-agent-2'
 key_file = 'holo_tester.key'
 name = 'Holo Tester 2'
-public_address = 'HoloTester2-----------------------------------------------------------------------AAAGy4WW9e'"#
+public_address = 'HoloTester2-----------------------------------------------------------------------AAAGy4WW9e'
+test_agent = true"#
     .to_string()
     }
 
@@ -864,6 +918,7 @@ id = 'new-dna'"#,
             &String::from("new-instance"),
             &String::from("new-dna"),
             &String::from("test-agent-1"),
+            None,
         );
 
         assert_eq!(add_result, Ok(()));
@@ -1040,6 +1095,46 @@ type = 'websocket'"#,
         );
     }
 
+    #[test]
+    fn test_export_and_import_instance_chain() {
+        let test_name = "test_export_and_import_instance_chain";
+        let mut conductor = create_test_conductor(test_name, 3012);
+        let archive_dir = tempdir().unwrap();
+        let archive_path = archive_dir.path().join("chain.json");
+        let instance_id = String::from("test-instance-1");
+
+        assert_eq!(
+            conductor.export_instance_chain(&instance_id, archive_path.clone()),
+            Ok(())
+        );
+        let top_chain_header = conductor
+            .instances()
+            .get(&instance_id)
+            .unwrap()
+            .read()
+            .unwrap()
+            .state()
+            .unwrap()
+            .agent()
+            .top_chain_header();
+        let json = fs::read_to_string(&archive_path).unwrap();
+        let archive = ChainArchive::try_from(JsonString::from(json)).unwrap();
+        assert_eq!(archive.top_chain_header(), top_chain_header);
+
+        assert_eq!(
+            conductor.import_instance_chain(&instance_id, archive_path.clone()),
+            Ok(())
+        );
+        // the chain of another agent gets rejected
+        assert!(conductor
+            .import_instance_chain(&String::from("test-instance-2"), archive_path.clone())
+            .is_err());
+        assert_eq!(
+            conductor.import_instance_chain(&String::from("non-existant-id"), archive_path),
+            Err(HolochainInstanceError::NoSuchInstance),
+        );
+    }
+
     #[test]
     fn test_add_interface() {
         let test_name = "test_add_interface";
@@ -1145,7 +1240,8 @@ type = 'http'"#,
             conductor.add_instance(
                 &String::from("new-instance"),
                 &String::from("test-dna"),
-                &String::from("test-agent-1")
+                &String::from("test-agent-1"),
+                None,
             ),
             Ok(())
         );
@@ -1288,6 +1384,7 @@ type = 'websocket'"#,
             name: String::from("Mr. New"),
             public_address: AgentId::generate_fake("new").address().to_string(),
             key_file: String::from("new-test-path"),
+            test_agent: None,
         };
 
         assert_eq!(conductor.add_agent(agent_config), Ok(()),);
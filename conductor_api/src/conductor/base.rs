@@ -9,27 +9,32 @@ use crate::{
     Holochain,
 };
 use holochain_core::{
+    context::AgentKeyStore,
     logger::{ChannelLogger, Logger},
-    signal::Signal,
+    signal::{signal_channel, Signal},
 };
 use holochain_core_types::{
-    agent::{AgentId, KeyBuffer},
+    agent::KeyBuffer,
     cas::content::AddressableContent,
     dna::Dna,
     error::HolochainError,
     json::JsonString,
 };
+use holochain_dpki::{bundle::KeyBundle, keypair::Keypair};
+use holochain_sodium::secbuf::SecBuf;
 use jsonrpc_ws_server::jsonrpc_core::IoHandler;
 
 use std::{
     clone::Clone,
     collections::HashMap,
     convert::TryFrom,
+    env,
     fs::{self, File},
     io::prelude::*,
+    mem,
     path::PathBuf,
     sync::{
-        mpsc::{channel, Sender, SyncSender},
+        mpsc::{channel, Receiver, Sender, SyncSender},
         Arc, Mutex, RwLock,
     },
     thread,
@@ -40,6 +45,7 @@ use holochain_net_connection::net_connection::NetShutdown;
 use holochain_net_ipc::spawn::{ipc_spawn, SpawnResult};
 use interface::{ConductorApiBuilder, InstanceMap, Interface};
 use static_file_server::StaticServer;
+use toml;
 
 lazy_static! {
     /// This is a global and mutable Conductor singleton.
@@ -74,14 +80,21 @@ pub fn mount_conductor_from_config(config: Configuration) {
 /// In order to not bind this code to the assumption that there is a filesystem
 /// and also enable easier testing, a DnaLoader ()which is a closure that returns a
 /// Dna object for a given path string) has to be injected on creation.
+/// The same goes for agent keys which get loaded through a KeyLoader.
+/// Interfaces with a custom driver get created by InterfaceFactories that embedders
+/// register by name.
 pub struct Conductor {
     pub(in crate::conductor) instances: InstanceMap,
     pub(in crate::conductor) config: Configuration,
     pub(in crate::conductor) static_servers: HashMap<String, StaticServer>,
     pub(in crate::conductor) interface_threads: HashMap<String, Sender<()>>,
     pub(in crate::conductor) dna_loader: DnaLoader,
+    pub(in crate::conductor) key_loader: KeyLoader,
+    pub(in crate::conductor) passphrase_loader: PassphraseLoader,
     pub(in crate::conductor) ui_dir_copier: UiDirCopier,
+    pub(in crate::conductor) interface_factories: HashMap<String, InterfaceFactory>,
     signal_tx: Option<SignalSender>,
+    signal_subscribers: SignalSubscribers,
     logger: DebugLogger,
     p2p_config: Option<JsonString>,
     network_child_process: NetShutdown,
@@ -96,9 +109,23 @@ impl Drop for Conductor {
 }
 
 type SignalSender = SyncSender<Signal>;
+/// Channels that running websocket interfaces push user signals to their clients through,
+/// by interface id, together with the ids of the instances each interface exposes
+type SignalSubscribers = Arc<RwLock<HashMap<String, (Vec<String>, Sender<Signal>)>>>;
 pub type DnaLoader = Arc<Box<FnMut(&PathBuf) -> Result<Dna, HolochainError> + Send + Sync>>;
+pub type KeyLoader = Arc<Box<FnMut(&PathBuf) -> Result<Keypair, HolochainError> + Send + Sync>>;
+/// Returns the passphrase for decrypting agent keys or instance storage.
+/// The argument describes what the passphrase is needed for.
+pub type PassphraseLoader = Arc<Box<Fn(&str) -> Result<SecBuf, HolochainError> + Send + Sync>>;
 pub type UiDirCopier =
     Arc<Box<FnMut(&PathBuf, &PathBuf) -> Result<(), HolochainError> + Send + Sync>>;
+/// Creates an Interface from the TOML table of an `InterfaceDriver::Custom` configuration.
+pub type InterfaceFactory =
+    Arc<Box<Fn(&toml::value::Value) -> Result<Box<Interface>, String> + Send + Sync>>;
+
+/// Name of the environment variable the default PassphraseLoader reads the passphrase
+/// for decrypting agent key bundles and instance storage from
+pub const PASSPHRASE_ENV_VAR: &str = "HC_PASSPHRASE";
 
 // preparing for having conductor notifiers go to one of the log streams
 pub fn notify(msg: String) {
@@ -108,6 +135,7 @@ pub fn notify(msg: String) {
 impl Conductor {
     pub fn from_config(config: Configuration) -> Self {
         let rules = config.logger.rules.clone();
+        let passphrase_loader: PassphraseLoader = Arc::new(Box::new(Self::passphrase_from_env));
 
         Conductor {
             instances: HashMap::new(),
@@ -115,8 +143,12 @@ impl Conductor {
             static_servers: HashMap::new(),
             config,
             dna_loader: Arc::new(Box::new(Self::load_dna)),
+            key_loader: Self::default_key_loader(passphrase_loader.clone()),
+            passphrase_loader,
             ui_dir_copier: Arc::new(Box::new(Self::copy_ui_dir)),
+            interface_factories: HashMap::new(),
             signal_tx: None,
+            signal_subscribers: Arc::new(RwLock::new(HashMap::new())),
             logger: DebugLogger::new(rules),
             p2p_config: None,
             network_child_process: None,
@@ -135,6 +167,22 @@ impl Conductor {
         self.config.clone()
     }
 
+    /// Registers a factory for interfaces configured with a custom driver whose `name`
+    /// property matches the given name, e.g.:
+    /// ```toml
+    /// [interfaces.driver]
+    /// type = "custom"
+    /// name = "stdio"
+    /// ```
+    /// Interfaces that are already running are not affected.
+    pub fn register_interface_factory<F>(&mut self, name: &str, factory: F)
+    where
+        F: Fn(&toml::value::Value) -> Result<Box<Interface>, String> + Send + Sync + 'static,
+    {
+        self.interface_factories
+            .insert(name.to_string(), Arc::new(Box::new(factory)));
+    }
+
     pub fn start_all_interfaces(&mut self) {
         self.interface_threads = self
             .config
@@ -145,6 +193,7 @@ impl Conductor {
     }
 
     pub fn stop_all_interfaces(&mut self) {
+        self.signal_subscribers.write().unwrap().clear();
         for (id, kill_switch) in self.interface_threads.iter() {
             notify(format!("Stopping interface {}", id));
             let _ = kill_switch.send(()).map_err(|err| {
@@ -165,6 +214,7 @@ impl Conductor {
                         id
                     )))?;
             notify(format!("Stopping interface {}", id));
+            self.signal_subscribers.write().unwrap().remove(id);
             kill_switch.send(()).map_err(|err| {
                 let message = format!("Error stopping interface: {}", err);
                 notify(message.clone());
@@ -399,23 +449,105 @@ impl Conductor {
 
                 // Agent:
                 let agent_config = config.agent_by_id(&instance_config.agent).unwrap();
-                let pub_key = KeyBuffer::with_corrected(&agent_config.public_address)?;
-                context_builder =
-                    context_builder.with_agent(AgentId::new(&agent_config.name, &pub_key));
+                let mut maybe_rotated_key_file = None;
+                let keypair = if agent_config.test_agent.unwrap_or(false) {
+                    Keypair::new_from_insecure_string(&agent_config.name)?
+                } else {
+                    let key_file = PathBuf::from(&agent_config.key_file);
+                    let keypair = Arc::get_mut(&mut self.key_loader).unwrap()(&key_file).map_err(
+                        |hc_err| {
+                            format!(
+                                "Could not load key file \"{}\": {}",
+                                agent_config.key_file, hc_err
+                            )
+                        },
+                    )?;
+                    let pub_key = KeyBuffer::with_corrected(&agent_config.public_address)?;
+                    if keypair.pub_keys != pub_key.render() {
+                        return Err(format!(
+                            "Key file \"{}\" does not match public address of agent \"{}\"",
+                            agent_config.key_file, agent_config.id
+                        ));
+                    }
+                    maybe_rotated_key_file =
+                        Some(Self::rotated_key_file(&key_file, &instance_config.id));
+                    keypair
+                };
+                let mut agent_id = keypair.agent_id(&agent_config.name)?;
+                if let Some(membrane_proof) = instance_config.membrane_proof.clone() {
+                    agent_id = agent_id.with_membrane_proof(membrane_proof);
+                }
+                context_builder = context_builder.with_agent(agent_id);
+                // The agent keeps its identity across key rotations,
+                // but signs with the key it rotated to last.
+                // The keys it rotated away from still open entries sealed for them.
+                match maybe_rotated_key_file {
+                    Some(rotated_key_file) => {
+                        let mut keypair = keypair;
+                        let mut previous_keypairs = Vec::new();
+                        let mut key_files = Self::previous_rotated_key_files(&rotated_key_file);
+                        if rotated_key_file.exists() {
+                            key_files.push(rotated_key_file.clone());
+                        }
+                        for key_file in key_files {
+                            let rotated_keypair = Arc::get_mut(&mut self.key_loader).unwrap()(
+                                &key_file,
+                            )
+                            .map_err(|hc_err| {
+                                format!(
+                                    "Could not load rotated key file \"{}\": {}",
+                                    key_file.display(),
+                                    hc_err
+                                )
+                            })?;
+                            previous_keypairs.push(mem::replace(&mut keypair, rotated_keypair));
+                        }
+                        context_builder = context_builder
+                            .with_agent_keypair(keypair)
+                            .with_previous_agent_keypairs(previous_keypairs)
+                            .with_agent_key_store(Self::agent_key_store(
+                                rotated_key_file,
+                                self.passphrase_loader.clone(),
+                            ));
+                    }
+                    None => context_builder = context_builder.with_agent_keypair(keypair),
+                }
 
                 context_builder = context_builder.with_network_config(self.instance_p2p_config()?);
 
-                // Signal config:
-                if let Some(tx) = signal_tx {
-                    context_builder = context_builder.with_signals(tx)
-                };
-
                 // Storage:
-                if let StorageConfiguration::File { path } = instance_config.storage {
-                    context_builder = context_builder.with_file_storage(path).map_err(|hc_err| {
-                        format!("Error creating context: {}", hc_err.to_string())
-                    })?
+                let encrypted = instance_config.storage.is_encrypted();
+                let verify_on_read = instance_config.storage.verifies_on_read();
+                // encrypted storages verify the decrypted content, not what they store
+                if verify_on_read && !encrypted {
+                    context_builder = context_builder.with_verify_on_read();
+                }
+                match instance_config.storage {
+                    StorageConfiguration::File { path, .. } => {
+                        context_builder =
+                            context_builder.with_file_storage(path).map_err(|hc_err| {
+                                format!("Error creating context: {}", hc_err.to_string())
+                            })?;
+                    }
+                    StorageConfiguration::Lmdb { path, map_size, .. } => {
+                        context_builder =
+                            context_builder.with_lmdb_storage(path, map_size).map_err(
+                                |hc_err| format!("Error creating context: {}", hc_err.to_string()),
+                            )?;
+                    }
+                    StorageConfiguration::Memory => {}
                 };
+                if encrypted {
+                    if verify_on_read {
+                        context_builder = context_builder.with_verify_on_read();
+                    }
+                    let mut passphrase = (self.passphrase_loader)("encrypt instance storage")?;
+                    context_builder = context_builder
+                        .with_storage_encryption(&mut passphrase)
+                        .map_err(|hc_err| {
+                            format!("Error creating context: {}", hc_err.to_string())
+                        })?;
+                }
 
                 if config.logger.logger_type == "debug" {
                     context_builder = context_builder.with_logger(Arc::new(Mutex::new(
@@ -445,9 +577,20 @@ impl Conductor {
                         .with_named_instance_config(bridge.handle.clone(), callee_config);
                 }
                 context_builder = context_builder.with_conductor_api(api_builder.spawn());
-                if let Some(signal_tx) = self.signal_tx.clone() {
-                    context_builder = context_builder.with_signals(signal_tx);
-                }
+
+                // Signals:
+                // Internal signals only go to the embedding application, user signals also go
+                // to the websocket interfaces exposing the instance when they get emitted,
+                // which includes interfaces that start exposing it after it got created.
+                let signal_tx = self.signal_tx.clone().or(signal_tx);
+                context_builder = context_builder.with_instance_id(id.clone());
+                context_builder = match signal_tx {
+                    Some(signal_tx) => context_builder
+                        .with_signals(self.spawn_signal_forwarding(&id, Some(signal_tx))),
+                    None => {
+                        context_builder.with_user_signals(self.spawn_signal_forwarding(&id, None))
+                    }
+                };
 
                 // Spawn context
                 let context = context_builder.spawn();
@@ -466,6 +609,41 @@ impl Conductor {
             })
     }
 
+    /// Creates the signal channel of an instance.
+    /// User signals get pushed to the websocket interfaces that expose the instance at the
+    /// time the signal arrives, all signals get passed on to the given signal channel of the
+    /// embedding application.
+    fn spawn_signal_forwarding(
+        &self,
+        instance_id: &String,
+        signal_tx: Option<SignalSender>,
+    ) -> SignalSender {
+        let (tx, rx) = signal_channel();
+        let subscribers = self.signal_subscribers.clone();
+        thread::Builder::new()
+            .name(format!("conductor-signals: {}", instance_id))
+            .spawn(move || {
+                // ends when the instance's context and with it the sending end got dropped
+                while let Ok(signal) = rx.recv() {
+                    if let Signal::User {
+                        ref instance_id, ..
+                    } = signal
+                    {
+                        for (instance_ids, subscriber) in subscribers.read().unwrap().values() {
+                            if instance_ids.contains(instance_id) {
+                                let _ = subscriber.send(signal.clone());
+                            }
+                        }
+                    }
+                    if let Some(ref signal_tx) = signal_tx {
+                        let _ = signal_tx.send(signal);
+                    }
+                }
+            })
+            .expect("Could not spawn thread for signals");
+        tx
+    }
+
     fn start_interface(&mut self, config: &InterfaceConfiguration) -> Result<(), String> {
         if self.interface_threads.contains_key(&config.id) {
             return Err(format!("Interface {} already started!", config.id));
@@ -485,6 +663,78 @@ impl Conductor {
         Dna::try_from(JsonString::from(contents))
     }
 
+    /// Default KeyLoader that decrypts key files with the passphrase
+    /// the given PassphraseLoader returns
+    fn default_key_loader(passphrase_loader: PassphraseLoader) -> KeyLoader {
+        Arc::new(Box::new(move |file: &PathBuf| {
+            Self::load_key(file, &passphrase_loader)
+        }))
+    }
+
+    /// Reads a JSON encoded KeyBundle from the filesystem
+    /// and decrypts it with the passphrase returned by passphrase_loader
+    fn load_key(
+        file: &PathBuf,
+        passphrase_loader: &PassphraseLoader,
+    ) -> Result<Keypair, HolochainError> {
+        notify(format!("Reading agent key from {}", file.display()));
+        let mut f = File::open(file)?;
+        let mut contents = String::new();
+        f.read_to_string(&mut contents)?;
+        let bundle: KeyBundle = serde_json::from_str(&contents)?;
+        let mut passphrase = passphrase_loader("decrypt agent keys")?;
+        Keypair::from_bundle(&bundle, &mut passphrase, None)
+    }
+
+    /// File the agent's key of the given instance gets persisted to after a key rotation.
+    /// Key rotations happen per instance, since each instance has its own source chain.
+    fn rotated_key_file(key_file: &PathBuf, instance_id: &str) -> PathBuf {
+        PathBuf::from(format!("{}.{}", key_file.display(), instance_id))
+    }
+
+    /// Files the keys an agent rotated away from got moved to by later rotations,
+    /// i.e. "<rotated key file>.1", "<rotated key file>.2", ..., oldest first.
+    fn previous_rotated_key_files(rotated_key_file: &PathBuf) -> Vec<PathBuf> {
+        (1..)
+            .map(|n| PathBuf::from(format!("{}.{}", rotated_key_file.display(), n)))
+            .take_while(|file| file.exists())
+            .collect()
+    }
+
+    /// AgentKeyStore that writes rotated keys to the given file as JSON encoded KeyBundle,
+    /// encrypted with the passphrase returned by passphrase_loader.
+    /// A key written by an earlier rotation gets moved to the next previous rotated key file,
+    /// see `previous_rotated_key_files`.
+    fn agent_key_store(file: PathBuf, passphrase_loader: PassphraseLoader) -> AgentKeyStore {
+        Arc::new(Box::new(move |keypair: &mut Keypair| {
+            if file.exists() {
+                let previous_files = Self::previous_rotated_key_files(&file);
+                let previous_file =
+                    PathBuf::from(format!("{}.{}", file.display(), previous_files.len() + 1));
+                fs::rename(&file, previous_file)?;
+            }
+            notify(format!("Writing rotated agent key to {}", file.display()));
+            let mut passphrase = passphrase_loader("encrypt rotated agent keys")?;
+            let bundle = keypair.get_bundle(&mut passphrase, String::from("rotated"), None)?;
+            let mut f = File::create(&file)?;
+            f.write_all(serde_json::to_string(&bundle)?.as_bytes())?;
+            Ok(())
+        }))
+    }
+
+    /// Default PassphraseLoader that reads the passphrase from the PASSPHRASE_ENV_VAR
+    /// environment variable.
+    /// The purpose ends up in the error message if the variable is not set.
+    fn passphrase_from_env(purpose: &str) -> Result<SecBuf, HolochainError> {
+        let passphrase = env::var(PASSPHRASE_ENV_VAR).map_err(|_| {
+            HolochainError::ConfigError(format!(
+                "Environment variable {} has to be set to {}",
+                PASSPHRASE_ENV_VAR, purpose
+            ))
+        })?;
+        Ok(SecBuf::with_insecure_from_string(passphrase))
+    }
+
     fn copy_ui_dir(source: &PathBuf, dest: &PathBuf) -> Result<(), HolochainError> {
         notify(format!(
             "Copying UI from {} to {}",
@@ -527,20 +777,38 @@ impl Conductor {
 
     fn spawn_interface_thread(&self, interface_config: InterfaceConfiguration) -> Sender<()> {
         let dispatcher = self.make_interface_handler(&interface_config);
+        let signals = match interface_config.driver {
+            InterfaceDriver::Websocket { .. } => {
+                let (signal_tx, signal_rx) = channel();
+                let instance_ids = interface_config
+                    .instances
+                    .iter()
+                    .map(|i| i.id.clone())
+                    .collect();
+                self.signal_subscribers
+                    .write()
+                    .unwrap()
+                    .insert(interface_config.id.clone(), (instance_ids, signal_tx));
+                Some(signal_rx)
+            }
+            _ => None,
+        };
         let log_sender = self.logger.get_sender();
+        let interface_factories = self.interface_factories.clone();
         let (tx, rx) = channel();
         thread::Builder::new()
             .name(format!("conductor-interface: {}", interface_config.id))
             .spawn(move || {
-                let iface = make_interface(&interface_config);
-                iface.run(dispatcher, rx).map_err(|error| {
-                    let message = format!(
-                        "err/conductor: Error running interface '{}': {}",
-                        interface_config.id, error
-                    );
-                    let _ = log_sender.send((String::from("conductor"), message));
-                    error
-                })
+                make_interface(&interface_config, &interface_factories, signals)
+                    .and_then(|iface| iface.run(dispatcher, rx))
+                    .map_err(|error| {
+                        let message = format!(
+                            "err/conductor: Error running interface '{}': {}",
+                            interface_config.id, error
+                        );
+                        let _ = log_sender.send((String::from("conductor"), message));
+                        error
+                    })
             })
             .expect("Could not spawn thread for interface");
         tx
@@ -614,13 +882,46 @@ impl<'a> TryFrom<&'a Configuration> for Conductor {
     }
 }
 
-/// This can eventually be dependency injected for third party Interface definitions
-fn make_interface(interface_config: &InterfaceConfiguration) -> Box<Interface> {
-    use interface_impls::{http::HttpInterface, websocket::WebsocketInterface};
+/// Creates the Interface for the configured driver.
+/// Custom drivers are looked up by name in the given registered factories.
+/// Websocket interfaces push the user signals they receive to their clients.
+fn make_interface(
+    interface_config: &InterfaceConfiguration,
+    interface_factories: &HashMap<String, InterfaceFactory>,
+    signals: Option<Receiver<Signal>>,
+) -> Result<Box<Interface>, String> {
+    use interface_impls::{
+        http::HttpInterface, unix_socket::UnixSocketInterface, websocket::WebsocketInterface,
+    };
     match interface_config.driver {
-        InterfaceDriver::Websocket { port } => Box::new(WebsocketInterface::new(port)),
-        InterfaceDriver::Http { port } => Box::new(HttpInterface::new(port)),
-        _ => unimplemented!(),
+        InterfaceDriver::Websocket { port } => {
+            let interface = WebsocketInterface::new(port);
+            Ok(Box::new(match signals {
+                Some(signals) => interface.with_signals(signals),
+                None => interface,
+            }))
+        }
+        InterfaceDriver::Http { port } => Ok(Box::new(HttpInterface::new(port))),
+        InterfaceDriver::DomainSocket { ref file, .. } => {
+            let permissions = interface_config.driver.socket_permissions()?;
+            Ok(Box::new(UnixSocketInterface::new(
+                PathBuf::from(file),
+                permissions,
+            )))
+        }
+        InterfaceDriver::Custom(ref table) => {
+            let name = interface_config
+                .driver
+                .custom_name()?
+                .expect("Custom drivers always have a name");
+            let factory = interface_factories.get(&name).ok_or_else(|| {
+                format!(
+                    "No interface factory registered for custom driver \"{}\"",
+                    name
+                )
+            })?;
+            factory(table)
+        }
     }
 }
 
@@ -641,6 +942,8 @@ pub mod tests {
     use std::{
         fs::{File, OpenOptions},
         io::Write,
+        sync::mpsc::Receiver,
+        time::Duration,
     };
     use tempfile::tempdir;
     use test_utils::*;
@@ -657,6 +960,12 @@ pub mod tests {
         Arc::new(loader)
     }
 
+    pub fn test_passphrase_loader(passphrase: &'static str) -> PassphraseLoader {
+        Arc::new(Box::new(move |_: &str| {
+            Ok(SecBuf::with_insecure_from_string(String::from(passphrase)))
+        }))
+    }
+
     pub fn test_toml() -> String {
         r#"
     [[agents]]
@@ -664,18 +973,21 @@ pub mod tests {
     name = "Holo Tester 1"
     public_address = "HoloTester1-----------------------------------------------------------------------AAACZp4xHB"
     key_file = "holo_tester.key"
+    test_agent = true
 
     [[agents]]
     id = "test-agent-2"
     name = "Holo Tester 2"
     public_address = "HoloTester2-----------------------------------------------------------------------AAAGy4WW9e"
     key_file = "holo_tester.key"
+    test_agent = true
 
     [[agents]]
     id = "test-agent-3"
     name = "Holo Tester 3"
     public_address = "HoloTester2-----------------------------------------------------------------------AAAGy4WW9e"
     key_file = "holo_tester.key"
+    test_agent = true
 
     [[dnas]]
     id = "test-dna"
@@ -832,6 +1144,69 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_default_key_loader() {
+        let tempdir = tempdir().unwrap();
+        let file_path = tempdir.path().join("test.key");
+        let mut keypair = Keypair::new_from_insecure_string("test key").unwrap();
+        let mut passphrase = SecBuf::with_insecure_from_string(String::from("secret"));
+        let bundle = keypair
+            .get_bundle(&mut passphrase, String::from("hint"), None)
+            .unwrap();
+        let mut tmp_file = File::create(file_path.clone()).unwrap();
+        writeln!(tmp_file, "{}", serde_json::to_string(&bundle).unwrap()).unwrap();
+
+        let loaded = Conductor::load_key(&file_path, &test_passphrase_loader("secret")).unwrap();
+        assert_eq!(loaded.pub_keys, keypair.pub_keys);
+
+        assert!(Conductor::load_key(&file_path, &test_passphrase_loader("wrong")).is_err());
+    }
+
+    #[test]
+    fn test_agent_key_store_keeps_previous_rotated_keys() {
+        let tempdir = tempdir().unwrap();
+        let file_path = tempdir.path().join("test.key.instance");
+        let key_store =
+            Conductor::agent_key_store(file_path.clone(), test_passphrase_loader("secret"));
+        let mut first = Keypair::new_from_insecure_string("first").unwrap();
+        let mut second = Keypair::new_from_insecure_string("second").unwrap();
+        let mut third = Keypair::new_from_insecure_string("third").unwrap();
+        key_store(&mut first).unwrap();
+        key_store(&mut second).unwrap();
+        key_store(&mut third).unwrap();
+
+        let previous_files = Conductor::previous_rotated_key_files(&file_path);
+        assert_eq!(previous_files.len(), 2);
+        let passphrase_loader = test_passphrase_loader("secret");
+        let load = |file: &PathBuf| {
+            Conductor::load_key(file, &passphrase_loader)
+                .unwrap()
+                .pub_keys
+        };
+        assert_eq!(load(&previous_files[0]), first.pub_keys);
+        assert_eq!(load(&previous_files[1]), second.pub_keys);
+        assert_eq!(load(&file_path), third.pub_keys);
+    }
+
+    #[test]
+    fn test_instantiate_rejects_key_not_matching_public_address() {
+        let toml = test_toml().replacen("    test_agent = true\n", "", 1);
+        let config = load_configuration::<Configuration>(&toml).unwrap();
+        let mut conductor = Conductor::from_config(config.clone());
+        conductor.dna_loader = test_dna_loader();
+        conductor.key_loader = Arc::new(Box::new(|_: &PathBuf| {
+            Keypair::new_from_insecure_string("somebody else")
+        }));
+        let result =
+            conductor.instantiate_from_config(&String::from("test-instance-1"), &config, None);
+        assert_eq!(
+            result.err(),
+            Some(String::from(
+                "Key file \"holo_tester.key\" does not match public address of agent \"test-agent-1\""
+            ))
+        );
+    }
+
     #[test]
     fn test_conductor_load_config() {
         let mut conductor = test_conductor();
@@ -906,6 +1281,142 @@ pub mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_user_signals_reach_interfaces_exposing_the_instance() {
+        let conductor = test_conductor();
+        let (ui_1_tx, ui_1_rx) = channel();
+        let (ui_2_tx, ui_2_rx) = channel();
+        {
+            let mut subscribers = conductor.signal_subscribers.write().unwrap();
+            subscribers.insert(
+                "ui-1".to_string(),
+                (vec!["test-instance-1".to_string()], ui_1_tx),
+            );
+            subscribers.insert(
+                "ui-2".to_string(),
+                (vec!["test-instance-2".to_string()], ui_2_tx),
+            );
+        }
+
+        let instance = conductor.instances().get("test-instance-1").unwrap();
+        let context = instance.read().unwrap().context().clone();
+        assert_eq!(context.instance_id, Some("test-instance-1".to_string()));
+        context
+            .signal_tx()
+            .send(Signal::User {
+                instance_id: "test-instance-1".to_string(),
+                name: "new_message".to_string(),
+                payload: JsonString::from("{\"text\":\"hello\"}"),
+            })
+            .unwrap();
+
+        match ui_1_rx.recv_timeout(Duration::from_millis(1000)) {
+            Ok(Signal::User {
+                instance_id, name, ..
+            }) => {
+                assert_eq!(instance_id, "test-instance-1");
+                assert_eq!(name, "new_message");
+            }
+            other => panic!("Expected user signal, got {:?}", other),
+        }
+        assert!(ui_2_rx.recv_timeout(Duration::from_millis(100)).is_err());
+    }
+
+    #[test]
+    fn test_user_signals_reach_interfaces_exposing_the_instance_after_its_creation() {
+        let conductor = test_conductor();
+        let instance = conductor.instances().get("bridge-caller").unwrap();
+        let context = instance.read().unwrap().context().clone();
+        let (ui_tx, ui_rx) = channel();
+        conductor
+            .signal_subscribers
+            .write()
+            .unwrap()
+            .insert("ui".to_string(), (vec!["bridge-caller".to_string()], ui_tx));
+
+        context
+            .signal_tx()
+            .send(Signal::User {
+                instance_id: "bridge-caller".to_string(),
+                name: "new_message".to_string(),
+                payload: JsonString::from("{\"text\":\"hello\"}"),
+            })
+            .unwrap();
+
+        match ui_rx.recv_timeout(Duration::from_millis(1000)) {
+            Ok(Signal::User { instance_id, .. }) => assert_eq!(instance_id, "bridge-caller"),
+            other => panic!("Expected user signal, got {:?}", other),
+        }
+    }
+
+    struct TestInterface {
+        greeting: String,
+        greeting_tx: Arc<Mutex<Sender<String>>>,
+    }
+
+    impl Interface for TestInterface {
+        fn run(&self, _: IoHandler, kill_switch: Receiver<()>) -> Result<(), String> {
+            self.greeting_tx
+                .lock()
+                .unwrap()
+                .send(self.greeting.clone())
+                .map_err(|e| e.to_string())?;
+            let _ = kill_switch.recv();
+            Ok(())
+        }
+    }
+
+    fn custom_interface_config(name: &str) -> InterfaceConfiguration {
+        let mut table = toml::value::Table::new();
+        table.insert("name".into(), toml::Value::String(name.into()));
+        table.insert("greeting".into(), toml::Value::String("hello".into()));
+        InterfaceConfiguration {
+            id: "custom-interface".into(),
+            driver: InterfaceDriver::Custom(toml::Value::Table(table)),
+            admin: false,
+            instances: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_custom_interface_factory() {
+        let mut config = load_configuration::<Configuration>(&test_toml()).unwrap();
+        config.interfaces = vec![custom_interface_config("test")];
+        let mut conductor = Conductor::from_config(config);
+
+        let (greeting_tx, greeting_rx) = channel();
+        let greeting_tx = Arc::new(Mutex::new(greeting_tx));
+        conductor.register_interface_factory("test", move |table| {
+            let greeting = table
+                .get("greeting")
+                .and_then(|greeting| greeting.as_str())
+                .ok_or_else(|| String::from("greeting missing"))?;
+            let interface: Box<Interface> = Box::new(TestInterface {
+                greeting: greeting.to_string(),
+                greeting_tx: greeting_tx.clone(),
+            });
+            Ok(interface)
+        });
+
+        conductor.start_all_interfaces();
+        assert_eq!(
+            greeting_rx.recv_timeout(Duration::from_secs(5)),
+            Ok(String::from("hello"))
+        );
+        conductor.stop_all_interfaces();
+    }
+
+    #[test]
+    fn test_custom_interface_without_factory() {
+        let result = make_interface(&custom_interface_config("unknown"), &HashMap::new());
+        assert_eq!(
+            result.err(),
+            Some(String::from(
+                "No interface factory registered for custom driver \"unknown\""
+            ))
+        );
+    }
+
     pub fn callee_wat() -> String {
         r#"
 (module
@@ -1056,6 +1567,7 @@ pub mod tests {
                 Some(dna::capabilities::CapabilityCall::new(
                     Address::from("fake_token"),
                     None,
+                    dna::capabilities::CallSignature::default(),
                 )),
                 "call_bridge",
                 "{}",
@@ -1065,5 +1577,4 @@ pub mod tests {
         // "Holo World" comes for the callee_wat above which runs in the callee instance
         assert_eq!(result, JsonString::from(RawString::from("Holo World")));
     }
-
 }
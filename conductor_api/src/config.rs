@@ -110,6 +110,8 @@ impl Configuration {
             })?;
         }
         for ref interface in self.interfaces.iter() {
+            interface.driver.socket_permissions()?;
+            interface.driver.custom_name()?;
             for ref instance in interface.instances.iter() {
                 self.instance_by_id(&instance.id).is_some().ok_or_else(|| {
                     format!(
@@ -304,6 +306,12 @@ pub struct AgentConfiguration {
     pub name: String,
     pub public_address: String,
     pub key_file: String,
+    /// Test agents don't read their key_file but get a deterministic keypair
+    /// derived from their name. Their public_address is ignored.
+    /// Never use this for real agents!
+    /// (Optional)
+    #[serde(default)]
+    pub test_agent: Option<bool>,
 }
 
 impl From<AgentConfiguration> for AgentId {
@@ -340,20 +348,66 @@ pub struct InstanceConfiguration {
     pub dna: String,
     pub agent: String,
     pub storage: StorageConfiguration,
+    /// Proof that the agent may join the DNA's network, e.g. an invitation.
+    /// It becomes part of the agent's AgentId entry, which the DNA's `validate_agent`
+    /// callbacks check on genesis and on every node that holds it.
+    /// (Optional)
+    #[serde(default)]
+    pub membrane_proof: Option<String>,
 }
 
 /// This configures the Content Addressable Storage (CAS) that
 /// the instance uses to store source chain and DHT shard in.
-/// There are two storage implementations in cas_implementations so far:
+/// There are three storage implementations in cas_implementations so far:
 /// * memory
 /// * file
+/// * lmdb (an embedded transactional key-value database)
+///
+/// Persistent storages can be `encrypted` at rest with a key derived from the passphrase
+/// the conductor reads from the `HC_PASSPHRASE` environment variable.
+/// With `verify_on_read` they check every item they read against its address,
+/// so that corrupted content fails to load instead of being used.
 ///
 /// Projected are various DB adapters.
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum StorageConfiguration {
     Memory,
-    File { path: String },
+    File {
+        path: String,
+        #[serde(default)]
+        encrypted: Option<bool>,
+        #[serde(default)]
+        verify_on_read: Option<bool>,
+    },
+    Lmdb {
+        path: String,
+        #[serde(default)]
+        encrypted: Option<bool>,
+        #[serde(default)]
+        verify_on_read: Option<bool>,
+        /// Maximum size in bytes the database files can grow to (default: 1 GiB)
+        #[serde(default)]
+        map_size: Option<usize>,
+    },
+}
+
+impl StorageConfiguration {
+    pub fn is_encrypted(&self) -> bool {
+        match self {
+            StorageConfiguration::File { encrypted, .. }
+            | StorageConfiguration::Lmdb { encrypted, .. } => encrypted.unwrap_or(false),
+            StorageConfiguration::Memory => false,
+        }
+    }
+
+    pub fn verifies_on_read(&self) -> bool {
+        match self {
+            StorageConfiguration::File { verify_on_read, .. }
+            | StorageConfiguration::Lmdb { verify_on_read, .. } => verify_on_read.unwrap_or(false),
+            StorageConfiguration::Memory => false,
+        }
+    }
 }
 
 /// Here, interfaces are user facing and make available zome functions to
@@ -361,8 +415,6 @@ pub enum StorageConfiguration {
 /// We currently have:
 /// * websockets
 /// * HTTP
-///
-/// We will also soon develop
 /// * Unix domain sockets
 ///
 /// The instances (referenced by ID) that are to be made available via that interface should be listed.
@@ -381,12 +433,62 @@ pub struct InterfaceConfiguration {
 #[derive(Deserialize, Serialize, Clone, Debug)]
 #[serde(tag = "type", rename_all = "lowercase")]
 pub enum InterfaceDriver {
-    Websocket { port: u16 },
-    Http { port: u16 },
-    DomainSocket { file: String },
+    Websocket {
+        port: u16,
+    },
+    Http {
+        port: u16,
+    },
+    /// `permissions` is the octal file mode the socket file gets created with, e.g. "600".
+    DomainSocket {
+        file: String,
+        #[serde(default)]
+        permissions: Option<String>,
+    },
+    /// Any other driver provided by the embedding application, see
+    /// `Conductor::register_interface_factory`. The table has to contain the `name` of the
+    /// registered factory and gets passed to it as a whole.
     Custom(toml::value::Value),
 }
 
+impl InterfaceDriver {
+    /// Returns the factory name of a custom driver, or an error if a custom driver
+    /// does not name its factory. Returns Ok(None) for built-in drivers.
+    pub fn custom_name(&self) -> Result<Option<String>, String> {
+        match self {
+            InterfaceDriver::Custom(table) => table
+                .get("name")
+                .and_then(|name| name.as_str())
+                .map(|name| Some(name.to_string()))
+                .ok_or_else(|| {
+                    String::from("Custom interface driver needs a `name` string property")
+                }),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses the `permissions` of a domain socket driver as octal file mode.
+    /// Returns Ok(None) for other drivers or if no permissions are configured.
+    pub fn socket_permissions(&self) -> Result<Option<u32>, String> {
+        match self {
+            InterfaceDriver::DomainSocket {
+                permissions: Some(permissions),
+                ..
+            } => u32::from_str_radix(permissions.trim_start_matches("0o"), 8)
+                .ok()
+                .filter(|mode| *mode <= 0o777)
+                .map(Some)
+                .ok_or_else(|| {
+                    format!(
+                        "Invalid domain socket permissions \"{}\", expected an octal file mode like \"600\"",
+                        permissions
+                    )
+                }),
+            _ => Ok(None),
+        }
+    }
+}
+
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct InstanceReferenceConfiguration {
     pub id: String,
@@ -807,6 +909,157 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_custom_interface_driver() {
+        let toml = r#"
+    [[interfaces]]
+    id = "stdio interface"
+    [interfaces.driver]
+    type = "custom"
+    name = "stdio"
+    line_delimiter = ";"
+    "#;
+
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        assert_eq!(config.check_consistency(), Ok(()));
+        let driver = config.interfaces[0].driver.clone();
+        assert_eq!(driver.custom_name(), Ok(Some(String::from("stdio"))));
+        match driver {
+            InterfaceDriver::Custom(table) => {
+                assert_eq!(
+                    table.get("line_delimiter").and_then(|d| d.as_str()),
+                    Some(";")
+                )
+            }
+            _ => panic!("expected custom interface driver"),
+        }
+
+        let toml = r#"
+    [[interfaces]]
+    id = "nameless interface"
+    [interfaces.driver]
+    type = "custom"
+    "#;
+
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        assert_eq!(
+            config.check_consistency(),
+            Err(String::from(
+                "Custom interface driver needs a `name` string property"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_domain_socket_permissions() {
+        let toml = r#"
+    [[interfaces]]
+    id = "socket with permissions"
+    [interfaces.driver]
+    type = "domainsocket"
+    file = "/tmp/holochain.sock"
+    permissions = "660"
+
+    [[interfaces]]
+    id = "socket without permissions"
+    [interfaces.driver]
+    type = "domainsocket"
+    file = "/tmp/holochain2.sock"
+    "#;
+
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        assert_eq!(config.check_consistency(), Ok(()));
+        assert_eq!(
+            config.interfaces[0].driver.socket_permissions(),
+            Ok(Some(0o660))
+        );
+        assert_eq!(config.interfaces[1].driver.socket_permissions(), Ok(None));
+
+        let toml = r#"
+    [[interfaces]]
+    id = "socket with invalid permissions"
+    [interfaces.driver]
+    type = "domainsocket"
+    file = "/tmp/holochain.sock"
+    permissions = "rw-rw----"
+    "#;
+
+        let config = load_configuration::<Configuration>(toml).unwrap();
+        assert_eq!(
+            config.check_consistency(),
+            Err("Invalid domain socket permissions \"rw-rw----\", expected an octal file mode like \"600\"".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lmdb_storage() {
+        let toml = r#"
+    type = "lmdb"
+    path = "app_spec_storage"
+    "#;
+
+        match load_configuration::<StorageConfiguration>(toml).unwrap() {
+            StorageConfiguration::Lmdb {
+                path,
+                encrypted,
+                verify_on_read,
+                map_size,
+            } => {
+                assert_eq!(path, "app_spec_storage");
+                assert_eq!(encrypted, None);
+                assert_eq!(verify_on_read, None);
+                assert_eq!(map_size, None);
+            }
+            _ => panic!("expected lmdb storage"),
+        }
+
+        let toml = r#"
+    type = "lmdb"
+    path = "app_spec_storage"
+    map_size = 4294967296
+    "#;
+
+        match load_configuration::<StorageConfiguration>(toml).unwrap() {
+            StorageConfiguration::Lmdb { map_size, .. } => {
+                assert_eq!(map_size, Some(4294967296));
+            }
+            _ => panic!("expected lmdb storage"),
+        }
+    }
+
+    #[test]
+    fn test_encrypted_storage() {
+        let toml = r#"
+    type = "file"
+    path = "app_spec_storage"
+    encrypted = true
+    "#;
+
+        match load_configuration::<StorageConfiguration>(toml).unwrap() {
+            StorageConfiguration::File {
+                path, encrypted, ..
+            } => {
+                assert_eq!(path, "app_spec_storage");
+                assert_eq!(encrypted, Some(true));
+            }
+            _ => panic!("expected file storage"),
+        }
+    }
+
+    #[test]
+    fn test_verify_on_read_storage() {
+        let toml = r#"
+    type = "file"
+    path = "app_spec_storage"
+    verify_on_read = true
+    "#;
+
+        let storage = load_configuration::<StorageConfiguration>(toml).unwrap();
+        assert!(storage.verifies_on_read());
+        assert!(!storage.is_encrypted());
+        assert!(!StorageConfiguration::Memory.verifies_on_read());
+    }
+
     #[test]
     fn test_invalid_toml_1() {
         let toml = &format!(
@@ -890,7 +1143,9 @@ pub mod tests {
     path = "app_spec_storage"
 
     {}
-    "#, bridges)
+    "#,
+            bridges
+        )
     }
 
     #[test]
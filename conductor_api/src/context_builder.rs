@@ -1,11 +1,20 @@
 use holochain_cas_implementations::{
-    cas::{file::FilesystemStorage, memory::MemoryStorage},
-    eav::{file::EavFileStorage, memory::EavMemoryStorage},
+    cas::{
+        encrypted::EncryptedStorage,
+        file::FilesystemStorage,
+        lmdb::{LmdbStorage, DEFAULT_LMDB_MAP_SIZE},
+        memory::MemoryStorage,
+    },
+    eav::{
+        encrypted::EavEncryptedStorage, file::EavFileStorage, lmdb::EavLmdbStorage,
+        memory::EavMemoryStorage,
+    },
+    encryption::StorageKey,
     path::create_path_if_not_exists,
 };
 
 use holochain_core::{
-    context::Context,
+    context::{AgentKeyStore, Context},
     logger::{Logger, SimpleLogger},
     persister::SimplePersister,
     signal::SignalSender,
@@ -14,7 +23,9 @@ use holochain_core_types::{
     agent::AgentId, cas::storage::ContentAddressableStorage, eav::EntityAttributeValueStorage,
     error::HolochainError, json::JsonString,
 };
+use holochain_dpki::keypair::Keypair;
 use holochain_net::p2p_config::P2pConfig;
+use holochain_sodium::secbuf::SecBuf;
 use jsonrpc_ws_server::jsonrpc_core::IoHandler;
 use std::{
     path::{Path, PathBuf},
@@ -31,6 +42,8 @@ use std::{
 /// `spawn()` to retrieve the context.
 pub struct ContextBuilder {
     agent_id: Option<AgentId>,
+    agent_keypair: Option<Keypair>,
+    previous_agent_keypairs: Vec<Keypair>,
     logger: Option<Arc<Mutex<Logger>>>,
     // Persister is currently set to a reasonable default in spawn().
     // TODO: add with_persister() function to ContextBuilder.
@@ -41,12 +54,18 @@ pub struct ContextBuilder {
     network_config: Option<JsonString>,
     conductor_api: Option<Arc<RwLock<IoHandler>>>,
     signal_tx: Option<SignalSender>,
+    internal_signals: bool,
+    instance_id: Option<String>,
+    agent_key_store: Option<AgentKeyStore>,
+    verify_on_read: bool,
 }
 
 impl ContextBuilder {
     pub fn new() -> Self {
         ContextBuilder {
             agent_id: None,
+            agent_keypair: None,
+            previous_agent_keypairs: Vec::new(),
             logger: None,
             chain_storage: None,
             dht_storage: None,
@@ -54,6 +73,10 @@ impl ContextBuilder {
             network_config: None,
             conductor_api: None,
             signal_tx: None,
+            internal_signals: true,
+            instance_id: None,
+            agent_key_store: None,
+            verify_on_read: false,
         }
     }
 
@@ -63,10 +86,37 @@ impl ContextBuilder {
         self
     }
 
+    /// Sets the keypair the agent signs its source chain headers with.
+    /// The keypair's public key has to match the key of the agent set with `with_agent()`.
+    pub fn with_agent_keypair(mut self, keypair: Keypair) -> Self {
+        self.agent_keypair = Some(keypair);
+        self
+    }
+
+    /// Sets the keypairs the agent rotated away from, oldest first.
+    /// They are only used for opening entries that got sealed for them before the rotation.
+    pub fn with_previous_agent_keypairs(mut self, keypairs: Vec<Keypair>) -> Self {
+        self.previous_agent_keypairs = keypairs;
+        self
+    }
+
+    /// Makes the content addressable storages set up after this call verify every item they
+    /// read against its address, so corrupted content fails to load instead of being used.
+    /// Combined with `with_storage_encryption`, call it between setting the storage and
+    /// encrypting it, so that the decrypted content gets verified rather than what is stored.
+    pub fn with_verify_on_read(mut self) -> Self {
+        self.verify_on_read = true;
+        self
+    }
+
     /// Sets all three storages, chain, DHT and EAV storage, to transient memory implementations.
     /// Chain and DHT storages get set to the same memory CAS.
     pub fn with_memory_storage(mut self) -> Self {
-        let cas = Arc::new(RwLock::new(MemoryStorage::new()));
+        let mut storage = MemoryStorage::new();
+        if self.verify_on_read {
+            storage = storage.with_verify_on_read();
+        }
+        let cas = Arc::new(RwLock::new(storage));
         let eav = Arc::new(RwLock::new(EavMemoryStorage::new()));
         self.chain_storage = Some(cas.clone());
         self.dht_storage = Some(cas);
@@ -84,7 +134,11 @@ impl ContextBuilder {
         create_path_if_not_exists(&cas_path)?;
         create_path_if_not_exists(&eav_path)?;
 
-        let file_storage = Arc::new(RwLock::new(FilesystemStorage::new(&cas_path)?));
+        let mut file_storage = FilesystemStorage::new(&cas_path)?;
+        if self.verify_on_read {
+            file_storage = file_storage.with_verify_on_read();
+        }
+        let file_storage = Arc::new(RwLock::new(file_storage));
         let eav_storage = Arc::new(RwLock::new(EavFileStorage::new(eav_path)?));
         self.chain_storage = Some(file_storage.clone());
         self.dht_storage = Some(file_storage);
@@ -92,6 +146,62 @@ impl ContextBuilder {
         Ok(self)
     }
 
+    /// Sets all three storages, chain, DHT and EAV storage, to persistent LMDB based implementations.
+    /// Chain and DHT storages get set to the same LMDB CAS.
+    /// The database files can grow to `map_size` bytes each, 1 GiB if not given.
+    /// Returns an error if no LMDB storage could be spawned on the given path.
+    pub fn with_lmdb_storage<P: AsRef<Path>>(
+        mut self,
+        path: P,
+        map_size: Option<usize>,
+    ) -> Result<Self, HolochainError> {
+        let map_size = map_size.unwrap_or(DEFAULT_LMDB_MAP_SIZE);
+        let base_path: PathBuf = path.as_ref().into();
+        let cas_path = base_path.join("cas");
+        let eav_path = base_path.join("eav");
+        create_path_if_not_exists(&cas_path)?;
+        create_path_if_not_exists(&eav_path)?;
+
+        let mut cas_storage = LmdbStorage::new_with_map_size(&cas_path, map_size)?;
+        if self.verify_on_read {
+            cas_storage = cas_storage.with_verify_on_read();
+        }
+        let cas_storage = Arc::new(RwLock::new(cas_storage));
+        let eav_storage = Arc::new(RwLock::new(EavLmdbStorage::new_with_map_size(
+            eav_path, map_size,
+        )?));
+        self.chain_storage = Some(cas_storage.clone());
+        self.dht_storage = Some(cas_storage);
+        self.eav_storage = Some(eav_storage);
+        Ok(self)
+    }
+
+    /// Wraps the storages set so far (memory storages if none were set) in storages that encrypt
+    /// everything before it gets stored, with keys derived from the given passphrase.
+    /// The chain and DHT storages have to be the same CAS, as set by the `with_*_storage` functions.
+    /// Returns an error if the passphrase does not match the one the storages were encrypted with.
+    pub fn with_storage_encryption(
+        mut self,
+        passphrase: &mut SecBuf,
+    ) -> Result<Self, HolochainError> {
+        if self.chain_storage.is_none() {
+            self = self.with_memory_storage();
+        }
+        let cas = self.chain_storage.take().unwrap();
+        let eav = self.eav_storage.take().unwrap();
+        let key = StorageKey::from_passphrase(&mut *cas.write()?, passphrase)?;
+
+        let mut encrypted_cas = EncryptedStorage::new(cas, key.clone());
+        if self.verify_on_read {
+            encrypted_cas = encrypted_cas.with_verify_on_read();
+        }
+        let encrypted_cas = Arc::new(RwLock::new(encrypted_cas));
+        self.chain_storage = Some(encrypted_cas.clone());
+        self.dht_storage = Some(encrypted_cas);
+        self.eav_storage = Some(Arc::new(RwLock::new(EavEncryptedStorage::new(eav, key))));
+        Ok(self)
+    }
+
     /// Sets the network config.
     pub fn with_network_config(mut self, network_config: JsonString) -> Self {
         self.network_config = Some(network_config);
@@ -110,11 +220,36 @@ impl ContextBuilder {
 
     pub fn with_signals(mut self, signal_tx: SignalSender) -> Self {
         self.signal_tx = Some(signal_tx);
+        self.internal_signals = true;
+        self
+    }
+
+    /// Sets a signal channel that only receives the user signals zomes emit,
+    /// without a `Signal::Internal` for every action the instance processes.
+    pub fn with_user_signals(mut self, signal_tx: SignalSender) -> Self {
+        self.signal_tx = Some(signal_tx);
+        self.internal_signals = false;
+        self
+    }
+
+    /// Sets the id of the instance the context is built for.
+    /// User signals emitted by its zomes carry this id.
+    pub fn with_instance_id<S: Into<String>>(mut self, instance_id: S) -> Self {
+        self.instance_id = Some(instance_id.into());
+        self
+    }
+
+    /// Sets where the agent's keypair gets persisted after a key rotation.
+    /// Without a key store, rotated keys only live as long as the context.
+    pub fn with_agent_key_store(mut self, agent_key_store: AgentKeyStore) -> Self {
+        self.agent_key_store = Some(agent_key_store);
         self
     }
 
     /// Actually creates the context.
-    /// Defaults to memory storages, an in-memory network config and a fake agent called "alice".
+    /// Defaults to memory storages, an in-memory network config and a test agent called "alice"
+    /// with a keypair derived from that name (see `Keypair::new_from_insecure_string()`).
+    /// If an agent was set without a keypair, the context can not sign and thus not commit.
     /// The logger gets set to SimpleLogger.
     /// The persister gets set to SimplePersister based on the chain storage.
     pub fn spawn(self) -> Context {
@@ -127,8 +262,20 @@ impl ContextBuilder {
         let eav_storage = self
             .eav_storage
             .unwrap_or(Arc::new(RwLock::new(EavMemoryStorage::new())));
-        Context::new(
-            self.agent_id.unwrap_or(AgentId::generate_fake("alice")),
+        let (agent_id, agent_keypair) = match self.agent_id {
+            Some(agent_id) => (agent_id, self.agent_keypair),
+            None => {
+                let keypair = Keypair::new_from_insecure_string("alice")
+                    .expect("Could not derive keypair for default test agent");
+                let agent_id = keypair
+                    .agent_id("alice")
+                    .expect("Could not create AgentId from keypair");
+                (agent_id, Some(keypair))
+            }
+        };
+        let mut context = Context::new(
+            agent_id,
+            agent_keypair,
             self.logger.unwrap_or(Arc::new(Mutex::new(SimpleLogger {}))),
             Arc::new(Mutex::new(SimplePersister::new(chain_storage.clone()))),
             chain_storage,
@@ -139,19 +286,31 @@ impl ContextBuilder {
             )),
             self.conductor_api,
             self.signal_tx,
-        )
+        );
+        context.internal_signals = self.internal_signals;
+        context.instance_id = self.instance_id;
+        context.agent_key_store = self.agent_key_store;
+        context.previous_agent_keypairs = Arc::new(Mutex::new(self.previous_agent_keypairs));
+        context
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use holochain_core::signal::signal_channel;
+    use holochain_core_types::{
+        cas::content::{AddressableContent, ExampleAddressableContent},
+        json::RawString,
+    };
     use tempfile::tempdir;
 
     #[test]
     fn vanilla() {
         let context = ContextBuilder::new().spawn();
-        assert_eq!(context.agent_id, AgentId::generate_fake("alice"));
+        let keypair = Keypair::new_from_insecure_string("alice").unwrap();
+        assert_eq!(context.agent_id, keypair.agent_id("alice").unwrap());
+        assert!(context.agent_keypair.is_some());
         assert!(context
             .network_config
             .to_string()
@@ -165,6 +324,18 @@ mod tests {
         assert_eq!(context.agent_id, agent);
     }
 
+    #[test]
+    fn with_agent_keypair() {
+        let keypair = Keypair::new_from_insecure_string("bob").unwrap();
+        let agent = keypair.agent_id("bob").unwrap();
+        let context = ContextBuilder::new()
+            .with_agent(agent.clone())
+            .with_agent_keypair(keypair)
+            .spawn();
+        assert_eq!(context.agent_id, agent);
+        assert!(context.sign(String::from("payload")).is_ok());
+    }
+
     #[test]
     fn with_network_config() {
         let net = JsonString::from(P2pConfig::new_with_unique_memory_backend().as_str());
@@ -174,6 +345,18 @@ mod tests {
         assert_eq!(context.network_config, net);
     }
 
+    #[test]
+    fn with_user_signals() {
+        let (signal_tx, _signal_rx) = signal_channel();
+        let context = ContextBuilder::new().with_signals(signal_tx).spawn();
+        assert!(context.internal_signals);
+
+        let (signal_tx, _signal_rx) = signal_channel();
+        let context = ContextBuilder::new().with_user_signals(signal_tx).spawn();
+        assert!(context.signal_tx.is_some());
+        assert!(!context.internal_signals);
+    }
+
     #[test]
     fn smoke_tests() {
         let _ = ContextBuilder::new().with_memory_storage().spawn();
@@ -183,5 +366,60 @@ mod tests {
             .with_file_storage(temp_path)
             .expect("Filestorage should get instantiated with tempdir")
             .spawn();
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let _ = ContextBuilder::new()
+            .with_lmdb_storage(temp.path(), None)
+            .expect("LMDB storage should get instantiated with tempdir")
+            .spawn();
+    }
+
+    #[test]
+    fn with_storage_encryption() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let mut passphrase = SecBuf::with_insecure_from_string("secret".to_string());
+        let _ = ContextBuilder::new()
+            .with_file_storage(temp.path())
+            .expect("Filestorage should get instantiated with tempdir")
+            .with_storage_encryption(&mut passphrase)
+            .expect("Storage encryption should get set up")
+            .spawn();
+
+        let mut passphrase = SecBuf::with_insecure_from_string("wrong".to_string());
+        assert!(ContextBuilder::new()
+            .with_file_storage(temp.path())
+            .expect("Filestorage should get instantiated with tempdir")
+            .with_storage_encryption(&mut passphrase)
+            .is_err());
+    }
+
+    #[test]
+    fn with_verify_on_read_and_storage_encryption() {
+        let temp = tempdir().expect("test was supposed to create temp dir");
+        let mut passphrase = SecBuf::with_insecure_from_string("secret".to_string());
+        let context = ContextBuilder::new()
+            .with_file_storage(temp.path())
+            .expect("Filestorage should get instantiated with tempdir")
+            .with_verify_on_read()
+            .with_storage_encryption(&mut passphrase)
+            .expect("Storage encryption should get set up")
+            .spawn();
+        let content =
+            ExampleAddressableContent::try_from_content(&RawString::from("foo").into()).unwrap();
+        context
+            .chain_storage
+            .write()
+            .unwrap()
+            .add(&content)
+            .unwrap();
+        // the decrypted content gets verified, the stored ciphertext would not match
+        assert_eq!(
+            context
+                .chain_storage
+                .read()
+                .unwrap()
+                .fetch(&content.address())
+                .unwrap(),
+            Some(content.content())
+        );
     }
 }
@@ -9,15 +9,16 @@
 //! extern crate holochain_conductor_api;
 //! extern crate holochain_core_types;
 //! extern crate holochain_core;
+//! extern crate holochain_dpki;
 //! extern crate holochain_net;
 //! extern crate holochain_cas_implementations;
 //! extern crate tempfile;
 //! use holochain_conductor_api::{*, context_builder::ContextBuilder};
 //! use holochain_core_types::{
 //!     cas::content::Address,
-//!     agent::AgentId,
-//!     dna::{Dna, capabilities::CapabilityCall},
+//!     dna::{Dna, capabilities::{CallSignature, CapabilityCall}},
 //!     json::JsonString};
+//! use holochain_dpki::keypair::Keypair;
 //! use std::sync::Arc;
 //! use tempfile::tempdir;
 //!
@@ -30,9 +31,11 @@
 //! let dna = Dna::new();
 //! let dir = tempdir().unwrap();
 //! let storage_directory_path = dir.path().to_str().unwrap();
-//! let agent = AgentId::generate_fake("bob");
+//! let keypair = Keypair::new_from_insecure_string("bob").unwrap();
+//! let agent = keypair.agent_id("bob").unwrap();
 //! let context = ContextBuilder::new()
 //!     .with_agent(agent)
+//!     .with_agent_keypair(keypair)
 //!     .with_file_storage(storage_directory_path)
 //!     .expect("Tempdir should be accessible")
 //!     .spawn();
@@ -42,7 +45,7 @@
 //! hc.start().expect("couldn't start the holochain instance");
 //!
 //! // call a function in the zome code
-//! hc.call("test_zome", Some(CapabilityCall::new(Address::from(""), None)), "some_fn", "{}");
+//! hc.call("test_zome", Some(CapabilityCall::new(Address::from(""), None, CallSignature::default())), "some_fn", "{}");
 //!
 //! // get the state
 //! {
@@ -59,14 +62,18 @@
 
 use crate::error::{HolochainInstanceError, HolochainResult};
 use holochain_core::{
+    agent::chain_archive::ChainArchive,
     context::Context,
     instance::Instance,
     nucleus::{call_and_wait_for_result, ZomeFnCall},
-    persister::{Persister, SimplePersister},
     state::State,
 };
 use holochain_core_types::{
-    dna::{capabilities::CapabilityCall, Dna},
+    cas::content::AddressableContent,
+    dna::{
+        capabilities::{CallSignature, CapabilityCall},
+        Dna,
+    },
     error::HolochainError,
     json::JsonString,
 };
@@ -82,8 +89,20 @@ pub struct Holochain {
 
 impl Holochain {
     /// create a new Holochain instance
+    /// If the context's persister holds the state of an earlier run with the same DNA,
+    /// the instance resumes from that state instead of initializing a new chain.
     pub fn new(dna: Dna, context: Arc<Context>) -> HolochainResult<Self> {
-        let instance = Instance::new(context.clone());
+        let persisted_state = context
+            .persister
+            .lock()
+            .map_err(HolochainError::from)?
+            .load(context.clone())?;
+        let instance = match persisted_state {
+            Some(ref state) if state.nucleus().dna() == Some(dna.clone()) => {
+                Instance::from_state(state.clone())
+            }
+            _ => Instance::new(context.clone()),
+        };
         Self::from_dna_and_context_and_instance(dna, context, instance)
     }
 
@@ -109,8 +128,9 @@ impl Holochain {
     }
 
     pub fn load(_path: String, context: Arc<Context>) -> Result<Self, HolochainError> {
-        let persister = SimplePersister::new(context.dht_storage.clone());
-        let loaded_state = persister
+        let loaded_state = context
+            .persister
+            .lock()?
             .load(context.clone())?
             .unwrap_or(State::new(context.clone()));
         let mut instance = Instance::from_state(loaded_state.clone());
@@ -155,6 +175,52 @@ impl Holochain {
         Ok(call_and_wait_for_result(zome_call, &mut self.instance)?)
     }
 
+    /// Exports the source chain of this instance as a portable archive.
+    pub fn export_chain(&self) -> HolochainResult<ChainArchive> {
+        let agent_state = self.instance.state().agent();
+        Ok(ChainArchive::from_chain(
+            &agent_state.chain_store(),
+            &agent_state.top_chain_header(),
+        )?)
+    }
+
+    /// Replaces the source chain of this instance with the archived one.
+    /// The instance has to be stopped and the archive has to hold a chain of the same DNA
+    /// and agent that contains the instance's current top header (see ChainArchive::import).
+    /// Its headers and entries get verified and added to the chain storage,
+    /// then the instance restarts from the imported chain.
+    pub fn import_chain(&mut self, archive: &ChainArchive) -> HolochainResult<()> {
+        if self.active {
+            return Err(HolochainInstanceError::InstanceAlreadyActive);
+        }
+        let state = self.state()?;
+        let dna_address = state.nucleus().dna().map(|dna| dna.address());
+        if dna_address != Some(archive.dna_address.clone()) {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Archived chain is of DNA {} but the instance runs {:?}",
+                archive.dna_address, dna_address
+            ))
+            .into());
+        }
+        if archive.agent_address() != Some(self.context.agent_id.address()) {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Archived chain is not the chain of agent {}",
+                self.context.agent_id.address()
+            ))
+            .into());
+        }
+        let snapshot = archive.import(
+            &state.agent().chain_store(),
+            &state.agent().top_chain_header(),
+        )?;
+        let mut instance =
+            Instance::from_state(State::try_from_snapshot(self.context.clone(), snapshot)?);
+        let context = instance.initialize(None, self.context.clone())?;
+        self.instance = instance;
+        self.context = context;
+        Ok(())
+    }
+
     /// checks to see if an instance is active
     pub fn active(&self) -> bool {
         self.active
@@ -183,7 +249,8 @@ mod tests {
         nucleus::ribosome::{callback::Callback, Defn},
         signal::{signal_channel, SignalReceiver},
     };
-    use holochain_core_types::{agent::AgentId, cas::content::Address, dna::Dna, json::RawString};
+    use holochain_core_types::{cas::content::Address, dna::Dna, json::RawString};
+    use holochain_dpki::keypair::Keypair;
     use holochain_wasm_utils::wasm_target_dir;
     use std::sync::{Arc, Mutex};
     use tempfile::tempdir;
@@ -193,16 +260,25 @@ mod tests {
     };
 
     fn test_context(agent_name: &str) -> (Arc<Context>, Arc<Mutex<TestLogger>>, SignalReceiver) {
-        let agent = AgentId::generate_fake(agent_name);
+        test_context_with_storage(agent_name, tempdir().unwrap().path().to_str().unwrap())
+    }
+
+    fn test_context_with_storage(
+        agent_name: &str,
+        storage_path: &str,
+    ) -> (Arc<Context>, Arc<Mutex<TestLogger>>, SignalReceiver) {
+        let keypair = Keypair::new_from_insecure_string(agent_name).unwrap();
+        let agent = keypair.agent_id(agent_name).unwrap();
         let (signal_tx, signal_rx) = signal_channel();
         let logger = test_logger();
         (
             Arc::new(
                 ContextBuilder::new()
                     .with_agent(agent)
+                    .with_agent_keypair(keypair)
                     .with_logger(logger.clone())
                     .with_signals(signal_tx)
-                    .with_file_storage(tempdir().unwrap().path().to_str().unwrap())
+                    .with_file_storage(storage_path)
                     .unwrap()
                     .spawn(),
             ),
@@ -225,7 +301,11 @@ mod tests {
     }
 
     fn example_capability_call() -> Option<CapabilityCall> {
-        Some(CapabilityCall::new(Address::from("test_token"), None))
+        Some(CapabilityCall::new(
+            Address::from("test_token"),
+            None,
+            CallSignature::default(),
+        ))
     }
 
     #[test]
@@ -267,6 +347,62 @@ mod tests {
         assert!(loaded_holo.instance.state().nucleus().has_initialized());
     }
 
+    #[test]
+    fn can_load_persisted_state() {
+        let storage = tempdir().unwrap();
+        let storage_path = storage.path().to_str().unwrap();
+        let mut dna = Dna::new();
+        dna.name = "TestApp".to_string();
+        let (context, _, _) = test_context_with_storage("bob", storage_path);
+        let hc = Holochain::new(dna.clone(), context).unwrap();
+        let top_chain_header = hc.instance.state().agent().top_chain_header();
+        assert!(top_chain_header.is_some());
+
+        let (context, _, _) = test_context_with_storage("bob", storage_path);
+        let loaded_holo = Holochain::load(storage_path.to_string(), context).unwrap();
+        let loaded_state = loaded_holo.instance.state();
+        assert_eq!(loaded_state.agent().top_chain_header(), top_chain_header);
+        assert_eq!(loaded_state.nucleus().dna(), Some(dna));
+        assert!(loaded_state.nucleus().has_initialized());
+    }
+
+    #[test]
+    fn can_import_exported_chain() {
+        let mut dna = Dna::new();
+        dna.name = "TestApp".to_string();
+        let (context, _, _) = test_context("bob");
+        let hc = Holochain::new(dna.clone(), context).unwrap();
+        let archive = hc.export_chain().unwrap();
+        assert_eq!(
+            archive.top_chain_header(),
+            hc.instance.state().agent().top_chain_header()
+        );
+
+        // same agent, other storage
+        let (context, _, _) = test_context("bob");
+        let mut restored = Holochain::new(dna.clone(), context).unwrap();
+        assert_ne!(
+            restored.instance.state().agent().top_chain_header(),
+            archive.top_chain_header()
+        );
+        restored.start().unwrap();
+        assert_eq!(
+            restored.import_chain(&archive),
+            Err(HolochainInstanceError::InstanceAlreadyActive)
+        );
+        restored.stop().unwrap();
+        assert_eq!(restored.import_chain(&archive), Ok(()));
+        assert_eq!(
+            restored.instance.state().agent().top_chain_header(),
+            archive.top_chain_header()
+        );
+        assert!(restored.instance.state().nucleus().has_initialized());
+
+        let (context, _, _) = test_context("alice");
+        let mut other_agent = Holochain::new(dna, context).unwrap();
+        assert!(other_agent.import_chain(&archive).is_err());
+    }
+
     #[test]
     fn fails_instantiate_if_genesis_fails() {
         let dna = create_test_dna_with_wat(
@@ -1,5 +1,8 @@
 use holochain_core::state::State;
-use holochain_core_types::{cas::content::Address, dna::capabilities::CapabilityCall};
+use holochain_core_types::{
+    cas::content::Address,
+    dna::capabilities::{CallSignature, CapabilityCall},
+};
 use Holochain;
 
 use jsonrpc_ws_server::jsonrpc_core::{self, types::params::Params, IoHandler, Value};
@@ -166,7 +169,11 @@ impl ConductorApiBuilder {
                             let response = hc
                                 .call(
                                     &zome_name,
-                                    Some(CapabilityCall::new(Address::from("fake_token"), None)),
+                                    Some(CapabilityCall::new(
+                                        Address::from("fake_token"),
+                                        None,
+                                        CallSignature::default(),
+                                    )),
                                     &func_name,
                                     &params_string,
                                 )
@@ -297,6 +304,22 @@ impl ConductorApiBuilder {
     ///  * `admin/instance/running`
     ///     Returns an array of all instances that are running.
     ///
+    ///  * `admin/instance/export`
+    ///     Writes a portable archive of an instance's source chain (headers, entries and
+    ///     the address of its DNA) to a local file.
+    ///     Params:
+    ///     * `id`: [string] Which instance's chain to export?
+    ///     * `path`: [string] local file path to write the archive to
+    ///
+    ///  * `admin/instance/import`
+    ///     Replaces the source chain of a stopped instance with an archived one of the same
+    ///     DNA and agent. The archive's hash chain, header links and signatures get verified
+    ///     before anything is imported. Unless the instance did not commit any entries
+    ///     besides its genesis entries, the archive has to contain its current top header.
+    ///     Params:
+    ///     * `id`: [string] Which instance to import the chain into?
+    ///     * `path`: [string] local file path of the archive
+    ///
     ///  * `admin/interface/add`
     ///     Adds a new DNA / zome / conductor interface (that provides access to zome functions
     ///     of selected instances and conductor functions, depending on the interfaces config).
@@ -405,7 +428,11 @@ impl ConductorApiBuilder {
             let id = Self::get_as_string("id", &params_map)?;
             let dna_id = Self::get_as_string("dna_id", &params_map)?;
             let agent_id = Self::get_as_string("agent_id", &params_map)?;
-            conductor_call!(|c| c.add_instance(&id, &dna_id, &agent_id))?;
+            let membrane_proof = params_map
+                .get("membrane_proof")
+                .and_then(|v| v.as_str())
+                .map(String::from);
+            conductor_call!(|c| c.add_instance(&id, &dna_id, &agent_id, membrane_proof))?;
             Ok(json!({"success": true}))
         });
 
@@ -476,6 +503,22 @@ impl ConductorApiBuilder {
                 ))
             });
 
+        self.io.add_method("admin/instance/export", move |params| {
+            let params_map = Self::unwrap_params_map(params)?;
+            let id = Self::get_as_string("id", &params_map)?;
+            let path = Self::get_as_string("path", &params_map)?;
+            conductor_call!(|c| c.export_instance_chain(&id, PathBuf::from(path)))?;
+            Ok(json!({"success": true}))
+        });
+
+        self.io.add_method("admin/instance/import", move |params| {
+            let params_map = Self::unwrap_params_map(params)?;
+            let id = Self::get_as_string("id", &params_map)?;
+            let path = Self::get_as_string("path", &params_map)?;
+            conductor_call!(|c| c.import_instance_chain(&id, PathBuf::from(path)))?;
+            Ok(json!({"success": true}))
+        });
+
         self.io.add_method("admin/interface/add", move |params| {
             let params_map = Self::unwrap_params_map(params)?;
 
@@ -546,12 +589,14 @@ impl ConductorApiBuilder {
             let name = Self::get_as_string("name", &params_map)?;
             let public_address = Self::get_as_string("public_address", &params_map)?;
             let key_file = Self::get_as_string("key_file", &params_map)?;
+            let test_agent = params_map.get("test_agent").and_then(|v| v.as_bool());
 
             let agent = AgentConfiguration {
                 id,
                 name,
                 public_address,
                 key_file,
+                test_agent,
             };
             conductor_call!(|c| c.add_agent(agent))?;
             Ok(json!({"success": true}))
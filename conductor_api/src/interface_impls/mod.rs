@@ -1,4 +1,5 @@
 pub mod http;
+pub mod unix_socket;
 pub mod websocket;
 
-pub use self::{http::*, websocket::*};
+pub use self::{http::*, unix_socket::*, websocket::*};
@@ -0,0 +1,213 @@
+use interface::Interface;
+use jsonrpc_ipc_server::{jsonrpc_core::IoHandler, Server, ServerBuilder};
+use std::{fs, io, path::PathBuf, sync::mpsc::Receiver};
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+/// JSON-RPC interface listening on a Unix domain socket (a named pipe on Windows).
+/// Requests and responses are newline delimited JSON-RPC messages.
+/// This makes zome and admin functions available to local processes without opening a TCP port,
+/// while access can be restricted via the socket file's permissions.
+pub struct UnixSocketInterface {
+    file: PathBuf,
+    permissions: Option<u32>,
+}
+
+impl UnixSocketInterface {
+    pub fn new(file: PathBuf, permissions: Option<u32>) -> Self {
+        UnixSocketInterface { file, permissions }
+    }
+
+    /// A socket file left over from a previous run would make binding fail.
+    /// Only remove it if it really is a socket so no other file gets deleted by accident.
+    #[cfg(unix)]
+    fn remove_stale_socket(&self) -> io::Result<()> {
+        match fs::symlink_metadata(&self.file) {
+            Ok(metadata) => {
+                if metadata.file_type().is_socket() {
+                    fs::remove_file(&self.file)
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} exists and is not a socket", self.file.display()),
+                    ))
+                }
+            }
+            Err(_) => Ok(()),
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn remove_stale_socket(&self) -> io::Result<()> {
+        Ok(())
+    }
+
+    /// A staging directory (see bind()) is left over if binding got interrupted, e.g. by a crash,
+    /// and would make creating it fail. It is only removed if it really is a directory,
+    /// not a symlink or anything else that happens to have its name.
+    #[cfg(unix)]
+    fn remove_stale_staging_dir(staging_dir: &PathBuf) -> io::Result<()> {
+        match fs::symlink_metadata(staging_dir) {
+            Ok(metadata) => {
+                if metadata.file_type().is_dir() {
+                    fs::remove_dir_all(staging_dir)
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{} exists and is not a directory", staging_dir.display()),
+                    ))
+                }
+            }
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Binding creates the socket file with the process' umask, so setting the permissions
+    /// afterwards would leave a window in which anybody could connect.
+    /// Instead the socket gets bound inside a directory only we can access, gets its
+    /// permissions set there and is then moved into place.
+    #[cfg(unix)]
+    fn bind(&self, handler: IoHandler) -> Result<Server, String> {
+        let file_name = self
+            .file
+            .file_name()
+            .ok_or_else(|| format!("Invalid socket path: {}", self.file.display()))?;
+        let staging_dir = self
+            .file
+            .with_file_name(format!(".{}.bind", file_name.to_string_lossy()));
+        Self::remove_stale_staging_dir(&staging_dir).map_err(|e| e.to_string())?;
+        fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&staging_dir)
+            .map_err(|e| e.to_string())?;
+        let staged_file = staging_dir.join(file_name);
+        let result = Self::start(handler, &staged_file).and_then(|server| {
+            let moved_into_place = match self.permissions {
+                Some(mode) => fs::set_permissions(&staged_file, fs::Permissions::from_mode(mode)),
+                None => Ok(()),
+            }
+            .and_then(|_| fs::rename(&staged_file, &self.file));
+            match moved_into_place {
+                Ok(()) => Ok(server),
+                Err(error) => {
+                    server.close();
+                    let _ = fs::remove_file(&staged_file);
+                    Err(error.to_string())
+                }
+            }
+        });
+        let _ = fs::remove_dir(&staging_dir);
+        result
+    }
+
+    #[cfg(not(unix))]
+    fn bind(&self, handler: IoHandler) -> Result<Server, String> {
+        Self::start(handler, &self.file)
+    }
+
+    fn start(handler: IoHandler, file: &PathBuf) -> Result<Server, String> {
+        let path = file
+            .to_str()
+            .ok_or_else(|| format!("Invalid socket path: {}", file.display()))?;
+        ServerBuilder::new(handler)
+            .start(path)
+            .map_err(|e| e.to_string())
+    }
+}
+
+impl Interface for UnixSocketInterface {
+    fn run(&self, handler: IoHandler, kill_switch: Receiver<()>) -> Result<(), String> {
+        self.remove_stale_socket().map_err(|e| e.to_string())?;
+        let server = self.bind(handler)?;
+        let _ = kill_switch.recv();
+        server.close();
+        let _ = fs::remove_file(&self.file);
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+pub mod tests {
+    use super::*;
+    use jsonrpc_ipc_server::jsonrpc_core::Value;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixStream,
+        sync::mpsc::channel,
+        thread,
+        time::Duration,
+    };
+    use tempfile::tempdir;
+
+    fn connect(file: &PathBuf) -> UnixStream {
+        for _ in 0..50 {
+            if let Ok(stream) = UnixStream::connect(file) {
+                return stream;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        panic!("Could not connect to {}", file.display());
+    }
+
+    #[test]
+    fn can_call_methods_over_domain_socket() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("holochain.sock");
+        let mut handler = IoHandler::new();
+        handler.add_method("ping", |_| Ok(Value::String("pong".into())));
+
+        let interface = UnixSocketInterface::new(file.clone(), Some(0o600));
+        let (kill_switch_tx, kill_switch_rx) = channel();
+        let server_thread = thread::spawn(move || interface.run(handler, kill_switch_rx));
+
+        let mut stream = connect(&file);
+        let mode = fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        stream
+            .write_all(b"{\"jsonrpc\":\"2.0\",\"method\":\"ping\",\"id\":1}\n")
+            .unwrap();
+        let mut response = String::new();
+        BufReader::new(stream).read_line(&mut response).unwrap();
+        assert_eq!(
+            response.trim(),
+            "{\"jsonrpc\":\"2.0\",\"result\":\"pong\",\"id\":1}"
+        );
+
+        kill_switch_tx.send(()).unwrap();
+        assert_eq!(server_thread.join().unwrap(), Ok(()));
+        assert!(!file.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn replaces_staging_dir_left_over_from_a_crash() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("holochain.sock");
+        let staging_dir = dir.path().join(".holochain.sock.bind");
+        fs::create_dir(&staging_dir).unwrap();
+        fs::write(staging_dir.join("holochain.sock"), "stale").unwrap();
+
+        let interface = UnixSocketInterface::new(file.clone(), None);
+        let (kill_switch_tx, kill_switch_rx) = channel();
+        let server_thread = thread::spawn(move || interface.run(IoHandler::new(), kill_switch_rx));
+        connect(&file);
+
+        kill_switch_tx.send(()).unwrap();
+        assert_eq!(server_thread.join().unwrap(), Ok(()));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn refuses_to_replace_regular_files() {
+        let dir = tempdir().unwrap();
+        let file = dir.path().join("not_a_socket");
+        fs::write(&file, "precious").unwrap();
+
+        let interface = UnixSocketInterface::new(file.clone(), None);
+        let (_kill_switch_tx, kill_switch_rx) = channel();
+        assert!(interface.run(IoHandler::new(), kill_switch_rx).is_err());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "precious");
+    }
+}
@@ -1,24 +1,102 @@
+use holochain_core::signal::Signal;
 use interface::Interface;
 use jsonrpc_ws_server::{jsonrpc_core::IoHandler, ServerBuilder};
+use serde_json::{self, Value};
 use std::sync::mpsc::Receiver;
 
 pub struct WebsocketInterface {
     port: u16,
+    signals: Option<Receiver<Signal>>,
 }
 
 impl WebsocketInterface {
     pub fn new(port: u16) -> Self {
-        WebsocketInterface { port }
+        WebsocketInterface {
+            port,
+            signals: None,
+        }
+    }
+
+    /// Pushes the user signals received through the given channel to all connected clients
+    pub fn with_signals(mut self, signals: Receiver<Signal>) -> Self {
+        self.signals = Some(signals);
+        self
+    }
+}
+
+/// Renders a user signal as the JSON-RPC notification websocket clients receive, i.e.
+/// `{"jsonrpc": "2.0", "method": "signal", "params": {"instance_id", "name", "payload"}}`
+fn signal_notification(signal: &Signal) -> Option<String> {
+    match signal {
+        Signal::User {
+            instance_id,
+            name,
+            payload,
+        } => {
+            let payload: Value = serde_json::from_str(&String::from(payload.clone()))
+                .unwrap_or_else(|_| Value::String(String::from(payload.clone())));
+            Some(
+                json!({
+                    "jsonrpc": "2.0",
+                    "method": "signal",
+                    "params": {
+                        "instance_id": instance_id,
+                        "name": name,
+                        "payload": payload,
+                    }
+                })
+                .to_string(),
+            )
+        }
+        Signal::Internal(_) => None,
     }
 }
 
 impl Interface for WebsocketInterface {
     fn run(&self, handler: IoHandler, kill_switch: Receiver<()>) -> Result<(), String> {
         let url = format!("0.0.0.0:{}", self.port);
-        let _server = ServerBuilder::new(handler)
+        let server = ServerBuilder::new(handler)
             .start(&url.parse().expect("Invalid URL!"))
             .map_err(|e| e.to_string())?;
+        if let Some(ref signals) = self.signals {
+            let broadcaster = server.broadcaster();
+            // The conductor drops the sending end when it stops the interface
+            for signal in signals.iter() {
+                if let Some(notification) = signal_notification(&signal) {
+                    let _ = broadcaster.send(notification);
+                }
+            }
+        }
         let _ = kill_switch.recv();
         Ok(())
     }
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core_types::json::JsonString;
+
+    #[test]
+    fn renders_user_signals_as_notifications() {
+        let notification = signal_notification(&Signal::User {
+            instance_id: "test-instance".to_string(),
+            name: "new_message".to_string(),
+            payload: JsonString::from("{\"text\":\"hello\"}"),
+        })
+        .unwrap();
+        let notification: Value = serde_json::from_str(&notification).unwrap();
+        assert_eq!(
+            notification,
+            json!({
+                "jsonrpc": "2.0",
+                "method": "signal",
+                "params": {
+                    "instance_id": "test-instance",
+                    "name": "new_message",
+                    "payload": {"text": "hello"},
+                }
+            })
+        );
+    }
+}
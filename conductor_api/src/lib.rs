@@ -92,9 +92,11 @@ extern crate futures;
 extern crate holochain_cas_implementations;
 extern crate holochain_core;
 extern crate holochain_core_types;
+extern crate holochain_dpki;
 extern crate holochain_net;
 extern crate holochain_net_connection;
 extern crate holochain_net_ipc;
+extern crate holochain_sodium;
 
 extern crate chrono;
 extern crate serde;
@@ -106,6 +108,7 @@ extern crate colored;
 #[cfg(test)]
 extern crate holochain_wasm_utils;
 extern crate jsonrpc_http_server;
+extern crate jsonrpc_ipc_server;
 extern crate jsonrpc_ws_server;
 extern crate petgraph;
 extern crate regex;
@@ -1,8 +1,10 @@
 use crate::{
-    agent::state::AgentState,
+    agent::{bundle::Bundle, state::AgentState},
     context::Context,
     network::{
-        direct_message::DirectMessage, entry_with_header::EntryWithHeader, state::NetworkState,
+        direct_message::DirectMessage,
+        entry_with_header::{EncryptedEntryWithHeader, EntryWithHeader},
+        state::NetworkState,
     },
     nucleus::{
         state::{NucleusState, ValidationResult},
@@ -12,21 +14,24 @@ use crate::{
 use holochain_core_types::{
     cas::content::Address,
     chain_header::ChainHeader,
+    crud_status::CrudStatus,
     dna::Dna,
-    entry::{Entry, EntryWithMeta},
+    entry::{encrypted_entry::EncryptedEntryWithMeta, Entry, EntryWithMeta},
     error::HolochainError,
     json::JsonString,
     link::Link,
     validation::ValidationPackage,
+    warrant::Warrant,
 };
 use holochain_net_connection::json_protocol::{
     FetchEntryData, FetchEntryResultData, FetchMetaData, FetchMetaResultData,
 };
-use snowflake;
+use holochain_wasm_utils::api_serialization::bundle::BundleOnClose;
 use std::{
     hash::{Hash, Hasher},
     sync::Arc,
 };
+use uuid::Uuid;
 
 /// Wrapper for actions that provides a unique ID
 /// The unique ID is needed for state tracking to ensure that we can differentiate between two
@@ -37,27 +42,32 @@ use std::{
 #[derive(Clone, Debug)]
 pub struct ActionWrapper {
     action: Action,
-    id: snowflake::ProcessUniqueId,
+    id: Uuid,
 }
 
 impl ActionWrapper {
     /// constructor from &Action
-    /// internal snowflake ID is automatically set
+    /// internal UUID is automatically set
     pub fn new(a: Action) -> Self {
         ActionWrapper {
             action: a,
             // auto generate id
-            id: snowflake::ProcessUniqueId::new(),
+            id: Uuid::new_v4(),
         }
     }
 
+    /// constructor for actions restored from a persisted state, which keep their id
+    pub fn with_id(a: Action, id: Uuid) -> Self {
+        ActionWrapper { action: a, id }
+    }
+
     /// read only access to action
     pub fn action(&self) -> &Action {
         &self.action
     }
 
     /// read only access to id
-    pub fn id(&self) -> &snowflake::ProcessUniqueId {
+    pub fn id(&self) -> &Uuid {
         &self.id
     }
 }
@@ -71,9 +81,7 @@ impl PartialEq for ActionWrapper {
 impl Eq for ActionWrapper {}
 
 impl Hash for ActionWrapper {
-    /// @TODO dangerous when persisted!
-    /// snowflake only guarantees uniqueness per process
-    /// @see https://github.com/holochain/holochain-rust/issues/203
+    /// UUIDs stay unique across restarts, so this is safe for persisted actions
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.id.hash(state);
     }
@@ -87,7 +95,16 @@ pub enum Action {
     // ----------------
     /// Writes an entry to the source chain.
     /// Does not validate, assumes entry is valid.
-    Commit((Entry, Option<Address>)),
+    /// If the given zome call has a bundle open, the entry only gets staged in that bundle.
+    Commit((Entry, Option<Address>, Option<ZomeFnCall>)),
+
+    /// Opens the given bundle for the given zome call.
+    /// Commits made by that call get staged in the bundle until it is closed.
+    StartBundle((ZomeFnCall, Bundle)),
+
+    /// Closes the bundle with the given id and either appends its staged commits
+    /// to the source chain or discards them.
+    CloseBundle((String, BundleOnClose)),
 
     // -------------
     // DHT actions:
@@ -96,10 +113,23 @@ pub enum Action {
     /// Does not validate, assumes entry is valid.
     Hold(EntryWithHeader),
 
+    /// Adds the cipher text of an entry of a type with encrypted sharing to the local DHT shard.
+    /// Does not validate, the content can only be read by the entry's recipients.
+    HoldEncrypted(EncryptedEntryWithHeader),
+
+    /// Records an entry that failed validation as rejected in the local DHT shard,
+    /// together with the warrant stating why.
+    /// Does not check the warrant.
+    HoldWarrant(Warrant),
+
     /// Adds a link to the local DHT shard's meta/EAV storage
     /// Does not validate, assumes link is valid.
     AddLink(Link),
 
+    /// Marks a link as removed in the local DHT shard's meta/EAV storage
+    /// Does not validate, assumes the link removal is valid.
+    RemoveLink(Link),
+
     // ----------------
     // Network actions:
     // ----------------
@@ -112,6 +142,9 @@ pub enum Action {
     /// (only publish for AppEntryType, publish and publish_meta for links etc)
     Publish(Address),
 
+    /// Makes the network PUT the given warrant as metadata of the entry it rejects.
+    PublishWarrant(Warrant),
+
     /// Fetch an Entry on the network by address
     FetchEntry(GetEntryKey),
 
@@ -120,6 +153,10 @@ pub enum Action {
     /// requested entry from our local DHT shard.
     RespondFetch((FetchEntryData, Option<EntryWithMeta>)),
 
+    /// Lets the network module respond to a FETCH request for an entry that we only
+    /// hold the cipher text of, because its type has encrypted sharing.
+    RespondFetchEncrypted((FetchEntryData, EncryptedEntryWithMeta)),
+
     /// We got a response for our FETCH request which needs to be added to the state.
     /// Triggered from the network handler.
     HandleFetchResult(FetchEntryResultData),
@@ -128,6 +165,9 @@ pub enum Action {
     UpdateEntry((Address, Address)),
     ///
     RemoveEntry((Address, Address)),
+    /// Records which one of several concurrent updates of an entry won the conflict resolution.
+    /// Payload: (address of the updated entry, address of the winning update)
+    ResolveConflict((Address, Address)),
     ///
     GetEntryTimeout(GetEntryKey),
 
@@ -135,7 +175,7 @@ pub enum Action {
     /// Last string is the stringified process unique id of this `hdk::get_links` call.
     GetLinks(GetLinksKey),
     GetLinksTimeout(GetLinksKey),
-    RespondGetLinks((FetchMetaData, Vec<Address>)),
+    RespondGetLinks((FetchMetaData, Vec<(Address, CrudStatus)>)),
     HandleGetLinksResult((FetchMetaResultData, String)),
 
     /// Makes the network module send a direct (node-to-node) message
@@ -188,16 +228,11 @@ pub enum Action {
     /// A validation result is returned from a local callback execution
     /// Key is an unique id of the calling context
     /// and the hash of the entry that was validated
-    ReturnValidationResult(((snowflake::ProcessUniqueId, Address), ValidationResult)),
+    ReturnValidationResult(((Uuid, Address), ValidationResult)),
 
     /// A validation package was created locally and is reported back
     /// to be added to the state
-    ReturnValidationPackage(
-        (
-            snowflake::ProcessUniqueId,
-            Result<ValidationPackage, HolochainError>,
-        ),
-    ),
+    ReturnValidationPackage((Uuid, Result<ValidationPackage, HolochainError>)),
 }
 
 /// function signature for action handler functions
@@ -293,14 +328,14 @@ pub mod tests {
 
     /// dummy action wrapper with commit of test_entry()
     pub fn test_action_wrapper_commit() -> ActionWrapper {
-        ActionWrapper::new(Action::Commit((test_entry(), None)))
+        ActionWrapper::new(Action::Commit((test_entry(), None, None)))
     }
 
     /// dummy action for a get of test_hash()
     pub fn test_action_wrapper_get() -> ActionWrapper {
         ActionWrapper::new(Action::FetchEntry(GetEntryKey {
             address: expected_entry_address(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         }))
     }
 
@@ -324,7 +359,7 @@ pub mod tests {
         let aw1 = test_action_wrapper();
         let aw2 = test_action_wrapper();
 
-        // snowflake enforces uniqueness
+        // UUIDs enforce uniqueness
         assert_eq!(aw1, aw1);
         assert_ne!(aw1, aw2);
     }
@@ -358,5 +393,4 @@ pub mod tests {
 
         assert_ne!(calculate_hash(&aw1), calculate_hash(&aw2));
     }
-
 }
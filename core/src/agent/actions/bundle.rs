@@ -0,0 +1,139 @@
+extern crate futures;
+use crate::{
+    action::{Action, ActionWrapper},
+    agent::{bundle::Bundle, state::ActionResponse},
+    context::Context,
+    instance::dispatch_action,
+};
+use futures::{
+    future::Future,
+    task::{LocalWaker, Poll},
+};
+use holochain_core_types::{cas::content::Address, error::HolochainError, time::Timeout};
+use holochain_wasm_utils::api_serialization::bundle::BundleOnClose;
+use serde_json::Value;
+use std::{pin::Pin, sync::Arc, thread};
+use uuid::Uuid;
+
+/// StartBundle Action Creator
+/// Opens a new bundle in the agent state for the zome call the context is scoped to,
+/// so that all following commits of that call get staged until the bundle gets closed.
+/// If the bundle is still open after the given timeout it gets discarded.
+///
+/// Returns a future that resolves to the id of the new bundle.
+pub async fn start_bundle(
+    user_param: Value,
+    timeout: Timeout,
+    context: &Arc<Context>,
+) -> Result<String, HolochainError> {
+    let zome_call = context.zome_call.clone().ok_or_else(|| {
+        HolochainError::ErrorGeneric("Bundles can only be opened by zome calls".to_string())
+    })?;
+    let id = Uuid::new_v4().to_string();
+    let action_wrapper = ActionWrapper::new(Action::StartBundle((
+        zome_call,
+        Bundle::new(id.clone(), user_param),
+    )));
+    dispatch_action(context.action_channel(), action_wrapper.clone());
+    await!(StartBundleFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    })?;
+
+    let id_inner = id.clone();
+    let context_inner = context.clone();
+    let _ = thread::spawn(move || {
+        thread::sleep(timeout.into());
+        let action_wrapper =
+            ActionWrapper::new(Action::CloseBundle((id_inner, BundleOnClose::Discard)));
+        dispatch_action(context_inner.action_channel(), action_wrapper.clone());
+    });
+
+    Ok(id)
+}
+
+/// CloseBundle Action Creator
+/// Closes the bundle of the zome call the context is scoped to and either appends
+/// the staged commits to the source chain or discards them.
+/// This does not validate the staged entries, see workflows::close_bundle for that.
+///
+/// Returns a future that resolves to the addresses of the appended entries.
+pub async fn close_bundle(
+    on_close: BundleOnClose,
+    context: &Arc<Context>,
+) -> Result<Vec<Address>, HolochainError> {
+    let bundle = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("State not initialized".to_string()))?
+        .agent()
+        .bundle(&context.zome_call)
+        .ok_or_else(|| HolochainError::ErrorGeneric("No bundle open".to_string()))?;
+    let action_wrapper = ActionWrapper::new(Action::CloseBundle((bundle.id().clone(), on_close)));
+    dispatch_action(context.action_channel(), action_wrapper.clone());
+    await!(CloseBundleFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    })
+}
+
+/// StartBundleFuture resolves to ActionResponse::StartBundle's result
+/// Tracks the state for a response to its ActionWrapper
+pub struct StartBundleFuture {
+    context: Arc<Context>,
+    action: ActionWrapper,
+}
+
+impl Future for StartBundleFuture {
+    type Output = Result<(), HolochainError>;
+
+    fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        //
+        // TODO: connect the waker to state updates for performance reasons
+        // See: https://github.com/holochain/holochain-rust/issues/314
+        //
+        lw.wake();
+        match self
+            .context
+            .state()
+            .unwrap()
+            .agent()
+            .actions()
+            .get(&self.action)
+        {
+            Some(ActionResponse::StartBundle(result)) => Poll::Ready(result.clone()),
+            Some(_) => unreachable!(),
+            None => Poll::Pending,
+        }
+    }
+}
+
+/// CloseBundleFuture resolves to ActionResponse::CloseBundle's result
+/// Tracks the state for a response to its ActionWrapper
+pub struct CloseBundleFuture {
+    context: Arc<Context>,
+    action: ActionWrapper,
+}
+
+impl Future for CloseBundleFuture {
+    type Output = Result<Vec<Address>, HolochainError>;
+
+    fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        //
+        // TODO: connect the waker to state updates for performance reasons
+        // See: https://github.com/holochain/holochain-rust/issues/314
+        //
+        lw.wake();
+        match self
+            .context
+            .state()
+            .unwrap()
+            .agent()
+            .actions()
+            .get(&self.action)
+        {
+            Some(ActionResponse::CloseBundle(result)) => Poll::Ready(result.clone()),
+            Some(_) => unreachable!(),
+            None => Poll::Pending,
+        }
+    }
+}
@@ -17,13 +17,19 @@ use std::{pin::Pin, sync::Arc};
 /// This is the high-level commit function that wraps the whole commit process and is what should
 /// be called from zome api functions and other contexts that don't care about implementation details.
 ///
+/// If the zome call the context is scoped to has a bundle open, the entry only gets staged.
+///
 /// Returns a future that resolves to an ActionResponse.
 pub async fn commit_entry(
     entry: Entry,
     maybe_crud_link: Option<Address>,
     context: &Arc<Context>,
 ) -> Result<Address, HolochainError> {
-    let action_wrapper = ActionWrapper::new(Action::Commit((entry, maybe_crud_link)));
+    let action_wrapper = ActionWrapper::new(Action::Commit((
+        entry,
+        maybe_crud_link,
+        context.zome_call.clone(),
+    )));
     dispatch_action(context.action_channel(), action_wrapper.clone());
     await!(CommitFuture {
         context: context.clone(),
@@ -1,2 +1,3 @@
+pub mod bundle;
 pub mod commit;
 pub mod update_entry;
@@ -0,0 +1,179 @@
+use crate::agent::chain_store::ChainStore;
+use holochain_cas_implementations::cas::memory::MemoryStorage;
+use holochain_core_types::{
+    cas::{
+        content::{Address, AddressableContent},
+        storage::ContentAddressableStorage,
+    },
+    chain_header::ChainHeader,
+    entry::Entry,
+    error::HolochainError,
+};
+use serde_json::Value;
+use std::{
+    convert::TryInto,
+    sync::{Arc, RwLock},
+};
+
+/// A bundle is a transactional scope for commits, opened by `hdk::start_bundle`.
+/// Every bundle belongs to the zome call that opened it.
+/// While a bundle is open, commits of that call are not appended to the agent's source chain but
+/// staged in a scratch chain that is held in memory. The scratch chain links onto the
+/// agent's top chain header, so the staged headers are exactly the headers that end up
+/// in the source chain if the bundle gets committed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bundle {
+    id: String,
+    user_param: Value,
+    scratch_chain: ChainStore,
+    top_chain_header: Option<ChainHeader>,
+}
+
+impl Bundle {
+    pub fn new(id: String, user_param: Value) -> Self {
+        Bundle {
+            id,
+            user_param,
+            scratch_chain: ChainStore::new(Arc::new(RwLock::new(MemoryStorage::new()))),
+            top_chain_header: None,
+        }
+    }
+
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    pub fn user_param(&self) -> &Value {
+        &self.user_param
+    }
+
+    pub fn scratch_chain(&self) -> ChainStore {
+        self.scratch_chain.clone()
+    }
+
+    /// The most recently staged header, or None if nothing was committed in this bundle yet
+    pub fn top_chain_header(&self) -> Option<ChainHeader> {
+        self.top_chain_header.clone()
+    }
+
+    /// Whether the staged headers link onto the given top of the source chain, i.e. whether
+    /// they can be appended to it without forking the chain.
+    /// A bundle with nothing staged fits onto any chain.
+    pub fn fits_onto(&self, top_chain_header: &Option<ChainHeader>) -> bool {
+        match self.staged_headers().first() {
+            Some(oldest_staged_header) => {
+                oldest_staged_header.link()
+                    == top_chain_header
+                        .as_ref()
+                        .map(|chain_header| chain_header.address())
+            }
+            None => true,
+        }
+    }
+
+    /// Stages an entry and its header on top of the scratch chain.
+    pub fn stage(
+        &mut self,
+        entry: &Entry,
+        chain_header: &ChainHeader,
+    ) -> Result<(), HolochainError> {
+        let storage = self.scratch_chain.content_storage();
+        storage.write().unwrap().add(entry)?;
+        storage.write().unwrap().add(chain_header)?;
+        self.top_chain_header = Some(chain_header.clone());
+        Ok(())
+    }
+
+    /// All staged headers, oldest first.
+    pub fn staged_headers(&self) -> Vec<ChainHeader> {
+        let mut headers: Vec<ChainHeader> =
+            self.scratch_chain.iter(&self.top_chain_header).collect();
+        headers.reverse();
+        headers
+    }
+
+    /// All staged entries together with their headers, oldest first.
+    pub fn staged_entries(&self) -> Result<Vec<(Entry, ChainHeader)>, HolochainError> {
+        self.staged_headers()
+            .into_iter()
+            .map(|chain_header| {
+                let entry = self
+                    .get_entry(chain_header.entry_address())?
+                    .ok_or_else(|| {
+                        HolochainError::ErrorGeneric(format!(
+                            "Staged entry {} missing from bundle",
+                            chain_header.entry_address()
+                        ))
+                    })?;
+                Ok((entry, chain_header))
+            })
+            .collect()
+    }
+
+    /// Looks up a staged entry by its address.
+    pub fn get_entry(&self, address: &Address) -> Result<Option<Entry>, HolochainError> {
+        let storage = self.scratch_chain.content_storage();
+        let maybe_content = storage.read().unwrap().fetch(address)?;
+        Ok(maybe_content.and_then(|content| content.try_into().ok()))
+    }
+
+    /// Returns the header of the most recently staged entry with the given address.
+    pub fn find_chain_header(&self, entry: &Entry) -> Option<ChainHeader> {
+        self.scratch_chain
+            .iter(&self.top_chain_header)
+            .find(|chain_header| *chain_header.entry_address() == entry.address())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core_types::{
+        chain_header::test_chain_header,
+        entry::{test_entry, test_entry_b},
+        time::Iso8601,
+    };
+
+    fn header_for(entry: &Entry, link: &Option<ChainHeader>) -> ChainHeader {
+        ChainHeader::new(
+            &entry.entry_type(),
+            &entry.address(),
+            &test_chain_header().provenances().clone(),
+            &link.as_ref().map(|header| header.address()),
+            &None,
+            &None,
+            &Iso8601::from(""),
+        )
+    }
+
+    #[test]
+    fn staged_entries_are_returned_in_commit_order() {
+        let mut bundle = Bundle::new("bundle".into(), json!({"some": "param"}));
+        assert_eq!(bundle.top_chain_header(), None);
+        assert_eq!(bundle.staged_entries(), Ok(Vec::new()));
+
+        // The first staged header links into the (non-scratch) source chain
+        let header_a = header_for(&test_entry(), &Some(test_chain_header()));
+        bundle.stage(&test_entry(), &header_a).unwrap();
+        let header_b = header_for(&test_entry_b(), &Some(header_a.clone()));
+        bundle.stage(&test_entry_b(), &header_b).unwrap();
+
+        assert_eq!(bundle.top_chain_header(), Some(header_b.clone()));
+        assert_eq!(
+            bundle.staged_entries(),
+            Ok(vec![
+                (test_entry(), header_a.clone()),
+                (test_entry_b(), header_b.clone())
+            ]),
+        );
+        assert_eq!(bundle.find_chain_header(&test_entry()), Some(header_a));
+        assert_eq!(
+            bundle.get_entry(&test_entry_b().address()),
+            Ok(Some(test_entry_b()))
+        );
+        assert_eq!(bundle.user_param(), &json!({"some": "param"}));
+        assert!(bundle.fits_onto(&Some(test_chain_header())));
+        assert!(!bundle.fits_onto(&Some(header_b)));
+        assert!(!bundle.fits_onto(&None));
+    }
+}
@@ -0,0 +1,310 @@
+use crate::{
+    agent::{
+        chain_store::ChainStore, state::AgentStateSnapshot, verify_agent_id_headers,
+        verify_provenance_signatures,
+    },
+    nucleus::state::NucleusStatus,
+    state::{StateSnapshot, STATE_SNAPSHOT_VERSION},
+};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    entry::{entry_type::EntryType, Entry},
+    error::{HcResult, HolochainError},
+    json::JsonString,
+};
+use std::collections::HashMap;
+
+pub const CHAIN_ARCHIVE_VERSION: u32 = 1;
+
+/// A header of an archived source chain together with its entry.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct ChainArchiveItem {
+    pub header: ChainHeader,
+    pub entry: Entry,
+}
+
+/// Portable backup of a source chain that does not depend on the storage it was read from.
+/// Holds all headers with their entries, from the top of the chain down to the DNA entry,
+/// and the address of that DNA so an archive can be matched with the instance it belongs to.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, DefaultJson)]
+pub struct ChainArchive {
+    pub version: u32,
+    pub dna_address: Address,
+    pub items: Vec<ChainArchiveItem>,
+}
+
+impl ChainArchive {
+    /// Walks the chain down from the given top header and collects all headers and entries.
+    pub fn from_chain(
+        chain_store: &ChainStore,
+        top_chain_header: &Option<ChainHeader>,
+    ) -> HcResult<ChainArchive> {
+        let storage = chain_store.content_storage();
+        let items = chain_store
+            .iter(top_chain_header)
+            .map(|header| {
+                let content = storage
+                    .read()?
+                    .fetch(header.entry_address())?
+                    .ok_or_else(|| {
+                        HolochainError::ErrorGeneric(format!(
+                            "Entry {} of the source chain is missing in storage",
+                            header.entry_address()
+                        ))
+                    })?;
+                Ok(ChainArchiveItem {
+                    entry: Entry::try_from_content(&content)?,
+                    header,
+                })
+            })
+            .collect::<HcResult<Vec<_>>>()?;
+        let archive = ChainArchive {
+            version: CHAIN_ARCHIVE_VERSION,
+            dna_address: match items.last() {
+                Some(ChainArchiveItem {
+                    entry: Entry::Dna(dna),
+                    ..
+                }) => dna.address(),
+                _ => {
+                    return Err(HolochainError::ErrorGeneric(
+                        "Source chain does not start with a DNA entry".to_string(),
+                    ));
+                }
+            },
+            items,
+        };
+        Ok(archive)
+    }
+
+    /// The header the archived chain ends with
+    pub fn top_chain_header(&self) -> Option<ChainHeader> {
+        self.items.first().map(|item| item.header.clone())
+    }
+
+    /// The newest AgentId entry of the archived chain, i.e. the agent's current key
+    pub fn agent_address(&self) -> Option<Address> {
+        self.items
+            .iter()
+            .find(|item| *item.header.entry_type() == EntryType::AgentId)
+            .map(|item| item.entry.address())
+    }
+
+    /// Re-verifies the archived chain as a whole: every entry has to hash to the address in
+    /// its header, every header has to link to the hash of the header below it (and of the
+    /// header of the same type below it), its provenances have to carry valid signatures,
+    /// and the chain has to start with the DNA the archive names.
+    pub fn verify(&self) -> HcResult<()> {
+        if self.version > CHAIN_ARCHIVE_VERSION {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Chain archive has version {} but only versions up to {} are supported",
+                self.version, CHAIN_ARCHIVE_VERSION
+            )));
+        }
+        match self.items.last() {
+            Some(ChainArchiveItem {
+                entry: Entry::Dna(dna),
+                ..
+            }) if dna.address() == self.dna_address => (),
+            _ => {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Archived chain does not start with DNA {}",
+                    self.dna_address
+                )));
+            }
+        }
+
+        let agent_id_headers: Vec<ChainHeader> = self
+            .items
+            .iter()
+            .filter(|item| *item.header.entry_type() == EntryType::AgentId)
+            .map(|item| item.header.clone())
+            .collect();
+        verify_agent_id_headers(&agent_id_headers)?;
+
+        // Walk the chain up from the DNA, keeping track of the headers each header
+        // has to link to and of the agent's key at its position
+        let mut previous_header: Option<Address> = None;
+        let mut previous_header_of_type: HashMap<EntryType, Address> = HashMap::new();
+        let mut initial_key: Option<Address> = None;
+        let mut current_key: Option<Address> = None;
+        for item in self.items.iter().rev() {
+            let header = &item.header;
+            if *header.entry_address() != item.entry.address()
+                || *header.entry_type() != item.entry.entry_type()
+            {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Entry of archived header {} does not match the header",
+                    header.address()
+                )));
+            }
+
+            if header.link() != previous_header
+                || header.link_same_type()
+                    != previous_header_of_type.get(header.entry_type()).cloned()
+            {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Archived header {} does not link to the header below it",
+                    header.address()
+                )));
+            }
+
+            verify_provenance_signatures(header, initial_key.as_ref(), current_key.as_ref())?;
+
+            if *header.entry_type() == EntryType::AgentId {
+                initial_key = initial_key.or_else(|| Some(header.entry_address().clone()));
+                current_key = Some(header.entry_address().clone());
+            }
+            previous_header = Some(header.address());
+            previous_header_of_type.insert(header.entry_type().clone(), header.address());
+        }
+        Ok(())
+    }
+
+    /// Verifies the archive and adds all its headers and entries to the given chain store.
+    /// Unless the chain the store currently holds up to the given top header consists of
+    /// genesis entries only, the archive has to contain that top header. Otherwise an older
+    /// archive would rewind the chain and the next commit would fork it.
+    /// Returns the snapshot of an initialized instance running the imported chain,
+    /// which is what a persister has to restore to resume with it.
+    pub fn import(
+        &self,
+        chain_store: &ChainStore,
+        top_chain_header: &Option<ChainHeader>,
+    ) -> HcResult<StateSnapshot> {
+        self.verify()?;
+        let genesis_only =
+            chain_store
+                .iter(top_chain_header)
+                .all(|header| match header.entry_type() {
+                    EntryType::Dna | EntryType::AgentId => true,
+                    _ => false,
+                });
+        if let Some(top_chain_header) = top_chain_header {
+            if !genesis_only
+                && !self
+                    .items
+                    .iter()
+                    .any(|item| item.header == *top_chain_header)
+            {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Archived chain does not contain the current top header {}",
+                    top_chain_header.address()
+                )));
+            }
+        }
+        let storage = chain_store.content_storage();
+        for item in self.items.iter().rev() {
+            storage.write()?.add(&item.entry)?;
+            storage.write()?.add(&item.header)?;
+        }
+        Ok(StateSnapshot {
+            version: STATE_SNAPSHOT_VERSION,
+            agent: self.top_chain_header().map(AgentStateSnapshot::new),
+            nucleus_status: NucleusStatus::Initialized,
+            pending_publishes: Vec::new(),
+        })
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{nucleus::actions::tests::instance, workflows::author_entry::author_entry};
+    use holochain_cas_implementations::cas::memory::MemoryStorage;
+    use holochain_core_types::entry::{test_entry, test_entry_b};
+    use std::{
+        convert::TryFrom,
+        sync::{Arc, RwLock},
+    };
+
+    fn test_archive() -> ChainArchive {
+        let (_instance, context) = instance(None);
+        context
+            .block_on(author_entry(&test_entry(), None, &context))
+            .unwrap();
+        context
+            .block_on(author_entry(&test_entry_b(), None, &context))
+            .unwrap();
+        let agent_state = context.state().unwrap().agent();
+        ChainArchive::from_chain(&agent_state.chain_store(), &agent_state.top_chain_header())
+            .unwrap()
+    }
+
+    #[test]
+    fn can_export_and_import_chain() {
+        let archive = test_archive();
+        assert_eq!(archive.version, CHAIN_ARCHIVE_VERSION);
+        assert_eq!(archive.items[0].entry, test_entry_b());
+        assert_eq!(archive.verify(), Ok(()));
+
+        let json = JsonString::from(archive.clone());
+        assert_eq!(ChainArchive::try_from(json).unwrap(), archive);
+
+        let chain_store = ChainStore::new(Arc::new(RwLock::new(MemoryStorage::new())));
+        let snapshot = archive.import(&chain_store, &None).unwrap();
+        assert_eq!(snapshot.nucleus_status, NucleusStatus::Initialized);
+        let top_chain_header = snapshot.agent.map(|agent| agent.top_chain_header().clone());
+        assert_eq!(top_chain_header, archive.top_chain_header());
+        assert_eq!(
+            ChainArchive::from_chain(&chain_store, &top_chain_header).unwrap(),
+            archive
+        );
+    }
+
+    #[test]
+    fn rejects_archives_missing_the_top_header() {
+        let (_instance, context) = instance(None);
+        let export = || {
+            let agent_state = context.state().unwrap().agent();
+            ChainArchive::from_chain(&agent_state.chain_store(), &agent_state.top_chain_header())
+                .unwrap()
+        };
+        let genesis_archive = export();
+        context
+            .block_on(author_entry(&test_entry(), None, &context))
+            .unwrap();
+        let older_archive = export();
+        context
+            .block_on(author_entry(&test_entry_b(), None, &context))
+            .unwrap();
+        let archive = export();
+
+        let agent_state = context.state().unwrap().agent();
+        let chain_store = agent_state.chain_store();
+        let top_chain_header = agent_state.top_chain_header();
+        assert!(older_archive
+            .import(&chain_store, &top_chain_header)
+            .is_err());
+        assert!(archive.import(&chain_store, &top_chain_header).is_ok());
+
+        // a chain holding nothing but genesis entries can be replaced
+        let genesis_top_chain_header = genesis_archive.top_chain_header();
+        assert!(older_archive
+            .import(&chain_store, &genesis_top_chain_header)
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_tampered_entries() {
+        let mut archive = test_archive();
+        archive.items[0].entry = test_entry();
+        assert!(archive.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_broken_links() {
+        let mut archive = test_archive();
+        archive.items.remove(1);
+        assert!(archive.verify().is_err());
+    }
+
+    #[test]
+    fn rejects_other_dna() {
+        let mut archive = test_archive();
+        archive.dna_address = Address::from("QmOtherDna");
+        assert!(archive.verify().is_err());
+        let chain_store = ChainStore::new(Arc::new(RwLock::new(MemoryStorage::new())));
+        assert!(archive.import(&chain_store, &None).is_err());
+    }
+}
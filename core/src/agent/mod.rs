@@ -1,19 +1,582 @@
 /// Agent is the module that handles the user’s identity and source chain for every Phenotype.
 ///
 pub mod actions;
+pub mod bundle;
+pub mod chain_archive;
 pub mod chain_store;
 pub mod state;
 
-use crate::context::Context;
+use crate::{
+    context::Context,
+    nucleus::actions::get_entry::{get_entry_from_agent, get_entry_from_bundle},
+};
 use holochain_core_types::{
-    cas::content::AddressableContent, chain_header::ChainHeader, entry::Entry,
+    agent::AgentId,
+    cas::content::{Address, AddressableContent},
+    chain_header::{provenance_payload, ChainHeader},
+    dna::Dna,
+    entry::{entry_type::EntryType, Entry},
+    error::HolochainError,
+    signature::Signature,
+    validation::ValidationPackage,
 };
+use holochain_dpki::keypair::Keypair;
+use holochain_sodium::{secbuf::SecBuf, sign};
 use std::sync::Arc;
 
+/// Finds the header of the given entry in the agent's source chain,
+/// or among the staged commits if the zome call the context is scoped to has a bundle open.
 pub fn find_chain_header(entry: &Entry, context: &Arc<Context>) -> Option<ChainHeader> {
+    if let Some(chain_header) = context
+        .state()
+        .unwrap()
+        .agent()
+        .bundle(&context.zome_call)
+        .and_then(|bundle| bundle.find_chain_header(entry))
+    {
+        return Some(chain_header);
+    }
     let chain = context.state().unwrap().agent().chain_store();
     let top_header = context.state().unwrap().agent().top_chain_header();
     chain
         .iter(&top_header)
         .find(|ref header| *header.entry_address() == entry.address())
 }
+
+/// Whether the given entry of the agent's source chain gets published to the DHT.
+/// Besides its type having to be publishable, deletions and links must not reveal entries of
+/// the agent's source chain that never got published, like the capability grant a revocation
+/// deletes or a private entry a link starts from or points to.
+pub fn can_publish_chain_entry(entry: &Entry, dna: &Dna, context: &Arc<Context>) -> bool {
+    if !entry.entry_type().can_publish(dna) {
+        return false;
+    }
+    let can_reveal = |address: &Address| match get_entry_from_agent(context, address) {
+        Ok(Some(referenced_entry)) => referenced_entry.entry_type().can_publish(dna),
+        // entries of other agents are not ours to hide
+        Ok(None) => true,
+        Err(_) => false,
+    };
+    match entry {
+        Entry::Deletion(deletion_entry) => {
+            can_reveal(&deletion_entry.clone().deleted_entry_address())
+        }
+        Entry::LinkAdd(link_add) => {
+            can_reveal(link_add.link().base()) && can_reveal(link_add.link().target())
+        }
+        Entry::LinkRemove(link_remove) => {
+            can_reveal(link_remove.link().base()) && can_reveal(link_remove.link().target())
+        }
+        _ => true,
+    }
+}
+
+/// Returns the headers of all AgentId entries that precede the given header in the
+/// agent's source chain (including commits staged in the bundle of the zome call the context
+/// is scoped to), newest first.
+pub fn agent_id_headers_before(
+    chain_header: &ChainHeader,
+    context: &Arc<Context>,
+) -> Vec<ChainHeader> {
+    headers_before(chain_header, context)
+        .into_iter()
+        .filter(|header| *header.entry_type() == EntryType::AgentId)
+        .collect()
+}
+
+/// Returns the headers that precede the given header in the agent's source chain back to
+/// the newest AgentId entry (exclusive), or to the start of the chain if there is none,
+/// newest first.
+pub fn headers_since_agent_id(
+    chain_header: &ChainHeader,
+    context: &Arc<Context>,
+) -> Vec<ChainHeader> {
+    headers_before(chain_header, context)
+        .into_iter()
+        .take_while(|header| *header.entry_type() != EntryType::AgentId)
+        .collect()
+}
+
+/// Returns the AgentId entry the given header of the agent's source chain got committed with.
+pub fn chain_agent_id(agent_id_header: &ChainHeader, context: &Arc<Context>) -> Option<AgentId> {
+    let address = agent_id_header.entry_address();
+    let entry = match get_entry_from_bundle(context, address) {
+        Ok(Some(entry)) => Some(entry),
+        _ => get_entry_from_agent(context, address).unwrap_or(None),
+    };
+    match entry {
+        Some(Entry::AgentId(agent_id)) => Some(agent_id),
+        _ => None,
+    }
+}
+
+fn headers_before(chain_header: &ChainHeader, context: &Arc<Context>) -> Vec<ChainHeader> {
+    let agent_state = context.state().unwrap().agent();
+    let mut preceding_headers = Vec::new();
+    let mut oldest_header = chain_header.clone();
+    if let Some(bundle) = agent_state.bundle(&context.zome_call) {
+        preceding_headers = bundle
+            .scratch_chain()
+            .iter(&Some(chain_header.clone()))
+            .skip(1)
+            .collect();
+        if let Some(oldest_staged_header) = preceding_headers.last() {
+            oldest_header = oldest_staged_header.clone();
+        }
+    }
+    preceding_headers.extend(agent_state.chain_store().iter(&Some(oldest_header)).skip(1));
+    preceding_headers
+}
+
+/// Signs the payload with the private key of the given keypair
+/// and returns the base64 encoded signature.
+pub fn sign_with_keypair(
+    keypair: &mut Keypair,
+    payload: String,
+) -> Result<Signature, HolochainError> {
+    let mut message = SecBuf::with_insecure_from_string(payload);
+    let mut signature = SecBuf::with_insecure(sign::BYTES);
+    keypair.sign(&mut message, &mut signature)?;
+    let signature = signature.read_lock();
+    Ok(Signature::from(base64::encode(&**signature)))
+}
+
+/// Checks that the membrane proof of the given AgentId, if it has one,
+/// is signed together with the agent's key by that key.
+pub fn verify_membrane_proof(agent_id: &AgentId) -> Result<(), HolochainError> {
+    let payload = match agent_id.membrane_proof_payload() {
+        Some(payload) => payload,
+        None => return Ok(()),
+    };
+    let signed = match agent_id.membrane_proof_signature {
+        Some(ref signature) => {
+            verify_signature(&agent_id.address(), signature, payload).unwrap_or(false)
+        }
+        None => false,
+    };
+    if signed {
+        Ok(())
+    } else {
+        Err(HolochainError::ValidationFailed(format!(
+            "Membrane proof of agent {} is not signed by the agent",
+            agent_id.address()
+        )))
+    }
+}
+
+/// Checks if the given signature was created over the payload with the private key
+/// of the agent with the given address.
+/// Returns Ok(false) for a signature that does not match and an error
+/// if the address is not a valid agent key.
+pub fn verify_signature(
+    agent_address: &Address,
+    signature: &Signature,
+    payload: String,
+) -> Result<bool, HolochainError> {
+    let signature_bytes = match base64::decode(&String::from(signature.clone())) {
+        Ok(bytes) => bytes,
+        Err(_) => return Ok(false),
+    };
+    if signature_bytes.len() != sign::BYTES {
+        return Ok(false);
+    }
+    let mut signature_buf = SecBuf::with_insecure(sign::BYTES);
+    signature_buf.write(0, &signature_bytes)?;
+    let mut message = SecBuf::with_insecure_from_string(payload);
+    let result = Keypair::verify(agent_address.to_string(), &mut signature_buf, &mut message)?;
+    Ok(result == 0)
+}
+
+/// Verifies that every provenance of the given header carries a valid signature
+/// of the header's entry address.
+/// The validation package's `agent_id_headers` are the AgentId headers preceding the header
+/// in its source chain, newest first (see [agent_id_headers_before](fn.agent_id_headers_before.html)).
+/// The author of a chain is identified by its initial AgentId, but signs with the key that
+/// is current at the header's position in the chain, which is the newest preceding AgentId.
+/// This keeps headers valid across key rotations.
+/// Since the AgentId headers come with the header, each of them has to be signed by the key
+/// that was current before it (the initial one by its own key), so that nobody but the agent
+/// can introduce a key rotation, and the package's `headers_since_agent_id` have to link the
+/// header to the newest of them, so that no key rotation can be left out
+/// (see [verify_chain_position](fn.verify_chain_position.html)).
+pub fn verify_header_provenances(
+    header: &ChainHeader,
+    validation_package: &ValidationPackage,
+) -> Result<(), HolochainError> {
+    let agent_id_headers = &validation_package.agent_id_headers;
+    verify_agent_id_headers(agent_id_headers)?;
+    verify_chain_position(
+        header,
+        agent_id_headers,
+        &validation_package.headers_since_agent_id,
+    )?;
+    verify_provenance_signatures(
+        header,
+        agent_id_headers
+            .last()
+            .map(|initial| initial.entry_address()),
+        agent_id_headers
+            .first()
+            .map(|current| current.entry_address()),
+    )
+}
+
+/// Verifies that the given AgentId headers of a source chain, newest first, link to each other
+/// and that each of them is signed by the key of the one before it.
+pub fn verify_agent_id_headers(agent_id_headers: &[ChainHeader]) -> Result<(), HolochainError> {
+    // AgentId headers link to each other through link_same_type
+    for pair in agent_id_headers.windows(2) {
+        if pair[0].link_same_type() != Some(pair[1].address()) {
+            return Err(HolochainError::ValidationFailed(format!(
+                "AgentId header {} does not link to its predecessor",
+                pair[0].address()
+            )));
+        }
+    }
+    for (index, agent_id_header) in agent_id_headers.iter().enumerate() {
+        let previous_key = agent_id_headers
+            .get(index + 1)
+            .unwrap_or(agent_id_header)
+            .entry_address();
+        let mut signed_by_previous_key = false;
+        for (_, signature) in agent_id_header.provenances() {
+            if verify_signature(
+                previous_key,
+                signature,
+                provenance_payload(agent_id_header.entry_address()),
+            )? {
+                signed_by_previous_key = true;
+            }
+        }
+        if !signed_by_previous_key {
+            return Err(HolochainError::ValidationFailed(format!(
+                "AgentId header {} is not signed by the agent's previous key",
+                agent_id_header.address()
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Verifies that the given AgentId headers, newest first, are all the AgentId headers that
+/// precede the header in its source chain: following the header's links through the given
+/// headers since the newest of them has to lead to that one without passing any other
+/// AgentId header, and the oldest of them has to be the agent's initial one.
+/// Otherwise a header signed with a key the agent rotated away from could pass as one that
+/// got committed before the rotation.
+pub fn verify_chain_position(
+    header: &ChainHeader,
+    agent_id_headers: &[ChainHeader],
+    headers_since_agent_id: &[ChainHeader],
+) -> Result<(), HolochainError> {
+    let mut previous_header = header.link();
+    for preceding_header in headers_since_agent_id {
+        if previous_header != Some(preceding_header.address())
+            || *preceding_header.entry_type() == EntryType::AgentId
+        {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Header {} does not link to the source chain headers it came with",
+                header.address()
+            )));
+        }
+        previous_header = preceding_header.link();
+    }
+    if previous_header != agent_id_headers.first().map(|newest| newest.address()) {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Header {} did not come with all AgentId headers preceding it",
+            header.address()
+        )));
+    }
+    match agent_id_headers.last() {
+        Some(initial) if initial.link_same_type().is_some() => {
+            Err(HolochainError::ValidationFailed(format!(
+                "AgentId header {} is not the agent's initial one",
+                initial.address()
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Verifies the signatures of the header's provenances, given the initial and the current key
+/// of the agent whose chain the header is part of, without verifying the keys themselves
+/// (see [verify_header_provenances](fn.verify_header_provenances.html)).
+pub fn verify_provenance_signatures(
+    header: &ChainHeader,
+    initial_key: Option<&Address>,
+    current_key: Option<&Address>,
+) -> Result<(), HolochainError> {
+    if header.provenances().is_empty() {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Header for entry {} has no provenances",
+            header.entry_address()
+        )));
+    }
+    for (agent_address, signature) in header.provenances() {
+        let signing_key = match (initial_key, current_key) {
+            (Some(initial), Some(current)) if initial == agent_address => current,
+            _ => agent_address,
+        };
+        if !verify_signature(
+            signing_key,
+            signature,
+            provenance_payload(header.entry_address()),
+        )? {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Signature of agent {} for entry {} is invalid",
+                agent_address,
+                header.entry_address()
+            )));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{
+        agent::{actions::commit::commit_entry, state::create_new_chain_header},
+        instance::tests::{test_context, test_context_with_state},
+        nucleus::actions::tests::{instance_by_name, test_dna},
+    };
+    use holochain_core_types::{
+        chain_header::test_chain_header,
+        dna::entry_types::Sharing,
+        entry::{
+            deletion_entry::DeletionEntry, entry_type::AppEntryType, test_entry, test_entry_b,
+        },
+        link::{link_add::LinkAdd, link_remove::LinkRemove},
+        time::Iso8601,
+    };
+
+    #[test]
+    fn can_verify_signature_of_own_agent() {
+        let context = test_context("alice", None);
+        let payload = test_entry().address().to_string();
+        let signature = context.sign(payload.clone()).unwrap();
+
+        assert_eq!(
+            verify_signature(&context.agent_id.address(), &signature, payload.clone()),
+            Ok(true)
+        );
+        assert_eq!(
+            verify_signature(
+                &context.agent_id.address(),
+                &signature,
+                String::from("other payload")
+            ),
+            Ok(false)
+        );
+        assert_eq!(
+            verify_signature(
+                &context.agent_id.address(),
+                &Signature::from("fake-signature"),
+                payload
+            ),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn rejects_signature_of_other_agent() {
+        let alice = test_context("alice", None);
+        let bob = test_context("bob", None);
+        let payload = test_entry().address().to_string();
+        let signature = bob.sign(payload.clone()).unwrap();
+
+        assert_eq!(
+            verify_signature(&alice.agent_id.address(), &signature, payload),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn can_verify_header_provenances() {
+        let context = test_context_with_state(None);
+        let header = create_new_chain_header(&test_entry(), context.clone(), &None, &None).unwrap();
+        let mut package = ValidationPackage::only_header(header.clone());
+        package.agent_id_headers = agent_id_headers_before(&header, &context);
+        package.headers_since_agent_id = headers_since_agent_id(&header, &context);
+        assert_eq!(verify_header_provenances(&header, &package), Ok(()));
+        assert!(verify_header_provenances(
+            &test_chain_header(),
+            &ValidationPackage::only_header(test_chain_header())
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn can_verify_header_provenances_after_key_rotation() {
+        let alice = test_context("alice", None);
+        // bob's key plays the role of alice's rotated key
+        let rotated_key = test_context("bob", None);
+        let initial_agent_header = ChainHeader::new(
+            &EntryType::AgentId,
+            &alice.agent_id.address(),
+            &vec![(
+                alice.agent_id.address(),
+                alice
+                    .sign(provenance_payload(&alice.agent_id.address()))
+                    .unwrap(),
+            )],
+            &None,
+            &None,
+            &None,
+            &Iso8601::from(""),
+        );
+        let rotated_agent_header_signed_by = |signer: &Arc<Context>| {
+            ChainHeader::new(
+                &EntryType::AgentId,
+                &rotated_key.agent_id.address(),
+                &vec![(
+                    alice.agent_id.address(),
+                    signer
+                        .sign(provenance_payload(&rotated_key.agent_id.address()))
+                        .unwrap(),
+                )],
+                &Some(initial_agent_header.address()),
+                &Some(initial_agent_header.address()),
+                &Some(alice.agent_id.address()),
+                &Iso8601::from(""),
+            )
+        };
+        let rotated_agent_header = rotated_agent_header_signed_by(&alice);
+        let header_signed_by = |signer: &Arc<Context>, entry: &Entry, link: &ChainHeader| {
+            ChainHeader::new(
+                &entry.entry_type(),
+                &entry.address(),
+                &vec![(
+                    alice.agent_id.address(),
+                    signer.sign(provenance_payload(&entry.address())).unwrap(),
+                )],
+                &Some(link.address()),
+                &None,
+                &None,
+                &Iso8601::from(""),
+            )
+        };
+        let package = |agent_id_headers: &[ChainHeader], headers_since_agent_id: &[ChainHeader]| {
+            let mut package = ValidationPackage::only_header(test_chain_header());
+            package.agent_id_headers = agent_id_headers.to_vec();
+            package.headers_since_agent_id = headers_since_agent_id.to_vec();
+            package
+        };
+        let header = header_signed_by(&rotated_key, &test_entry(), &rotated_agent_header);
+        let all_agent_id_headers = [rotated_agent_header.clone(), initial_agent_header.clone()];
+
+        assert_eq!(
+            verify_header_provenances(&header, &package(&all_agent_id_headers, &[])),
+            Ok(())
+        );
+        // signed with a key that is not current at this position
+        let signed_by_old_key = header_signed_by(&alice, &test_entry(), &rotated_agent_header);
+        assert!(verify_header_provenances(
+            &signed_by_old_key,
+            &package(&all_agent_id_headers, &[])
+        )
+        .is_err());
+        // leaving out the key rotation does not help signing with the old key
+        for agent_id_headers in vec![vec![initial_agent_header.clone()], vec![]] {
+            assert!(verify_header_provenances(
+                &signed_by_old_key,
+                &package(&agent_id_headers, &[])
+            )
+            .is_err());
+            assert!(verify_header_provenances(&header, &package(&agent_id_headers, &[])).is_err());
+        }
+        // leaving out the initial AgentId
+        assert!(
+            verify_header_provenances(&header, &package(&[rotated_agent_header.clone()], &[]))
+                .is_err()
+        );
+        // AgentId headers that do not form a chain
+        assert!(verify_header_provenances(
+            &header,
+            &package(
+                &[initial_agent_header.clone(), rotated_agent_header.clone()],
+                &[]
+            )
+        )
+        .is_err());
+        // headers between the key rotation and the header have to come with it
+        let later_header = header_signed_by(&rotated_key, &test_entry_b(), &header);
+        assert_eq!(
+            verify_header_provenances(
+                &later_header,
+                &package(&all_agent_id_headers, &[header.clone()])
+            ),
+            Ok(())
+        );
+        assert!(
+            verify_header_provenances(&later_header, &package(&all_agent_id_headers, &[])).is_err()
+        );
+        // a key rotation that was not signed by the previous key
+        let forged_agent_header = rotated_agent_header_signed_by(&rotated_key);
+        let forged_header = header_signed_by(&rotated_key, &test_entry(), &forged_agent_header);
+        assert!(verify_header_provenances(
+            &forged_header,
+            &package(&[forged_agent_header, initial_agent_header], &[])
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn membrane_proofs_have_to_be_signed_by_the_agent() {
+        let alice = test_context("alice", None);
+        let bob = test_context("bob", None);
+        assert_eq!(verify_membrane_proof(&alice.agent_id), Ok(()));
+
+        let mut agent_id = alice.agent_id.clone().with_membrane_proof("invitation");
+        assert!(verify_membrane_proof(&agent_id).is_err());
+        let payload = agent_id.membrane_proof_payload().unwrap();
+        agent_id.membrane_proof_signature = Some(alice.sign(payload.clone()).unwrap());
+        assert_eq!(verify_membrane_proof(&agent_id), Ok(()));
+
+        // a proof swapped in by somebody passing the entry on
+        let mut swapped_proof = agent_id.clone();
+        swapped_proof.membrane_proof = Some("other invitation".to_string());
+        assert!(verify_membrane_proof(&swapped_proof).is_err());
+
+        // a proof signed by another agent
+        let mut signed_by_bob = agent_id.clone();
+        signed_by_bob.membrane_proof_signature = Some(bob.sign(payload).unwrap());
+        assert!(verify_membrane_proof(&signed_by_bob).is_err());
+    }
+
+    #[test]
+    fn links_to_private_chain_entries_are_not_published() {
+        let mut dna = test_dna();
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&EntryType::App(AppEntryType::from("testEntryType")))
+            .unwrap()
+            .sharing = Sharing::Private;
+        let (_instance, context) = instance_by_name("jill", dna.clone(), None);
+        context
+            .block_on(commit_entry(test_entry(), None, &context))
+            .unwrap();
+        let private_address = test_entry().address();
+        let other_address = test_entry_b().address();
+
+        let links_between = |base: &Address, target: &Address| {
+            vec![
+                Entry::LinkAdd(LinkAdd::new(base, target, "tag")),
+                Entry::LinkRemove(LinkRemove::new(base, target, "tag")),
+            ]
+        };
+        for link in links_between(&private_address, &other_address)
+            .into_iter()
+            .chain(links_between(&other_address, &private_address))
+        {
+            assert!(!can_publish_chain_entry(&link, &dna, &context));
+        }
+        for link in links_between(&other_address, &other_address) {
+            assert!(can_publish_chain_entry(&link, &dna, &context));
+        }
+        let deletion = Entry::Deletion(DeletionEntry::new(private_address));
+        assert!(!can_publish_chain_entry(&deletion, &dna, &context));
+    }
+}
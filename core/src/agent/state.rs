@@ -1,23 +1,31 @@
 use crate::{
     action::{Action, ActionWrapper, AgentReduceFn},
-    agent::chain_store::{ChainStore, ChainStoreIterator},
+    agent::{
+        bundle::Bundle,
+        chain_store::{ChainStore, ChainStoreIterator},
+    },
     context::Context,
+    nucleus::ZomeFnCall,
     state::State,
     workflows::get_entry_result::get_entry_result_workflow,
 };
 use holochain_core_types::{
     agent::AgentId,
     cas::content::{Address, AddressableContent, Content},
-    chain_header::ChainHeader,
-    entry::{entry_type::EntryType, Entry},
+    chain_header::{provenance_payload, ChainHeader},
+    chain_migrate::ChainMigrate,
+    entry::{cap_entries::CapTokenGrant, entry_type::EntryType, Entry},
     error::{HcResult, HolochainError},
     json::*,
-    signature::Signature,
     time::Iso8601,
 };
-use holochain_wasm_utils::api_serialization::get_entry::*;
+use holochain_wasm_utils::api_serialization::{bundle::BundleOnClose, get_entry::*};
 use serde_json;
-use std::{collections::HashMap, convert::TryFrom, sync::Arc};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Arc,
+};
 
 /// The state-slice for the Agent.
 /// Holds the agent's source chain and keys.
@@ -29,6 +37,13 @@ pub struct AgentState {
     actions: HashMap<ActionWrapper, ActionResponse>,
     chain_store: ChainStore,
     top_chain_header: Option<ChainHeader>,
+    /// the currently open commit bundles by the zome call that opened them
+    bundles: HashMap<ZomeFnCall, Bundle>,
+    /// the capability grants of the chain that did not get revoked, newest first,
+    /// together with their entry's address.
+    /// Kept up to date on commit so that checking a call's capability does not have to
+    /// walk the chain.
+    active_cap_grants: Vec<(Address, CapTokenGrant)>,
 }
 
 impl AgentState {
@@ -38,6 +53,8 @@ impl AgentState {
             actions: HashMap::new(),
             chain_store,
             top_chain_header: None,
+            bundles: HashMap::new(),
+            active_cap_grants: Vec::new(),
         }
     }
 
@@ -45,10 +62,14 @@ impl AgentState {
         chain_store: ChainStore,
         chain_header: ChainHeader,
     ) -> AgentState {
+        let top_chain_header = Some(chain_header);
+        let active_cap_grants = chain_active_cap_grants(&chain_store, &top_chain_header);
         AgentState {
             actions: HashMap::new(),
             chain_store,
-            top_chain_header: Some(chain_header),
+            top_chain_header,
+            bundles: HashMap::new(),
+            active_cap_grants,
         }
     }
 
@@ -66,6 +87,27 @@ impl AgentState {
         self.top_chain_header.clone()
     }
 
+    /// The bundle the given zome call has open, if any.
+    /// Commits that are not made by a zome call never go into a bundle.
+    pub fn bundle(&self, maybe_zome_call: &Option<ZomeFnCall>) -> Option<Bundle> {
+        maybe_zome_call
+            .as_ref()
+            .and_then(|zome_call| self.bundles.get(zome_call))
+            .cloned()
+    }
+
+    /// The open bundle with the given id, if any
+    pub fn bundle_by_id(&self, bundle_id: &str) -> Option<Bundle> {
+        self.bundles
+            .values()
+            .find(|bundle| bundle.id() == bundle_id)
+            .cloned()
+    }
+
+    pub fn bundles(&self) -> HashMap<ZomeFnCall, Bundle> {
+        self.bundles.clone()
+    }
+
     pub fn iter_chain(&self) -> ChainStoreIterator {
         self.chain_store.iter(&self.top_chain_header)
     }
@@ -80,6 +122,18 @@ impl AgentState {
             ))
     }
 
+    /// Returns the address of the first AgentId entry of the chain,
+    /// i.e. the agent's address before any key rotation.
+    pub fn get_initial_agent_address(&self) -> HcResult<Address> {
+        self.chain_store()
+            .iter_type(&self.top_chain_header, &EntryType::AgentId)
+            .last()
+            .and_then(|chain_header| Some(chain_header.entry_address().clone()))
+            .ok_or(HolochainError::ErrorGeneric(
+                "Agent entry not found".to_string(),
+            ))
+    }
+
     pub async fn get_agent<'a>(&'a self, context: &'a Arc<Context>) -> HcResult<AgentId> {
         let agent_entry_address = self.get_agent_address()?;
         let entry_args = GetEntryArgs {
@@ -102,9 +156,92 @@ impl AgentState {
             .iter_type(&self.top_chain_header(), &entry.entry_type())
             .find(|h| h.entry_address() == &entry.address())
     }
+
+    /// Returns the ChainMigrate entry that closed this chain, if it got closed.
+    pub fn get_closing_chain_migrate(&self) -> Option<ChainMigrate> {
+        let chain_header = self
+            .chain_store()
+            .iter_type(&self.top_chain_header, &EntryType::ChainMigrate)
+            .nth(0)?;
+        let content = self
+            .chain_store()
+            .content_storage()
+            .read()
+            .unwrap()
+            .fetch(chain_header.entry_address())
+            .ok()??;
+        match Entry::try_from_content(&content) {
+            Ok(Entry::ChainMigrate(chain_migrate)) => {
+                Some(chain_migrate).filter(|chain_migrate| chain_migrate.closes_chain())
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns all capability grants committed to this chain that did not get revoked
+    /// by a subsequent Deletion entry, newest first.
+    pub fn get_active_cap_grants(&self) -> Vec<CapTokenGrant> {
+        self.active_cap_grants
+            .iter()
+            .map(|(_, grant)| grant.clone())
+            .collect()
+    }
+
+    /// Keeps the active capability grants up to date with an entry that got appended
+    /// to the chain: grants become active, deletions revoke them.
+    fn record_committed_entry(&mut self, entry: &Entry) {
+        match entry {
+            Entry::CapTokenGrant(grant) => self
+                .active_cap_grants
+                .insert(0, (entry.address(), grant.clone())),
+            Entry::Deletion(deletion) => {
+                let deleted_address = deletion.clone().deleted_entry_address();
+                self.active_cap_grants
+                    .retain(|(address, _)| *address != deleted_address);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Walks the chain for its capability grants that did not get revoked, newest first.
+fn chain_active_cap_grants(
+    chain_store: &ChainStore,
+    top_chain_header: &Option<ChainHeader>,
+) -> Vec<(Address, CapTokenGrant)> {
+    let get_chain_entry = |address: &Address| -> Option<Entry> {
+        let content = chain_store
+            .content_storage()
+            .read()
+            .unwrap()
+            .fetch(address)
+            .ok()??;
+        Entry::try_from_content(&content).ok()
+    };
+    let revoked: HashSet<Address> = chain_store
+        .iter_type(top_chain_header, &EntryType::Deletion)
+        .filter_map(
+            |chain_header| match get_chain_entry(chain_header.entry_address()) {
+                Some(Entry::Deletion(deletion)) => Some(deletion.deleted_entry_address()),
+                _ => None,
+            },
+        )
+        .collect();
+    chain_store
+        .iter_type(top_chain_header, &EntryType::CapTokenGrant)
+        .filter(|chain_header| !revoked.contains(chain_header.entry_address()))
+        .filter_map(
+            |chain_header| match get_chain_entry(chain_header.entry_address()) {
+                Some(Entry::CapTokenGrant(grant)) => {
+                    Some((chain_header.entry_address().clone(), grant))
+                }
+                _ => None,
+            },
+        )
+        .collect()
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize, DefaultJson)]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, DefaultJson)]
 pub struct AgentStateSnapshot {
     top_chain_header: ChainHeader,
 }
@@ -161,38 +298,67 @@ pub enum ActionResponse {
     FetchEntry(Option<Entry>),
     GetLinks(Result<Vec<Address>, HolochainError>),
     LinkEntries(Result<Entry, HolochainError>),
+    StartBundle(Result<(), HolochainError>),
+    CloseBundle(Result<Vec<Address>, HolochainError>),
 }
 
+/// Creates a new header for the given entry on top of the agent's current chain.
+/// If the given zome call has a bundle open, the header is created on top of the bundle's
+/// scratch chain instead.
+/// The agent signs the entry address with its current private key. The signature gets stored
+/// as the header's provenance together with the agent's initial address, which stays the
+/// same across key rotations.
+/// Fails if the chain got closed by a ChainMigrate entry.
 pub fn create_new_chain_header(
     entry: &Entry,
     context: Arc<Context>,
     crud_link: &Option<Address>,
-) -> ChainHeader {
+    maybe_zome_call: &Option<ZomeFnCall>,
+) -> Result<ChainHeader, HolochainError> {
     let agent_state = context
         .state()
         .expect("create_new_chain_header called without state")
         .agent();
+    if let Some(chain_migrate) = agent_state.get_closing_chain_migrate() {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Source chain is closed and migrated to agent {} of DNA {}",
+            chain_migrate.agent_address(),
+            chain_migrate.dna_address()
+        )));
+    }
     let agent_address = agent_state
-        .get_agent_address()
+        .get_initial_agent_address()
         .unwrap_or(context.agent_id.address());
-    ChainHeader::new(
+    let signature = context.sign(provenance_payload(&entry.address()))?;
+    let maybe_bundle = agent_state.bundle(maybe_zome_call);
+    let staged_top_chain_header = maybe_bundle
+        .as_ref()
+        .and_then(|bundle| bundle.top_chain_header());
+    let staged_of_same_type = maybe_bundle.as_ref().and_then(|bundle| {
+        bundle
+            .scratch_chain()
+            .iter(&staged_top_chain_header)
+            .find(|chain_header| chain_header.entry_type() == &entry.entry_type())
+    });
+    Ok(ChainHeader::new(
         &entry.entry_type(),
         &entry.address(),
-        // @TODO signatures
-        &vec![(agent_address, Signature::from("TODO"))],
-        &agent_state
-            .top_chain_header
-            .clone()
+        &vec![(agent_address, signature)],
+        &staged_top_chain_header
+            .or(agent_state.top_chain_header.clone())
             .and_then(|chain_header| Some(chain_header.address())),
-        &agent_state
-            .chain_store()
-            .iter_type(&agent_state.top_chain_header, &entry.entry_type())
-            .nth(0)
+        &staged_of_same_type
+            .or_else(|| {
+                agent_state
+                    .chain_store()
+                    .iter_type(&agent_state.top_chain_header, &entry.entry_type())
+                    .nth(0)
+            })
             .and_then(|chain_header| Some(chain_header.address())),
         crud_link,
         // @TODO timestamp
         &Iso8601::from(""),
-    )
+    ))
 }
 
 /// Do a Commit Action against an agent state.
@@ -201,16 +367,27 @@ pub fn create_new_chain_header(
 /// action reduction to hang
 /// @TODO is there a way to reduce that doesn't block indefinitely on callback fns?
 /// @see https://github.com/holochain/holochain-rust/issues/222
-/// @TODO Better error handling in the state persister section
-/// https://github.com/holochain/holochain-rust/issues/555
 fn reduce_commit_entry(
     context: Arc<Context>,
     state: &mut AgentState,
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
-    let (entry, maybe_crud_link) = unwrap_to!(action => Action::Commit);
-    let chain_header = create_new_chain_header(&entry, context.clone(), &maybe_crud_link);
+    let (entry, maybe_crud_link, maybe_zome_call) = unwrap_to!(action => Action::Commit);
+    let chain_header = match create_new_chain_header(
+        &entry,
+        context.clone(),
+        &maybe_crud_link,
+        &maybe_zome_call,
+    ) {
+        Ok(chain_header) => chain_header,
+        Err(error) => {
+            state
+                .actions
+                .insert(action_wrapper.clone(), ActionResponse::Commit(Err(error)));
+            return;
+        }
+    };
 
     fn response(
         state: &mut AgentState,
@@ -222,26 +399,121 @@ fn reduce_commit_entry(
         storage.write().unwrap().add(chain_header)?;
         Ok(entry.address())
     }
+    // While the committing zome call has a bundle open, its commits only get staged
+    // in the bundle's scratch chain
+    if let Some(bundle) = maybe_zome_call
+        .as_ref()
+        .and_then(|zome_call| state.bundles.get_mut(zome_call))
+    {
+        let result = bundle.stage(&entry, &chain_header).map(|_| entry.address());
+        state
+            .actions
+            .insert(action_wrapper.clone(), ActionResponse::Commit(result));
+        return;
+    }
+
     let result = response(state, &entry, &chain_header);
     state.top_chain_header = Some(chain_header);
-    let con = context.clone();
-
-    #[allow(unused_must_use)]
-    con.state().map(|global_state_lock| {
-        let persis_lock = context.clone().persister.clone();
-        let persister = &mut *persis_lock.lock().unwrap();
-        persister.save(global_state_lock.clone());
-    });
+    if result.is_ok() {
+        state.record_committed_entry(&entry);
+    }
 
     state
         .actions
         .insert(action_wrapper.clone(), ActionResponse::Commit(result));
 }
 
+/// Opens the bundle given in the action for the given zome call,
+/// if that zome call has no other bundle open already.
+fn reduce_start_bundle(
+    _context: Arc<Context>,
+    state: &mut AgentState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let (zome_call, bundle) = unwrap_to!(action => Action::StartBundle);
+    let result = if state.bundles.contains_key(zome_call) {
+        Err(HolochainError::ErrorGeneric(
+            "A bundle is already open".to_string(),
+        ))
+    } else {
+        state.bundles.insert(zome_call.clone(), bundle.clone());
+        Ok(())
+    };
+    state
+        .actions
+        .insert(action_wrapper.clone(), ActionResponse::StartBundle(result));
+}
+
+/// Closes the bundle with the given id.
+/// On BundleOnClose::Commit all staged entries and headers get appended to the source chain
+/// in one step, on BundleOnClose::Discard they are dropped.
+/// Committing fails if the source chain got extended outside of the bundle while it was open,
+/// since the staged headers would fork the chain then.
+/// Responds with the addresses of the entries that got appended.
+fn reduce_close_bundle(
+    _context: Arc<Context>,
+    state: &mut AgentState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let (bundle_id, on_close) = unwrap_to!(action => Action::CloseBundle);
+
+    fn append_bundle(
+        state: &mut AgentState,
+        bundle: &Bundle,
+    ) -> Result<Vec<Address>, HolochainError> {
+        if !bundle.fits_onto(&state.top_chain_header) {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Source chain changed while bundle {} was open",
+                bundle.id()
+            )));
+        }
+        let staged_entries = bundle.staged_entries()?;
+        let storage = &state.chain_store.content_storage().clone();
+        for (entry, chain_header) in staged_entries.iter() {
+            storage.write().unwrap().add(entry)?;
+            storage.write().unwrap().add(chain_header)?;
+        }
+        if let Some(top_chain_header) = bundle.top_chain_header() {
+            state.top_chain_header = Some(top_chain_header);
+        }
+        for (entry, _) in staged_entries.iter() {
+            state.record_committed_entry(entry);
+        }
+        Ok(staged_entries
+            .into_iter()
+            .map(|(entry, _)| entry.address())
+            .collect())
+    }
+
+    let maybe_zome_call = state
+        .bundles
+        .iter()
+        .find(|(_, bundle)| bundle.id() == bundle_id)
+        .map(|(zome_call, _)| zome_call.clone());
+    let result = match maybe_zome_call.and_then(|zome_call| state.bundles.remove(&zome_call)) {
+        Some(bundle) => match on_close {
+            BundleOnClose::Commit => append_bundle(state, &bundle),
+            BundleOnClose::Discard => Ok(Vec::new()),
+        },
+        None => Err(HolochainError::ErrorGeneric(format!(
+            "Bundle {} is not open",
+            bundle_id
+        ))),
+    };
+
+    state
+        .actions
+        .insert(action_wrapper.clone(), ActionResponse::CloseBundle(result));
+}
+
 /// maps incoming action to the correct handler
 fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<AgentReduceFn> {
     match action_wrapper.action() {
         Action::Commit(_) => Some(reduce_commit_entry),
+        Action::StartBundle(_) => Some(reduce_start_bundle),
+        Action::CloseBundle(_) => Some(reduce_close_bundle),
         _ => None,
     }
 }
@@ -266,18 +538,30 @@ pub fn reduce(
 #[cfg(test)]
 pub mod tests {
     extern crate tempfile;
-    use super::{reduce_commit_entry, ActionResponse, AgentState, AgentStateSnapshot};
+    use super::{
+        create_new_chain_header, reduce_close_bundle, reduce_commit_entry, reduce_start_bundle,
+        ActionResponse, AgentState, AgentStateSnapshot,
+    };
     use crate::{
-        action::tests::test_action_wrapper_commit, agent::chain_store::tests::test_chain_store,
-        instance::tests::test_context, state::State,
+        action::{tests::test_action_wrapper_commit, Action, ActionWrapper},
+        agent::{bundle::Bundle, chain_store::tests::test_chain_store},
+        instance::tests::test_context,
+        nucleus::ZomeFnCall,
+        state::State,
     };
     use holochain_core_types::{
-        cas::content::AddressableContent,
+        cas::content::{Address, AddressableContent},
         chain_header::test_chain_header,
-        entry::{expected_entry_address, test_entry, Entry},
+        chain_migrate::ChainMigrate,
+        dna::capabilities::CapabilityType,
+        entry::{
+            cap_entries::CapTokenGrant, deletion_entry::DeletionEntry, expected_entry_address,
+            test_entry, Entry,
+        },
         error::HolochainError,
         json::JsonString,
     };
+    use holochain_wasm_utils::api_serialization::bundle::BundleOnClose;
     use serde_json;
     use std::{
         collections::HashMap,
@@ -327,6 +611,176 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// test that commits inside a bundle only reach the chain when the bundle gets committed
+    fn test_reduce_commit_entry_in_bundle() {
+        let mut agent_state = test_agent_state();
+        let netname = Some("test_reduce_commit_entry_in_bundle");
+        let mut context = test_context("bob", netname);
+        let zome_call = ZomeFnCall::new("test_zome", None, "test_fn", "{}");
+        let start_action = ActionWrapper::new(Action::StartBundle((
+            zome_call.clone(),
+            Bundle::new("test-bundle".to_string(), json!({})),
+        )));
+        reduce_start_bundle(context.clone(), &mut agent_state, &start_action);
+        assert_eq!(
+            agent_state.actions().get(&start_action),
+            Some(&ActionResponse::StartBundle(Ok(()))),
+        );
+
+        // commits read the open bundle from the context's state
+        let state = State::new_with_agent(context.clone(), Arc::new(agent_state.clone()));
+        Arc::get_mut(&mut context)
+            .unwrap()
+            .set_state(Arc::new(RwLock::new(state)));
+        let commit_action = ActionWrapper::new(Action::Commit((
+            test_entry(),
+            None,
+            Some(zome_call.clone()),
+        )));
+        reduce_commit_entry(context.clone(), &mut agent_state, &commit_action);
+        assert_eq!(
+            agent_state.actions().get(&commit_action),
+            Some(&test_action_response_commit()),
+        );
+        assert_eq!(agent_state.top_chain_header(), None);
+        let staged_header = agent_state
+            .bundle(&Some(zome_call.clone()))
+            .and_then(|bundle| bundle.top_chain_header())
+            .expect("commit should be staged in the bundle");
+
+        // commits of other zome calls don't see the bundle
+        let other_zome_call = ZomeFnCall::new("test_zome", None, "test_fn", "{}");
+        assert_eq!(agent_state.bundle(&Some(other_zome_call)), None);
+        assert_eq!(agent_state.bundle(&None), None);
+
+        let close_action = ActionWrapper::new(Action::CloseBundle((
+            "test-bundle".to_string(),
+            BundleOnClose::Commit,
+        )));
+        reduce_close_bundle(context, &mut agent_state, &close_action);
+        assert_eq!(
+            agent_state.actions().get(&close_action),
+            Some(&ActionResponse::CloseBundle(Ok(vec![
+                expected_entry_address()
+            ]))),
+        );
+        assert_eq!(agent_state.top_chain_header(), Some(staged_header));
+        assert_eq!(agent_state.bundle(&Some(zome_call)), None);
+    }
+
+    #[test]
+    /// test that committed grants are active until a deletion revokes them
+    fn test_active_cap_grants_follow_commits() {
+        let mut agent_state = test_agent_state();
+        let netname = Some("test_active_cap_grants_follow_commits");
+        let commit = |agent_state: &mut AgentState, entry: Entry| {
+            // every commit needs to see the chain as the previous one left it
+            let state =
+                State::new_with_agent(test_context("bob", netname), Arc::new(agent_state.clone()));
+            let mut context = test_context("bob", netname);
+            Arc::get_mut(&mut context)
+                .unwrap()
+                .set_state(Arc::new(RwLock::new(state)));
+            let action = ActionWrapper::new(Action::Commit((entry, None, None)));
+            reduce_commit_entry(context, agent_state, &action);
+        };
+        let grant = CapTokenGrant::create("test_cap", CapabilityType::Transferable, None).unwrap();
+        let grant_entry = Entry::CapTokenGrant(grant.clone());
+        commit(&mut agent_state, grant_entry.clone());
+        commit(&mut agent_state, test_entry());
+        assert_eq!(agent_state.get_active_cap_grants(), vec![grant.clone()]);
+        // states restored from the chain find the same grants
+        let restored_state = AgentState::new_with_top_chain_header(
+            agent_state.chain_store(),
+            agent_state.top_chain_header().unwrap(),
+        );
+        assert_eq!(restored_state.get_active_cap_grants(), vec![grant]);
+
+        commit(
+            &mut agent_state,
+            Entry::Deletion(DeletionEntry::new(grant_entry.address())),
+        );
+        assert_eq!(agent_state.get_active_cap_grants(), Vec::new());
+        let restored_state = AgentState::new_with_top_chain_header(
+            agent_state.chain_store(),
+            agent_state.top_chain_header().unwrap(),
+        );
+        assert_eq!(restored_state.get_active_cap_grants(), Vec::new());
+    }
+
+    #[test]
+    /// test that a bundle can not be committed once the chain moved on without it
+    fn test_reduce_close_bundle_fails_if_chain_changed() {
+        let mut agent_state = test_agent_state();
+        let netname = Some("test_reduce_close_bundle_fails_if_chain_changed");
+        let mut context = test_context("bob", netname);
+        let zome_call = ZomeFnCall::new("test_zome", None, "test_fn", "{}");
+        let start_action = ActionWrapper::new(Action::StartBundle((
+            zome_call.clone(),
+            Bundle::new("test-bundle".to_string(), json!({})),
+        )));
+        reduce_start_bundle(context.clone(), &mut agent_state, &start_action);
+
+        let state = State::new_with_agent(context.clone(), Arc::new(agent_state.clone()));
+        Arc::get_mut(&mut context)
+            .unwrap()
+            .set_state(Arc::new(RwLock::new(state)));
+        let staged_commit = ActionWrapper::new(Action::Commit((
+            test_entry(),
+            None,
+            Some(zome_call.clone()),
+        )));
+        reduce_commit_entry(context.clone(), &mut agent_state, &staged_commit);
+        // another zome call commits directly to the chain meanwhile
+        let direct_commit = test_action_wrapper_commit();
+        reduce_commit_entry(context.clone(), &mut agent_state, &direct_commit);
+        assert!(agent_state.top_chain_header().is_some());
+
+        let close_action = ActionWrapper::new(Action::CloseBundle((
+            "test-bundle".to_string(),
+            BundleOnClose::Commit,
+        )));
+        reduce_close_bundle(context, &mut agent_state, &close_action);
+        match agent_state.actions().get(&close_action) {
+            Some(ActionResponse::CloseBundle(Err(_))) => (),
+            other => panic!("expected closing the bundle to fail, got {:?}", other),
+        }
+        assert_eq!(agent_state.bundles(), HashMap::new());
+    }
+
+    #[test]
+    /// test that nothing can be committed on top of a closing ChainMigrate entry
+    fn test_closed_chain_rejects_new_headers() {
+        let mut agent_state = test_agent_state();
+        let netname = Some("test_closed_chain_rejects_new_headers");
+        let context = test_context("bob", netname);
+        let state = State::new_with_agent(context, Arc::new(agent_state.clone()));
+        let mut context = test_context("bob", netname);
+        Arc::get_mut(&mut context)
+            .unwrap()
+            .set_state(Arc::new(RwLock::new(state)));
+        let chain_migrate = ChainMigrate::close(
+            Address::from("QmSuccessorDna"),
+            Address::from("QmSuccessorAgent"),
+            String::from("upgrade"),
+        );
+        let commit_action = ActionWrapper::new(Action::Commit((
+            Entry::ChainMigrate(chain_migrate.clone()),
+            None,
+            None,
+        )));
+        reduce_commit_entry(context.clone(), &mut agent_state, &commit_action);
+        assert_eq!(agent_state.get_closing_chain_migrate(), Some(chain_migrate));
+
+        let state = State::new_with_agent(context, Arc::new(agent_state.clone()));
+        let mut context = test_context("bob", netname);
+        Arc::get_mut(&mut context)
+            .unwrap()
+            .set_state(Arc::new(RwLock::new(state)));
+        assert!(create_new_chain_header(&test_entry(), context, &None, &None).is_err());
+    }
+
     #[test]
     /// test response to json
     fn test_commit_response_to_json() {
@@ -1,7 +1,9 @@
 use crate::{
     action::ActionWrapper,
+    agent::sign_with_keypair,
     instance::Observer,
     logger::Logger,
+    nucleus::{entry_schemas::EntrySchemas, ZomeFnCall},
     persister::Persister,
     signal::{Signal, SignalSender},
     state::State,
@@ -18,12 +20,16 @@ use holochain_core_types::{
     },
     dna::{wasm::DnaWasm, Dna},
     eav::EntityAttributeValueStorage,
+    entry::{encrypted_entry::EncryptedEntry, Entry},
     error::{HcResult, HolochainError},
     json::JsonString,
+    signature::Signature,
 };
+use holochain_dpki::keypair::Keypair;
 use holochain_net::p2p_config::P2pConfig;
 use jsonrpc_ws_server::jsonrpc_core::IoHandler;
 use std::{
+    mem,
     sync::{
         mpsc::{channel, Receiver, SyncSender},
         Arc, Mutex, RwLock, RwLockReadGuard,
@@ -32,6 +38,10 @@ use std::{
     time::Duration,
 };
 
+/// Persists the agent's keypair once it got rotated with `hdk::update_agent`,
+/// so that the instance keeps signing with the new key after a restart.
+pub type AgentKeyStore = Arc<Box<Fn(&mut Keypair) -> Result<(), HolochainError> + Send + Sync>>;
+
 /// Context holds the components that parts of a Holochain instance need in order to operate.
 /// This includes components that are injected from the outside like logger and persister
 /// but also the store of the instance that gets injected before passing on the context
@@ -39,6 +49,10 @@ use std::{
 #[derive(Clone)]
 pub struct Context {
     pub agent_id: AgentId,
+    pub agent_keypair: Option<Arc<Mutex<Keypair>>>,
+    /// The keypairs the agent rotated away from with `hdk::update_agent`, oldest first.
+    /// They are kept to open entries that got sealed for the agent before a rotation.
+    pub previous_agent_keypairs: Arc<Mutex<Vec<Keypair>>>,
     pub logger: Arc<Mutex<Logger>>,
     pub persister: Arc<Mutex<Persister>>,
     state: Option<Arc<RwLock<State>>>,
@@ -50,6 +64,18 @@ pub struct Context {
     pub network_config: JsonString,
     pub conductor_api: Option<Arc<RwLock<IoHandler>>>,
     pub signal_tx: Option<SyncSender<Signal>>,
+    /// Whether every processed action gets emitted as a `Signal::Internal` on `signal_tx`.
+    /// Consumers that are only interested in user signals turn this off.
+    pub internal_signals: bool,
+    /// Id the conductor runs the instance under, which user signals get tagged with
+    pub instance_id: Option<String>,
+    /// Where rotated agent keys get persisted, if anywhere
+    pub agent_key_store: Option<AgentKeyStore>,
+    /// The zome call this context got scoped to with `Context::with_zome_call`.
+    /// Commit bundles are opened per zome call, see `agent::bundle::Bundle`.
+    pub zome_call: Option<ZomeFnCall>,
+    /// The entry schemas of the DNA, compiled once when the DNA gets loaded
+    entry_schemas: Arc<RwLock<Option<Arc<EntrySchemas>>>>,
 }
 
 impl Context {
@@ -59,6 +85,7 @@ impl Context {
 
     pub fn new(
         agent_id: AgentId,
+        agent_keypair: Option<Keypair>,
         logger: Arc<Mutex<Logger>>,
         persister: Arc<Mutex<Persister>>,
         chain_storage: Arc<RwLock<ContentAddressableStorage>>,
@@ -70,22 +97,30 @@ impl Context {
     ) -> Self {
         Context {
             agent_id,
+            agent_keypair: agent_keypair.map(|keypair| Arc::new(Mutex::new(keypair))),
+            previous_agent_keypairs: Arc::new(Mutex::new(Vec::new())),
             logger,
             persister,
             state: None,
             action_channel: None,
             signal_tx: signal_tx,
+            internal_signals: true,
             observer_channel: None,
             chain_storage,
             dht_storage,
             eav_storage: eav,
             network_config,
             conductor_api,
+            instance_id: None,
+            agent_key_store: None,
+            zome_call: None,
+            entry_schemas: Arc::new(RwLock::new(None)),
         }
     }
 
     pub fn new_with_channels(
         agent_id: AgentId,
+        agent_keypair: Option<Keypair>,
         logger: Arc<Mutex<Logger>>,
         persister: Arc<Mutex<Persister>>,
         action_channel: Option<SyncSender<ActionWrapper>>,
@@ -97,17 +132,24 @@ impl Context {
     ) -> Result<Context, HolochainError> {
         Ok(Context {
             agent_id,
+            agent_keypair: agent_keypair.map(|keypair| Arc::new(Mutex::new(keypair))),
+            previous_agent_keypairs: Arc::new(Mutex::new(Vec::new())),
             logger,
             persister,
             state: None,
             action_channel,
             signal_tx,
+            internal_signals: true,
             observer_channel,
             chain_storage: cas.clone(),
             dht_storage: cas,
             eav_storage: eav,
             network_config,
             conductor_api: None,
+            instance_id: None,
+            agent_key_store: None,
+            zome_call: None,
+            entry_schemas: Arc::new(RwLock::new(None)),
         })
     }
 
@@ -117,10 +159,100 @@ impl Context {
             .logger
             .lock()
             .or(Err(HolochainError::LoggingError))
-            .expect("Logger should work");;
+            .expect("Logger should work");
         logger.log(msg.into());
     }
 
+    /// Signs the given payload with the agent's private signing key.
+    /// Returns the base64 encoded signature or an error if this context got
+    /// created without a keypair for the agent.
+    pub fn sign(&self, payload: String) -> Result<Signature, HolochainError> {
+        let keypair = self.agent_keypair.as_ref().ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Agent {} has no keypair to sign with",
+                self.agent_id.nick
+            ))
+        })?;
+        sign_with_keypair(&mut *keypair.lock()?, payload)
+    }
+
+    /// Returns the public key the agent currently signs with.
+    /// This is the agent's address unless the key got rotated with `hdk::update_agent`.
+    pub fn agent_signing_key(&self) -> Result<Address, HolochainError> {
+        let keypair = self.agent_keypair.as_ref().ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Agent {} has no keypair",
+                self.agent_id.nick
+            ))
+        })?;
+        let keypair = keypair.lock()?;
+        Ok(Address::from(keypair.pub_keys.clone()))
+    }
+
+    /// Encrypts the entry for the given recipient agents and the agent itself,
+    /// see `Keypair::encrypt_entry`.
+    pub fn encrypt_entry(
+        &self,
+        entry: &Entry,
+        recipients: &[Address],
+    ) -> Result<EncryptedEntry, HolochainError> {
+        let keypair = self.agent_keypair.as_ref().ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Agent {} has no keypair to encrypt with",
+                self.agent_id.nick
+            ))
+        })?;
+        let recipient_ids: Vec<String> = recipients.iter().map(|a| a.to_string()).collect();
+        keypair.lock()?.encrypt_entry(entry, &recipient_ids)
+    }
+
+    /// Decrypts the entry if the agent is one of its recipients, returns None otherwise.
+    /// Entries sealed for the agent before a key rotation get opened with the previous keys.
+    pub fn decrypt_entry(
+        &self,
+        encrypted_entry: &EncryptedEntry,
+    ) -> Result<Option<Entry>, HolochainError> {
+        let keypair = self.agent_keypair.as_ref().ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Agent {} has no keypair to decrypt with",
+                self.agent_id.nick
+            ))
+        })?;
+        if let Some(entry) = keypair.lock()?.decrypt_entry(encrypted_entry)? {
+            return Ok(Some(entry));
+        }
+        for previous_keypair in self.previous_agent_keypairs.lock()?.iter_mut().rev() {
+            if let Some(entry) = previous_keypair.decrypt_entry(encrypted_entry)? {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Replaces the agent's keypair, e.g. after a key rotation.
+    /// The keypair is shared between all clones of this context, so subsequent
+    /// signatures of all of them are made with the new key.
+    /// The replaced keypair is kept for decrypting entries sealed for it.
+    pub fn set_agent_keypair(&self, new_keypair: Keypair) -> Result<(), HolochainError> {
+        let keypair = self.agent_keypair.as_ref().ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Agent {} has no keypair to replace",
+                self.agent_id.nick
+            ))
+        })?;
+        let previous_keypair = mem::replace(&mut *keypair.lock()?, new_keypair);
+        self.previous_agent_keypairs.lock()?.push(previous_keypair);
+        Ok(())
+    }
+
+    /// Returns a copy of this context that is scoped to the given zome call,
+    /// so that commits made through it go into the bundle that call has open.
+    pub fn with_zome_call(&self, zome_call: &ZomeFnCall) -> Context {
+        let mut context = self.clone();
+        context.zome_call = Some(zome_call.clone());
+        context
+    }
+
     pub fn set_state(&mut self, state: Arc<RwLock<State>>) {
         self.state = Some(state);
     }
@@ -169,6 +301,27 @@ impl Context {
             .and_then(|wasm| Some(wasm.clone()).filter(|_| !wasm.code.is_empty()))
     }
 
+    /// Compiles the entry schemas of the given DNA and caches them for validation.
+    /// Fails with a `DnaError::InvalidEntrySchema` if one of them doesn't compile.
+    pub fn load_entry_schemas(&self, dna: &Dna) -> Result<Arc<EntrySchemas>, HolochainError> {
+        let entry_schemas = Arc::new(EntrySchemas::compile(dna)?);
+        *self.entry_schemas.write()? = Some(entry_schemas.clone());
+        Ok(entry_schemas)
+    }
+
+    /// The compiled entry schemas of the DNA.
+    /// They get loaded from the DNA in the state if the instance was not initialized
+    /// with this context, i.e. when it got restored from a persisted state.
+    pub fn entry_schemas(&self) -> Result<Arc<EntrySchemas>, HolochainError> {
+        if let Some(entry_schemas) = self.entry_schemas.read()?.as_ref() {
+            return Ok(entry_schemas.clone());
+        }
+        let dna = self
+            .get_dna()
+            .ok_or_else(|| HolochainError::ErrorGeneric("No DNA set".to_string()))?;
+        self.load_entry_schemas(&dna)
+    }
+
     // @NB: these three getters smell bad because previously Instance and Context had SyncSenders
     // rather than Option<SyncSenders>, but these would be initialized by default to broken channels
     // which would panic if `send` was called upon them. These `expect`s just bring more visibility to
@@ -265,8 +418,11 @@ pub mod tests {
         context::unique_memory_network_config, logger::test_logger, persister::SimplePersister,
         state::State,
     };
-    use holochain_cas_implementations::{cas::file::FilesystemStorage, eav::file::EavFileStorage};
-    use holochain_core_types::agent::AgentId;
+    use holochain_cas_implementations::{
+        cas::{file::FilesystemStorage, memory::MemoryStorage},
+        eav::{file::EavFileStorage, memory::EavMemoryStorage},
+    };
+    use holochain_core_types::{agent::AgentId, entry::test_entry};
     use std::sync::{Arc, Mutex, RwLock};
 
     #[test]
@@ -281,6 +437,7 @@ pub mod tests {
         ));
         let mut maybe_context = Context::new(
             AgentId::generate_fake("Terence"),
+            None,
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
             file_storage.clone(),
@@ -305,6 +462,37 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn decrypts_entries_sealed_before_key_rotation() {
+        let storage = Arc::new(RwLock::new(MemoryStorage::new()));
+        let bob_keypair = Keypair::new_from_insecure_string("bob").unwrap();
+        let bob_key = bob_keypair.pub_keys.clone();
+        let context = Context::new(
+            AgentId::generate_fake("bob"),
+            Some(bob_keypair),
+            test_logger(),
+            Arc::new(Mutex::new(SimplePersister::new(storage.clone()))),
+            storage.clone(),
+            storage,
+            Arc::new(RwLock::new(EavMemoryStorage::new())),
+            unique_memory_network_config(),
+            None,
+            None,
+        );
+        let mut alice = Keypair::new_from_insecure_string("alice").unwrap();
+        let sealed_for_bob = alice.encrypt_entry(&test_entry(), &[bob_key]).unwrap();
+        let sealed_for_alice = alice.encrypt_entry(&test_entry(), &[]).unwrap();
+
+        context
+            .set_agent_keypair(Keypair::new_from_insecure_string("bob rotated").unwrap())
+            .unwrap();
+        assert_eq!(
+            context.decrypt_entry(&sealed_for_bob),
+            Ok(Some(test_entry()))
+        );
+        assert_eq!(context.decrypt_entry(&sealed_for_alice), Ok(None));
+    }
+
     #[test]
     #[should_panic]
     #[cfg(not(windows))] // RwLock does not panic on windows since mutexes are recursive
@@ -314,6 +502,7 @@ pub mod tests {
         ));
         let mut context = Context::new(
             AgentId::generate_fake("Terence"),
+            None,
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
             file_storage.clone(),
@@ -4,7 +4,7 @@ use crate::{
     action::{Action, ActionWrapper},
     context::Context,
     instance::dispatch_action,
-    network::entry_with_header::EntryWithHeader,
+    network::entry_with_header::{EncryptedEntryWithHeader, EntryWithHeader},
 };
 use futures::{
     future::Future,
@@ -13,6 +13,7 @@ use futures::{
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
     error::HolochainError,
+    warrant::Warrant,
 };
 use std::{pin::Pin, sync::Arc};
 
@@ -29,6 +30,36 @@ pub async fn hold_entry<'a>(
     })
 }
 
+/// Holds the cipher text of an entry of a type with encrypted sharing.
+/// Resolves to the address of the cipher text once it is held.
+pub async fn hold_encrypted_entry<'a>(
+    entry_wh: EncryptedEntryWithHeader,
+    context: Arc<Context>,
+) -> Result<Address, HolochainError> {
+    let action_wrapper = ActionWrapper::new(Action::HoldEncrypted(entry_wh.clone()));
+    dispatch_action(context.action_channel(), action_wrapper.clone());
+
+    await!(HoldEntryFuture {
+        context: context,
+        address: entry_wh.encrypted_entry.address(),
+    })
+}
+
+/// Records the entry of the given warrant as rejected.
+/// Resolves to the address of the warrant once it is held.
+pub async fn hold_warrant<'a>(
+    warrant: Warrant,
+    context: Arc<Context>,
+) -> Result<Address, HolochainError> {
+    let action_wrapper = ActionWrapper::new(Action::HoldWarrant(warrant.clone()));
+    dispatch_action(context.action_channel(), action_wrapper.clone());
+
+    await!(HoldEntryFuture {
+        context: context,
+        address: warrant.address(),
+    })
+}
+
 pub struct HoldEntryFuture {
     context: Arc<Context>,
     address: Address,
@@ -1,3 +1,5 @@
 pub mod add_link;
 pub mod hold;
 pub mod remove_entry;
+pub mod remove_link;
+pub mod resolve_conflict;
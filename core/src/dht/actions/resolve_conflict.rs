@@ -0,0 +1,57 @@
+use crate::{
+    action::{Action, ActionWrapper},
+    context::Context,
+    instance::dispatch_action,
+};
+use futures::{
+    future::Future,
+    task::{LocalWaker, Poll},
+};
+use holochain_core_types::{cas::content::Address, error::HolochainError};
+use std::{pin::Pin, sync::Arc};
+
+/// ResolveConflict Action Creator
+/// Records the update with address `winner_address` as the latest version of the entry
+/// with address `old_address`, which got updated concurrently.
+///
+/// Returns a future that resolves to an Ok(winner_address) or an Err(HolochainError).
+pub fn resolve_conflict(
+    context: &Arc<Context>,
+    old_address: Address,
+    winner_address: Address,
+) -> ResolveConflictFuture {
+    let action_wrapper = ActionWrapper::new(Action::ResolveConflict((old_address, winner_address)));
+    dispatch_action(context.action_channel(), action_wrapper.clone());
+    ResolveConflictFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    }
+}
+
+/// ResolveConflictFuture resolves to ActionResponse
+/// Tracks the state for a response to its ActionWrapper
+pub struct ResolveConflictFuture {
+    context: Arc<Context>,
+    action: ActionWrapper,
+}
+
+impl Future for ResolveConflictFuture {
+    type Output = Result<Address, HolochainError>;
+
+    fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
+        //
+        // TODO: connect the waker to state updates for performance reasons
+        // See: https://github.com/holochain/holochain-rust/issues/314
+        //
+        lw.wake();
+        if let Some(state) = self.context.state() {
+            match state.dht().actions().get(&self.action) {
+                Some(Ok(address)) => Poll::Ready(Ok(address.clone())),
+                Some(Err(e)) => Poll::Ready(Err(e.clone())),
+                None => Poll::Pending,
+            }
+        } else {
+            Poll::Pending
+        }
+    }
+}
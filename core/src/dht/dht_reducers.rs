@@ -2,20 +2,28 @@
 
 use crate::{
     action::{Action, ActionWrapper},
+    agent::bundle::Bundle,
     context::Context,
     dht::dht_store::DhtStore,
-    network::entry_with_header::EntryWithHeader,
+    network::entry_with_header::{EncryptedEntryWithHeader, EntryWithHeader},
+    nucleus::ZomeFnCall,
 };
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
-    crud_status::{create_crud_link_eav, create_crud_status_eav, CrudStatus, STATUS_NAME},
+    crud_status::{
+        create_crud_link_eav, create_crud_resolved_link_eav, create_crud_status_eav, CrudStatus,
+        STATUS_NAME,
+    },
     eav::{EntityAttributeValueIndex, IndexQuery},
     entry::Entry,
     error::HolochainError,
 };
+use holochain_wasm_utils::api_serialization::bundle::BundleOnClose;
 use std::{collections::BTreeSet, convert::TryFrom, str::FromStr, sync::Arc};
 
 pub const ENTRY_HEADER_ATTRIBUTE: &'static str = "entry-headers";
+pub const ENCRYPTED_ENTRY_ATTRIBUTE: &'static str = "encrypted-entry";
+pub const REJECTED_HEADER_ATTRIBUTE: &'static str = "rejected-headers";
 
 // A function that might return a mutated DhtStore
 type DhtReducer = fn(Arc<Context>, &DhtStore, &ActionWrapper) -> Option<DhtStore>;
@@ -47,9 +55,14 @@ fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<DhtReducer> {
     match action_wrapper.action() {
         Action::Commit(_) => Some(reduce_hold_entry),
         Action::Hold(_) => Some(reduce_hold_entry),
+        Action::HoldEncrypted(_) => Some(reduce_hold_encrypted_entry),
+        Action::HoldWarrant(_) => Some(reduce_hold_warrant),
         Action::UpdateEntry(_) => Some(reduce_update_entry),
         Action::RemoveEntry(_) => Some(reduce_remove_entry),
+        Action::ResolveConflict(_) => Some(reduce_resolve_conflict),
         Action::AddLink(_) => Some(reduce_add_link),
+        Action::RemoveLink(_) => Some(reduce_remove_link),
+        Action::CloseBundle(_) => Some(reduce_close_bundle),
         _ => None,
     }
 }
@@ -60,7 +73,9 @@ pub(crate) fn reduce_hold_entry(
     action_wrapper: &ActionWrapper,
 ) -> Option<DhtStore> {
     match action_wrapper.action().clone() {
-        Action::Commit((entry, _)) => reduce_store_entry_common(context, old_store, &entry),
+        // Commits inside an open bundle only get held once the bundle is committed
+        Action::Commit((_, _, zome_call)) if open_bundle(&context, &zome_call).is_some() => None,
+        Action::Commit((entry, _, _)) => reduce_store_entry_common(context, old_store, &entry),
         Action::Hold(EntryWithHeader { entry, header }) => {
             reduce_store_entry_common(context.clone(), old_store, &entry).and_then(|state| {
                 state.add_header_for_entry(&entry, &header).ok()?;
@@ -71,6 +86,89 @@ pub(crate) fn reduce_hold_entry(
     }
 }
 
+pub(crate) fn reduce_hold_encrypted_entry(
+    context: Arc<Context>,
+    old_store: &DhtStore,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore> {
+    let action = action_wrapper.action();
+    let EncryptedEntryWithHeader {
+        encrypted_entry,
+        header,
+    } = unwrap_to!(action => Action::HoldEncrypted);
+    let new_store = (*old_store).clone();
+    match new_store.add_encrypted_entry(encrypted_entry, header) {
+        Ok(()) => Some(new_store),
+        Err(error) => {
+            context.log(format!(
+                "err/dht: reduce_hold_encrypted_entry: could not hold {}: {:?}",
+                encrypted_entry.entry_address(),
+                error
+            ));
+            None
+        }
+    }
+}
+
+pub(crate) fn reduce_hold_warrant(
+    context: Arc<Context>,
+    old_store: &DhtStore,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore> {
+    let action = action_wrapper.action();
+    let warrant = unwrap_to!(action => Action::HoldWarrant);
+    let new_store = (*old_store).clone();
+    match new_store.add_warrant(warrant) {
+        Ok(()) => Some(new_store),
+        Err(error) => {
+            context.log(format!(
+                "err/dht: reduce_hold_warrant: could not hold warrant for {}: {:?}",
+                warrant.entry_address(),
+                error
+            ));
+            None
+        }
+    }
+}
+
+/// Holds all entries staged in the bundle once it gets committed.
+pub(crate) fn reduce_close_bundle(
+    context: Arc<Context>,
+    old_store: &DhtStore,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore> {
+    let action = action_wrapper.action();
+    let (bundle_id, on_close) = unwrap_to!(action => Action::CloseBundle);
+    if *on_close != BundleOnClose::Commit {
+        return None;
+    }
+    // The agent reducer refuses to commit bundles that would fork the chain
+    let agent_state = context.state()?.agent();
+    let bundle = agent_state
+        .bundle_by_id(bundle_id)
+        .filter(|bundle| bundle.fits_onto(&agent_state.top_chain_header()))?;
+    let staged_entries = bundle
+        .staged_entries()
+        .map_err(|err| {
+            context.log(format!(
+                "err/dht: reduce_close_bundle: could not read staged entries: {:?}",
+                err
+            ));
+        })
+        .ok()?;
+    let mut new_store = None;
+    for (entry, _) in staged_entries.iter() {
+        let store = new_store.as_ref().unwrap_or(old_store);
+        new_store = Some(reduce_store_entry_common(context.clone(), store, entry)?);
+    }
+    new_store
+}
+
+/// The bundle the given zome call has open in the agent state, if any
+fn open_bundle(context: &Arc<Context>, maybe_zome_call: &Option<ZomeFnCall>) -> Option<Bundle> {
+    context.state()?.agent().bundle(maybe_zome_call)
+}
+
 fn reduce_store_entry_common(
     context: Arc<Context>,
     old_store: &DhtStore,
@@ -147,6 +245,47 @@ pub(crate) fn reduce_add_link(
     }
 }
 
+//
+pub(crate) fn reduce_remove_link(
+    _context: Arc<Context>,
+    old_store: &DhtStore,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore> {
+    // Get Action's input data
+    let action = action_wrapper.action();
+    let link = unwrap_to!(action => Action::RemoveLink);
+
+    let mut new_store = (*old_store).clone();
+    let storage = &old_store.content_storage().clone();
+    if !(*storage.read().unwrap()).contains(link.base()).unwrap() {
+        new_store.actions_mut().insert(
+            action_wrapper.clone(),
+            Err(HolochainError::ErrorGeneric(String::from(
+                "Base for link not found",
+            ))),
+        );
+        Some(new_store)
+    } else {
+        // Links are never deleted from the EAV store. Instead a tombstone is added
+        // which shadows all additions of the same link, regardless of their order.
+        let eav = EntityAttributeValueIndex::new(
+            link.base(),
+            &format!("removed_link__{}", link.tag()),
+            link.target(),
+        );
+        eav.map(|e| {
+            let storage = new_store.meta_storage();
+            let result = storage.write().unwrap().add_eavi(&e);
+            new_store
+                .actions_mut()
+                .insert(action_wrapper.clone(), result.map(|_| link.base().clone()));
+            Some(new_store)
+        })
+        .ok()
+        .unwrap_or(None)
+    }
+}
+
 //
 pub(crate) fn reduce_update_entry(
     _context: Arc<Context>,
@@ -205,6 +344,23 @@ pub(crate) fn reduce_update_entry(
     }
 }
 
+pub(crate) fn reduce_resolve_conflict(
+    _context: Arc<Context>,
+    old_store: &DhtStore,
+    action_wrapper: &ActionWrapper,
+) -> Option<DhtStore> {
+    let action = action_wrapper.action();
+    let (old_address, winner_address) = unwrap_to!(action => Action::ResolveConflict);
+    let mut new_store = (*old_store).clone();
+    let meta_storage = &new_store.meta_storage().clone();
+    let res = create_crud_resolved_link_eav(old_address, winner_address).and_then(|eav| {
+        (*meta_storage.write().unwrap()).add_eavi(&eav)?;
+        Ok(winner_address.clone())
+    });
+    new_store.actions_mut().insert(action_wrapper.clone(), res);
+    Some(new_store)
+}
+
 pub(crate) fn reduce_remove_entry(
     context: Arc<Context>,
     old_store: &DhtStore,
@@ -318,6 +474,7 @@ pub mod tests {
     use holochain_core_types::{
         cas::content::AddressableContent,
         chain_header::test_chain_header,
+        crud_status::CrudStatus,
         eav::IndexQuery,
         entry::{test_entry, test_sys_entry, Entry},
         link::Link,
@@ -444,6 +601,55 @@ pub mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn can_remove_links() {
+        let context = test_context("bob", None);
+        let store = test_store(context.clone());
+        let entry = test_entry();
+
+        let locked_state = Arc::new(RwLock::new(store));
+
+        let mut context = (*context).clone();
+        context.set_state(locked_state.clone());
+        let storage = context.dht_storage.clone();
+        let _ = (storage.write().unwrap()).add(&entry);
+        let context = Arc::new(context);
+
+        let link = Link::new(&entry.address(), &entry.address(), "test-tag");
+        let add_action = ActionWrapper::new(Action::AddLink(link.clone()));
+        let remove_action = ActionWrapper::new(Action::RemoveLink(link.clone()));
+
+        let new_dht_store: DhtStore;
+        {
+            let state = locked_state.read().unwrap();
+
+            let dht_store = reduce(Arc::clone(&context), state.dht(), &add_action);
+            new_dht_store = (*reduce(Arc::clone(&context), dht_store, &remove_action)).clone();
+        }
+        assert!(new_dht_store.actions().get(&remove_action).unwrap().is_ok());
+
+        let storage = new_dht_store.meta_storage();
+        let fetched = storage.read().unwrap().fetch_eavi(
+            Some(entry.address()),
+            Some(format!("removed_link__{}", link.tag())),
+            None,
+            IndexQuery::default(),
+        );
+
+        assert!(fetched.is_ok());
+        let hash_set = fetched.unwrap();
+        assert_eq!(hash_set.len(), 1);
+        let eav = hash_set.iter().nth(0).unwrap();
+        assert_eq!(eav.entity(), *link.base());
+        assert_eq!(eav.value(), *link.target());
+
+        let links = new_dht_store
+            .get_links(entry.address(), link.tag().clone())
+            .unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].1, CrudStatus::Deleted);
+    }
+
     #[test]
     pub fn reduce_hold_test() {
         let context = test_context("bill", None);
@@ -468,5 +674,4 @@ pub mod tests {
 
         assert_eq!(&entry, &result_entry,);
     }
-
 }
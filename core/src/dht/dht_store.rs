@@ -1,17 +1,24 @@
-use crate::{action::ActionWrapper, dht::dht_reducers::ENTRY_HEADER_ATTRIBUTE};
+use crate::{
+    action::ActionWrapper,
+    dht::dht_reducers::{
+        ENCRYPTED_ENTRY_ATTRIBUTE, ENTRY_HEADER_ATTRIBUTE, REJECTED_HEADER_ATTRIBUTE,
+    },
+};
 use holochain_core_types::{
     cas::{
         content::{Address, AddressableContent},
         storage::ContentAddressableStorage,
     },
     chain_header::ChainHeader,
+    crud_status::{create_crud_status_eav, CrudStatus},
     eav::{EntityAttributeValueIndex, EntityAttributeValueStorage, IndexQuery},
-    entry::Entry,
+    entry::{encrypted_entry::EncryptedEntry, Entry},
     error::HolochainError,
+    warrant::{Warrant, WARRANT_NAME},
 };
 
 use std::{
-    collections::{BTreeSet, HashMap},
+    collections::HashMap,
     sync::{Arc, RwLock},
 };
 
@@ -53,17 +60,42 @@ impl DhtStore {
         }
     }
 
+    /// Get all links from the given base with the given tag, together with their status.
+    /// A link is reported as `CrudStatus::Deleted` as soon as there is a removal tombstone
+    /// for it, no matter whether the tombstone arrived before or after the link itself,
+    /// and as `CrudStatus::Live` otherwise.
     pub fn get_links(
         &self,
         address: Address,
         tag: String,
-    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
-        self.meta_storage.read()?.fetch_eavi(
-            Some(address),
-            Some(format!("link__{}", tag)),
+    ) -> Result<Vec<(EntityAttributeValueIndex, CrudStatus)>, HolochainError> {
+        let meta_storage = self.meta_storage.read()?;
+        let removals = meta_storage.fetch_eavi(
+            Some(address.clone()),
+            Some(format!("removed_link__{}", tag)),
             None,
             IndexQuery::default(),
-        )
+        )?;
+        Ok(meta_storage
+            .fetch_eavi(
+                Some(address),
+                Some(format!("link__{}", tag)),
+                None,
+                IndexQuery::default(),
+            )?
+            .into_iter()
+            .map(|eavi| {
+                let removed = removals
+                    .iter()
+                    .any(|removal| removal.value() == eavi.value());
+                let status = if removed {
+                    CrudStatus::Deleted
+                } else {
+                    CrudStatus::Live
+                };
+                (eavi, status)
+            })
+            .collect())
     }
 
     /// Get all headers for an entry by first looking in the DHT meta store
@@ -111,6 +143,147 @@ impl DhtStore {
         Ok(())
     }
 
+    /// Holds the cipher text of an entry of a type with encrypted sharing, together with
+    /// the header of the plain text entry. The cipher text is stored under its own address
+    /// and referenced from the address of the plain text entry, which gets the same
+    /// metadata as any other entry held in this shard.
+    pub fn add_encrypted_entry(
+        &self,
+        encrypted_entry: &EncryptedEntry,
+        header: &ChainHeader,
+    ) -> Result<(), HolochainError> {
+        let entry_address = encrypted_entry.entry_address();
+        {
+            let mut meta_storage = self.meta_storage.write()?;
+            meta_storage.add_eavi(&EntityAttributeValueIndex::new(
+                entry_address,
+                &ENCRYPTED_ENTRY_ATTRIBUTE.to_string(),
+                &encrypted_entry.address(),
+            )?)?;
+            meta_storage.add_eavi(&EntityAttributeValueIndex::new(
+                entry_address,
+                &ENTRY_HEADER_ATTRIBUTE.to_string(),
+                &header.address(),
+            )?)?;
+            meta_storage.add_eavi(&create_crud_status_eav(entry_address, CrudStatus::Live)?)?;
+        }
+        let mut content_storage = self.content_storage.write()?;
+        content_storage.add(header)?;
+        // added last, since holding is complete once the cipher text is in the CAS
+        content_storage.add(encrypted_entry)?;
+        Ok(())
+    }
+
+    /// The cipher text held for the plain text entry with the given address,
+    /// None if the entry is not held encrypted.
+    pub fn get_encrypted_entry(
+        &self,
+        entry_address: &Address,
+    ) -> Result<Option<EncryptedEntry>, HolochainError> {
+        let eavis = self.meta_storage.read()?.fetch_eavi(
+            Some(entry_address.clone()),
+            Some(ENCRYPTED_ENTRY_ATTRIBUTE.to_string()),
+            None,
+            IndexQuery::default(),
+        )?;
+        match eavis.iter().next_back() {
+            Some(eavi) => self
+                .content_storage
+                .read()?
+                .fetch(&eavi.value())?
+                .map(|content| EncryptedEntry::try_from_content(&content))
+                .transpose(),
+            None => Ok(None),
+        }
+    }
+
+    /// Records that the entry of the given warrant failed validation with the warrant's header:
+    /// the header gets recorded as rejected for the entry and the warrant is stored for the
+    /// address of the header, so that it can be handed to nodes asking for the entry.
+    /// Only that header is rejected, the entry can still be held with another one.
+    pub fn add_warrant(&self, warrant: &Warrant) -> Result<(), HolochainError> {
+        let header_address = warrant.header().address();
+        {
+            let mut meta_storage = self.meta_storage.write()?;
+            meta_storage.add_eavi(&EntityAttributeValueIndex::new(
+                &header_address,
+                &WARRANT_NAME.to_string(),
+                &warrant.address(),
+            )?)?;
+            meta_storage.add_eavi(&EntityAttributeValueIndex::new(
+                &warrant.entry_address(),
+                &REJECTED_HEADER_ATTRIBUTE.to_string(),
+                &header_address,
+            )?)?;
+        }
+        let mut content_storage = self.content_storage.write()?;
+        content_storage.add(warrant.header())?;
+        content_storage.add(warrant.entry())?;
+        // added last, since holding is complete once the warrant is in the CAS
+        content_storage.add(warrant)?;
+        Ok(())
+    }
+
+    /// The addresses of the headers the entry with the given address got rejected with
+    pub fn get_rejected_headers(
+        &self,
+        entry_address: &Address,
+    ) -> Result<Vec<Address>, HolochainError> {
+        Ok(self
+            .meta_storage
+            .read()?
+            .fetch_eavi(
+                Some(entry_address.clone()),
+                Some(REJECTED_HEADER_ATTRIBUTE.to_string()),
+                None,
+                IndexQuery::default(),
+            )?
+            .into_iter()
+            .map(|eavi| eavi.value())
+            .collect())
+    }
+
+    /// Whether the entry with the given address got rejected with every header it is held with,
+    /// i.e. whether it got rejected and not held with any valid header.
+    pub fn is_rejected(&self, entry_address: &Address) -> Result<bool, HolochainError> {
+        let rejected_headers = self.get_rejected_headers(entry_address)?;
+        if rejected_headers.is_empty() {
+            return Ok(false);
+        }
+        Ok(self
+            .get_headers(entry_address.clone())?
+            .iter()
+            .all(|header| rejected_headers.contains(&header.address())))
+    }
+
+    /// All warrants held for the entry with the given address, for any of its rejected headers
+    pub fn get_entry_warrants(
+        &self,
+        entry_address: &Address,
+    ) -> Result<Vec<Warrant>, HolochainError> {
+        let mut warrants = Vec::new();
+        for header_address in self.get_rejected_headers(entry_address)? {
+            warrants.extend(self.get_warrants(&header_address)?);
+        }
+        Ok(warrants)
+    }
+
+    /// All warrants held for the header with the given address
+    pub fn get_warrants(&self, header_address: &Address) -> Result<Vec<Warrant>, HolochainError> {
+        let eavis = self.meta_storage.read()?.fetch_eavi(
+            Some(header_address.clone()),
+            Some(WARRANT_NAME.to_string()),
+            None,
+            IndexQuery::default(),
+        )?;
+        let content_storage = self.content_storage.read()?;
+        eavis
+            .iter()
+            .filter_map(|eavi| content_storage.fetch(&eavi.value()).transpose())
+            .map(|content| Warrant::try_from_content(&content?))
+            .collect()
+    }
+
     // Getters (for reducers)
     // =======
     pub(crate) fn content_storage(&self) -> Arc<RwLock<ContentAddressableStorage>> {
@@ -134,9 +307,18 @@ pub mod tests {
     use super::*;
     use holochain_core_types::{
         cas::storage::ExampleContentAddressableStorage, chain_header::test_chain_header_with_sig,
-        eav::ExampleEntityAttributeValueStorage, entry::test_entry,
+        eav::ExampleEntityAttributeValueStorage, entry::test_entry, signature::Signature,
     };
 
+    fn test_dht_store() -> DhtStore {
+        DhtStore::new(
+            Arc::new(RwLock::new(
+                ExampleContentAddressableStorage::new().unwrap(),
+            )),
+            Arc::new(RwLock::new(ExampleEntityAttributeValueStorage::new())),
+        )
+    }
+
     #[test]
     fn get_headers_roundtrip() {
         let store = DhtStore::new(
@@ -153,4 +335,148 @@ pub mod tests {
         let headers = store.get_headers(entry.address()).unwrap();
         assert_eq!(headers, vec![header1, header2]);
     }
+
+    #[test]
+    fn encrypted_entry_roundtrip() {
+        let store = test_dht_store();
+        let entry = test_entry();
+        let header = test_chain_header_with_sig("sig");
+        let encrypted_entry = EncryptedEntry::new(
+            entry.address(),
+            entry.entry_type(),
+            "author".to_string(),
+            Vec::new(),
+            "cipher".to_string(),
+        );
+        assert_eq!(store.get_encrypted_entry(&entry.address()), Ok(None));
+
+        store
+            .add_encrypted_entry(&encrypted_entry, &header)
+            .unwrap();
+        assert_eq!(
+            store.get_encrypted_entry(&entry.address()),
+            Ok(Some(encrypted_entry))
+        );
+        assert_eq!(store.get_headers(entry.address()), Ok(vec![header]));
+        // only the cipher text is held, not the entry
+        assert!(!store
+            .content_storage()
+            .read()
+            .unwrap()
+            .contains(&entry.address())
+            .unwrap());
+    }
+
+    #[test]
+    fn warrant_roundtrip() {
+        let store = test_dht_store();
+        let entry = test_entry();
+        let header = test_chain_header_with_sig("sig");
+        let warrant = Warrant::new(
+            entry.clone(),
+            header.clone(),
+            "FAIL".to_string(),
+            Address::from("issuer"),
+            Signature::from("sig"),
+        );
+        assert_eq!(store.get_warrants(&header.address()), Ok(Vec::new()));
+        assert_eq!(store.is_rejected(&entry.address()), Ok(false));
+
+        store.add_warrant(&warrant).unwrap();
+        assert_eq!(
+            store.get_warrants(&header.address()),
+            Ok(vec![warrant.clone()])
+        );
+        assert_eq!(
+            store.get_entry_warrants(&entry.address()),
+            Ok(vec![warrant])
+        );
+        assert_eq!(
+            store.get_rejected_headers(&entry.address()),
+            Ok(vec![header.address()])
+        );
+        assert_eq!(store.get_headers(entry.address()), Ok(Vec::new()));
+        assert_eq!(store.is_rejected(&entry.address()), Ok(true));
+
+        // the entry is not rejected anymore once it is held with a valid header
+        let valid_header = test_chain_header_with_sig("other sig");
+        store.add_header_for_entry(&entry, &valid_header).unwrap();
+        assert_eq!(store.is_rejected(&entry.address()), Ok(false));
+        assert!(store
+            .get_warrants(&valid_header.address())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn get_links_reports_removed_links() {
+        let store = test_dht_store();
+        let base = test_entry().address();
+        let target = Address::from("target");
+        let add = |attribute: &str, index: i64| {
+            let eavi = EntityAttributeValueIndex::new_with_index(
+                &base,
+                &attribute.to_string(),
+                &target,
+                index,
+            )
+            .unwrap();
+            store
+                .meta_storage()
+                .write()
+                .unwrap()
+                .add_eavi(&eavi)
+                .unwrap();
+        };
+
+        add("link__test-tag", 1);
+        let links = store
+            .get_links(base.clone(), "test-tag".to_string())
+            .unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].0.value(), target);
+        assert_eq!(links[0].1, CrudStatus::Live);
+
+        add("removed_link__test-tag", 2);
+        let links = store
+            .get_links(base.clone(), "test-tag".to_string())
+            .unwrap();
+        assert_eq!(links[0].1, CrudStatus::Deleted);
+
+        // adding the same link again does not revive it
+        add("link__test-tag", 3);
+        let links = store
+            .get_links(base.clone(), "test-tag".to_string())
+            .unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].1, CrudStatus::Deleted);
+    }
+
+    #[test]
+    fn get_links_honours_removals_arriving_before_the_link() {
+        let store = test_dht_store();
+        let base = test_entry().address();
+        let target = Address::from("target");
+        for (attribute, index) in vec![("removed_link__test-tag", 1), ("link__test-tag", 2)] {
+            let eavi = EntityAttributeValueIndex::new_with_index(
+                &base,
+                &attribute.to_string(),
+                &target,
+                index,
+            )
+            .unwrap();
+            store
+                .meta_storage()
+                .write()
+                .unwrap()
+                .add_eavi(&eavi)
+                .unwrap();
+        }
+
+        let links = store
+            .get_links(base.clone(), "test-tag".to_string())
+            .unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].1, CrudStatus::Deleted);
+    }
 }
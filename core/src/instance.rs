@@ -8,18 +8,25 @@ use crate::{
 };
 #[cfg(test)]
 use holochain_core_types::cas::content::Address;
-use holochain_core_types::{dna::Dna, error::HcResult};
+use holochain_core_types::{
+    dna::Dna,
+    error::{HcResult, HolochainError},
+};
 use std::{
     sync::{
-        mpsc::{sync_channel, Receiver, Sender, SyncSender},
-        Arc, RwLock, RwLockReadGuard,
+        mpsc::{sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender},
+        Arc, Mutex, RwLock, RwLockReadGuard,
     },
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 pub const RECV_DEFAULT_TIMEOUT_MS: Duration = Duration::from_millis(10000);
 
+/// Minimum time between two persisted snapshots of the state.
+/// Changes of the top chain header or the nucleus status get persisted right away.
+pub const STATE_PERSISTENCE_INTERVAL: Duration = Duration::from_millis(1000);
+
 /// Object representing a Holochain instance, i.e. a running holochain (DNA + DHT + source-chain)
 /// Holds the Event loop and processes it with the redux pattern.
 #[derive(Clone)]
@@ -28,6 +35,14 @@ pub struct Instance {
     state: Arc<RwLock<State>>,
     action_channel: Option<SyncSender<ActionWrapper>>,
     observer_channel: Option<SyncSender<Observer>>,
+    persistence: Arc<Mutex<PersistenceSchedule>>,
+}
+
+/// Keeps track of when the state got persisted last and whether it changed since.
+#[derive(Default)]
+struct PersistenceSchedule {
+    persisted_at: Option<Instant>,
+    outdated: bool,
 }
 
 /// State Observer that executes a closure everytime the State changes.
@@ -152,13 +167,26 @@ impl Instance {
 
         thread::spawn(move || {
             let mut state_observers: Vec<Observer> = Vec::new();
-            for action_wrapper in rx_action {
-                state_observers = sync_self.process_action(
-                    action_wrapper,
-                    state_observers,
-                    &rx_observer,
-                    &sub_context,
-                );
+            loop {
+                match rx_action.recv_timeout(STATE_PERSISTENCE_INTERVAL) {
+                    Ok(action_wrapper) => {
+                        state_observers = sync_self.process_action(
+                            action_wrapper,
+                            state_observers,
+                            &rx_observer,
+                            &sub_context,
+                        );
+                    }
+                    // Changes that got held back while actions came in fast get persisted
+                    // as soon as the instance is idle or shutting down.
+                    Err(RecvTimeoutError::Timeout) => {
+                        sync_self.persist_outdated_state(&sub_context)
+                    }
+                    Err(RecvTimeoutError::Disconnected) => {
+                        sync_self.persist_outdated_state(&sub_context);
+                        break;
+                    }
+                }
             }
         });
     }
@@ -173,6 +201,7 @@ impl Instance {
         context: &Arc<Context>,
     ) -> Vec<Observer> {
         // Mutate state
+        let persist_immediately;
         {
             let new_state: State;
 
@@ -185,6 +214,11 @@ impl Instance {
 
                 // Create new state by reducing the action on old state
                 new_state = state.reduce(context.clone(), action_wrapper.clone());
+
+                // A restarted instance must not fall back to an older top chain header
+                persist_immediately = new_state.agent().top_chain_header()
+                    != state.agent().top_chain_header()
+                    || new_state.nucleus().status() != state.nucleus().status();
             }
 
             // Get write lock
@@ -197,6 +231,8 @@ impl Instance {
             *state = new_state;
         }
 
+        self.persist_state(context, persist_immediately);
+
         // context.log(format!("trace/reduce: {:?}", action_wrapper.action()));
         self.maybe_emit_action_signal(context, action_wrapper.clone());
 
@@ -209,9 +245,55 @@ impl Instance {
             .collect()
     }
 
+    /// Hands the current state to the persister so that a restarted instance can resume from it.
+    /// Unless `immediately` is set, the state gets persisted at most once per
+    /// STATE_PERSISTENCE_INTERVAL and is only marked as outdated otherwise.
+    /// Failing to persist must not stop the instance, so errors only get logged.
+    fn persist_state(&self, context: &Arc<Context>, immediately: bool) {
+        let mut schedule = self
+            .persistence
+            .lock()
+            .expect("owners of the persistence schedule shouldn't panic");
+        let due = immediately
+            || schedule
+                .persisted_at
+                .map(|persisted_at| persisted_at.elapsed() >= STATE_PERSISTENCE_INTERVAL)
+                .unwrap_or(true);
+        if !due {
+            schedule.outdated = true;
+            return;
+        }
+        let state = self.state();
+        let result = context
+            .persister
+            .lock()
+            .map_err(HolochainError::from)
+            .and_then(|mut persister| persister.save(&state));
+        if let Err(error) = result {
+            context.log(format!("err/instance: could not persist state: {}", error));
+        }
+        schedule.persisted_at = Some(Instant::now());
+        schedule.outdated = false;
+    }
+
+    /// Persists the state if changes got held back by the last calls to persist_state.
+    fn persist_outdated_state(&self, context: &Arc<Context>) {
+        let outdated = self
+            .persistence
+            .lock()
+            .expect("owners of the persistence schedule shouldn't panic")
+            .outdated;
+        if outdated {
+            self.persist_state(context, true);
+        }
+    }
+
     /// Given an `Action` that is being processed, decide whether or not it should be
     /// emitted as a `Signal::Internal`, and if so, send it
     fn maybe_emit_action_signal(&self, context: &Arc<Context>, action: ActionWrapper) {
+        if !context.internal_signals {
+            return;
+        }
         if let Some(ref tx) = context.signal_tx {
             // @TODO: if needed for performance, could add a filter predicate here
             // to prevent emitting too many unneeded signals
@@ -227,6 +309,7 @@ impl Instance {
             state: Arc::new(RwLock::new(State::new(context))),
             action_channel: None,
             observer_channel: None,
+            persistence: Arc::new(Mutex::new(PersistenceSchedule::default())),
         }
     }
 
@@ -235,6 +318,7 @@ impl Instance {
             state: Arc::new(RwLock::new(state)),
             action_channel: None,
             observer_channel: None,
+            persistence: Arc::new(Mutex::new(PersistenceSchedule::default())),
         }
     }
 
@@ -304,6 +388,7 @@ pub mod tests {
         entry::{entry_type::EntryType, test_entry},
         json::{JsonString, RawString},
     };
+    use holochain_dpki::keypair::Keypair;
 
     use crate::{
         nucleus::ribosome::{callback::Callback, Defn},
@@ -319,13 +404,24 @@ pub mod tests {
 
     use holochain_core_types::entry::Entry;
 
+    /// create an agent with a keypair derived from its name so that it can sign
+    #[cfg_attr(tarpaulin, skip)]
+    pub fn test_agent_and_keypair(agent_name: &str) -> (AgentId, Keypair) {
+        let keypair = Keypair::new_from_insecure_string(agent_name)
+            .expect("Could not derive keypair for test agent");
+        let agent = keypair
+            .agent_id(agent_name)
+            .expect("Could not create AgentId from test keypair");
+        (agent, keypair)
+    }
+
     /// create a test context and TestLogger pair so we can use the logger in assertions
     #[cfg_attr(tarpaulin, skip)]
     pub fn test_context_and_logger(
         agent_name: &str,
         network_name: Option<&str>,
     ) -> (Arc<Context>, Arc<Mutex<TestLogger>>) {
-        let agent = AgentId::generate_fake(agent_name);
+        let (agent, keypair) = test_agent_and_keypair(agent_name);
         let file_storage = Arc::new(RwLock::new(
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap(),
         ));
@@ -333,6 +429,7 @@ pub mod tests {
         (
             Arc::new(Context::new(
                 agent,
+                Some(keypair),
                 logger.clone(),
                 Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
                 file_storage.clone(),
@@ -364,7 +461,7 @@ pub mod tests {
         observer_channel: &SyncSender<Observer>,
         network_name: Option<&str>,
     ) -> Arc<Context> {
-        let agent = AgentId::generate_fake(agent_name);
+        let (agent, keypair) = test_agent_and_keypair(agent_name);
         let logger = test_logger();
         let file_storage = Arc::new(RwLock::new(
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap(),
@@ -372,6 +469,7 @@ pub mod tests {
         Arc::new(
             Context::new_with_channels(
                 agent,
+                Some(keypair),
                 logger.clone(),
                 Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
                 Some(action_channel.clone()),
@@ -393,8 +491,10 @@ pub mod tests {
         let file_storage = Arc::new(RwLock::new(
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap(),
         ));
+        let (agent, keypair) = test_agent_and_keypair("Florence");
         let mut context = Context::new(
-            AgentId::generate_fake("Florence"),
+            agent,
+            Some(keypair),
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(file_storage.clone()))),
             file_storage.clone(),
@@ -417,8 +517,10 @@ pub mod tests {
         let file_system =
             FilesystemStorage::new(tempdir().unwrap().path().to_str().unwrap()).unwrap();
         let cas = Arc::new(RwLock::new(file_system.clone()));
+        let (agent, keypair) = test_agent_and_keypair("Florence");
         let mut context = Context::new(
-            AgentId::generate_fake("Florence"),
+            agent,
+            Some(keypair),
             test_logger(),
             Arc::new(Mutex::new(SimplePersister::new(cas.clone()))),
             cas.clone(),
@@ -501,7 +603,7 @@ pub mod tests {
             .history
             .iter()
             .find(|aw| match aw.action() {
-                Action::Commit((entry, _)) => {
+                Action::Commit((entry, _, _)) => {
                     assert!(
                         entry.entry_type() == EntryType::AgentId
                             || entry.entry_type() == EntryType::Dna
@@ -590,6 +692,40 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// tests that changes coming in quick succession are held back
+    /// until the instance persists its outdated state
+    fn throttles_state_persistence() {
+        let context = test_context_with_agent_state(None);
+        let mut instance = Instance::from_state(context.state().unwrap().clone());
+        let context = instance.initialize_context(context);
+        let (_rx_action, rx_observer) = instance.initialize_channels();
+        let persisted_publishes = || {
+            context
+                .persister
+                .lock()
+                .unwrap()
+                .load(context.clone())
+                .unwrap()
+                .unwrap()
+                .pending_publishes()
+                .len()
+        };
+
+        // the network is not initialized, so the publishes fail and stay pending
+        let publish = ActionWrapper::new(Action::Publish(test_entry().address()));
+        instance.process_action(publish, Vec::new(), &rx_observer, &context);
+        assert_eq!(persisted_publishes(), 1);
+
+        let publish = ActionWrapper::new(Action::Publish(test_entry().address()));
+        instance.process_action(publish, Vec::new(), &rx_observer, &context);
+        assert_eq!(instance.state().pending_publishes().len(), 2);
+        assert_eq!(persisted_publishes(), 1);
+
+        instance.persist_outdated_state(&context);
+        assert_eq!(persisted_publishes(), 2);
+    }
+
     #[test]
     /// tests that we can dispatch an action and block until it completes
     fn can_dispatch_and_wait() {
@@ -697,6 +833,58 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// tests that agents accepted by the DNA's membrane rules can initialize
+    fn test_validate_agent_ok() {
+        let dna = test_utils::create_test_dna_with_wat(
+            "test_zome",
+            Callback::Genesis.capability().as_str(),
+            Some(
+                r#"
+            (module
+                (memory (;0;) 1)
+                (func (export "validate_agent") (param $p0 i64) (result i64)
+                    i64.const 0
+                )
+                (export "memory" (memory 0))
+            )
+        "#,
+            ),
+        );
+
+        let maybe_instance = test_instance(dna, None);
+        assert!(maybe_instance.is_ok());
+        assert!(maybe_instance.unwrap().state().nucleus().has_initialized());
+    }
+
+    #[test]
+    /// tests that agents rejected by the DNA's membrane rules can't initialize
+    fn test_validate_agent_err() {
+        let dna = test_utils::create_test_dna_with_wat(
+            "test_zome",
+            Callback::Genesis.capability().as_str(),
+            Some(
+                r#"
+            (module
+                (memory (;0;) 1)
+                (func (export "validate_agent") (param $p0 i64) (result i64)
+                    ;; allocation of the 13 bytes at offset 32768, after the input
+                    i64.const 140737488355341
+                )
+                (data (i32.const 32768)
+                    "\"Not invited\""
+                )
+                (export "memory" (memory 0))
+            )
+        "#,
+            ),
+        );
+
+        let instance = test_instance(dna, None);
+        assert!(instance.is_err());
+        assert!(instance.err().unwrap().contains("Not invited"));
+    }
+
     /// Committing a DnaEntry to source chain should work
     #[test]
     fn can_commit_dna() {
@@ -705,7 +893,7 @@ pub mod tests {
         let context = test_context("alex", netname);
         let dna = test_utils::create_test_dna_with_wat("test_zome", "test_cap", None);
         let dna_entry = Entry::Dna(dna);
-        let commit_action = ActionWrapper::new(Action::Commit((dna_entry.clone(), None)));
+        let commit_action = ActionWrapper::new(Action::Commit((dna_entry.clone(), None, None)));
 
         // Set up instance and process the action
         let instance = Instance::new(test_context("jason", netname));
@@ -721,7 +909,7 @@ pub mod tests {
             .history
             .iter()
             .find(|aw| match aw.action() {
-                Action::Commit((entry, _)) => {
+                Action::Commit((entry, _, _)) => {
                     assert_eq!(entry.entry_type(), EntryType::Dna);
                     assert_eq!(entry.content(), dna_entry.content());
                     true
@@ -737,7 +925,8 @@ pub mod tests {
         // Create Context, Agent and Commit AgentIdEntry Action
         let context = test_context("alex", netname);
         let agent_entry = Entry::AgentId(context.agent_id.clone());
-        let commit_agent_action = ActionWrapper::new(Action::Commit((agent_entry.clone(), None)));
+        let commit_agent_action =
+            ActionWrapper::new(Action::Commit((agent_entry.clone(), None, None)));
 
         // Set up instance and process the action
         let instance = Instance::new(test_context("jason", netname));
@@ -753,7 +942,7 @@ pub mod tests {
             .history
             .iter()
             .find(|aw| match aw.action() {
-                Action::Commit((entry, _)) => {
+                Action::Commit((entry, _, _)) => {
                     assert_eq!(entry.entry_type(), EntryType::AgentId);
                     assert_eq!(entry.content(), agent_entry.content());
                     true
@@ -11,9 +11,9 @@ extern crate serde;
 #[allow(unused_imports)]
 #[macro_use]
 extern crate serde_json;
-extern crate snowflake;
 #[cfg(test)]
 extern crate test_utils;
+extern crate uuid;
 extern crate wasmi;
 #[macro_use]
 extern crate unwrap_to;
@@ -32,7 +32,11 @@ extern crate holochain_core_types;
 extern crate holochain_core_types_derive;
 extern crate base64;
 extern crate globset;
+extern crate holochain_dpki;
 extern crate holochain_net_connection;
+extern crate holochain_sodium;
+extern crate url;
+extern crate valico;
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
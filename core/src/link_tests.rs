@@ -53,7 +53,8 @@ pub mod tests {
         let link = create_example_link();
         let link_list = LinkList::new(&[link]);
         let link_list_entry = Entry::LinkList(link_list);
-        let commit_action = ActionWrapper::new(Action::Commit((link_list_entry.clone(), None)));
+        let commit_action =
+            ActionWrapper::new(Action::Commit((link_list_entry.clone(), None, None)));
         // Set up instance and process the action
         let instance = Instance::new(test_context("jason", netname));
         let state_observers: Vec<Observer> = Vec::new();
@@ -67,7 +68,7 @@ pub mod tests {
             .history
             .iter()
             .find(|aw| match aw.action() {
-                Action::Commit((entry, _)) => {
+                Action::Commit((entry, _, _)) => {
                     assert_eq!(entry.entry_type(), EntryType::LinkList,);
                     assert_eq!(entry.content(), link_list_entry.content());
                     true
@@ -87,7 +88,8 @@ pub mod tests {
         let link_c = create_test_link_c();
         let link_list = LinkList::new(&[link_a, link_b, link_c]);
         let link_list_entry = Entry::LinkList(link_list.clone());
-        let commit_action = ActionWrapper::new(Action::Commit((link_list_entry.clone(), None)));
+        let commit_action =
+            ActionWrapper::new(Action::Commit((link_list_entry.clone(), None, None)));
         println!("commit_multilink: {:?}", commit_action);
         // Set up instance and process the action
         let instance = Instance::new(test_context("jason", netname));
@@ -102,7 +104,7 @@ pub mod tests {
             .history
             .iter()
             .find(|aw| match aw.action() {
-                Action::Commit((entry, _)) => {
+                Action::Commit((entry, _, _)) => {
                     assert_eq!(entry.entry_type(), EntryType::LinkList,);
                     assert_eq!(entry.content(), link_list_entry.content());
                     true
@@ -10,8 +10,8 @@ use futures::{
     task::{LocalWaker, Poll},
 };
 use holochain_core_types::{cas::content::Address, error::HolochainError, time::Timeout};
-use snowflake::ProcessUniqueId;
 use std::{pin::Pin, sync::Arc, thread};
+use uuid::Uuid;
 
 /// SendDirectMessage Action Creator for custom (=app) messages
 /// This triggers the network module to open a synchronous node-to-node connection
@@ -22,7 +22,7 @@ pub async fn custom_send(
     timeout: Timeout,
     context: Arc<Context>,
 ) -> Result<String, HolochainError> {
-    let id = ProcessUniqueId::new().to_string();
+    let id = Uuid::new_v4().to_string();
     let direct_message = DirectMessage::Custom(custom_direct_message);
     let direct_message_data = DirectMessageData {
         address: to_agent,
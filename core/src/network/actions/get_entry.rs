@@ -12,6 +12,7 @@ use holochain_core_types::{
     cas::content::Address, entry::EntryWithMeta, error::HcResult, time::Timeout,
 };
 use std::{pin::Pin, sync::Arc, thread};
+use uuid::Uuid;
 
 /// FetchEntry Action Creator
 /// This is the network version of get_entry that makes the network module start
@@ -25,7 +26,7 @@ pub async fn get_entry(
 ) -> HcResult<Option<EntryWithMeta>> {
     let key = GetEntryKey {
         address: address,
-        id: snowflake::ProcessUniqueId::new().to_string(),
+        id: Uuid::new_v4().to_string(),
     };
 
     let action_wrapper = ActionWrapper::new(Action::FetchEntry(key.clone()));
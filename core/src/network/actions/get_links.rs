@@ -8,23 +8,28 @@ use futures::{
     future::Future,
     task::{LocalWaker, Poll},
 };
-use holochain_core_types::{cas::content::Address, error::HcResult, time::Timeout};
-use snowflake::ProcessUniqueId;
+use holochain_core_types::{
+    cas::content::Address, crud_status::CrudStatus, error::HcResult, time::Timeout,
+};
+use holochain_wasm_utils::api_serialization::get_links::LinksStatusRequestKind;
 use std::{pin::Pin, sync::Arc, thread};
+use uuid::Uuid;
 
 /// GetLinks Action Creator
 /// This is the network version of get_links that makes the network module start
 /// a look-up process.
+/// Only links whose status matches the given `status_request` are returned.
 pub async fn get_links(
     context: Arc<Context>,
     address: Address,
     tag: String,
+    status_request: LinksStatusRequestKind,
     timeout: Timeout,
 ) -> HcResult<Vec<Address>> {
     let key = GetLinksKey {
         base_address: address.clone(),
         tag: tag.clone(),
-        id: ProcessUniqueId::new().to_string(),
+        id: Uuid::new_v4().to_string(),
     };
     let action_wrapper = ActionWrapper::new(Action::GetLinks(key.clone()));
     dispatch_action(context.action_channel(), action_wrapper.clone());
@@ -37,13 +42,23 @@ pub async fn get_links(
         dispatch_action(context_inner.action_channel(), action_wrapper.clone());
     });
 
-    await!(GetLinksFuture {
+    let links = await!(GetLinksFuture {
         context: context.clone(),
         key
-    })
+    })?;
+
+    Ok(links
+        .into_iter()
+        .filter(|(_, status)| match status_request {
+            LinksStatusRequestKind::Live => *status == CrudStatus::Live,
+            LinksStatusRequestKind::Deleted => *status == CrudStatus::Deleted,
+            LinksStatusRequestKind::All => true,
+        })
+        .map(|(address, _)| address)
+        .collect())
 }
 
-/// GetLinksFuture resolves to a HcResult<Vec<Address>>.
+/// GetLinksFuture resolves to a HcResult<Vec<(Address, CrudStatus)>>.
 /// Tracks the state of the network module
 pub struct GetLinksFuture {
     context: Arc<Context>,
@@ -51,7 +66,7 @@ pub struct GetLinksFuture {
 }
 
 impl Future for GetLinksFuture {
-    type Output = HcResult<Vec<Address>>;
+    type Output = HcResult<Vec<(Address, CrudStatus)>>;
 
     fn poll(self: Pin<&mut Self>, lw: &LocalWaker) -> Poll<Self::Output> {
         let state = self.context.state().unwrap().network();
@@ -9,7 +9,7 @@ use futures::{
     future::Future,
     task::{LocalWaker, Poll},
 };
-use holochain_core_types::{cas::content::Address, error::HcResult};
+use holochain_core_types::{cas::content::Address, error::HcResult, warrant::Warrant};
 use std::{pin::Pin, sync::Arc};
 
 /// Publish Action Creator
@@ -26,6 +26,19 @@ pub async fn publish(address: Address, context: &Arc<Context>) -> HcResult<Addre
     })
 }
 
+/// Publishes the given warrant as metadata of the entry it rejects, so that the other nodes
+/// holding (or asking for) the entry learn about the rejection.
+///
+/// Returns a future that resolves to the address of the warrant.
+pub async fn publish_warrant(warrant: Warrant, context: &Arc<Context>) -> HcResult<Address> {
+    let action_wrapper = ActionWrapper::new(Action::PublishWarrant(warrant));
+    dispatch_action(context.action_channel(), action_wrapper.clone());
+    await!(PublishFuture {
+        context: context.clone(),
+        action: action_wrapper,
+    })
+}
+
 /// PublishFuture resolves to ActionResponse
 /// Tracks the state for a response to its ActionWrapper
 pub struct PublishFuture {
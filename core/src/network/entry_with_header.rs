@@ -1,6 +1,9 @@
 use crate::{agent::find_chain_header, context::Context};
 use holochain_core_types::{
-    cas::content::Address, chain_header::ChainHeader, entry::Entry, error::HolochainError,
+    cas::content::Address,
+    chain_header::ChainHeader,
+    entry::{encrypted_entry::EncryptedEntry, Entry},
+    error::HolochainError,
 };
 use std::{convert::TryInto, sync::Arc};
 
@@ -16,6 +19,23 @@ impl EntryWithHeader {
     }
 }
 
+/// What gets published for an entry of a type with encrypted sharing:
+/// the cipher text together with the header of the plain text entry it got sealed from.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EncryptedEntryWithHeader {
+    pub encrypted_entry: EncryptedEntry,
+    pub header: ChainHeader,
+}
+
+impl EncryptedEntryWithHeader {
+    pub fn new(encrypted_entry: EncryptedEntry, header: ChainHeader) -> EncryptedEntryWithHeader {
+        EncryptedEntryWithHeader {
+            encrypted_entry,
+            header,
+        }
+    }
+}
+
 fn fetch_entry_from_cas(
     address: &Address,
     context: &Arc<Context>,
@@ -9,7 +9,7 @@ use holochain_net_connection::json_protocol::{
     FetchEntryData, FetchEntryResultData, FetchMetaData, FetchMetaResultData,
 };
 use regex::Regex;
-use std::{collections::BTreeSet, sync::Arc};
+use std::sync::Arc;
 
 lazy_static! {
     static ref LINK: Regex =
@@ -18,7 +18,32 @@ lazy_static! {
 
 /// The network has requested a DHT entry from us.
 /// Lets try to get it and trigger a response.
+/// Entries of types the DNA declares private are never handed out, even though they
+/// sit in the same CAS as the DHT shard.
+/// Entries of encrypted types are only handed out as the sealed copy we hold.
 pub fn handle_fetch_entry(get_dht_data: FetchEntryData, context: Arc<Context>) {
+    let maybe_encrypted_entry_with_meta =
+        nucleus::actions::get_entry::get_encrypted_entry_with_meta(
+            &context,
+            Address::from(get_dht_data.entry_address.clone()),
+        )
+        .unwrap_or_else(|error| {
+            context.log(format!(
+                "err/net: Error trying to find sealed entry {:?}",
+                error
+            ));
+            None
+        });
+    if let Some(encrypted_entry_with_meta) = maybe_encrypted_entry_with_meta {
+        let action_wrapper = ActionWrapper::new(Action::RespondFetchEncrypted((
+            get_dht_data,
+            encrypted_entry_with_meta,
+        )));
+        dispatch_action(context.action_channel(), action_wrapper.clone());
+        return;
+    }
+
+    let dna = context.get_dna();
     let maybe_entry_with_meta = nucleus::actions::get_entry::get_entry_with_meta(
         &context,
         Address::from(get_dht_data.entry_address.clone()),
@@ -26,6 +51,14 @@ pub fn handle_fetch_entry(get_dht_data: FetchEntryData, context: Arc<Context>) {
     .unwrap_or_else(|error| {
         context.log(format!("err/net: Error trying to find entry {:?}", error));
         None
+    })
+    .filter(|entry_with_meta| {
+        dna.as_ref()
+            .map(|dna| {
+                let entry_type = entry_with_meta.entry.entry_type();
+                entry_type.can_publish(dna) && !entry_type.is_encrypted(dna)
+            })
+            .unwrap_or(false)
     });
 
     let action_wrapper =
@@ -56,9 +89,9 @@ pub fn handle_fetch_meta(fetch_meta_data: FetchMetaData, context: Arc<Context>)
                 Address::from(fetch_meta_data.entry_address.clone()),
                 tag.clone(),
             )
-            .unwrap_or(BTreeSet::new())
+            .unwrap_or(Vec::new())
             .into_iter()
-            .map(|eav| eav.value())
+            .map(|(eav, status)| (eav.value(), status))
             .collect::<Vec<_>>();
         let action_wrapper = ActionWrapper::new(Action::RespondGetLinks((fetch_meta_data, links)));
         dispatch_action(context.action_channel(), action_wrapper.clone());
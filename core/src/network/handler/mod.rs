@@ -3,11 +3,13 @@ pub mod send;
 pub mod store;
 
 use crate::{
+    agent::can_publish_chain_entry,
     context::Context,
     network::{
         actions::publish::publish,
         handler::{get::*, send::*, store::*},
     },
+    nucleus::actions::get_entry::get_entry_from_agent,
 };
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
@@ -196,11 +198,21 @@ pub fn create_handler(c: &Arc<Context>) -> NetHandler {
 }
 
 fn republish_all_public_chain_entries(context: &Arc<Context>) {
+    let dna = match context.get_dna() {
+        Some(dna) => dna,
+        None => return,
+    };
     let chain = context.state().unwrap().agent().chain_store();
     let top_header = context.state().unwrap().agent().top_chain_header();
     chain
         .iter(&top_header)
-        .filter(|ref chain_header| chain_header.entry_type().can_publish())
+        .filter(|ref chain_header| {
+            get_entry_from_agent(context, chain_header.entry_address())
+                .ok()
+                .and_then(|maybe_entry| maybe_entry)
+                .map(|entry| can_publish_chain_entry(&entry, &dna, context))
+                .unwrap_or(false)
+        })
         .for_each(|chain_header| {
             let hash = HashString::from(chain_header.entry_address().to_string());
             match context.block_on(publish(hash.clone(), context)) {
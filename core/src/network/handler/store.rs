@@ -1,16 +1,37 @@
 use crate::{
     context::Context,
-    network::entry_with_header::EntryWithHeader,
-    workflows::{hold_entry::hold_entry_workflow, hold_link::hold_link_workflow},
+    network::entry_with_header::{EncryptedEntryWithHeader, EntryWithHeader},
+    workflows::{
+        hold_entry::{hold_encrypted_entry_workflow, hold_entry_workflow},
+        hold_link::hold_link_workflow,
+        hold_update_delete::hold_update_delete_workflow,
+        hold_warrant::hold_warrant_workflow,
+    },
+};
+use holochain_core_types::{
+    crud_status::{CRUD_NAME, LINK_NAME, STATUS_NAME},
+    warrant::{Warrant, WARRANT_NAME},
 };
-use holochain_core_types::crud_status::{LINK_NAME, STATUS_NAME};
 use holochain_net_connection::json_protocol::{DhtMetaData, EntryData};
 use std::{sync::Arc, thread};
 
 /// The network requests us to store (i.e. hold) the given entry.
+/// Entries of encrypted types come as a sealed copy which we hold without being able to read it.
 pub fn handle_store_entry(dht_data: EntryData, context: Arc<Context>) {
-    let entry_with_header: EntryWithHeader =
-        serde_json::from_str(&serde_json::to_string(&dht_data.entry_content).unwrap()).unwrap();
+    let content = serde_json::to_string(&dht_data.entry_content).unwrap();
+    if let Ok(entry_with_header) = serde_json::from_str::<EncryptedEntryWithHeader>(&content) {
+        thread::spawn(move || {
+            match context.block_on(hold_encrypted_entry_workflow(
+                entry_with_header,
+                context.clone(),
+            )) {
+                Err(error) => context.log(format!("err/net/dht: {}", error)),
+                _ => (),
+            }
+        });
+        return;
+    }
+    let entry_with_header: EntryWithHeader = serde_json::from_str(&content).unwrap();
     thread::spawn(move || {
         match context.block_on(hold_entry_workflow(entry_with_header, context.clone())) {
             Err(error) => context.log(format!("err/net/dht: {}", error)),
@@ -39,6 +60,42 @@ pub fn handle_store_meta(dht_meta_data: DhtMetaData, context: Arc<Context>) {
                 }
             });
         }
+        CRUD_NAME => {
+            context.log("debug/net/handle: HandleStoreMeta: got CRUD. processing...");
+            assert_eq!(dht_meta_data.content_list.len(), 1);
+            let entry_with_header: EntryWithHeader = serde_json::from_str(
+                &serde_json::to_string(&dht_meta_data.content_list[0])
+                    .expect("dht_meta_data should be EntryWithHeader"),
+            )
+            .expect("dht_meta_data should be EntryWithHeader");
+            let old_entry_address = dht_meta_data.entry_address.clone();
+            thread::spawn(move || {
+                match context.block_on(hold_update_delete_workflow(
+                    old_entry_address,
+                    entry_with_header,
+                    context.clone(),
+                )) {
+                    Err(error) => context.log(format!("err/net/dht: {}", error)),
+                    _ => (),
+                }
+            });
+        }
+        WARRANT_NAME => {
+            context.log("debug/net/handle: HandleStoreMeta: got WARRANT. processing...");
+            for content in dht_meta_data.content_list {
+                let warrant: Warrant = serde_json::from_str(
+                    &serde_json::to_string(&content).expect("dht_meta_data should be Warrant"),
+                )
+                .expect("dht_meta_data should be Warrant");
+                let context = context.clone();
+                thread::spawn(move || {
+                    match context.block_on(hold_warrant_workflow(warrant, context.clone())) {
+                        Err(error) => context.log(format!("err/net/dht: {}", error)),
+                        _ => (),
+                    }
+                });
+            }
+        }
         STATUS_NAME => {
             context.log("debug/net/handle: HandleStoreMeta: got CRUD status. processing...");
             // FIXME: block_on hold crud_status metadata in DHT?
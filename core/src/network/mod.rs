@@ -11,7 +11,7 @@ pub mod test_utils;
 pub mod tests {
     use crate::{
         agent::{actions::commit::commit_entry, state::create_new_chain_header},
-        dht::actions::add_link::add_link,
+        dht::actions::{add_link::add_link, remove_link::remove_link},
         instance::tests::test_instance_and_context_by_name,
         network::{
             actions::{
@@ -31,6 +31,7 @@ pub mod tests {
     use holochain_wasm_utils::api_serialization::get_entry::{
         GetEntryArgs, GetEntryOptions, GetEntryResultType,
     };
+    use holochain_wasm_utils::api_serialization::get_links::LinksStatusRequestKind;
     use test_utils::*;
 
     #[test]
@@ -78,8 +79,8 @@ pub mod tests {
 
         // Create Entry & crud-status metadata, and store it.
         let entry = test_entry();
-        let header1 = create_new_chain_header(&entry, context1.clone(), &None);
-        let header2 = create_new_chain_header(&entry, context2.clone(), &None);
+        let header1 = create_new_chain_header(&entry, context1.clone(), &None, &None).unwrap();
+        let header2 = create_new_chain_header(&entry, context2.clone(), &None, &None).unwrap();
         context1
             .block_on(commit_entry(entry.clone(), None, &context1))
             .unwrap();
@@ -211,6 +212,7 @@ pub mod tests {
             entry_addresses[0].clone(),
             String::from("test-tag"),
             Default::default(),
+            Default::default(),
         ));
 
         assert!(maybe_links.is_ok());
@@ -220,5 +222,29 @@ pub mod tests {
             (links[0] == entry_addresses[1] || links[0] == entry_addresses[2])
                 && (links[1] == entry_addresses[1] || links[1] == entry_addresses[2])
         );
+
+        assert!(context1.block_on(remove_link(&link1, &context1)).is_ok());
+
+        let get_links_with_status = |status_request| {
+            context2
+                .block_on(get_links(
+                    context2.clone(),
+                    entry_addresses[0].clone(),
+                    String::from("test-tag"),
+                    status_request,
+                    Default::default(),
+                ))
+                .expect("Could not get links")
+        };
+
+        assert_eq!(
+            get_links_with_status(LinksStatusRequestKind::Live),
+            vec![entry_addresses[2].clone()]
+        );
+        assert_eq!(
+            get_links_with_status(LinksStatusRequestKind::Deleted),
+            vec![entry_addresses[1].clone()]
+        );
+        assert_eq!(get_links_with_status(LinksStatusRequestKind::All).len(), 2);
     }
 }
@@ -77,6 +77,7 @@ mod tests {
     use holochain_core_types::{
         cas::content::AddressableContent, entry::test_entry, error::HolochainError,
     };
+    use uuid::Uuid;
 
     #[test]
     pub fn reduce_get_entry_without_network_initialized() {
@@ -87,7 +88,7 @@ mod tests {
         let entry = test_entry();
         let key = GetEntryKey {
             address: entry.address(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         };
         let action_wrapper = ActionWrapper::new(Action::FetchEntry(key.clone()));
 
@@ -125,7 +126,7 @@ mod tests {
         let entry = test_entry();
         let key = GetEntryKey {
             address: entry.address(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         };
         let action_wrapper = ActionWrapper::new(Action::FetchEntry(key.clone()));
 
@@ -165,7 +166,7 @@ mod tests {
         let entry = test_entry();
         let key = GetEntryKey {
             address: entry.address(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         };
         let action_wrapper = ActionWrapper::new(Action::FetchEntry(key.clone()));
 
@@ -204,10 +205,12 @@ mod tests {
             entry: entry.clone(),
             crud_status: CrudStatus::Live,
             maybe_crud_link: None,
+            crud_fork: Vec::new(),
+            warrant: None,
         };
         let new_key = GetEntryKey {
             address: entry.address(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         };
         let dht_data = DhtData {
             msg_id: new_key.id.clone(),
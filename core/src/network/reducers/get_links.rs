@@ -70,6 +70,7 @@ mod tests {
     };
     use holochain_core_types::error::HolochainError;
     //use std::sync::{Arc, RwLock};
+    use uuid::Uuid;
 
     #[test]
     pub fn reduce_get_links_without_network_initialized() {
@@ -81,7 +82,7 @@ mod tests {
         let key = GetLinksKey {
             base_address: entry.address(),
             tag: tag.clone(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         };
         let action_wrapper = ActionWrapper::new(Action::GetLinks(key.clone()));
 
@@ -123,7 +124,7 @@ mod tests {
         let key = GetLinksKey {
             base_address: entry.address(),
             tag: tag.clone(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         };
         let action_wrapper = ActionWrapper::new(Action::GetLinks(key.clone()));
 
@@ -165,7 +166,7 @@ mod tests {
         let key = GetLinksKey {
             base_address: entry.address(),
             tag: tag.clone(),
-            id: snowflake::ProcessUniqueId::new().to_string(),
+            id: Uuid::new_v4().to_string(),
         };
         let action_wrapper = ActionWrapper::new(Action::GetLinks(key.clone()));
 
@@ -3,16 +3,16 @@ use crate::{
     context::Context,
     network::state::NetworkState,
 };
-use holochain_core_types::{cas::content::Address, error::HolochainError};
+use holochain_core_types::{cas::content::Address, crud_status::CrudStatus, error::HolochainError};
 use holochain_net_connection::json_protocol::FetchMetaResultData;
 use std::sync::Arc;
 
 fn reduce_handle_get_links_result_inner(
     network_state: &mut NetworkState,
     dht_meta_data: &FetchMetaResultData,
-) -> Result<Vec<Address>, HolochainError> {
+) -> Result<Vec<(Address, CrudStatus)>, HolochainError> {
     network_state.initialized()?;
-    // expecting dht_meta_data.content_list to be a jsonified array of EntryWithHeader or Address
+    // expecting dht_meta_data.content_list to be a jsonified array of (Address, CrudStatus) pairs
     // TODO: do a loop on content once links properly implemented
     assert_eq!(dht_meta_data.content_list.len(), 1);
     let res = serde_json::from_str(
@@ -21,7 +21,7 @@ fn reduce_handle_get_links_result_inner(
     );
     if let Err(_) = res {
         return Err(HolochainError::ErrorGeneric(
-            "Failed to deserialize Vec<Address> from HandleGetLinkResult DhtMetaData content"
+            "Failed to deserialize Vec<(Address, CrudStatus)> from HandleGetLinkResult DhtMetaData content"
                 .to_string(),
         ));
     }
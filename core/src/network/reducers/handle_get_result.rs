@@ -2,18 +2,38 @@ use crate::{
     action::{ActionWrapper, GetEntryKey},
     context::Context,
     network::state::NetworkState,
+    nucleus::actions::get_entry::decrypt_entry_with_meta,
+};
+use holochain_core_types::{
+    cas::content::Address,
+    entry::{encrypted_entry::EncryptedEntryWithMeta, EntryWithMeta},
+    error::HolochainError,
 };
-use holochain_core_types::{cas::content::Address, entry::EntryWithMeta, error::HolochainError};
 use holochain_net_connection::json_protocol::FetchEntryResultData;
 use std::sync::Arc;
 
+/// Entries of encrypted types come back sealed and get opened with our keys here,
+/// so they end up as not found if we are not one of their recipients.
 fn reduce_handle_get_result_inner(
+    context: &Arc<Context>,
     network_state: &mut NetworkState,
     dht_data: &FetchEntryResultData,
 ) -> Result<Option<EntryWithMeta>, HolochainError> {
     network_state.initialized()?;
 
-    let res = serde_json::from_str(&serde_json::to_string(&dht_data.entry_content).unwrap());
+    let content = serde_json::to_string(&dht_data.entry_content).unwrap();
+    if let Ok(encrypted_entry_with_meta) = serde_json::from_str::<EncryptedEntryWithMeta>(&content)
+    {
+        if *encrypted_entry_with_meta.encrypted_entry.entry_address()
+            != Address::from(dht_data.entry_address.clone())
+        {
+            return Err(HolochainError::ErrorGeneric(
+                "Received sealed entry for another address than requested".to_string(),
+            ));
+        }
+        return decrypt_entry_with_meta(context, &encrypted_entry_with_meta);
+    }
+    let res = serde_json::from_str(&content);
     if let Err(_) = res {
         return Err(HolochainError::ErrorGeneric(
             "Failed to deserialize EntryWithMeta from HandleFetchResult action argument"
@@ -24,14 +44,14 @@ fn reduce_handle_get_result_inner(
 }
 
 pub fn reduce_handle_get_result(
-    _context: Arc<Context>,
+    context: Arc<Context>,
     network_state: &mut NetworkState,
     action_wrapper: &ActionWrapper,
 ) {
     let action = action_wrapper.action();
     let dht_data = unwrap_to!(action => crate::action::Action::HandleFetchResult);
 
-    let result = reduce_handle_get_result_inner(network_state, dht_data);
+    let result = reduce_handle_get_result_inner(&context, network_state, dht_data);
 
     let key = GetEntryKey {
         address: Address::from(dht_data.entry_address.clone()),
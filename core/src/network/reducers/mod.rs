@@ -26,9 +26,9 @@ use crate::{
             handle_get_result::reduce_handle_get_result,
             handle_get_validation_package::reduce_handle_get_validation_package,
             init::reduce_init,
-            publish::reduce_publish,
+            publish::{reduce_publish, reduce_publish_warrant},
             resolve_direct_connection::reduce_resolve_direct_connection,
-            respond_get::reduce_respond_fetch_data,
+            respond_get::{reduce_respond_fetch_data, reduce_respond_fetch_encrypted_data},
             respond_get_links::reduce_respond_get_links,
             send_direct_message::{reduce_send_direct_message, reduce_send_direct_message_timeout},
         },
@@ -40,8 +40,8 @@ use holochain_net_connection::{
     json_protocol::{JsonProtocol, MessageData},
     net_connection::NetSend,
 };
-use snowflake::ProcessUniqueId;
 use std::sync::Arc;
+use uuid::Uuid;
 
 /// maps incoming action to the correct handler
 fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<NetworkReduceFn> {
@@ -57,8 +57,10 @@ fn resolve_reducer(action_wrapper: &ActionWrapper) -> Option<NetworkReduceFn> {
         Action::HandleGetValidationPackage(_) => Some(reduce_handle_get_validation_package),
         Action::InitNetwork(_) => Some(reduce_init),
         Action::Publish(_) => Some(reduce_publish),
+        Action::PublishWarrant(_) => Some(reduce_publish_warrant),
         Action::ResolveDirectConnection(_) => Some(reduce_resolve_direct_connection),
         Action::RespondFetch(_) => Some(reduce_respond_fetch_data),
+        Action::RespondFetchEncrypted(_) => Some(reduce_respond_fetch_encrypted_data),
         Action::RespondGetLinks(_) => Some(reduce_respond_get_links),
         Action::SendDirectMessage(_) => Some(reduce_send_direct_message),
         Action::SendDirectMessageTimeout(_) => Some(reduce_send_direct_message_timeout),
@@ -112,7 +114,7 @@ pub fn send_message(
     to_agent_id: &Address,
     message: DirectMessage,
 ) -> Result<(), HolochainError> {
-    let id = ProcessUniqueId::new().to_string();
+    let id = Uuid::new_v4().to_string();
 
     let data = MessageData {
         request_id: id.clone(),
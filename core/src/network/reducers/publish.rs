@@ -3,7 +3,7 @@ use crate::{
     context::Context,
     network::{
         actions::ActionResponse,
-        entry_with_header::{fetch_entry_with_header, EntryWithHeader},
+        entry_with_header::{fetch_entry_with_header, EncryptedEntryWithHeader, EntryWithHeader},
         reducers::send,
         state::NetworkState,
     },
@@ -11,9 +11,11 @@ use crate::{
 };
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
-    crud_status::{CrudStatus, LINK_NAME, STATUS_NAME},
+    chain_header::ChainHeader,
+    crud_status::{CrudStatus, CRUD_NAME, LINK_NAME, STATUS_NAME},
     entry::{entry_type::EntryType, Entry},
     error::HolochainError,
+    warrant::{Warrant, WARRANT_NAME},
 };
 use holochain_net_connection::json_protocol::{DhtMetaData, EntryData, JsonProtocol};
 use std::sync::Arc;
@@ -37,6 +39,50 @@ fn publish_entry(
     )
 }
 
+/// Send to network a PublishDhtData message holding the sealed copy of an entry of an
+/// encrypted type, under the address of the plain text entry
+fn publish_encrypted_entry(
+    network_state: &mut NetworkState,
+    entry_with_header: &EncryptedEntryWithHeader,
+) -> Result<(), HolochainError> {
+    send(
+        network_state,
+        JsonProtocol::PublishEntry(EntryData {
+            dna_address: network_state.dna_address.clone().unwrap(),
+            provider_agent_id: network_state.agent_id.clone().unwrap(),
+            entry_address: entry_with_header.encrypted_entry.entry_address().clone(),
+            entry_content: serde_json::from_str(
+                &serde_json::to_string(&entry_with_header).unwrap(),
+            )
+            .unwrap(),
+        }),
+    )
+}
+
+/// Looks up the sealed copy the author's node holds for an entry of an encrypted type.
+/// The plain text entry never gets published, so there is nothing to send without it.
+fn sealed_entry_with_header(
+    context: &Arc<Context>,
+    entry_with_header: &EntryWithHeader,
+) -> Result<EncryptedEntryWithHeader, HolochainError> {
+    let address = entry_with_header.entry.address();
+    let encrypted_entry = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Could not get state".to_string()))?
+        .dht()
+        .get_encrypted_entry(&address)?
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Entry {} is of an encrypted type but was not sealed for publishing",
+                address
+            ))
+        })?;
+    Ok(EncryptedEntryWithHeader::new(
+        encrypted_entry,
+        entry_with_header.header.clone(),
+    ))
+}
+
 /// Send to network:
 ///  - a PublishDhtMeta message for the crud-status
 ///  - a PublishDhtMeta message for the crud-link
@@ -81,21 +127,22 @@ fn publish_crud_meta(
 }
 
 /// Send to network a PublishMeta message holding a link metadata to `entry_with_header`
+/// (either a link addition or a link removal)
 fn publish_link_meta(
     context: &Arc<Context>,
     network_state: &mut NetworkState,
     entry_with_header: &EntryWithHeader,
 ) -> Result<(), HolochainError> {
-    let link_add_entry = match entry_with_header.entry.clone() {
-        Entry::LinkAdd(link_add_entry) => link_add_entry,
+    let link = match entry_with_header.entry.clone() {
+        Entry::LinkAdd(link_add_entry) => link_add_entry.link().clone(),
+        Entry::LinkRemove(link_remove_entry) => link_remove_entry.link().clone(),
         _ => {
             return Err(HolochainError::ErrorGeneric(format!(
-                "Received bad entry type. Expected Entry::LinkAdd received {:?}",
+                "Received bad entry type. Expected Entry::LinkAdd or Entry::LinkRemove received {:?}",
                 entry_with_header.entry,
             )));
         }
     };
-    let link = link_add_entry.link().clone();
 
     context.log(format!(
         "debug/reduce/link_meta: Publishing link meta for link: {:?}",
@@ -117,6 +164,82 @@ fn publish_link_meta(
     )
 }
 
+/// The address of the entry an update or a deletion modifies, if `entry_with_header` is one.
+/// An update's header links to the header of the old entry, which the author committed,
+/// so it can be looked up in the author's chain.
+/// Key rotations are updates of the agent's previous AgentId entry.
+fn modified_entry_address(
+    context: &Arc<Context>,
+    entry_with_header: &EntryWithHeader,
+) -> Result<Option<Address>, HolochainError> {
+    match (
+        &entry_with_header.entry,
+        entry_with_header.header.link_crud(),
+    ) {
+        (Entry::Deletion(deletion_entry), _) => {
+            Ok(Some(deletion_entry.clone().deleted_entry_address()))
+        }
+        (Entry::App(_, _), Some(old_header_address))
+        | (Entry::AgentId(_), Some(old_header_address)) => {
+            let content = context
+                .state()
+                .ok_or_else(|| HolochainError::ErrorGeneric("Could not get state".to_string()))?
+                .agent()
+                .chain_store()
+                .content_storage()
+                .read()
+                .unwrap()
+                .fetch(&old_header_address)?
+                .ok_or_else(|| {
+                    HolochainError::ErrorGeneric(format!(
+                        "Header {} of the updated entry is not in the source chain",
+                        old_header_address
+                    ))
+                })?;
+            Ok(Some(
+                ChainHeader::try_from_content(&content)?
+                    .entry_address()
+                    .clone(),
+            ))
+        }
+        _ => Ok(None),
+    }
+}
+
+/// Send to network a PublishMeta message holding an update or a deletion (`entry_with_header`)
+/// for the entry it modifies, so that the nodes holding that entry validate it with
+/// the old entry at hand and record it in their CRUD metadata
+fn publish_update_delete_meta(
+    context: &Arc<Context>,
+    network_state: &mut NetworkState,
+    entry_with_header: &EntryWithHeader,
+) -> Result<(), HolochainError> {
+    let old_entry_address = match modified_entry_address(context, entry_with_header)? {
+        Some(address) => address,
+        None => return Ok(()),
+    };
+
+    context.log(format!(
+        "debug/reduce/crud_meta: Publishing modification of {} by {}",
+        old_entry_address,
+        entry_with_header.entry.address()
+    ));
+
+    send(
+        network_state,
+        JsonProtocol::PublishMeta(DhtMetaData {
+            dna_address: network_state.dna_address.clone().unwrap(),
+            provider_agent_id: network_state.agent_id.clone().unwrap(),
+            entry_address: old_entry_address,
+            attribute: CRUD_NAME.to_string(),
+            content_list: vec![serde_json::from_str(
+                &serde_json::to_string(&entry_with_header).unwrap(),
+            )
+            .unwrap()],
+        }),
+    )
+}
+
 fn reduce_publish_inner(
     context: &Arc<Context>,
     network_state: &mut NetworkState,
@@ -128,32 +251,57 @@ fn reduce_publish_inner(
     let (crud_status, maybe_crud_link) = get_entry_crud_meta_from_dht(context, address.clone())?
         .expect("Entry should have crud-status metadata in DHT.");
     match entry_with_header.entry.entry_type() {
-        EntryType::AgentId => publish_entry(network_state, &entry_with_header).and_then(|_| {
-            publish_crud_meta(
-                network_state,
-                entry_with_header.entry.address(),
-                crud_status,
-                maybe_crud_link,
-            )
-        }),
-        EntryType::App(_) => publish_entry(network_state, &entry_with_header).and_then(|_| {
-            publish_crud_meta(
-                network_state,
-                entry_with_header.entry.address(),
-                crud_status,
-                maybe_crud_link,
-            )
-        }),
-        EntryType::LinkAdd => publish_entry(network_state, &entry_with_header)
-            .and_then(|_| publish_link_meta(context, network_state, &entry_with_header)),
-        EntryType::Deletion => publish_entry(network_state, &entry_with_header).and_then(|_| {
-            publish_crud_meta(
-                network_state,
-                entry_with_header.entry.address(),
-                crud_status,
-                maybe_crud_link,
-            )
-        }),
+        EntryType::AgentId => publish_entry(network_state, &entry_with_header)
+            .and_then(|_| {
+                publish_crud_meta(
+                    network_state,
+                    entry_with_header.entry.address(),
+                    crud_status,
+                    maybe_crud_link,
+                )
+            })
+            .and_then(|_| publish_update_delete_meta(context, network_state, &entry_with_header)),
+        EntryType::App(_)
+            if context
+                .get_dna()
+                .map(|dna| entry_with_header.entry.entry_type().is_encrypted(&dna))
+                .unwrap_or(false) =>
+        {
+            sealed_entry_with_header(context, &entry_with_header)
+                .and_then(|sealed| publish_encrypted_entry(network_state, &sealed))
+                .and_then(|_| {
+                    publish_crud_meta(
+                        network_state,
+                        entry_with_header.entry.address(),
+                        crud_status,
+                        maybe_crud_link,
+                    )
+                })
+        }
+        EntryType::App(_) => publish_entry(network_state, &entry_with_header)
+            .and_then(|_| {
+                publish_crud_meta(
+                    network_state,
+                    entry_with_header.entry.address(),
+                    crud_status,
+                    maybe_crud_link,
+                )
+            })
+            .and_then(|_| publish_update_delete_meta(context, network_state, &entry_with_header)),
+        EntryType::LinkAdd | EntryType::LinkRemove => {
+            publish_entry(network_state, &entry_with_header)
+                .and_then(|_| publish_link_meta(context, network_state, &entry_with_header))
+        }
+        EntryType::Deletion => publish_entry(network_state, &entry_with_header)
+            .and_then(|_| {
+                publish_crud_meta(
+                    network_state,
+                    entry_with_header.entry.address(),
+                    crud_status,
+                    maybe_crud_link,
+                )
+            })
+            .and_then(|_| publish_update_delete_meta(context, network_state, &entry_with_header)),
         _ => Err(HolochainError::NotImplemented(
             "reduce_publish_inner".into(),
         )),
@@ -178,6 +326,44 @@ pub fn reduce_publish(
     );
 }
 
+/// Send to network a PublishMeta message holding the warrant for the entry it rejects
+fn reduce_publish_warrant_inner(
+    network_state: &mut NetworkState,
+    warrant: &Warrant,
+) -> Result<(), HolochainError> {
+    network_state.initialized()?;
+    send(
+        network_state,
+        JsonProtocol::PublishMeta(DhtMetaData {
+            dna_address: network_state.dna_address.clone().unwrap(),
+            provider_agent_id: network_state.agent_id.clone().unwrap(),
+            entry_address: warrant.entry_address(),
+            attribute: WARRANT_NAME.to_string(),
+            content_list: vec![
+                serde_json::from_str(&serde_json::to_string(&warrant).unwrap()).unwrap(),
+            ],
+        }),
+    )
+}
+
+pub fn reduce_publish_warrant(
+    _context: Arc<Context>,
+    network_state: &mut NetworkState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let warrant = unwrap_to!(action => crate::action::Action::PublishWarrant);
+
+    let result = reduce_publish_warrant_inner(network_state, warrant);
+    network_state.actions.insert(
+        action_wrapper.clone(),
+        ActionResponse::Publish(match result {
+            Ok(_) => Ok(warrant.address()),
+            Err(e) => Err(HolochainError::ErrorGeneric(e.to_string())),
+        }),
+    );
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -198,5 +384,4 @@ mod tests {
 
         store.reduce(context.clone(), action_wrapper);
     }
-
 }
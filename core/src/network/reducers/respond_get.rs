@@ -3,16 +3,17 @@ use crate::{
     context::Context,
     network::{actions::ActionResponse, reducers::send, state::NetworkState},
 };
-use holochain_core_types::{entry::EntryWithMeta, error::HolochainError};
+use holochain_core_types::error::HolochainError;
 use holochain_net_connection::json_protocol::{FetchEntryData, FetchEntryResultData, JsonProtocol};
+use serde::Serialize;
 use std::sync::Arc;
 
 /// Send back to network a HandleFetchEntryResult, no matter what.
 /// Will return an empty content field if it actually doesn't have the data.
-fn reduce_respond_fetch_data_inner(
+fn reduce_respond_fetch_data_inner<T: Serialize>(
     network_state: &mut NetworkState,
     get_dht_data: &FetchEntryData,
-    maybe_entry: &Option<EntryWithMeta>,
+    maybe_entry: &T,
 ) -> Result<(), HolochainError> {
     network_state.initialized()?;
 
@@ -46,3 +47,23 @@ pub fn reduce_respond_fetch_data(
         }),
     );
 }
+
+/// Send back to network a HandleFetchEntryResult holding the sealed copy of an entry
+/// of an encrypted type, which only its recipients can open.
+pub fn reduce_respond_fetch_encrypted_data(
+    _context: Arc<Context>,
+    network_state: &mut NetworkState,
+    action_wrapper: &ActionWrapper,
+) {
+    let action = action_wrapper.action();
+    let (get_dht_data, encrypted_entry) =
+        unwrap_to!(action => crate::action::Action::RespondFetchEncrypted);
+    let result = reduce_respond_fetch_data_inner(network_state, get_dht_data, encrypted_entry);
+    network_state.actions.insert(
+        action_wrapper.clone(),
+        ActionResponse::RespondFetch(match result {
+            Ok(_) => Ok(()),
+            Err(e) => Err(HolochainError::ErrorGeneric(e.to_string())),
+        }),
+    );
+}
@@ -3,7 +3,7 @@ use crate::{
     context::Context,
     network::{actions::ActionResponse, reducers::send, state::NetworkState},
 };
-use holochain_core_types::{cas::content::Address, error::HolochainError};
+use holochain_core_types::{cas::content::Address, crud_status::CrudStatus, error::HolochainError};
 use holochain_net_connection::json_protocol::{FetchMetaData, FetchMetaResultData, JsonProtocol};
 use std::sync::Arc;
 
@@ -12,7 +12,7 @@ use std::sync::Arc;
 fn reduce_respond_get_links_inner(
     network_state: &mut NetworkState,
     get_dht_meta_data: &FetchMetaData,
-    links: &Vec<Address>,
+    links: &Vec<(Address, CrudStatus)>,
 ) -> Result<(), HolochainError> {
     network_state.initialized()?;
 
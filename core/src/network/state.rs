@@ -4,15 +4,15 @@ use crate::{
 };
 use boolinator::*;
 use holochain_core_types::{
-    cas::content::Address, entry::EntryWithMeta, error::HolochainError,
+    cas::content::Address, crud_status::CrudStatus, entry::EntryWithMeta, error::HolochainError,
     validation::ValidationPackage,
 };
 use holochain_net::p2p_network::P2pNetwork;
-use snowflake;
 use std::{
     collections::HashMap,
     sync::{Arc, Mutex},
 };
+use uuid::Uuid;
 
 type Actions = HashMap<ActionWrapper, ActionResponse>;
 
@@ -26,8 +26,8 @@ type GetEntryWithMetaResult = Option<Result<Option<EntryWithMeta>, HolochainErro
 /// This represents the state of a get_links network process:
 /// None: process started, but no response yet from the network
 /// Some(Err(_)): there was a problem at some point
-/// Some(Ok(_)): we got the list of links
-type GetLinksResult = Option<Result<Vec<Address>, HolochainError>>;
+/// Some(Ok(_)): we got the list of links together with their status
+type GetLinksResult = Option<Result<Vec<(Address, CrudStatus)>, HolochainError>>;
 
 /// This represents the state of a get_validation_package network process:
 /// None: process started, but no response yet from the network
@@ -67,7 +67,7 @@ pub struct NetworkState {
 
     pub custom_direct_message_replys: HashMap<String, Result<String, HolochainError>>,
 
-    id: snowflake::ProcessUniqueId,
+    id: Uuid,
 }
 
 impl PartialEq for NetworkState {
@@ -90,7 +90,7 @@ impl NetworkState {
             direct_message_connections: HashMap::new(),
             custom_direct_message_replys: HashMap::new(),
 
-            id: snowflake::ProcessUniqueId::new(),
+            id: Uuid::new_v4(),
         }
     }
 
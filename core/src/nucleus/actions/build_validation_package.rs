@@ -18,11 +18,11 @@ use holochain_core_types::{
     error::HolochainError,
     validation::{ValidationPackage, ValidationPackageDefinition::*},
 };
-use snowflake;
 use std::{convert::TryInto, pin::Pin, sync::Arc, thread};
+use uuid::Uuid;
 
 pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> ValidationPackageFuture {
-    let id = snowflake::ProcessUniqueId::new();
+    let id = Uuid::new_v4();
 
     match entry.entry_type() {
         EntryType::App(app_entry_type) => {
@@ -46,8 +46,8 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
             }
         }
 
-        EntryType::LinkAdd => {
-            // LinkAdd can always be validated
+        EntryType::LinkAdd | EntryType::LinkRemove => {
+            // LinkAdd and LinkRemove can always be validated
         }
 
         EntryType::Deletion => {
@@ -61,6 +61,10 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
         EntryType::AgentId => {
             // FIXME
         }
+
+        EntryType::ChainMigrate => {
+            // ChainMigrate entries only need their header
+        }
         _ => {
             return ValidationPackageFuture {
                 context: context.clone(),
@@ -77,23 +81,45 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
         let id = id.clone();
         let entry = entry.clone();
         let context = context.clone();
-        let entry_header = find_chain_header(&entry.clone(), &context).unwrap_or(
-            // TODO: make sure that we don't run into race conditions with respect to the chain
-            // We need the source chain header as part of the validation package.
-            // For an already committed entry (when asked to deliver the validation package to
-            // a DHT node) we should have gotten one from chain_header() above.
-            // But when we commit an entry, there is no header for it in the chain yet.
-            // That is why we have to create a pre-flight header here.
-            // If there is another zome function call that also calls commit before this commit
-            // is done, we might create two pre-flight chain headers linking to the same
-            // previous header. Since these pre-flight headers are not written to the chain
-            // and just used for the validation, I don't see why it would be a problem.
-            // If it was a problem, we would have to make sure that the whole commit process
-            // (including validtion) is atomic.
-            agent::state::create_new_chain_header(&entry, context.clone(), &None),
-        );
+        let entry_header = match find_chain_header(&entry.clone(), &context) {
+            Some(entry_header) => entry_header,
+            None => {
+                // TODO: make sure that we don't run into race conditions with respect to the chain
+                // We need the source chain header as part of the validation package.
+                // For an already committed entry (when asked to deliver the validation package to
+                // a DHT node) we should have gotten one from chain_header() above.
+                // But when we commit an entry, there is no header for it in the chain yet.
+                // That is why we have to create a pre-flight header here.
+                // If there is another zome function call that also calls commit before this commit
+                // is done, we might create two pre-flight chain headers linking to the same
+                // previous header. Since these pre-flight headers are not written to the chain
+                // and just used for the validation, I don't see why it would be a problem.
+                // If it was a problem, we would have to make sure that the whole commit process
+                // (including validtion) is atomic.
+                match agent::state::create_new_chain_header(
+                    &entry,
+                    context.clone(),
+                    &None,
+                    &context.zome_call,
+                ) {
+                    Ok(entry_header) => entry_header,
+                    Err(error) => {
+                        return ValidationPackageFuture {
+                            context: context.clone(),
+                            key: id,
+                            error: Some(error),
+                        };
+                    }
+                }
+            }
+        };
 
         thread::spawn(move || {
+            let agent_id_headers = agent::agent_id_headers_before(&entry_header, &context);
+            let headers_since_agent_id = agent::headers_since_agent_id(&entry_header, &context);
+            let author_agent_id = agent_id_headers
+                .first()
+                .and_then(|agent_id_header| agent::chain_agent_id(agent_id_header, &context));
             let maybe_callback_result = get_validation_package_definition(&entry, context.clone());
             let maybe_validation_package = maybe_callback_result
                 .and_then(|callback_result| match callback_result {
@@ -135,6 +161,12 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
                             package
                         }
                     })
+                })
+                .map(|mut package| {
+                    package.agent_id_headers = agent_id_headers;
+                    package.headers_since_agent_id = headers_since_agent_id;
+                    package.author_agent_id = author_agent_id;
+                    package
                 });
 
             context
@@ -155,11 +187,17 @@ pub fn build_validation_package(entry: &Entry, context: &Arc<Context>) -> Valida
 }
 
 fn all_public_chain_entries(context: &Arc<Context>) -> Vec<Entry> {
+    let dna = context
+        .get_dna()
+        .expect("Validation package built without DNA set!");
     let chain = context.state().unwrap().agent().chain_store();
     let top_header = context.state().unwrap().agent().top_chain_header();
     chain
         .iter(&top_header)
-        .filter(|ref chain_header| chain_header.entry_type().can_publish())
+        .filter(|ref chain_header| {
+            let entry_type = chain_header.entry_type();
+            entry_type.can_publish(&dna) && !entry_type.is_encrypted(&dna)
+        })
         .map(|chain_header| {
             let storage = chain.content_storage().clone();
             let json = (*storage.read().unwrap())
@@ -181,7 +219,7 @@ fn all_chain_headers(context: &Arc<Context>) -> Vec<ChainHeader> {
 /// ValidationPackageFuture resolves to the ValidationPackage or a HolochainError.
 pub struct ValidationPackageFuture {
     context: Arc<Context>,
-    key: snowflake::ProcessUniqueId,
+    key: Uuid,
     error: Option<HolochainError>,
 }
 
@@ -216,6 +254,23 @@ mod tests {
 
     use holochain_core_types::validation::ValidationPackage;
 
+    /// The AgentId header committed during genesis precedes all entries of these tests
+    fn genesis_agent_id_headers(context: &Arc<Context>) -> Vec<ChainHeader> {
+        all_chain_headers(context)
+            .into_iter()
+            .filter(|chain_header| *chain_header.entry_type() == EntryType::AgentId)
+            .collect()
+    }
+
+    /// The headers of the entries these tests commit after genesis, except the newest one
+    fn headers_since_genesis_agent_id(context: &Arc<Context>) -> Vec<ChainHeader> {
+        all_chain_headers(context)
+            .into_iter()
+            .skip(1)
+            .take_while(|chain_header| *chain_header.entry_type() != EntryType::AgentId)
+            .collect()
+    }
+
     #[test]
     fn test_building_validation_package_entry() {
         let (_instance, context) = instance(None);
@@ -239,7 +294,12 @@ mod tests {
             source_chain_entries: None,
             source_chain_headers: None,
             custom: None,
+            agent_id_headers: genesis_agent_id_headers(&context),
+            headers_since_agent_id: headers_since_genesis_agent_id(&context),
+            author_agent_id: Some(context.agent_id.clone()),
         };
+        assert_eq!(expected.agent_id_headers.len(), 1);
+        assert_eq!(expected.headers_since_agent_id.len(), 2);
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
     }
@@ -266,6 +326,9 @@ mod tests {
             source_chain_entries: Some(all_public_chain_entries(&context)),
             source_chain_headers: None,
             custom: None,
+            agent_id_headers: genesis_agent_id_headers(&context),
+            headers_since_agent_id: headers_since_genesis_agent_id(&context),
+            author_agent_id: Some(context.agent_id.clone()),
         };
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
@@ -293,6 +356,9 @@ mod tests {
             source_chain_entries: None,
             source_chain_headers: Some(all_chain_headers(&context)),
             custom: None,
+            agent_id_headers: genesis_agent_id_headers(&context),
+            headers_since_agent_id: headers_since_genesis_agent_id(&context),
+            author_agent_id: Some(context.agent_id.clone()),
         };
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
@@ -320,6 +386,9 @@ mod tests {
             source_chain_entries: Some(all_public_chain_entries(&context)),
             source_chain_headers: Some(all_chain_headers(&context)),
             custom: None,
+            agent_id_headers: genesis_agent_id_headers(&context),
+            headers_since_agent_id: headers_since_genesis_agent_id(&context),
+            author_agent_id: Some(context.agent_id.clone()),
         };
 
         assert_eq!(maybe_validation_package.unwrap(), expected);
@@ -2,9 +2,9 @@ extern crate serde_json;
 use crate::context::Context;
 use holochain_core_types::{
     cas::{content::Address, storage::ContentAddressableStorage},
-    crud_status::{CrudStatus, LINK_NAME, STATUS_NAME},
+    crud_status::{CrudStatus, LINK_NAME, RESOLVED_LINK_NAME, STATUS_NAME},
     eav::{EntityAttributeValueIndex, IndexQuery},
-    entry::{Entry, EntryWithMeta},
+    entry::{encrypted_entry::EncryptedEntryWithMeta, Entry, EntryWithMeta},
     error::HolochainError,
 };
 
@@ -47,10 +47,26 @@ pub(crate) fn get_entry_from_dht(
     get_entry_from_cas(&cas.clone(), address)
 }
 
+/// Looks up an entry that was committed inside the bundle of the zome call the context
+/// is scoped to.
+pub(crate) fn get_entry_from_bundle(
+    context: &Arc<Context>,
+    address: &Address,
+) -> Result<Option<Entry>, HolochainError> {
+    match context.state().unwrap().agent().bundle(&context.zome_call) {
+        Some(bundle) => bundle.get_entry(address),
+        None => Ok(None),
+    }
+}
+
 pub(crate) fn get_entry_crud_meta_from_dht(
     context: &Arc<Context>,
     address: Address,
 ) -> Result<Option<(CrudStatus, Option<Address>)>, HolochainError> {
+    // Entries are rejected as long as we hold them with rejected headers only
+    if context.state().unwrap().dht().is_rejected(&address)? {
+        return Ok(Some((CrudStatus::Rejected, None)));
+    }
     let dht = context.state().unwrap().dht().meta_storage();
     let storage = &dht.clone();
     // Get crud-status
@@ -90,24 +106,120 @@ pub(crate) fn get_entry_crud_meta_from_dht(
             crud_status = CrudStatus::Modified;
         }
     }
-    // Get crud-link
-    let mut maybe_crud_link = None;
-    let link_eavs = (*storage.read().unwrap()).fetch_eavi(
-        Some(address),
+    // Get crud-link.
+    // Concurrent updates leave several crud-links, in which case the entry is Locked
+    // until the conflict resolution recorded which one of them won.
+    let crud_links = get_entry_crud_links_from_dht(context, &address)?;
+    let maybe_crud_link = match crud_links.len() {
+        0 => None,
+        1 => crud_links.into_iter().next(),
+        _ => {
+            let latest_link_index = (*storage.read().unwrap())
+                .fetch_eavi(
+                    Some(address.clone()),
+                    Some(LINK_NAME.to_string()),
+                    None,
+                    IndexQuery::default(),
+                )?
+                .iter()
+                .map(|e| e.index())
+                .max();
+            let resolved_link_eavs = (*storage.read().unwrap()).fetch_eavi(
+                Some(address),
+                Some(RESOLVED_LINK_NAME.to_string()),
+                None,
+                IndexQuery::default(),
+            )?;
+            match resolved_link_eavs.iter().max_by_key(|e| e.index()) {
+                Some(resolved) if Some(resolved.index()) > latest_link_index => {
+                    Some(resolved.value())
+                }
+                _ => {
+                    if crud_status == CrudStatus::Modified {
+                        crud_status = CrudStatus::Locked;
+                    }
+                    None
+                }
+            }
+        }
+    };
+    // Done
+    Ok(Some((crud_status, maybe_crud_link)))
+}
+
+/// All updates of the entry with the given address recorded in the local DHT shard,
+/// ordered by address. More than one means that the entry got updated concurrently.
+pub(crate) fn get_entry_crud_links_from_dht(
+    context: &Arc<Context>,
+    address: &Address,
+) -> Result<Vec<Address>, HolochainError> {
+    let dht = context.state().unwrap().dht().meta_storage();
+    let link_eavs = (*dht.read().unwrap()).fetch_eavi(
+        Some(address.clone()),
         Some(LINK_NAME.to_string()),
         None,
         IndexQuery::default(),
     )?;
-    assert!(
-        link_eavs.len() <= 1,
-        "link_eavs.len() = {}",
-        link_eavs.len()
-    );
-    if link_eavs.len() == 1 {
-        maybe_crud_link = Some(link_eavs.iter().next().unwrap().value());
+    Ok(link_eavs
+        .into_iter()
+        .map(|e| e.value())
+        .collect::<BTreeSet<Address>>()
+        .into_iter()
+        .collect())
+}
+
+/// The competing updates of an entry that got updated concurrently, empty if there are none
+fn get_entry_crud_fork_from_dht(
+    context: &Arc<Context>,
+    address: &Address,
+) -> Result<Vec<Address>, HolochainError> {
+    let crud_links = get_entry_crud_links_from_dht(context, address)?;
+    if crud_links.len() > 1 {
+        Ok(crud_links)
+    } else {
+        Ok(Vec::new())
     }
-    // Done
-    Ok(Some((crud_status, maybe_crud_link)))
+}
+
+/// Looks up the cipher text held in the local DHT shard for an entry of a type with
+/// encrypted sharing, together with the entry's metadata.
+pub(crate) fn get_encrypted_entry_with_meta(
+    context: &Arc<Context>,
+    address: Address,
+) -> Result<Option<EncryptedEntryWithMeta>, HolochainError> {
+    let encrypted_entry = match context
+        .state()
+        .unwrap()
+        .dht()
+        .get_encrypted_entry(&address)?
+    {
+        Some(encrypted_entry) => encrypted_entry,
+        None => return Ok(None),
+    };
+    let (crud_status, maybe_crud_link) =
+        get_entry_crud_meta_from_dht(context, address.clone())?.unwrap_or((CrudStatus::Live, None));
+    Ok(Some(EncryptedEntryWithMeta {
+        encrypted_entry,
+        crud_status,
+        maybe_crud_link,
+        crud_fork: get_entry_crud_fork_from_dht(context, &address)?,
+    }))
+}
+
+/// Decrypts the entry with the agent's keys, None if the agent is not one of its recipients.
+pub(crate) fn decrypt_entry_with_meta(
+    context: &Arc<Context>,
+    encrypted_entry_with_meta: &EncryptedEntryWithMeta,
+) -> Result<Option<EntryWithMeta>, HolochainError> {
+    Ok(context
+        .decrypt_entry(&encrypted_entry_with_meta.encrypted_entry)?
+        .map(|entry| EntryWithMeta {
+            entry,
+            crud_status: encrypted_entry_with_meta.crud_status.clone(),
+            maybe_crud_link: encrypted_entry_with_meta.maybe_crud_link.clone(),
+            crud_fork: encrypted_entry_with_meta.crud_fork.clone(),
+            warrant: None,
+        }))
 }
 
 /// FetchEntry Action Creator
@@ -120,21 +232,53 @@ pub fn get_entry_with_meta<'a>(
     // 1. try to get the entry
     let entry = match get_entry_from_dht(context, &address) {
         Err(err) => return Err(err),
-        Ok(None) => return Ok(None),
+        Ok(None) => {
+            // Entries of types with encrypted sharing are only held as cipher text,
+            // which is readable for their recipients
+            if let Some(encrypted_entry_with_meta) =
+                get_encrypted_entry_with_meta(context, address.clone())?
+            {
+                return decrypt_entry_with_meta(context, &encrypted_entry_with_meta);
+            }
+            // Entries staged in an open bundle are visible to the bundle's author only
+            return Ok(
+                get_entry_from_bundle(context, &address)?.map(|entry| EntryWithMeta {
+                    entry,
+                    crud_status: CrudStatus::Live,
+                    maybe_crud_link: None,
+                    crud_fork: Vec::new(),
+                    warrant: None,
+                }),
+            );
+        }
         Ok(Some(entry)) => entry,
     };
     // 2. try to get the entry's metadata
-    let maybe_meta = get_entry_crud_meta_from_dht(context, address);
+    let maybe_meta = get_entry_crud_meta_from_dht(context, address.clone());
     if let Err(err) = maybe_meta {
         return Err(err);
     }
-    let (crud_status, maybe_crud_link) = maybe_meta
-        .unwrap()
-        .expect("Entry should have crud-status metadata");
+    // Entries without metadata never got held in the DHT shard, like private entries
+    // that are only in the agent's own source chain, which share the CAS with the DHT.
+    let (crud_status, maybe_crud_link) = maybe_meta.unwrap().unwrap_or((CrudStatus::Live, None));
+    // Rejected entries get handed out with a warrant, so nodes asking for them can check it
+    let warrant = if crud_status == CrudStatus::Rejected {
+        context
+            .state()
+            .unwrap()
+            .dht()
+            .get_entry_warrants(&address)?
+            .into_iter()
+            .next()
+    } else {
+        None
+    };
     let item = EntryWithMeta {
         entry,
         crud_status,
         maybe_crud_link,
+        crud_fork: get_entry_crud_fork_from_dht(context, &address)?,
+        warrant,
     };
     Ok(Some(item))
 }
@@ -142,7 +286,16 @@ pub fn get_entry_with_meta<'a>(
 #[cfg(test)]
 pub mod tests {
     use crate::instance::tests::test_context_with_state;
-    use holochain_core_types::{cas::content::AddressableContent, entry::test_entry};
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        chain_header::{test_chain_header, test_chain_header_with_sig},
+        crud_status::{
+            create_crud_link_eav, create_crud_resolved_link_eav, create_crud_status_eav, CrudStatus,
+        },
+        entry::{test_entry, test_entry_a, test_entry_b},
+        signature::Signature,
+        warrant::Warrant,
+    };
 
     #[test]
     fn get_entry_from_dht_cas() {
@@ -155,4 +308,78 @@ pub mod tests {
         let result = super::get_entry_from_dht(&context, &entry.address());
         assert_eq!(Ok(Some(entry.clone())), result);
     }
+
+    #[test]
+    fn concurrent_updates_lock_entry_until_resolved() {
+        let context = test_context_with_state(None);
+        let address = test_entry().address();
+        let storage = context.state().unwrap().dht().meta_storage();
+        for eav in vec![
+            create_crud_status_eav(&address, CrudStatus::Live).unwrap(),
+            create_crud_status_eav(&address, CrudStatus::Modified).unwrap(),
+            create_crud_link_eav(&address, &test_entry_a().address()).unwrap(),
+            create_crud_link_eav(&address, &test_entry_b().address()).unwrap(),
+        ] {
+            (*storage.write().unwrap()).add_eavi(&eav).unwrap();
+        }
+
+        let mut fork = vec![test_entry_a().address(), test_entry_b().address()];
+        fork.sort();
+        assert_eq!(
+            super::get_entry_crud_links_from_dht(&context, &address),
+            Ok(fork)
+        );
+        assert_eq!(
+            super::get_entry_crud_meta_from_dht(&context, address.clone()),
+            Ok(Some((CrudStatus::Locked, None)))
+        );
+
+        let resolved_link_eav =
+            create_crud_resolved_link_eav(&address, &test_entry_b().address()).unwrap();
+        (*storage.write().unwrap())
+            .add_eavi(&resolved_link_eav)
+            .unwrap();
+        assert_eq!(
+            super::get_entry_crud_meta_from_dht(&context, address),
+            Ok(Some((CrudStatus::Modified, Some(test_entry_b().address()))))
+        );
+    }
+
+    #[test]
+    fn rejected_entries_come_with_their_warrant() {
+        let context = test_context_with_state(None);
+        let entry = test_entry();
+        let warrant = Warrant::new(
+            entry.clone(),
+            test_chain_header(),
+            "FAIL".to_string(),
+            Address::from("issuer"),
+            Signature::from("sig"),
+        );
+        context
+            .state()
+            .unwrap()
+            .dht()
+            .add_warrant(&warrant)
+            .unwrap();
+
+        let entry_with_meta = super::get_entry_with_meta(&context, entry.address())
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry_with_meta.crud_status, CrudStatus::Rejected);
+        assert_eq!(entry_with_meta.warrant, Some(warrant));
+
+        // only the header got rejected, the entry is valid with another one
+        context
+            .state()
+            .unwrap()
+            .dht()
+            .add_header_for_entry(&entry, &test_chain_header_with_sig("valid"))
+            .unwrap();
+        let entry_with_meta = super::get_entry_with_meta(&context, entry.address())
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry_with_meta.crud_status, CrudStatus::Live);
+        assert_eq!(entry_with_meta.warrant, None);
+    }
 }
@@ -5,6 +5,7 @@ use crate::{
     context::Context,
     instance::dispatch_action_and_wait,
     nucleus::{
+        actions::{build_validation_package::build_validation_package, validate::validate_entry},
         ribosome::callback::{genesis::genesis, CallbackParams, CallbackResult},
         state::NucleusStatus,
     },
@@ -13,7 +14,12 @@ use futures::{
     future::Future,
     task::{LocalWaker, Poll},
 };
-use holochain_core_types::{dna::Dna, entry::Entry, error::HolochainError};
+use holochain_core_types::{
+    dna::Dna,
+    entry::Entry,
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
 use std::{pin::Pin, sync::Arc, time::*};
 
 /// Timeout in seconds for initialization process.
@@ -26,6 +32,8 @@ const INITIALIZATION_TIMEOUT: u64 = 60;
 ///
 /// Returns a future that resolves to an Ok(NucleusStatus) or an Err(String) which carries either
 /// the Dna error or errors from the genesis callback.
+/// The entry schemas of the DNA get compiled and cached in the context before anything else,
+/// so an invalid schema fails the initialization with a `DnaError::InvalidEntrySchema`.
 ///
 /// Use futures::executor::block_on to wait for an initialized instance.
 pub async fn initialize_application(
@@ -38,6 +46,9 @@ pub async fn initialize_application(
         ));
     }
 
+    // A DNA with entry schemas that don't compile can't be run
+    context.load_entry_schemas(&dna)?;
+
     let action_wrapper = ActionWrapper::new(Action::InitApplication(dna.clone()));
     dispatch_action_and_wait(context.clone(), action_wrapper.clone());
 
@@ -60,8 +71,21 @@ pub async fn initialize_application(
         return Err(HolochainError::new("error committing DNA"));
     }
 
+    // Check the agent (and its membrane proof) against the DNA's membrane rules
+    let agent_id_entry = match await!(validated_agent_id_entry(&context_clone)) {
+        Ok(agent_id_entry) => agent_id_entry,
+        Err(error) => {
+            context_clone
+                .action_channel()
+                .send(ActionWrapper::new(Action::ReturnInitializationResult(
+                    Some(error.to_string()),
+                )))
+                .expect("Action channel not usable in initialize_application()");
+            return Err(error);
+        }
+    };
+
     // Commit AgentId to chain
-    let agent_id_entry = Entry::AgentId(context_clone.agent_id.clone());
     let agent_id_commit = await!(commit_entry(agent_id_entry, None, &context_clone));
 
     // Let initialization fail if AgentId could not be committed.
@@ -112,6 +136,32 @@ pub async fn initialize_application(
     })
 }
 
+/// Creates the AgentId entry of the initializing agent and validates it like any other entry
+/// that gets committed, which runs the `validate_agent` callbacks of the DNA.
+/// A membrane proof gets signed together with the agent's key first,
+/// since the address of the entry only covers the key.
+async fn validated_agent_id_entry<'a>(context: &'a Arc<Context>) -> Result<Entry, HolochainError> {
+    let mut agent_id = context.agent_id.clone();
+    if let Some(payload) = agent_id.membrane_proof_payload() {
+        agent_id.membrane_proof_signature = Some(context.sign(payload)?);
+    }
+    let agent_id_entry = Entry::AgentId(agent_id);
+    let validation_package = await!(build_validation_package(&agent_id_entry, context))?;
+    let validation_data = ValidationData {
+        package: validation_package,
+        lifecycle: EntryLifecycle::Chain,
+        action: EntryAction::Create,
+        old_entry: None,
+        old_entry_header: None,
+    };
+    await!(validate_entry(
+        agent_id_entry.clone(),
+        validation_data,
+        context
+    ))?;
+    Ok(agent_id_entry)
+}
+
 /// InitializationFuture resolves to an Ok(NucleusStatus) or an Err(String).
 /// Tracks the nucleus status.
 pub struct InitializationFuture {
@@ -16,20 +16,23 @@ use holochain_core_types::{
     hash::HashString,
     validation::ValidationData,
 };
-use snowflake;
 use std::{pin::Pin, sync::Arc, thread};
+use uuid::Uuid;
 
 /// ValidateEntry Action Creator
 /// This is the high-level validate function that wraps the whole validation process and is what should
 /// be called from zome api functions and other contexts that don't care about implementation details.
 ///
-/// Returns a future that resolves to an Ok(ActionWrapper) or an Err(error_message:String).
+/// Returns a future that resolves to an Ok(ActionWrapper) or an Err(HolochainError).
+/// Only a definitive failure of the entry resolves to HolochainError::ValidationFailed,
+/// errors that kept the entry from being validated (like the base of a link not being
+/// found yet) are passed on as they are, so that DHT nodes can tell them apart.
 pub fn validate_entry<'a>(
     entry: Entry,
     validation_data: ValidationData,
     context: &'a Arc<Context>,
 ) -> FutureObj<'a, Result<HashString, HolochainError>> {
-    let id = snowflake::ProcessUniqueId::new();
+    let id = Uuid::new_v4();
     let address = entry.address();
 
     match entry.entry_type() {
@@ -52,8 +55,8 @@ pub fn validate_entry<'a>(
             }
         }
 
-        EntryType::LinkAdd => {
-            // LinkAdd can always be validated
+        EntryType::LinkAdd | EntryType::LinkRemove => {
+            // LinkAdd and LinkRemove can always be validated
         }
 
         EntryType::Deletion => {
@@ -67,6 +70,10 @@ pub fn validate_entry<'a>(
         EntryType::AgentId => {
             // FIXME
         }
+
+        EntryType::ChainMigrate => {
+            // ChainMigrate entries can always be validated
+        }
         _ => {
             return FutureObj::new(Box::new(future::err(HolochainError::ValidationFailed(
                 format!(
@@ -91,16 +98,20 @@ pub fn validate_entry<'a>(
 
             let result = match maybe_validation_result {
                 Ok(validation_result) => match validation_result {
-                    CallbackResult::Fail(error_string) => Err(error_string),
+                    CallbackResult::Fail(error_string) => {
+                        Err(HolochainError::ValidationFailed(error_string))
+                    }
                     CallbackResult::Pass => Ok(()),
-                    CallbackResult::NotImplemented(reason) => Err(format!(
-                        "Validation callback not implemented for {:?} ({})",
-                        entry.entry_type().clone(),
-                        reason
-                    )),
+                    CallbackResult::NotImplemented(reason) => {
+                        Err(HolochainError::ValidationFailed(format!(
+                            "Validation callback not implemented for {:?} ({})",
+                            entry.entry_type().clone(),
+                            reason
+                        )))
+                    }
                     _ => unreachable!(),
                 },
-                Err(error) => Err(error.to_string()),
+                Err(error) => Err(error),
             };
 
             context
@@ -119,11 +130,11 @@ pub fn validate_entry<'a>(
     }))
 }
 
-/// ValidationFuture resolves to an Ok(ActionWrapper) or an Err(error_message:String).
+/// ValidationFuture resolves to an Ok(ActionWrapper) or an Err(HolochainError).
 /// Tracks the state for ValidationResults.
 pub struct ValidationFuture {
     context: Arc<Context>,
-    key: (snowflake::ProcessUniqueId, HashString),
+    key: (Uuid, HashString),
 }
 
 impl Future for ValidationFuture {
@@ -138,7 +149,7 @@ impl Future for ValidationFuture {
         if let Some(state) = self.context.state() {
             match state.nucleus().validation_results.get(&self.key) {
                 Some(Ok(())) => Poll::Ready(Ok(self.key.1.clone())),
-                Some(Err(e)) => Poll::Ready(Err(HolochainError::ValidationFailed(e.clone()))),
+                Some(Err(e)) => Poll::Ready(Err(e.clone())),
                 None => Poll::Pending,
             }
         } else {
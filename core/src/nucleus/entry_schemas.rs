@@ -0,0 +1,182 @@
+//! The JSON Schemas the app entry types of a DNA declare, compiled once when the DNA gets
+//! loaded instead of for every entry that gets validated against them.
+use holochain_core_types::{
+    dna::Dna,
+    entry::{
+        entry_type::{AppEntryType, EntryType},
+        Entry,
+    },
+    error::{DnaError, HolochainError},
+};
+use serde_json::Value;
+use std::{collections::HashMap, fmt};
+use url::Url;
+use valico::json_schema;
+
+pub struct EntrySchemas {
+    scope: json_schema::Scope,
+    /// ids of the compiled schemas in the scope, by the name of their entry type
+    schema_ids: HashMap<String, Url>,
+}
+
+impl fmt::Debug for EntrySchemas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("EntrySchemas")
+            .field("schema_ids", &self.schema_ids)
+            .finish()
+    }
+}
+
+impl EntrySchemas {
+    /// Compiles the schemas of all entry types of the DNA that declare one.
+    /// A schema that does not compile is an error of the DNA, not of the entries of its type.
+    pub fn compile(dna: &Dna) -> Result<EntrySchemas, HolochainError> {
+        let mut scope = json_schema::Scope::new();
+        let mut schema_ids = HashMap::new();
+        for zome in dna.zomes.values() {
+            for (entry_type, entry_type_def) in zome.entry_types.iter() {
+                let (app_entry_type, schema) = match (entry_type, &entry_type_def.schema) {
+                    (EntryType::App(app_entry_type), Some(schema)) => (app_entry_type, schema),
+                    _ => continue,
+                };
+                let entry_type_name = String::from(app_entry_type.to_owned());
+                let schema_id = scope.compile(schema.clone(), false).map_err(|error| {
+                    HolochainError::Dna(DnaError::InvalidEntrySchema(format!(
+                        "Schema of entry type {} is invalid: {:?}",
+                        entry_type_name, error
+                    )))
+                })?;
+                schema_ids.insert(entry_type_name, schema_id);
+            }
+        }
+        Ok(EntrySchemas { scope, schema_ids })
+    }
+
+    /// Checks the value of an app entry against the schema of its type,
+    /// entries of types without a schema always match.
+    /// The fail string names every part of the entry that does not match the schema.
+    pub fn validate(&self, entry: &Entry, app_entry_type: &AppEntryType) -> Result<(), String> {
+        let entry_type_name = String::from(app_entry_type.to_owned());
+        let schema = match self
+            .schema_ids
+            .get(&entry_type_name)
+            .and_then(|schema_id| self.scope.resolve(schema_id))
+        {
+            Some(schema) => schema,
+            None => return Ok(()),
+        };
+        let value: Value = match entry {
+            Entry::App(_, app_entry_value) => {
+                serde_json::from_str(&String::from(app_entry_value.to_owned())).map_err(|_| {
+                    format!(
+                        "Entry of type {} is not a JSON value and can't match its schema",
+                        entry_type_name
+                    )
+                })?
+            }
+            _ => return Ok(()),
+        };
+
+        let state = schema.validate(&value);
+        if state.is_valid() {
+            return Ok(());
+        }
+
+        let errors: Vec<String> = state
+            .errors
+            .iter()
+            .map(|error| {
+                let path = match error.get_path() {
+                    "" => "/",
+                    path => path,
+                };
+                match error.get_detail() {
+                    Some(detail) => format!("{}: {} ({})", path, error.get_title(), detail),
+                    None => format!("{}: {}", path, error.get_title()),
+                }
+            })
+            .collect();
+        Err(format!(
+            "Entry of type {} does not match its schema: {}",
+            entry_type_name,
+            errors.join("; ")
+        ))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use holochain_core_types::{
+        dna::{entry_types::EntryTypeDef, zome::Zome},
+        json::JsonString,
+    };
+
+    fn post_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "content": { "type": "string", "maxLength": 10 }
+            },
+            "required": ["content"]
+        })
+    }
+
+    fn dna_with_post_schema(schema: Value) -> Dna {
+        let mut entry_type_def = EntryTypeDef::new();
+        entry_type_def.schema = Some(schema);
+        let mut zome = Zome::default();
+        zome.entry_types
+            .insert(EntryType::App(AppEntryType::from("post")), entry_type_def);
+        let mut dna = Dna::new();
+        dna.zomes.insert("blog".to_string(), zome);
+        dna
+    }
+
+    fn post(value: &str) -> Entry {
+        Entry::App(AppEntryType::from("post"), JsonString::from(value))
+    }
+
+    #[test]
+    fn entry_matching_schema_passes() {
+        let schemas = EntrySchemas::compile(&dna_with_post_schema(post_schema())).unwrap();
+        assert_eq!(
+            schemas.validate(&post(r#"{"content":"hello"}"#), &AppEntryType::from("post")),
+            Ok(())
+        );
+        // entry types without a schema
+        assert_eq!(
+            schemas.validate(&post("anything"), &AppEntryType::from("comment")),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn entry_not_matching_schema_fails() {
+        let schemas = EntrySchemas::compile(&dna_with_post_schema(post_schema())).unwrap();
+        let fail_string = schemas
+            .validate(
+                &post(r#"{"content":"hello world!"}"#),
+                &AppEntryType::from("post"),
+            )
+            .unwrap_err();
+        assert!(fail_string.starts_with("Entry of type post does not match its schema: "));
+        assert!(fail_string.contains("/content"));
+
+        let fail_string = schemas
+            .validate(&post(r#"{"title":"hello"}"#), &AppEntryType::from("post"))
+            .unwrap_err();
+        assert!(fail_string.starts_with("Entry of type post does not match its schema: "));
+        assert!(fail_string.contains("content"));
+    }
+
+    #[test]
+    fn invalid_schema_fails_to_compile() {
+        match EntrySchemas::compile(&dna_with_post_schema(json!({ "type": 5 }))) {
+            Err(HolochainError::Dna(DnaError::InvalidEntrySchema(error))) => {
+                assert!(error.starts_with("Schema of entry type post is invalid"))
+            }
+            other => panic!("Expected invalid schema error, got {:?}", other),
+        }
+    }
+}
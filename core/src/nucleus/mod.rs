@@ -1,6 +1,7 @@
 /// Nucleus is the module that handles DNA, including the Ribosome.
 ///
 pub mod actions;
+pub mod entry_schemas;
 pub mod ribosome;
 pub mod state;
 
@@ -23,7 +24,6 @@ use holochain_core_types::{
     error::{HcResult, HolochainError},
     json::JsonString,
 };
-use snowflake;
 use std::{
     sync::{
         mpsc::{channel, SyncSender},
@@ -32,11 +32,12 @@ use std::{
     thread,
     time::Duration,
 };
+use uuid::Uuid;
 
 /// Struct holding data for requesting the execution of a Zome function (ExecutionZomeFunction Action)
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct ZomeFnCall {
-    id: snowflake::ProcessUniqueId,
+    id: Uuid,
     pub zome_name: String,
     pub cap: Option<CapabilityCall>,
     pub fn_name: String,
@@ -53,7 +54,7 @@ impl ZomeFnCall {
         ZomeFnCall {
             // @TODO can we defer to the ActionWrapper id?
             // @see https://github.com/holochain/holochain-rust/issues/198
-            id: snowflake::ProcessUniqueId::new(),
+            id: Uuid::new_v4(),
             zome_name: zome.to_string(),
             cap: cap,
             fn_name: function.to_string(),
@@ -61,9 +62,12 @@ impl ZomeFnCall {
         }
     }
 
+    /// Whether both calls go to the same function with the same capability token.
+    /// Callers and signatures are not compared as they differ for every call.
     pub fn same_fn_as(&self, fn_call: &ZomeFnCall) -> bool {
         self.zome_name == fn_call.zome_name
-            && self.cap == fn_call.cap
+            && self.cap.as_ref().map(|cap| &cap.cap_token)
+                == fn_call.cap.as_ref().map(|cap| &cap.cap_token)
             && self.fn_name == fn_call.fn_name
     }
 
@@ -379,7 +383,10 @@ pub mod tests {
         },
         nucleus::state::tests::test_nucleus_state,
     };
-    use holochain_core_types::dna::{capabilities::CapabilityCall, Dna};
+    use holochain_core_types::dna::{
+        capabilities::{CallSignature, CapabilityCall},
+        Dna,
+    };
     use std::sync::{mpsc::sync_channel, Arc};
 
     use holochain_core_types::{
@@ -404,7 +411,7 @@ pub mod tests {
 
     /// dummy capability call
     pub fn test_capability_call() -> CapabilityCall {
-        CapabilityCall::new(test_capability_token(), None)
+        CapabilityCall::new(test_capability_token(), None, CallSignature::default())
     }
 
     /// dummy capability name compatible with ZomeFnCall
@@ -438,7 +445,7 @@ pub mod tests {
     }
 
     #[test]
-    /// test the equality and uniqueness of function calls (based on internal snowflakes)
+    /// test the equality and uniqueness of function calls (based on internal UUIDs)
     fn test_zome_call_eq() {
         let zc1 = test_zome_call();
         let zc2 = test_zome_call();
@@ -686,6 +693,14 @@ pub mod tests {
         assert!(base.same_fn_as(&same));
         assert!(!base.same_fn_as(&diff1));
         assert!(!base.same_fn_as(&diff2));
+
+        let mut signed = base.clone();
+        signed.cap = Some(CapabilityCall::new(
+            test_capability_token(),
+            Some(Address::from("caller")),
+            CallSignature::default(),
+        ));
+        assert!(base.same_fn_as(&signed));
     }
 
     #[test]
@@ -712,7 +727,11 @@ pub mod tests {
 
         let call = ZomeFnCall::new(
             test_zome_name,
-            Some(CapabilityCall::new(test_capability_token(), None)),
+            Some(CapabilityCall::new(
+                test_capability_token(),
+                None,
+                CallSignature::default(),
+            )),
             "non_pub_fn",
             test_parameters(),
         );
@@ -1,7 +1,9 @@
 use crate::{
     action::{Action, ActionWrapper},
+    agent::verify_signature,
     context::Context,
     nucleus::{
+        actions::get_entry::get_entry_crud_meta_from_dht,
         is_fn_public, launch_zome_fn_call,
         ribosome::{api::ZomeApiResult, Runtime},
         state::NucleusState,
@@ -9,15 +11,20 @@ use crate::{
     },
 };
 use holochain_core_types::{
-    dna::{capabilities::CapabilityCall, Dna},
+    cas::content::{Address, AddressableContent},
+    crud_status::CrudStatus,
+    dna::{
+        capabilities::{CallSignature, CapabilityCall, CapabilityType},
+        Dna,
+    },
     entry::cap_entries::CapTokenGrant,
     error::HolochainError,
     json::JsonString,
 };
 use holochain_wasm_utils::api_serialization::{ZomeFnCallArgs, THIS_INSTANCE};
 use jsonrpc_lite::JsonRpc;
-use snowflake::ProcessUniqueId;
 use std::{convert::TryFrom, sync::Arc, time::Duration};
+use uuid::Uuid;
 use wasmi::{RuntimeArgs, RuntimeValue};
 
 // ZomeFnCallArgs to ZomeFnCall
@@ -68,7 +75,16 @@ pub fn invoke_call(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
 
 fn local_call(runtime: &mut Runtime, input: ZomeFnCallArgs) -> Result<JsonString, HolochainError> {
     // ZomeFnCallArgs to ZomeFnCall
-    let zome_call = ZomeFnCall::from_args(input);
+    let mut zome_call = ZomeFnCall::from_args(input);
+    // The call is made by this agent, so sign it with the agent's key
+    if let Some(cap) = zome_call.cap.take() {
+        zome_call.cap = Some(sign_capability_call(
+            runtime.context.clone(),
+            cap.cap_token,
+            &zome_call.fn_name,
+            &zome_call.parameters,
+        )?);
+    }
     // Create Call Action
     let action_wrapper = ActionWrapper::new(Action::Call(zome_call.clone()));
 
@@ -107,7 +123,7 @@ fn bridge_call(runtime: &mut Runtime, input: ZomeFnCallArgs) -> Result<JsonStrin
 
     let handler = conductor_api.write().unwrap();
 
-    let id = ProcessUniqueId::new();
+    let id = Uuid::new_v4();
     let request = format!(
         r#"{{"jsonrpc": "2.0", "method": "{}", "params": {}, "id": "{}"}}"#,
         method, input.fn_args, id
@@ -147,7 +163,7 @@ pub fn validate_call(
         .map_err(|e| HolochainError::Dna(e))?;
 
     let public = is_fn_public(&dna, &fn_call)?;
-    if !public && !check_capability(context.clone(), &fn_call.clone()) {
+    if !public && !check_capability(context.clone(), &dna, &fn_call.clone()) {
         return Err(HolochainError::CapabilityCheckFailed);
     }
     Ok(dna)
@@ -186,32 +202,116 @@ pub(crate) fn reduce_call(
     launch_zome_fn_call(context, fn_call, &code, state.dna.clone().unwrap().name);
 }
 
-// TODO: check the signature too
-fn is_token_the_agent(context: Arc<Context>, cap: &Option<CapabilityCall>) -> bool {
-    match cap {
+/// Builds a capability call for calling `fn_name` with `parameters` using `token`,
+/// with this agent's address as the caller and signed with the agent's current key.
+/// Grants get assigned to agent addresses, which do not change when the key gets rotated.
+pub(crate) fn sign_capability_call(
+    context: Arc<Context>,
+    token: Address,
+    fn_name: &str,
+    parameters: &JsonString,
+) -> Result<CapabilityCall, HolochainError> {
+    let caller = context.agent_id.address();
+    let signature = context.sign(CallSignature::payload(fn_name, parameters, &token))?;
+    Ok(CapabilityCall::new(
+        token,
+        Some(caller),
+        CallSignature::from(signature),
+    ))
+}
+
+/// Returns the key the agent with the given address currently signs with.
+/// That is the current keypair's for this agent. For other agents it is the newest
+/// AgentId entry the local DHT shard knows of that updates the agent's initial AgentId,
+/// following the updates of each key rotation.
+fn current_agent_key(context: &Arc<Context>, agent_address: &Address) -> Address {
+    if *agent_address == context.agent_id.address() {
+        if let Ok(key) = context.agent_signing_key() {
+            return key;
+        }
+    }
+    let mut keys = vec![agent_address.clone()];
+    while let Ok(Some((CrudStatus::Modified, Some(newer_key)))) =
+        get_entry_crud_meta_from_dht(context, keys.last().unwrap().clone())
+    {
+        if keys.contains(&newer_key) {
+            break;
+        }
+        keys.push(newer_key);
+    }
+    keys.pop().unwrap()
+}
+
+/// checks that the call's signature was made by its caller over the called function,
+/// the call parameters and the capability token, with the caller's current key.
+fn is_call_signed_by_caller(
+    context: &Arc<Context>,
+    fn_call: &ZomeFnCall,
+    call: &CapabilityCall,
+) -> bool {
+    match call.caller {
         None => false,
-        Some(call) => context.agent_id.key == call.cap_token.to_string(),
+        Some(ref caller) => verify_signature(
+            &current_agent_key(context, caller),
+            call.signature.signature(),
+            CallSignature::payload(&fn_call.fn_name, &fn_call.parameters, &call.cap_token),
+        )
+        .unwrap_or(false),
     }
 }
 
+/// checks if the call uses the agent's key as token and was made by the agent itself,
+/// signed with its current key.
+fn is_token_the_agent(context: Arc<Context>, fn_call: &ZomeFnCall) -> bool {
+    match fn_call.cap {
+        None => false,
+        Some(ref call) => {
+            context.agent_id.key == call.cap_token.to_string()
+                && call.caller == Some(context.agent_id.address())
+                && is_call_signed_by_caller(&context, fn_call, call)
+        }
+    }
+}
+
+/// checks if the capability the grant was made for includes the called function
+fn grant_covers_fn(dna: &Dna, grant: &CapTokenGrant, fn_call: &ZomeFnCall) -> bool {
+    dna.zomes
+        .get(&fn_call.zome_name)
+        .and_then(|zome| zome.capabilities.get(&grant.cap_name()))
+        .map(|capability| capability.functions.contains(&fn_call.fn_name))
+        .unwrap_or(false)
+}
+
 /// checks to see if a given function call is allowable according to the capabilities
 /// that have been registered to callers in the chain.
-fn check_capability(context: Arc<Context>, fn_call: &ZomeFnCall) -> bool {
+/// Only grants that were not revoked count and they need to be for a capability that
+/// includes the called function.
+/// Apart from public grants, the call also needs to be signed by the caller the grant
+/// gets verified for.
+fn check_capability(context: Arc<Context>, dna: &Dna, fn_call: &ZomeFnCall) -> bool {
     // the agent can always do everything
-    if is_token_the_agent(context.clone(), &fn_call.cap) {
+    if is_token_the_agent(context.clone(), fn_call) {
         return true;
     }
 
     match fn_call.cap.clone() {
         None => false,
         Some(call) => {
-            let chain = &context.chain_storage;
-            let maybe_json = chain.read().unwrap().fetch(&call.cap_token).unwrap();
-            let grant = match maybe_json {
-                Some(content) => CapTokenGrant::try_from(content).unwrap(),
+            let maybe_grant = context.state().and_then(|state| {
+                state
+                    .agent()
+                    .get_active_cap_grants()
+                    .into_iter()
+                    .find(|grant| grant.token() == call.cap_token)
+            });
+            let grant = match maybe_grant {
+                Some(grant) => grant,
                 None => return false,
             };
-            grant.verify(call.cap_token.clone(), call.caller, &call.signature)
+            grant_covers_fn(dna, &grant, fn_call)
+                && grant.verify(call.cap_token.clone(), call.caller.clone())
+                && (grant.cap_type() == CapabilityType::Public
+                    || is_call_signed_by_caller(&context, fn_call, &call))
         }
     }
 }
@@ -229,6 +329,7 @@ pub mod tests {
             ribosome::{
                 api::{
                     call::{Action, ActionWrapper, ZomeFnCall},
+                    revoke_capability::revoke_grant,
                     tests::{
                         test_function_name, test_parameters, test_zome_api_function_wasm,
                         test_zome_name,
@@ -244,7 +345,7 @@ pub mod tests {
     use holochain_core_types::{
         cas::content::Address,
         dna::{
-            capabilities::{Capability, CapabilityCall, CapabilityType},
+            capabilities::{CallSignature, Capability, CapabilityCall, CapabilityType},
             fn_declarations::FnDeclaration,
             Dna,
         },
@@ -252,6 +353,8 @@ pub mod tests {
         error::{DnaError, HolochainError},
         json::JsonString,
     };
+    use holochain_dpki::keypair::{Keypair, SEEDSIZE};
+    use holochain_sodium::{random::random_secbuf, secbuf::SecBuf};
     use holochain_wasm_utils::api_serialization::ZomeFnCallArgs;
 
     use serde_json;
@@ -272,7 +375,11 @@ pub mod tests {
         let args = ZomeFnCallArgs {
             instance_handle: "instance_handle".to_string(),
             zome_name: "zome_name".to_string(),
-            cap: Some(CapabilityCall::new(Address::from("bad cap_token"), None)),
+            cap: Some(CapabilityCall::new(
+                Address::from("bad cap_token"),
+                None,
+                CallSignature::default(),
+            )),
             fn_name: "fn_name".to_string(),
             fn_args: "fn_args".to_string(),
         };
@@ -309,19 +416,33 @@ pub mod tests {
         }
     }
 
+    /// a call of the test function that is not signed at all
+    fn unsigned_cap_call(token_str: &str, caller: Address) -> CapabilityCall {
+        CapabilityCall::new(
+            Address::from(token_str),
+            Some(caller),
+            CallSignature::default(),
+        )
+    }
+
+    /// a call of the test function made and signed by the test setup's agent
+    fn agent_signed_cap_call(test_setup: &TestSetup, token_str: &str) -> CapabilityCall {
+        sign_capability_call(
+            test_setup.context.clone(),
+            Address::from(token_str),
+            "test",
+            &JsonString::from("{}"),
+        )
+        .expect("Could not sign call")
+    }
+
     #[cfg_attr(tarpaulin, skip)]
     fn test_reduce_call(
         test_setup: &TestSetup,
-        token_str: &str,
-        _caller: Address,
+        cap_call: CapabilityCall,
         expected: Result<Result<JsonString, HolochainError>, RecvTimeoutError>,
     ) {
-        let zome_call = ZomeFnCall::new(
-            "test_zome",
-            Some(CapabilityCall::new(Address::from(token_str), None)),
-            "test",
-            "{}",
-        );
+        let zome_call = ZomeFnCall::new("test_zome", Some(cap_call), "test", "{}");
         let zome_call_action = ActionWrapper::new(Action::Call(zome_call.clone()));
 
         let (_, rx_observer) = sync_channel(1);
@@ -359,7 +480,11 @@ pub mod tests {
         let expected = Ok(Err(HolochainError::Dna(DnaError::ZomeNotFound(
             r#"Zome 'test_zome' not found"#.to_string(),
         ))));
-        test_reduce_call(&test_setup, "foo token", Address::from("caller"), expected);
+        test_reduce_call(
+            &test_setup,
+            unsigned_cap_call("foo token", Address::from("caller")),
+            expected,
+        );
     }
 
     fn setup_dna_for_cap_test(cap_type: CapabilityType) -> Dna {
@@ -387,7 +512,11 @@ pub mod tests {
         let expected = Ok(Err(HolochainError::RibosomeFailed(
             "Argument deserialization failed".to_string(),
         )));
-        test_reduce_call(&test_setup, "", Address::from("caller"), expected);
+        test_reduce_call(
+            &test_setup,
+            unsigned_cap_call("", Address::from("caller")),
+            expected,
+        );
     }
 
     #[test]
@@ -395,7 +524,11 @@ pub mod tests {
         let dna = setup_dna_for_cap_test(CapabilityType::Transferable);
         let test_setup = setup_test(dna);
         let expected_failure = Ok(Err(HolochainError::CapabilityCheckFailed));
-        test_reduce_call(&test_setup, "", Address::from("caller"), expected_failure);
+        test_reduce_call(
+            &test_setup,
+            unsigned_cap_call("", Address::from("caller")),
+            expected_failure.clone(),
+        );
 
         // Expecting error since there is no function in wasm to call
         let expected = Ok(Err(HolochainError::RibosomeFailed(
@@ -404,12 +537,19 @@ pub mod tests {
         let agent_token_str = test_setup.context.agent_id.key.clone();
         test_reduce_call(
             &test_setup,
-            &agent_token_str,
-            Address::from(agent_token_str.clone()),
+            agent_signed_cap_call(&test_setup, &agent_token_str),
             expected.clone(),
         );
+        // knowing the agent's key is not enough to act as the agent
+        test_reduce_call(
+            &test_setup,
+            unsigned_cap_call(&agent_token_str, Address::from(agent_token_str.clone())),
+            expected_failure.clone(),
+        );
 
-        let grant = CapTokenGrant::create(CapabilityType::Transferable, None).unwrap();
+        let grant =
+            CapTokenGrant::create(&test_capability_name(), CapabilityType::Transferable, None)
+                .unwrap();
         let grant_entry = Entry::CapTokenGrant(grant);
         let addr = test_setup
             .context
@@ -417,10 +557,43 @@ pub mod tests {
             .unwrap();
         test_reduce_call(
             &test_setup,
-            &String::from(addr),
-            Address::from("any caller"),
+            agent_signed_cap_call(&test_setup, &String::from(addr.clone())),
             expected,
         );
+        // a transferable grant can be used by anybody, but calls still need to be signed
+        test_reduce_call(
+            &test_setup,
+            unsigned_cap_call(&String::from(addr.clone()), Address::from("any caller")),
+            expected_failure.clone(),
+        );
+
+        // grants only give access to the functions of their capability
+        let other_grant =
+            CapTokenGrant::create("other_cap", CapabilityType::Transferable, None).unwrap();
+        let other_addr = test_setup
+            .context
+            .block_on(author_entry(
+                &Entry::CapTokenGrant(other_grant),
+                None,
+                &test_setup.context,
+            ))
+            .unwrap();
+        test_reduce_call(
+            &test_setup,
+            agent_signed_cap_call(&test_setup, &String::from(other_addr)),
+            expected_failure.clone(),
+        );
+
+        // revoked grants can't be used anymore
+        test_setup
+            .context
+            .block_on(revoke_grant(&test_setup.context, addr.clone()))
+            .unwrap();
+        test_reduce_call(
+            &test_setup,
+            agent_signed_cap_call(&test_setup, &String::from(addr)),
+            expected_failure,
+        );
     }
 
     #[cfg(feature = "broken-tests")] // blocking bug in call function
@@ -431,8 +604,7 @@ pub mod tests {
         let expected_failure = Ok(Err(HolochainError::CapabilityCheckFailed));
         test_reduce_call(
             &test_setup,
-            "",
-            Address::from("any caller"),
+            unsigned_cap_call("", Address::from("any caller")),
             expected_failure.clone(),
         );
 
@@ -443,14 +615,17 @@ pub mod tests {
         let agent_token_str = test_setup.context.agent_id.key.clone();
         test_reduce_call(
             &test_setup,
-            &agent_token_str,
-            Address::from(agent_token_str.clone()),
+            agent_signed_cap_call(&test_setup, &agent_token_str),
             expected.clone(),
         );
 
-        let someone = Address::from("somoeone");
-        let grant =
-            CapTokenGrant::create(CapabilityType::Assigned, Some(vec![someone.clone()])).unwrap();
+        let someone = test_setup.context.agent_id.address();
+        let grant = CapTokenGrant::create(
+            &test_capability_name(),
+            CapabilityType::Assigned,
+            Some(vec![someone.clone()]),
+        )
+        .unwrap();
         let grant_entry = Entry::CapTokenGrant(grant);
         let addr = test_setup
             .context
@@ -458,15 +633,16 @@ pub mod tests {
             .unwrap();
         test_reduce_call(
             &test_setup,
-            &String::from(addr.clone()),
-            someone,
+            agent_signed_cap_call(&test_setup, &String::from(addr.clone())),
             expected.clone(),
         );
 
-        /* function call doesn't know who the caller is yet so can't do the check in reduce
-                let someone_else = Address::from("somoeone_else");
-                test_reduce_call(&test_setup,&String::from(addr),someone_else, expected_failure.clone());
-        */
+        let someone_else = Address::from("somoeone_else");
+        test_reduce_call(
+            &test_setup,
+            unsigned_cap_call(&String::from(addr), someone_else),
+            expected_failure.clone(),
+        );
     }
 
     #[test]
@@ -475,9 +651,32 @@ pub mod tests {
         let test_setup = setup_test(dna);
         let agent_token = Address::from(test_setup.context.agent_id.key.clone());
         let context = test_setup.context.clone();
-        let cap_call = CapabilityCall::new(agent_token, None);
-        assert!(is_token_the_agent(context.clone(), &Some(cap_call)));
-        let cap_call = CapabilityCall::new(Address::from(""), None);
-        assert!(!is_token_the_agent(context, &Some(cap_call)));
+        let zome_call = |cap_call| ZomeFnCall::new("test_zome", Some(cap_call), "test", "{}");
+
+        let cap_call = agent_signed_cap_call(&test_setup, &String::from(agent_token.clone()));
+        assert!(is_token_the_agent(context.clone(), &zome_call(cap_call)));
+
+        let cap_call = unsigned_cap_call(&String::from(agent_token.clone()), agent_token);
+        assert!(!is_token_the_agent(context.clone(), &zome_call(cap_call)));
+
+        let cap_call = agent_signed_cap_call(&test_setup, "");
+        assert!(!is_token_the_agent(context.clone(), &zome_call(cap_call)));
+
+        // the signature only holds for the function and parameters it was made for
+        let cap_call = agent_signed_cap_call(&test_setup, &test_setup.context.agent_id.key);
+        let other_call = ZomeFnCall::new("test_zome", Some(cap_call), "test", "{\"foo\":1}");
+        assert!(!is_token_the_agent(context.clone(), &other_call));
+
+        // after a key rotation the agent still calls as its address, but signs with the new key
+        let old_key_call = agent_signed_cap_call(&test_setup, &String::from(agent_token.clone()));
+        let mut seed = SecBuf::with_secure(SEEDSIZE);
+        random_secbuf(&mut seed);
+        context
+            .set_agent_keypair(Keypair::new_from_seed(&mut seed).unwrap())
+            .unwrap();
+        let cap_call = agent_signed_cap_call(&test_setup, &String::from(agent_token));
+        assert_eq!(cap_call.caller, Some(context.agent_id.address()));
+        assert!(is_token_the_agent(context.clone(), &zome_call(cap_call)));
+        assert!(!is_token_the_agent(context, &zome_call(old_key_call)));
     }
 }
@@ -0,0 +1,69 @@
+use crate::{
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    workflows::close_bundle::close_bundle_workflow,
+};
+use holochain_core_types::error::HolochainError;
+use holochain_wasm_utils::api_serialization::bundle::CloseBundleArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::CloseBundle function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: CloseBundleArgs
+pub fn invoke_close_bundle(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let input = match CloseBundleArgs::try_from(args_str.clone()) {
+        Ok(input) => input,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_close_bundle failed to deserialize CloseBundleArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    // Wait for future to be resolved
+    let result: Result<(), HolochainError> = runtime
+        .context
+        .block_on(close_bundle_workflow(input.action, &runtime.context))
+        .map(|_| ());
+
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::nucleus::ribosome::{
+        api::{tests::test_zome_api_function, ZomeApiFunction},
+        Defn,
+    };
+    use holochain_core_types::{error::ZomeApiInternalResult, json::JsonString};
+    use holochain_wasm_utils::api_serialization::bundle::{BundleOnClose, CloseBundleArgs};
+    use std::convert::TryFrom;
+
+    /// dummy close_bundle args
+    pub fn test_close_bundle_args_bytes() -> Vec<u8> {
+        let args = CloseBundleArgs {
+            action: BundleOnClose::Commit,
+        };
+        JsonString::from(args).into_bytes()
+    }
+
+    #[test]
+    /// test that closing a bundle fails if no bundle was started
+    fn test_close_bundle_without_open_bundle() {
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::CloseBundle.as_str(),
+            test_close_bundle_args_bytes(),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(!result.ok);
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    workflows::author_entry::author_encrypted_entry,
+};
+use holochain_core_types::{cas::content::Address, error::HolochainError};
+use holochain_wasm_utils::api_serialization::CommitEncryptedEntryArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::CommitEncryptedEntry function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: CommitEncryptedEntryArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_commit_encrypted_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let commit_args = match CommitEncryptedEntryArgs::try_from(args_str.clone()) {
+        Ok(commit_args) => commit_args,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_commit_encrypted_entry failed to deserialize CommitEncryptedEntryArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+    // Wait for future to be resolved
+    let task_result: Result<Address, HolochainError> = runtime.context.block_on(
+        author_encrypted_entry(&commit_args.entry, commit_args.recipients, &runtime.context),
+    );
+
+    runtime.store_result(task_result)
+}
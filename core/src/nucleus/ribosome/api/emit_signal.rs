@@ -0,0 +1,121 @@
+use crate::{
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    signal::Signal,
+};
+use holochain_core_types::json::JsonString;
+use holochain_wasm_utils::api_serialization::emit_signal::EmitSignalArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::EmitSignal function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: EmitSignalArgs
+/// Returns an HcApiReturnCode as I64
+/// The signal gets tagged with the instance's id and passed on to the conductor,
+/// which pushes it to the UIs connected to the instance.
+/// Instances that run without a signal channel drop it.
+pub fn invoke_emit_signal(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let emit_args = match EmitSignalArgs::try_from(args_str.clone()) {
+        Ok(emit_args) => emit_args,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_emit_signal failed to deserialize EmitSignalArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    if let Some(ref tx) = runtime.context.signal_tx {
+        let signal = Signal::User {
+            instance_id: runtime.context.instance_id.clone().unwrap_or_default(),
+            name: emit_args.name,
+            payload: JsonString::from(emit_args.payload),
+        };
+        tx.send(signal).unwrap_or(());
+    }
+
+    ribosome_success!()
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+
+    use crate::{
+        instance::tests::test_instance_and_context,
+        nucleus::{
+            ribosome::{
+                api::{
+                    tests::{
+                        test_zome_api_function_call, test_zome_api_function_wasm, test_zome_name,
+                    },
+                    ZomeApiFunction,
+                },
+                Defn,
+            },
+            tests::test_capability_name,
+        },
+        signal::{signal_channel, Signal},
+    };
+    use holochain_core_types::json::JsonString;
+    use holochain_wasm_utils::api_serialization::emit_signal::EmitSignalArgs;
+    use std::{sync::Arc, time::Duration};
+
+    /// dummy emit_signal args
+    pub fn test_emit_signal_args_bytes() -> Vec<u8> {
+        JsonString::from(EmitSignalArgs {
+            name: "new_message".to_string(),
+            payload: "{\"text\":\"hello\"}".to_string(),
+        })
+        .into_bytes()
+    }
+
+    #[test]
+    /// test that a zome can emit a user signal tagged with the instance id
+    fn test_emit_signal() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::EmitSignal.as_str());
+        let dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability_name(),
+            wasm.clone(),
+        );
+        let dna_name = dna.name.to_string();
+        let (instance, context) =
+            test_instance_and_context(dna, None).expect("Could not create test instance");
+        let (signal_tx, signal_rx) = signal_channel();
+        let mut context = (*context).clone();
+        context.signal_tx = Some(signal_tx);
+        context.instance_id = Some("test-instance".to_string());
+
+        test_zome_api_function_call(
+            &dna_name,
+            Arc::new(context),
+            &instance,
+            &wasm,
+            test_emit_signal_args_bytes(),
+        );
+
+        loop {
+            match signal_rx
+                .recv_timeout(Duration::from_millis(1000))
+                .expect("zome should have emitted a signal")
+            {
+                Signal::User {
+                    instance_id,
+                    name,
+                    payload,
+                } => {
+                    assert_eq!(instance_id, "test-instance");
+                    assert_eq!(name, "new_message");
+                    assert_eq!(payload, JsonString::from("{\"text\":\"hello\"}"));
+                    break;
+                }
+                _ => continue,
+            }
+        }
+    }
+}
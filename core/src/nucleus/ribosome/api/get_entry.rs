@@ -255,6 +255,8 @@ pub mod tests {
             entry: entry.clone(),
             crud_status: CrudStatus::Live,
             maybe_crud_link: None,
+            crud_fork: Vec::new(),
+            warrant: None,
         };
         // let header = create_new_chain_header(&entry, context.clone(), &None);
         let entry_result =
@@ -2,9 +2,7 @@ use crate::{
     network::actions::get_links::get_links,
     nucleus::ribosome::{api::ZomeApiResult, Runtime},
 };
-use holochain_wasm_utils::api_serialization::get_links::{
-    GetLinksArgs, GetLinksResult, LinksStatusRequestKind,
-};
+use holochain_wasm_utils::api_serialization::get_links::{GetLinksArgs, GetLinksResult};
 use std::convert::TryFrom;
 use wasmi::{RuntimeArgs, RuntimeValue};
 
@@ -26,13 +24,6 @@ pub fn invoke_get_links(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiRes
         }
     };
 
-    if input.options.status_request != LinksStatusRequestKind::Live {
-        runtime
-            .context
-            .log("get links status request other than Live not implemented!");
-        return ribosome_error_code!(Unspecified);
-    }
-
     if input.options.sources {
         runtime
             .context
@@ -45,6 +36,7 @@ pub fn invoke_get_links(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiRes
         runtime.context.clone(),
         input.entry_address,
         input.tag,
+        input.options.status_request,
         input.options.timeout,
     ));
 
@@ -0,0 +1,138 @@
+use crate::{
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    workflows::author_entry::author_entry,
+};
+use holochain_core_types::{
+    cas::content::Address,
+    entry::{cap_entries::CapTokenGrant, Entry},
+    error::HolochainError,
+};
+use holochain_wasm_utils::api_serialization::capabilities::GrantCapabilityArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::GrantCapability function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: GrantCapabilityArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_grant_capability(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let grant_args = match GrantCapabilityArgs::try_from(args_str.clone()) {
+        Ok(grant_input) => grant_input,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_grant_capability failed to deserialize GrantCapabilityArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = grant_capability(runtime, grant_args);
+    runtime.store_result(result)
+}
+
+/// Commits a grant for a capability of the calling zome to the agent's source chain.
+/// Grants are private entries, so they never get published.
+/// The address of the grant entry is the token callers have to present.
+fn grant_capability(
+    runtime: &mut Runtime,
+    grant_args: GrantCapabilityArgs,
+) -> Result<Address, HolochainError> {
+    let zome_name = runtime.zome_call.zome_name.clone();
+    let dna = runtime
+        .context
+        .get_dna()
+        .ok_or(HolochainError::DnaMissing)?;
+    let zome = dna.get_zome(&zome_name).map_err(HolochainError::Dna)?;
+    if !zome.capabilities.contains_key(&grant_args.cap_name) {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Capability '{}' not found in Zome '{}'",
+            grant_args.cap_name, zome_name
+        )));
+    }
+
+    let grant = CapTokenGrant::create(
+        &grant_args.cap_name,
+        grant_args.cap_type,
+        grant_args.assignees,
+    )?;
+    runtime.context.block_on(author_entry(
+        &Entry::CapTokenGrant(grant),
+        None,
+        &runtime.context,
+    ))
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::nucleus::ribosome::{
+        api::{tests::test_zome_api_function, ZomeApiFunction},
+        Defn,
+    };
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        dna::capabilities::CapabilityType,
+        error::ZomeApiInternalResult,
+        json::JsonString,
+    };
+    use holochain_wasm_utils::api_serialization::capabilities::GrantCapabilityArgs;
+    use std::convert::TryFrom;
+
+    /// dummy grant capability args
+    pub fn test_grant_capability_args_bytes(cap_name: &str) -> Vec<u8> {
+        let args = GrantCapabilityArgs {
+            cap_name: cap_name.to_string(),
+            cap_type: CapabilityType::Assigned,
+            assignees: Some(vec![Address::from("some agent")]),
+        };
+        JsonString::from(args).into_bytes()
+    }
+
+    #[test]
+    /// test that a grant gets committed to the chain and its token is returned
+    fn test_grant_capability() {
+        let (call_result, context) = test_zome_api_function(
+            ZomeApiFunction::GrantCapability.as_str(),
+            test_grant_capability_args_bytes("test_cap"),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(result.ok);
+        let token = Address::try_from(JsonString::from(result.value)).unwrap();
+
+        let grants = context.state().unwrap().agent().get_active_cap_grants();
+        assert_eq!(grants.len(), 1);
+        assert_eq!(grants[0].token(), token);
+        assert_eq!(grants[0].cap_name(), "test_cap");
+        assert_eq!(grants[0].cap_type(), CapabilityType::Assigned);
+        assert_eq!(
+            grants[0].assignees(),
+            Some(vec![Address::from("some agent")])
+        );
+        assert_ne!(token, context.agent_id.address());
+    }
+
+    #[test]
+    /// test that only capabilities defined in the calling zome can be granted
+    fn test_grant_unknown_capability() {
+        let (call_result, context) = test_zome_api_function(
+            ZomeApiFunction::GrantCapability.as_str(),
+            test_grant_capability_args_bytes("unknown_cap"),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(!result.ok);
+        assert!(context
+            .state()
+            .unwrap()
+            .agent()
+            .get_active_cap_grants()
+            .is_empty());
+    }
+}
@@ -43,7 +43,8 @@ pub fn invoke_init_globals(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeAp
             if found_entries.len() > 0 {
                 globals.agent_latest_hash = found_entries[0].clone();
                 globals.agent_initial_hash = found_entries.pop().unwrap();
-                globals.agent_address = globals.agent_latest_hash.clone();
+                // The agent stays addressable by its initial key across key rotations
+                globals.agent_address = globals.agent_initial_hash.clone();
             }
         }
     };
@@ -54,11 +55,14 @@ pub fn invoke_init_globals(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeAp
 
 #[cfg(test)]
 pub mod tests {
-    use crate::nucleus::ribosome::{
-        api::{tests::test_zome_api_function, ZomeApiFunction},
-        Defn,
+    use crate::{
+        instance::tests::test_agent_and_keypair,
+        nucleus::ribosome::{
+            api::{tests::test_zome_api_function, ZomeApiFunction},
+            Defn,
+        },
     };
-    use holochain_core_types::{agent::AgentId, error::ZomeApiInternalResult, json::JsonString};
+    use holochain_core_types::{error::ZomeApiInternalResult, json::JsonString};
     use holochain_wasm_utils::api_serialization::ZomeApiGlobals;
     use std::convert::TryFrom;
 
@@ -73,7 +77,7 @@ pub mod tests {
             ZomeApiGlobals::try_from(JsonString::from(zome_api_internal_result.value)).unwrap();
 
         assert_eq!(globals.dna_name, "TestApp");
-        let expected_agent = AgentId::generate_fake("jane");
+        let (expected_agent, _) = test_agent_and_keypair("jane");
         assert_eq!(globals.agent_address.to_string(), expected_agent.key);
         // TODO (david.b) this should work:
         //assert_eq!(globals.agent_id_str, String::from(AgentId::generate_fake("jane")));
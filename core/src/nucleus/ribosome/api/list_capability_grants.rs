@@ -0,0 +1,49 @@
+use crate::nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use holochain_core_types::error::HolochainError;
+use holochain_wasm_utils::api_serialization::capabilities::CapabilityGrantsResult;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::ListCapabilityGrants function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Not expecting any complex input
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_list_capability_grants(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeApiResult {
+    let result = runtime
+        .context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Context without state".to_string()))
+        .map(|state| CapabilityGrantsResult {
+            grants: state.agent().get_active_cap_grants(),
+        });
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::nucleus::ribosome::{
+        api::{tests::test_zome_api_function, ZomeApiFunction},
+        Defn,
+    };
+    use holochain_core_types::{error::ZomeApiInternalResult, json::JsonString};
+    use holochain_wasm_utils::api_serialization::capabilities::CapabilityGrantsResult;
+    use std::convert::TryFrom;
+
+    #[test]
+    /// test that a fresh chain has no grants
+    fn test_list_capability_grants() {
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::ListCapabilityGrants.as_str(),
+            JsonString::empty_object().into_bytes(),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(result.ok);
+        assert_eq!(
+            CapabilityGrantsResult::try_from(JsonString::from(result.value)),
+            Ok(CapabilityGrantsResult { grants: Vec::new() }),
+        );
+    }
+}
@@ -2,26 +2,44 @@
 //! ZomeApiFunctions are the functions provided by the ribosome that are callable by Zomes.
 
 pub mod call;
+pub mod close_bundle;
 pub mod commit;
+pub mod commit_encrypted_entry;
 pub mod debug;
+pub mod emit_signal;
 pub mod entry_address;
 pub mod get_entry;
 pub mod get_links;
+pub mod grant_capability;
 pub mod init_globals;
 pub mod link_entries;
+pub mod list_capability_grants;
+pub mod property;
 pub mod query;
 pub mod remove_entry;
+pub mod remove_link;
+pub mod revoke_capability;
 pub mod send;
+pub mod sign;
 pub mod sleep;
+pub mod start_bundle;
+pub mod update_agent;
 pub mod update_entry;
+pub mod verify_signature;
 
 use crate::nucleus::ribosome::{
     api::{
-        call::invoke_call, commit::invoke_commit_app_entry, debug::invoke_debug,
-        entry_address::invoke_entry_address, get_entry::invoke_get_entry,
-        get_links::invoke_get_links, init_globals::invoke_init_globals,
-        link_entries::invoke_link_entries, query::invoke_query, remove_entry::invoke_remove_entry,
-        send::invoke_send, sleep::invoke_sleep, update_entry::invoke_update_entry,
+        call::invoke_call, close_bundle::invoke_close_bundle, commit::invoke_commit_app_entry,
+        commit_encrypted_entry::invoke_commit_encrypted_entry, debug::invoke_debug,
+        emit_signal::invoke_emit_signal, entry_address::invoke_entry_address,
+        get_entry::invoke_get_entry, get_links::invoke_get_links,
+        grant_capability::invoke_grant_capability, init_globals::invoke_init_globals,
+        link_entries::invoke_link_entries, list_capability_grants::invoke_list_capability_grants,
+        property::invoke_property, query::invoke_query, remove_entry::invoke_remove_entry,
+        remove_link::invoke_remove_link, revoke_capability::invoke_revoke_capability,
+        send::invoke_send, sign::invoke_sign, sleep::invoke_sleep,
+        start_bundle::invoke_start_bundle, update_agent::invoke_update_agent,
+        update_entry::invoke_update_entry, verify_signature::invoke_verify_signature,
     },
     runtime::Runtime,
     Defn,
@@ -90,6 +108,55 @@ pub enum ZomeApiFunction {
 
     Send,
     Sleep,
+
+    /// Sign a payload with the agent's private key
+    /// sign(payload: String) -> Signature
+    Sign,
+
+    /// Verify that a signature was made over a payload by the given public key
+    /// verify_signature(pub_key: String, signature: String, payload: String) -> bool
+    VerifySignature,
+
+    /// Get a property of the DNA by name or JSON pointer
+    /// property(key: String) -> JsonString
+    Property,
+
+    /// Remove a link between two entries by committing a LinkRemove entry
+    /// remove_link(base: Address, target: Address, tag: String)
+    RemoveLink,
+
+    /// Open a bundle so that all following commits get staged until it is closed
+    /// start_bundle(timeout: usize, user_param: Value)
+    StartBundle,
+
+    /// Close the open bundle, committing or discarding all staged commits
+    /// close_bundle(action: BundleOnClose)
+    CloseBundle,
+
+    /// Rotate the agent's key by committing a new AgentId entry
+    /// update_agent() -> Address
+    UpdateAgent,
+
+    /// Commit a capability grant for a capability of the calling zome
+    /// and return its token
+    /// grant_capability(cap_name, cap_type, assignees) -> Address
+    GrantCapability,
+
+    /// Revoke a capability grant by committing a deletion of it
+    /// revoke_capability(token) -> ()
+    RevokeCapability,
+
+    /// Get all capability grants of the agent that were not revoked
+    /// list_capability_grants() -> CapabilityGrantsResult
+    ListCapabilityGrants,
+
+    /// Commit an app entry of an encrypted type, sealed for the given recipients
+    /// commit_encrypted_entry(entry: Entry, recipients: Vec<Address>) -> Address
+    CommitEncryptedEntry,
+
+    /// Emit a signal to the UIs connected to the instance
+    /// emit_signal(name: String, payload: JsonString)
+    EmitSignal,
 }
 
 impl Defn for ZomeApiFunction {
@@ -110,6 +177,18 @@ impl Defn for ZomeApiFunction {
             ZomeApiFunction::EntryAddress => "hc_entry_address",
             ZomeApiFunction::Send => "hc_send",
             ZomeApiFunction::Sleep => "hc_sleep",
+            ZomeApiFunction::Sign => "hc_sign",
+            ZomeApiFunction::VerifySignature => "hc_verify_signature",
+            ZomeApiFunction::Property => "hc_property",
+            ZomeApiFunction::RemoveLink => "hc_remove_link",
+            ZomeApiFunction::StartBundle => "hc_start_bundle",
+            ZomeApiFunction::CloseBundle => "hc_close_bundle",
+            ZomeApiFunction::UpdateAgent => "hc_update_agent",
+            ZomeApiFunction::GrantCapability => "hc_grant_capability",
+            ZomeApiFunction::RevokeCapability => "hc_revoke_capability",
+            ZomeApiFunction::ListCapabilityGrants => "hc_list_capability_grants",
+            ZomeApiFunction::CommitEncryptedEntry => "hc_commit_encrypted_entry",
+            ZomeApiFunction::EmitSignal => "hc_emit_signal",
         }
     }
 
@@ -149,6 +228,18 @@ impl FromStr for ZomeApiFunction {
             "hc_entry_address" => Ok(ZomeApiFunction::EntryAddress),
             "hc_send" => Ok(ZomeApiFunction::Send),
             "hc_sleep" => Ok(ZomeApiFunction::Sleep),
+            "hc_sign" => Ok(ZomeApiFunction::Sign),
+            "hc_verify_signature" => Ok(ZomeApiFunction::VerifySignature),
+            "hc_property" => Ok(ZomeApiFunction::Property),
+            "hc_remove_link" => Ok(ZomeApiFunction::RemoveLink),
+            "hc_start_bundle" => Ok(ZomeApiFunction::StartBundle),
+            "hc_close_bundle" => Ok(ZomeApiFunction::CloseBundle),
+            "hc_update_agent" => Ok(ZomeApiFunction::UpdateAgent),
+            "hc_grant_capability" => Ok(ZomeApiFunction::GrantCapability),
+            "hc_revoke_capability" => Ok(ZomeApiFunction::RevokeCapability),
+            "hc_list_capability_grants" => Ok(ZomeApiFunction::ListCapabilityGrants),
+            "hc_commit_encrypted_entry" => Ok(ZomeApiFunction::CommitEncryptedEntry),
+            "hc_emit_signal" => Ok(ZomeApiFunction::EmitSignal),
             _ => Err("Cannot convert string to ZomeApiFunction"),
         }
     }
@@ -182,6 +273,18 @@ impl ZomeApiFunction {
             ZomeApiFunction::EntryAddress => invoke_entry_address,
             ZomeApiFunction::Send => invoke_send,
             ZomeApiFunction::Sleep => invoke_sleep,
+            ZomeApiFunction::Sign => invoke_sign,
+            ZomeApiFunction::VerifySignature => invoke_verify_signature,
+            ZomeApiFunction::Property => invoke_property,
+            ZomeApiFunction::RemoveLink => invoke_remove_link,
+            ZomeApiFunction::StartBundle => invoke_start_bundle,
+            ZomeApiFunction::CloseBundle => invoke_close_bundle,
+            ZomeApiFunction::UpdateAgent => invoke_update_agent,
+            ZomeApiFunction::GrantCapability => invoke_grant_capability,
+            ZomeApiFunction::RevokeCapability => invoke_revoke_capability,
+            ZomeApiFunction::ListCapabilityGrants => invoke_list_capability_grants,
+            ZomeApiFunction::CommitEncryptedEntry => invoke_commit_encrypted_entry,
+            ZomeApiFunction::EmitSignal => invoke_emit_signal,
         }
     }
 }
@@ -438,6 +541,24 @@ pub mod tests {
             ("hc_entry_address", ZomeApiFunction::EntryAddress),
             ("hc_send", ZomeApiFunction::Send),
             ("hc_sleep", ZomeApiFunction::Sleep),
+            ("hc_sign", ZomeApiFunction::Sign),
+            ("hc_verify_signature", ZomeApiFunction::VerifySignature),
+            ("hc_property", ZomeApiFunction::Property),
+            ("hc_remove_link", ZomeApiFunction::RemoveLink),
+            ("hc_start_bundle", ZomeApiFunction::StartBundle),
+            ("hc_close_bundle", ZomeApiFunction::CloseBundle),
+            ("hc_update_agent", ZomeApiFunction::UpdateAgent),
+            ("hc_grant_capability", ZomeApiFunction::GrantCapability),
+            ("hc_revoke_capability", ZomeApiFunction::RevokeCapability),
+            (
+                "hc_list_capability_grants",
+                ZomeApiFunction::ListCapabilityGrants,
+            ),
+            (
+                "hc_commit_encrypted_entry",
+                ZomeApiFunction::CommitEncryptedEntry,
+            ),
+            ("hc_emit_signal", ZomeApiFunction::EmitSignal),
         ] {
             assert_eq!(ZomeApiFunction::from_str(input).unwrap(), output);
         }
@@ -468,6 +589,24 @@ pub mod tests {
             (ZomeApiFunction::EntryAddress, "hc_entry_address"),
             (ZomeApiFunction::Send, "hc_send"),
             (ZomeApiFunction::Sleep, "hc_sleep"),
+            (ZomeApiFunction::Sign, "hc_sign"),
+            (ZomeApiFunction::VerifySignature, "hc_verify_signature"),
+            (ZomeApiFunction::Property, "hc_property"),
+            (ZomeApiFunction::RemoveLink, "hc_remove_link"),
+            (ZomeApiFunction::StartBundle, "hc_start_bundle"),
+            (ZomeApiFunction::CloseBundle, "hc_close_bundle"),
+            (ZomeApiFunction::UpdateAgent, "hc_update_agent"),
+            (ZomeApiFunction::GrantCapability, "hc_grant_capability"),
+            (ZomeApiFunction::RevokeCapability, "hc_revoke_capability"),
+            (
+                ZomeApiFunction::ListCapabilityGrants,
+                "hc_list_capability_grants",
+            ),
+            (
+                ZomeApiFunction::CommitEncryptedEntry,
+                "hc_commit_encrypted_entry",
+            ),
+            (ZomeApiFunction::EmitSignal, "hc_emit_signal"),
         ] {
             assert_eq!(output, input.as_str());
         }
@@ -489,6 +628,18 @@ pub mod tests {
             ("hc_entry_address", 12),
             ("hc_send", 13),
             ("hc_sleep", 14),
+            ("hc_sign", 15),
+            ("hc_verify_signature", 16),
+            ("hc_property", 17),
+            ("hc_remove_link", 18),
+            ("hc_start_bundle", 19),
+            ("hc_close_bundle", 20),
+            ("hc_update_agent", 21),
+            ("hc_grant_capability", 22),
+            ("hc_revoke_capability", 23),
+            ("hc_list_capability_grants", 24),
+            ("hc_commit_encrypted_entry", 25),
+            ("hc_emit_signal", 26),
         ] {
             assert_eq!(output, ZomeApiFunction::str_to_index(input));
         }
@@ -510,6 +661,18 @@ pub mod tests {
             (12, ZomeApiFunction::EntryAddress),
             (13, ZomeApiFunction::Send),
             (14, ZomeApiFunction::Sleep),
+            (15, ZomeApiFunction::Sign),
+            (16, ZomeApiFunction::VerifySignature),
+            (17, ZomeApiFunction::Property),
+            (18, ZomeApiFunction::RemoveLink),
+            (19, ZomeApiFunction::StartBundle),
+            (20, ZomeApiFunction::CloseBundle),
+            (21, ZomeApiFunction::UpdateAgent),
+            (22, ZomeApiFunction::GrantCapability),
+            (23, ZomeApiFunction::RevokeCapability),
+            (24, ZomeApiFunction::ListCapabilityGrants),
+            (25, ZomeApiFunction::CommitEncryptedEntry),
+            (26, ZomeApiFunction::EmitSignal),
         ] {
             assert_eq!(output, ZomeApiFunction::from_index(input));
         }
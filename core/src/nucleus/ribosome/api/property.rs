@@ -0,0 +1,81 @@
+use crate::nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use holochain_core_types::{error::HolochainError, json::JsonString};
+use holochain_wasm_utils::api_serialization::property::PropertyArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::Property function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: PropertyArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_property(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let property_args = match PropertyArgs::try_from(args_str.clone()) {
+        Ok(property_input) => property_input,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_property failed to deserialize PropertyArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let dna = runtime
+        .context
+        .state()
+        .unwrap()
+        .nucleus()
+        .dna()
+        .expect("Should have DNA");
+
+    let result = dna
+        .get_property(&property_args.key)
+        .map(|value| JsonString::from(value.clone()))
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "DNA property \"{}\" not found",
+                property_args.key
+            ))
+        });
+
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::nucleus::ribosome::{
+        api::{tests::test_zome_api_function, ZomeApiFunction},
+        Defn,
+    };
+    use holochain_core_types::{error::ZomeApiInternalResult, json::JsonString};
+    use holochain_wasm_utils::api_serialization::property::PropertyArgs;
+    use std::convert::TryFrom;
+
+    /// dummy property args
+    pub fn test_property_args_bytes(key: &str) -> Vec<u8> {
+        let args = PropertyArgs {
+            key: String::from(key),
+        };
+        JsonString::from(args).into_bytes()
+    }
+
+    #[test]
+    /// test that asking for a property the DNA does not define returns an error
+    fn test_property_not_found() {
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::Property.as_str(),
+            test_property_args_bytes("/missing/key"),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(!result.ok);
+        assert!(result.error.contains("/missing/key"));
+        assert!(result.error.contains("not found"));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     agent::actions::commit::commit_entry,
     dht::actions::remove_entry::remove_entry,
+    network::actions::publish::publish,
     nucleus::{
         actions::{build_validation_package::*, validate::*},
         ribosome::{api::ZomeApiResult, Runtime},
@@ -52,13 +53,66 @@ pub fn invoke_remove_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
     if !entry_result.found() {
         return ribosome_error_code!(Unspecified);
     }
-    let deleted_entry_address = entry_result.latest().unwrap().address();
+    let deleted_entry = entry_result.latest().unwrap();
+    let deleted_entry_address = deleted_entry.address();
+
+    // Header the deleted entry got committed with, preferably by ourselves.
+    // Entries committed in the open bundle only have a staged header.
+    let agent_address = runtime.context.agent_id.address();
+    let maybe_bundle = runtime
+        .context
+        .state()
+        .unwrap()
+        .agent()
+        .bundle(&runtime.context.zome_call);
+    let staged_header = maybe_bundle
+        .as_ref()
+        .and_then(|bundle| bundle.find_chain_header(&deleted_entry));
+    let deleted_entry_header = staged_header.or_else(|| {
+        runtime
+            .context
+            .state()
+            .unwrap()
+            .get_headers(deleted_entry_address.clone())
+            .ok()
+            .and_then(|headers| {
+                headers
+                    .iter()
+                    .find(|header| {
+                        header
+                            .provenances()
+                            .first()
+                            .map(|(addr, _)| addr == &agent_address)
+                            .unwrap_or(false)
+                    })
+                    .or_else(|| headers.first())
+                    .cloned()
+            })
+    });
+    let deleted_entry_header = match deleted_entry_header {
+        Some(header) => header,
+        None => {
+            runtime.context.log(format!(
+                "err/zome: invoke_remove_entry found no header of entry {}",
+                deleted_entry_address
+            ));
+            return ribosome_error_code!(Unspecified);
+        }
+    };
+
+    // Deletions of private entries only get recorded in the source chain,
+    // the DHT metadata would reveal them
+    let dna = runtime
+        .context
+        .get_dna()
+        .expect("Callback called without DNA set!");
+    let publish_deletion = deleted_entry.entry_type().can_publish(&dna);
 
     // Create deletion entry
     let deletion_entry = Entry::Deletion(DeletionEntry::new(deleted_entry_address.clone()));
 
     // Resolve future
-    let result: Result<(), HolochainError> = runtime.context.block_on(
+    let result: Result<Address, HolochainError> = runtime.context.block_on(
         // 1. Build the context needed for validation of the entry
         build_validation_package(&deletion_entry, &runtime.context)
             .and_then(|validation_package| {
@@ -66,30 +120,45 @@ pub fn invoke_remove_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
                     package: validation_package,
                     lifecycle: EntryLifecycle::Chain,
                     action: EntryAction::Delete,
+                    old_entry: Some(deleted_entry.clone()),
+                    old_entry_header: Some(deleted_entry_header.clone()),
                 }))
             })
             // 2. Validate the entry
             .and_then(|validation_data| {
                 validate_entry(deletion_entry.clone(), validation_data, &runtime.context)
             })
-            // 3. Commit the valid entry to chain and DHT
+            // 3. Commit the valid entry to chain and DHT.
+            // Its crud link names the deleted header, which the DHT validates the deletion with.
             .and_then(|_| {
                 commit_entry(
                     deletion_entry.clone(),
-                    Some(deleted_entry_address.clone()),
-                    &runtime.context,
-                )
-            })
-            // 4. Remove the entry in DHT metadata
-            .and_then(|_| {
-                remove_entry(
+                    Some(deleted_entry_header.address()),
                     &runtime.context,
-                    runtime.context.action_channel(),
-                    deleted_entry_address.clone(),
-                    deletion_entry.address().clone(),
                 )
             }),
     );
 
+    // 4. Remove the entry in DHT metadata
+    // and publish the deletion, so that the nodes holding the deleted entry can validate it.
+    // Inside a bundle this happens when the bundle gets committed, see close_bundle_workflow.
+    let result = match result {
+        Ok(_) if publish_deletion && maybe_bundle.is_none() => runtime
+            .context
+            .block_on(remove_entry(
+                &runtime.context,
+                runtime.context.action_channel(),
+                deleted_entry_address.clone(),
+                deletion_entry.address().clone(),
+            ))
+            .and_then(|_| {
+                runtime
+                    .context
+                    .block_on(publish(deletion_entry.address(), &runtime.context))
+            })
+            .map(|_| ()),
+        result => result.map(|_| ()),
+    };
+
     runtime.store_result(result)
 }
@@ -0,0 +1,103 @@
+use crate::{
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    workflows::author_entry::author_entry,
+};
+use holochain_core_types::{entry::Entry, error::HolochainError, link::link_remove::LinkRemove};
+use holochain_wasm_utils::api_serialization::link_entries::LinkEntriesArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::RemoveLink function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: LinkEntriesArgs
+pub fn invoke_remove_link(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let input = match LinkEntriesArgs::try_from(args_str.clone()) {
+        Ok(entry_input) => entry_input,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_remove_link failed to deserialize LinkEntriesArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let link = input.to_link();
+    let link_remove = LinkRemove::from_link(&link);
+    let entry = Entry::LinkRemove(link_remove);
+
+    // Wait for future to be resolved
+    let result: Result<(), HolochainError> = runtime
+        .context
+        .block_on(author_entry(&entry, None, &runtime.context))
+        .map(|_| ());
+
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::{
+        agent::actions::commit::commit_entry,
+        context::Context,
+        instance::{
+            tests::{test_context_and_logger, test_instance},
+            Instance,
+        },
+        nucleus::{
+            ribosome::{
+                api::{link_entries::tests::test_link_args_bytes, tests::*, ZomeApiFunction},
+                Defn,
+            },
+            tests::*,
+        },
+    };
+    use holochain_core_types::{entry::test_entry, error::ZomeApiInternalResult, json::JsonString};
+    use std::sync::Arc;
+
+    fn create_test_instance() -> (Instance, Arc<Context>) {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::RemoveLink.as_str());
+        let dna = test_utils::create_test_dna_with_wasm(
+            &test_zome_name(),
+            &test_capability_name(),
+            wasm.clone(),
+        );
+
+        let netname = Some("remove_link_create_test_instance");
+        let instance = test_instance(dna, netname).expect("Could not create test instance");
+
+        let (context, _) = test_context_and_logger("joan", netname);
+        let initialized_context = instance.initialize_context(context);
+        (instance, initialized_context)
+    }
+
+    #[test]
+    fn removes_link_if_base_is_present() {
+        let (instance, context) = create_test_instance();
+
+        context
+            .block_on(commit_entry(test_entry(), None, &context))
+            .expect("Could not commit entry for testing");
+
+        let call_result = test_zome_api_function_call(
+            &context.get_dna().unwrap().name.to_string(),
+            context.clone(),
+            &instance,
+            &context.get_wasm(&test_zome_name()).unwrap().code,
+            test_link_args_bytes(String::from("test-tag")),
+        );
+
+        assert_eq!(
+            call_result,
+            JsonString::from(
+                String::from(JsonString::from(ZomeApiInternalResult::success(None))) + "\u{0}"
+            ),
+        );
+    }
+}
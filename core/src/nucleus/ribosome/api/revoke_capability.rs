@@ -0,0 +1,163 @@
+use crate::{
+    agent::actions::commit::commit_entry,
+    context::Context,
+    nucleus::{
+        actions::{build_validation_package::build_validation_package, validate::validate_entry},
+        ribosome::{api::ZomeApiResult, Runtime},
+    },
+};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    entry::{deletion_entry::DeletionEntry, Entry},
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
+use std::{convert::TryFrom, sync::Arc};
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::RevokeCapability function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected Address argument (the token of the grant)
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_revoke_capability(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let token = match Address::try_from(args_str.clone()) {
+        Ok(token) => token,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_revoke_capability failed to deserialize Address: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = runtime
+        .context
+        .block_on(revoke_grant(&runtime.context, token))
+        .map(|_| ());
+    runtime.store_result(result)
+}
+
+/// Revokes the grant with the given token by committing a Deletion entry for it.
+/// Like the grant itself, the deletion stays on the agent's source chain and does not
+/// get published, so the token is never shared with the DHT
+/// (see `agent::can_publish_chain_entry`).
+/// Returns the address of the Deletion entry.
+pub(crate) async fn revoke_grant<'a>(
+    context: &'a Arc<Context>,
+    token: Address,
+) -> Result<Address, HolochainError> {
+    let agent_state = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Context without state".to_string()))?
+        .agent();
+    let grant_entry = agent_state
+        .get_active_cap_grants()
+        .into_iter()
+        .find(|grant| grant.token() == token)
+        .map(Entry::CapTokenGrant)
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!("No active capability grant with token {}", token))
+        })?;
+    let grant_header = agent_state
+        .get_most_recent_header_for_entry(&grant_entry)
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!("No header for capability grant {}", token))
+        })?;
+
+    let deletion_entry = Entry::Deletion(DeletionEntry::new(token.clone()));
+    let validation_package = await!(build_validation_package(&deletion_entry, context))?;
+    let validation_data = ValidationData {
+        package: validation_package,
+        lifecycle: EntryLifecycle::Chain,
+        action: EntryAction::Delete,
+        old_entry: Some(grant_entry),
+        old_entry_header: Some(grant_header.clone()),
+    };
+    await!(validate_entry(
+        deletion_entry.clone(),
+        validation_data,
+        context
+    ))?;
+    await!(commit_entry(
+        deletion_entry,
+        Some(grant_header.address()),
+        context
+    ))
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::{
+        agent::can_publish_chain_entry,
+        nucleus::{
+            actions::get_entry::get_entry_from_agent,
+            ribosome::{
+                api::{
+                    grant_capability::tests::test_grant_capability_args_bytes,
+                    tests::test_zome_api_function, ZomeApiFunction,
+                },
+                Defn,
+            },
+        },
+    };
+    use holochain_core_types::{
+        cas::content::Address, error::ZomeApiInternalResult, json::JsonString,
+    };
+    use std::convert::TryFrom;
+
+    #[test]
+    /// test that a revoked grant is not active anymore and can't be revoked twice
+    fn test_revoke_capability() {
+        let (call_result, context) = test_zome_api_function(
+            ZomeApiFunction::GrantCapability.as_str(),
+            test_grant_capability_args_bytes("test_cap"),
+        );
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        let token = Address::try_from(JsonString::from(result.value)).unwrap();
+        assert_eq!(
+            context
+                .state()
+                .unwrap()
+                .agent()
+                .get_active_cap_grants()
+                .len(),
+            1
+        );
+
+        let result = context.block_on(super::revoke_grant(&context, token.clone()));
+        assert!(result.is_ok());
+        // the revocation would reveal the token, so it must never be published
+        let deletion_entry = get_entry_from_agent(&context, &result.unwrap())
+            .unwrap()
+            .unwrap();
+        let dna = context.get_dna().unwrap();
+        assert!(!can_publish_chain_entry(&deletion_entry, &dna, &context));
+        assert!(context
+            .state()
+            .unwrap()
+            .agent()
+            .get_active_cap_grants()
+            .is_empty());
+
+        let result = context.block_on(super::revoke_grant(&context, token));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    /// test that revoking an unknown token fails
+    fn test_revoke_unknown_capability() {
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::RevokeCapability.as_str(),
+            JsonString::from(Address::from("unknown token")).into_bytes(),
+        );
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(!result.ok);
+    }
+}
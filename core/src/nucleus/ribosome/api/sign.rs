@@ -0,0 +1,86 @@
+use crate::nucleus::ribosome::{api::ZomeApiResult, Runtime};
+use holochain_core_types::signature::{domain_payload, SIGN_DOMAIN};
+use holochain_wasm_utils::api_serialization::sign::SignArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::Sign function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: SignArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_sign(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let sign_args = match SignArgs::try_from(args_str.clone()) {
+        Ok(sign_input) => sign_input,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_sign failed to deserialize SignArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = runtime
+        .context
+        .sign(domain_payload(SIGN_DOMAIN, &sign_args.payload))
+        .map(String::from);
+
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::{
+        agent::verify_signature,
+        nucleus::ribosome::{
+            api::{tests::test_zome_api_function, ZomeApiFunction},
+            Defn,
+        },
+    };
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        error::ZomeApiInternalResult,
+        json::JsonString,
+        signature::{domain_payload, Signature, SIGN_DOMAIN},
+    };
+    use holochain_wasm_utils::api_serialization::sign::SignArgs;
+    use std::convert::TryFrom;
+
+    /// dummy sign args
+    pub fn test_sign_args_bytes() -> Vec<u8> {
+        let args = SignArgs {
+            payload: String::from("test payload"),
+        };
+        JsonString::from(args).into_bytes()
+    }
+
+    #[test]
+    /// test that the signature returned from WASM was made by the agent of the instance
+    fn test_sign_round_trip() {
+        let (call_result, context) =
+            test_zome_api_function(ZomeApiFunction::Sign.as_str(), test_sign_args_bytes());
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(result.ok);
+        let signature = Signature::from(result.value);
+
+        assert!(verify_signature(
+            &context.agent_id.address(),
+            &signature,
+            domain_payload(SIGN_DOMAIN, "test payload")
+        )
+        .unwrap());
+        assert!(!verify_signature(
+            &context.agent_id.address(),
+            &signature,
+            domain_payload(SIGN_DOMAIN, "other payload")
+        )
+        .unwrap());
+    }
+}
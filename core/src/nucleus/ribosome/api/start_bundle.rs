@@ -0,0 +1,83 @@
+use crate::{
+    agent::actions::bundle::start_bundle,
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+};
+use holochain_core_types::error::HolochainError;
+use holochain_wasm_utils::api_serialization::bundle::StartBundleArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::StartBundle function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: StartBundleArgs
+pub fn invoke_start_bundle(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let input = match StartBundleArgs::try_from(args_str.clone()) {
+        Ok(input) => input,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_start_bundle failed to deserialize StartBundleArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    // Wait for future to be resolved
+    let result: Result<(), HolochainError> = runtime
+        .context
+        .block_on(start_bundle(
+            input.user_param,
+            input.timeout,
+            &runtime.context,
+        ))
+        .map(|_| ());
+
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::nucleus::ribosome::{
+        api::{tests::test_zome_api_function, ZomeApiFunction},
+        Defn,
+    };
+    use holochain_core_types::{error::ZomeApiInternalResult, json::JsonString, time::Timeout};
+    use holochain_wasm_utils::api_serialization::bundle::StartBundleArgs;
+    use std::convert::TryFrom;
+
+    /// dummy start_bundle args
+    pub fn test_start_bundle_args_bytes() -> Vec<u8> {
+        let args = StartBundleArgs {
+            timeout: Timeout::default(),
+            user_param: json!({"purpose": "testing"}),
+        };
+        JsonString::from(args).into_bytes()
+    }
+
+    #[test]
+    /// test that a bundle is open in the agent state after start_bundle was called
+    fn test_start_bundle_opens_bundle() {
+        let (call_result, context) = test_zome_api_function(
+            ZomeApiFunction::StartBundle.as_str(),
+            test_start_bundle_args_bytes(),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(result.ok);
+
+        let bundles = context.state().unwrap().agent().bundles();
+        assert_eq!(bundles.len(), 1);
+        let bundle = bundles
+            .values()
+            .next()
+            .expect("There should be an open bundle");
+        assert_eq!(bundle.user_param(), &json!({"purpose": "testing"}));
+        assert_eq!(bundle.staged_entries(), Ok(Vec::new()));
+    }
+}
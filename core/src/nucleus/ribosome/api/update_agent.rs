@@ -0,0 +1,201 @@
+use crate::{
+    agent::sign_with_keypair,
+    context::Context,
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+    workflows::author_entry::author_entry,
+};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    entry::{entry_type::EntryType, Entry},
+    error::HolochainError,
+};
+use holochain_dpki::keypair::{Keypair, SEEDSIZE};
+use holochain_sodium::{random::random_secbuf, secbuf::SecBuf};
+use std::sync::Arc;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::UpdateAgent function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Not expecting any complex input
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_update_agent(runtime: &mut Runtime, _args: &RuntimeArgs) -> ZomeApiResult {
+    let result = update_agent(runtime);
+    runtime.store_result(result)
+}
+
+/// Rotates the agent's key:
+/// generates a fresh keypair, persists it through the context's agent key store,
+/// commits its AgentId entry (signed with the old key) as an update of the previous
+/// AgentId entry and then switches the context over to the new keypair, so every
+/// subsequent header gets signed with the new key.
+/// The new key gets persisted first so that a restart never leaves the instance
+/// signing with the old key after the AgentId entry got committed.
+fn update_agent(runtime: &mut Runtime) -> Result<Address, HolochainError> {
+    let previous_agent_header_address = {
+        let agent_state = runtime.context.state().unwrap().agent();
+        if agent_state.bundle(&runtime.context.zome_call).is_some() {
+            return Err(HolochainError::ErrorGeneric(
+                "Can not update agent while a bundle is open".to_string(),
+            ));
+        }
+        agent_state
+            .chain_store()
+            .iter_type(&agent_state.top_chain_header(), &EntryType::AgentId)
+            .nth(0)
+            .map(|chain_header| chain_header.address())
+            .ok_or_else(|| HolochainError::ErrorGeneric("Agent entry not found".to_string()))?
+    };
+
+    let mut seed = SecBuf::with_secure(SEEDSIZE);
+    random_secbuf(&mut seed);
+    let mut new_keypair = Keypair::new_from_seed(&mut seed)?;
+    let mut new_agent_id = new_keypair.agent_id(&runtime.context.agent_id.nick)?;
+    // the rotated key still has to pass the DNA's membrane rules,
+    // with the proof signed by the new key
+    new_agent_id.membrane_proof = runtime.context.agent_id.membrane_proof.clone();
+    if let Some(payload) = new_agent_id.membrane_proof_payload() {
+        new_agent_id.membrane_proof_signature = Some(sign_with_keypair(&mut new_keypair, payload)?);
+    }
+
+    if let Some(ref agent_key_store) = runtime.context.agent_key_store {
+        agent_key_store(&mut new_keypair)?;
+    }
+
+    let address = match runtime.context.block_on(author_entry(
+        &Entry::AgentId(new_agent_id),
+        Some(previous_agent_header_address),
+        &runtime.context,
+    )) {
+        Ok(address) => address,
+        Err(error) => {
+            restore_agent_key(&runtime.context)?;
+            return Err(error);
+        }
+    };
+    runtime.context.set_agent_keypair(new_keypair)?;
+    Ok(address)
+}
+
+/// Persists the key the context signs with again, after a key rotation failed.
+fn restore_agent_key(context: &Arc<Context>) -> Result<(), HolochainError> {
+    if let (Some(agent_key_store), Some(keypair)) =
+        (&context.agent_key_store, &context.agent_keypair)
+    {
+        agent_key_store(&mut *keypair.lock()?)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::{
+        agent::verify_signature,
+        instance::tests::test_instance_and_context,
+        nucleus::ribosome::{
+            api::{
+                tests::{
+                    test_zome_api_function, test_zome_api_function_call,
+                    test_zome_api_function_wasm, test_zome_name,
+                },
+                ZomeApiFunction,
+            },
+            Defn,
+        },
+    };
+    use holochain_core_types::{
+        cas::content::{Address, AddressableContent},
+        entry::entry_type::EntryType,
+        error::ZomeApiInternalResult,
+        json::JsonString,
+    };
+    use holochain_dpki::keypair::Keypair;
+    use std::{
+        convert::TryFrom,
+        sync::{Arc, Mutex},
+    };
+
+    #[test]
+    /// test that a new AgentId entry gets committed and is used for signing afterwards
+    fn test_update_agent() {
+        let (call_result, context) = test_zome_api_function(
+            ZomeApiFunction::UpdateAgent.as_str(),
+            JsonString::empty_object().into_bytes(),
+        );
+
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(result.ok);
+        let new_agent_address = Address::try_from(JsonString::from(result.value)).unwrap();
+        assert_ne!(new_agent_address, context.agent_id.address());
+
+        let agent_state = context.state().unwrap().agent();
+        assert_eq!(
+            agent_state.get_agent_address(),
+            Ok(new_agent_address.clone())
+        );
+        assert_eq!(
+            agent_state.get_initial_agent_address(),
+            Ok(context.agent_id.address())
+        );
+        let agent_id_headers: Vec<_> = agent_state
+            .chain_store()
+            .iter_type(&agent_state.top_chain_header(), &EntryType::AgentId)
+            .collect();
+        assert_eq!(agent_id_headers.len(), 2);
+        assert_eq!(
+            agent_id_headers[0].link_crud(),
+            Some(agent_id_headers[1].address())
+        );
+
+        let signature = context.sign(String::from("payload")).unwrap();
+        assert!(verify_signature(&new_agent_address, &signature, String::from("payload")).unwrap());
+        assert!(!verify_signature(
+            &context.agent_id.address(),
+            &signature,
+            String::from("payload")
+        )
+        .unwrap());
+    }
+
+    #[test]
+    /// test that the rotated key gets persisted through the agent key store
+    fn test_update_agent_persists_new_key() {
+        let wasm = test_zome_api_function_wasm(ZomeApiFunction::UpdateAgent.as_str());
+        let dna =
+            test_utils::create_test_dna_with_wasm(&test_zome_name(), "test_cap", wasm.clone());
+        let dna_name = dna.name.clone();
+        let (instance, context) =
+            test_instance_and_context(dna, None).expect("Could not create test instance");
+
+        let stored_keys = Arc::new(Mutex::new(Vec::new()));
+        let stored_keys_inner = stored_keys.clone();
+        let mut context = (*context).clone();
+        context.agent_key_store = Some(Arc::new(Box::new(move |keypair: &mut Keypair| {
+            stored_keys_inner
+                .lock()
+                .unwrap()
+                .push(Address::from(keypair.pub_keys.clone()));
+            Ok(())
+        })));
+        let context = Arc::new(context);
+
+        let call_result = test_zome_api_function_call(
+            &dna_name,
+            context.clone(),
+            &instance,
+            &wasm,
+            JsonString::empty_object().into_bytes(),
+        );
+        let result = ZomeApiInternalResult::try_from(call_result).unwrap();
+        assert!(result.ok);
+        let new_agent_address = Address::try_from(JsonString::from(result.value)).unwrap();
+
+        assert_eq!(
+            *stored_keys.lock().unwrap(),
+            vec![new_agent_address.clone()]
+        );
+        assert_eq!(context.agent_signing_key(), Ok(new_agent_address));
+    }
+}
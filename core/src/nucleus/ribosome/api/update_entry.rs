@@ -1,10 +1,14 @@
 use crate::{
     agent::actions::{commit::commit_entry, update_entry::update_entry},
+    network::actions::publish::publish,
     nucleus::{
         actions::{build_validation_package::*, validate::*},
         ribosome::{api::ZomeApiResult, Runtime},
     },
-    workflows::get_entry_result::get_entry_result_workflow,
+    workflows::{
+        author_entry::{hold_sealed_entry, sealed_recipients},
+        get_entry_result::get_entry_result_workflow,
+    },
 };
 use futures::future::{self, TryFutureExt};
 use holochain_core_types::{
@@ -53,18 +57,42 @@ pub fn invoke_update_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
     }
     let latest_entry = entry_result.latest().unwrap();
 
-    // Get latest entry's ChainHeader
+    // Get latest entry's ChainHeader, which is still staged if the entry got committed
+    // in the open bundle
     let agent_state = &runtime.context.state().unwrap().agent();
-    let chain_header_address = agent_state
-        .chain_store()
-        .iter(&agent_state.top_chain_header())
-        .find(|header| header.entry_address() == &latest_entry.address())
-        .map(|header| header.address().clone())
-        .expect("Modified entry should be in chain");
+    let maybe_bundle = agent_state.bundle(&runtime.context.zome_call);
+    let maybe_chain_header = maybe_bundle
+        .as_ref()
+        .and_then(|bundle| bundle.find_chain_header(&latest_entry))
+        .or_else(|| {
+            agent_state
+                .chain_store()
+                .iter(&agent_state.top_chain_header())
+                .find(|header| header.entry_address() == &latest_entry.address())
+        });
+    let chain_header = match maybe_chain_header {
+        Some(chain_header) => chain_header,
+        None => {
+            runtime.context.log(format!(
+                "err/zome: invoke_update_entry: entry {} is not in the source chain",
+                latest_entry.address()
+            ));
+            return ribosome_error_code!(Unspecified);
+        }
+    };
 
     // Create Chain Entry
     let entry = Entry::from(entry_args.new_entry.clone());
 
+    // Updates involving private entries only get recorded in the source chain,
+    // the DHT metadata would reveal them
+    let dna = runtime
+        .context
+        .get_dna()
+        .expect("Callback called without DNA set!");
+    let publish_update =
+        latest_entry.entry_type().can_publish(&dna) && entry.entry_type().can_publish(&dna);
+
     // Wait for future to be resolved
     let task_result: Result<Address, HolochainError> = runtime.context.block_on(
         // 1. Build the context needed for validation of the entry
@@ -74,6 +102,8 @@ pub fn invoke_update_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
                     package: validation_package,
                     lifecycle: EntryLifecycle::Chain,
                     action: EntryAction::Modify,
+                    old_entry: Some(latest_entry.clone()),
+                    old_entry_header: Some(chain_header.clone()),
                 }))
             })
             // 2. Validate the entry
@@ -81,17 +111,44 @@ pub fn invoke_update_entry(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApi
                 validate_entry(entry.clone(), validation_data, &runtime.context)
             })
             // 3. Commit the valid entry to chain and DHT
-            .and_then(|_| commit_entry(entry.clone(), Some(chain_header_address), &runtime.context))
-            // 4. Update the entry in DHT metadata
-            .and_then(|new_address| {
-                update_entry(
+            .and_then(|_| {
+                commit_entry(
+                    entry.clone(),
+                    Some(chain_header.address()),
                     &runtime.context,
-                    runtime.context.action_channel(),
-                    latest_entry.address().clone(),
-                    new_address,
                 )
             }),
     );
 
+    // 4. Update the entry in DHT metadata
+    // and publish the new entry, so that the nodes holding the old one can validate the update.
+    // Entries of encrypted types get sealed first, like in author_entry,
+    // for the author and the recipients of the entry they update.
+    // Inside a bundle this happens when the bundle gets committed, see close_bundle_workflow.
+    let task_result = match task_result {
+        Ok(new_address) if publish_update && maybe_bundle.is_none() => runtime
+            .context
+            .block_on(update_entry(
+                &runtime.context,
+                runtime.context.action_channel(),
+                latest_entry.address().clone(),
+                new_address.clone(),
+            ))
+            .and_then(|_| {
+                if entry.entry_type().is_encrypted(&dna) {
+                    let recipients = sealed_recipients(&runtime.context, &latest_entry.address())?;
+                    runtime.context.block_on(hold_sealed_entry(
+                        &entry,
+                        &recipients,
+                        &runtime.context,
+                    ))?;
+                }
+                runtime
+                    .context
+                    .block_on(publish(new_address, &runtime.context))
+            }),
+        result => result,
+    };
+
     runtime.store_result(task_result)
 }
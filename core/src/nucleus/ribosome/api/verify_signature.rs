@@ -0,0 +1,110 @@
+use crate::{
+    agent::verify_signature,
+    nucleus::ribosome::{api::ZomeApiResult, Runtime},
+};
+use holochain_core_types::{
+    cas::content::Address,
+    signature::{domain_payload, Signature, SIGN_DOMAIN},
+};
+use holochain_wasm_utils::api_serialization::sign::VerifySignatureArgs;
+use std::convert::TryFrom;
+use wasmi::{RuntimeArgs, RuntimeValue};
+
+/// ZomeApiFunction::VerifySignature function code
+/// args: [0] encoded MemoryAllocation as u64
+/// Expected complex argument: VerifySignatureArgs
+/// Returns an HcApiReturnCode as I64
+pub fn invoke_verify_signature(runtime: &mut Runtime, args: &RuntimeArgs) -> ZomeApiResult {
+    // deserialize args
+    let args_str = runtime.load_json_string_from_args(&args);
+    let verify_args = match VerifySignatureArgs::try_from(args_str.clone()) {
+        Ok(verify_input) => verify_input,
+        // Exit on error
+        Err(_) => {
+            runtime.context.log(format!(
+                "err/zome: invoke_verify_signature failed to deserialize VerifySignatureArgs: {:?}",
+                args_str
+            ));
+            return ribosome_error_code!(ArgumentDeserializationFailed);
+        }
+    };
+
+    let result = verify_signature(
+        &Address::from(verify_args.pub_key),
+        &Signature::from(verify_args.signature),
+        domain_payload(SIGN_DOMAIN, &verify_args.payload),
+    );
+
+    runtime.store_result(result)
+}
+
+#[cfg(test)]
+pub mod tests {
+    extern crate test_utils;
+    extern crate wabt;
+
+    use crate::{
+        instance::tests::test_agent_and_keypair,
+        nucleus::ribosome::{
+            api::{tests::test_zome_api_function, ZomeApiFunction},
+            Defn,
+        },
+    };
+    use holochain_core_types::{
+        error::ZomeApiInternalResult,
+        json::JsonString,
+        signature::{domain_payload, SIGN_DOMAIN},
+    };
+    use holochain_sodium::{secbuf::SecBuf, sign};
+    use holochain_wasm_utils::api_serialization::sign::VerifySignatureArgs;
+
+    /// verify args for a signature that an agent named "alice" made over "test payload",
+    /// claiming it was made over the given payload
+    pub fn test_verify_signature_args_bytes(payload: &str) -> Vec<u8> {
+        let (agent, mut keypair) = test_agent_and_keypair("alice");
+        let mut message =
+            SecBuf::with_insecure_from_string(domain_payload(SIGN_DOMAIN, "test payload"));
+        let mut signature = SecBuf::with_insecure(sign::BYTES);
+        keypair.sign(&mut message, &mut signature).unwrap();
+        let signature = base64::encode(&**signature.read_lock());
+
+        let args = VerifySignatureArgs {
+            pub_key: agent.key,
+            signature,
+            payload: String::from(payload),
+        };
+        JsonString::from(args).into_bytes()
+    }
+
+    #[test]
+    /// test that a valid signature gets verified through WASM
+    fn test_verify_signature_round_trip() {
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::VerifySignature.as_str(),
+            test_verify_signature_args_bytes("test payload"),
+        );
+
+        assert_eq!(
+            call_result,
+            JsonString::from(
+                String::from(JsonString::from(ZomeApiInternalResult::success(true))) + "\u{0}"
+            ),
+        );
+    }
+
+    #[test]
+    /// test that a signature of a different payload does not get verified
+    fn test_verify_signature_of_other_payload() {
+        let (call_result, _) = test_zome_api_function(
+            ZomeApiFunction::VerifySignature.as_str(),
+            test_verify_signature_args_bytes("other payload"),
+        );
+
+        assert_eq!(
+            call_result,
+            JsonString::from(
+                String::from(JsonString::from(ZomeApiInternalResult::success(false))) + "\u{0}"
+            ),
+        );
+    }
+}
@@ -4,6 +4,7 @@
 pub mod genesis;
 pub mod links_utils;
 pub mod receive;
+pub mod resolve_conflict;
 pub mod validate_entry;
 pub mod validation_package;
 
@@ -21,7 +22,7 @@ use crate::{
 use holochain_core_types::{
     cas::content::Address,
     dna::{
-        capabilities::{CapabilityCall, ReservedCapabilityNames},
+        capabilities::{CallSignature, CapabilityCall, ReservedCapabilityNames},
         wasm::DnaWasm,
     },
     entry::Entry,
@@ -149,6 +150,7 @@ pub enum CallbackResult {
     NotImplemented(String),
     ValidationPackageDefinition(ValidationPackageDefinition),
     ReceiveResult(String),
+    ConflictWinner(Address),
 }
 
 impl From<CallbackResult> for JsonString {
@@ -220,6 +222,7 @@ pub fn call(
         Some(CapabilityCall::new(
             Address::from(""), //FIXME!!
             None,
+            CallSignature::default(),
         )),
         //&function.capability().as_str().to_string(),
         //"", //TODO: token?
@@ -388,5 +391,4 @@ pub mod tests {
             assert_eq!(output, Callback::from_index(input));
         }
     }
-
 }
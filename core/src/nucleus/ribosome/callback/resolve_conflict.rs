@@ -0,0 +1,73 @@
+use crate::{
+    context::Context,
+    nucleus::{
+        ribosome::{self, callback::CallbackResult},
+        ZomeFnCall,
+    },
+};
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    entry::{entry_type::AppEntryType, Entry},
+    error::HolochainError,
+};
+use holochain_wasm_utils::api_serialization::resolve_conflict::{
+    ConflictResolutionArgs, ConflictingUpdate,
+};
+use std::{convert::TryFrom, sync::Arc};
+
+/// Runs the `resolve_conflict` callback of the zome defining the given entry type,
+/// which picks the winner among the concurrent updates of an entry of that type.
+/// It returns a CallbackResult which would be
+/// * CallbackResult::ConflictWinner(address) with the address of the winning update
+/// * CallbackResult::Fail(message) when the callback failed or returned an address
+///         that is not one of the updates
+/// * CallbackResult::NotImplemented if the zome does not implement the callback
+pub fn resolve_conflict(
+    context: Arc<Context>,
+    app_entry_type: AppEntryType,
+    old_entry: Entry,
+    updates: Vec<ConflictingUpdate>,
+) -> Result<CallbackResult, HolochainError> {
+    let dna = context.get_dna().expect("Callback called without DNA set!");
+    let zome_name = match dna.get_zome_name_for_app_entry_type(&app_entry_type) {
+        Some(zome_name) => zome_name,
+        None => {
+            return Ok(CallbackResult::NotImplemented("resolve_conflict/1".into()));
+        }
+    };
+    let wasm = match context.get_wasm(&zome_name) {
+        Some(wasm) => wasm,
+        None => {
+            return Ok(CallbackResult::NotImplemented("resolve_conflict/2".into()));
+        }
+    };
+
+    let candidates: Vec<Address> = updates
+        .iter()
+        .map(|update| update.entry.address())
+        .collect();
+    let params = ConflictResolutionArgs {
+        entry_type: String::from(app_entry_type),
+        old_entry,
+        updates,
+    };
+    let call = ZomeFnCall::new(&zome_name, None, "resolve_conflict", params);
+    match ribosome::run_dna(
+        &dna.name,
+        context,
+        wasm.code.clone(),
+        &call,
+        Some(call.clone().parameters.into_bytes()),
+    ) {
+        Ok(call_result) => match Address::try_from(call_result.clone()) {
+            Ok(winner) if candidates.contains(&winner) => {
+                Ok(CallbackResult::ConflictWinner(winner))
+            }
+            _ => Ok(CallbackResult::Fail(call_result.to_string())),
+        },
+        Err(HolochainError::NotImplemented(_)) => {
+            Ok(CallbackResult::NotImplemented("resolve_conflict/3".into()))
+        }
+        Err(error) => Ok(CallbackResult::Fail(error.to_string())),
+    }
+}
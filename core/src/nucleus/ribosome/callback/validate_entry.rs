@@ -1,5 +1,6 @@
 extern crate serde_json;
 use crate::{
+    agent::verify_membrane_proof,
     context::Context,
     nucleus::{
         ribosome::{
@@ -19,7 +20,7 @@ use holochain_core_types::{
     validation::ValidationData,
 };
 use holochain_wasm_utils::api_serialization::validation::{
-    EntryValidationArgs, LinkValidationArgs,
+    AgentIdValidationArgs, EntryValidationArgs, LinkValidationArgs,
 };
 use std::sync::Arc;
 
@@ -48,26 +49,22 @@ pub fn validate_entry(
             context,
         )?),
 
-        EntryType::LinkAdd => Ok(validate_link_entry(
+        EntryType::LinkAdd | EntryType::LinkRemove => Ok(validate_link_entry(
             entry.clone(),
             validation_data,
             context,
         )?),
 
-        // Deletion entries are not validated currently and always valid
-        // TODO: Specify how Deletion can be commited to chain.
-        EntryType::Deletion => Ok(CallbackResult::Pass),
+        EntryType::Deletion => Ok(validate_deletion_entry(validation_data, context)?),
 
         // a grant should always be private, so it should always pass
         EntryType::CapTokenGrant => Ok(CallbackResult::Pass),
 
-        // TODO: actually check agent against app specific membrane validation rule
-        // like for instance: validate_agent_id(
-        //                      entry.clone(),
-        //                      validation_data,
-        //                      context,
-        //                    )?
-        EntryType::AgentId => Ok(CallbackResult::Pass),
+        EntryType::AgentId => Ok(validate_agent_id(entry.clone(), validation_data, context)?),
+
+        // Whether a chain may still be migrated is checked when committing,
+        // a closed chain does not accept any further entries
+        EntryType::ChainMigrate => Ok(CallbackResult::Pass),
 
         _ => Ok(CallbackResult::NotImplemented("validate_entry".into())),
     }
@@ -78,15 +75,15 @@ fn validate_link_entry(
     validation_data: ValidationData,
     context: Arc<Context>,
 ) -> Result<CallbackResult, HolochainError> {
-    let link_add = match entry {
-        Entry::LinkAdd(link_add) => link_add,
+    let link = match entry {
+        Entry::LinkAdd(link_add) => link_add.link().clone(),
+        Entry::LinkRemove(link_remove) => link_remove.link().clone(),
         _ => {
             return Err(HolochainError::ValidationFailed(
-                "Could not extract link_add from entry".into(),
+                "Could not extract link from entry".into(),
             ));
         }
     };
-    let link = link_add.link().clone();
     let (base, target) = links_utils::get_link_entries(&link, &context)?;
     let link_definition_path = links_utils::find_link_definition_in_dna(
         &base.entry_type(),
@@ -120,6 +117,84 @@ fn validate_link_entry(
     ))
 }
 
+/// Checks an agent joining the network against the membrane rules of the DNA,
+/// i.e. the `validate_agent` callbacks of its zomes, which get the AgentId entry
+/// (including the agent's membrane proof) and the validation data.
+/// A membrane proof has to be signed by the agent first (see `agent::verify_membrane_proof`),
+/// so the callbacks can rely on it being the proof the agent presented.
+/// The agent passes if none of the zomes that implement the callback rejects it,
+/// so a DNA without any `validate_agent` callback lets every agent join.
+fn validate_agent_id(
+    entry: Entry,
+    validation_data: ValidationData,
+    context: Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    let agent_id = match entry {
+        Entry::AgentId(agent_id) => agent_id,
+        _ => {
+            return Err(HolochainError::ValidationFailed(
+                "Could not extract agent from entry".into(),
+            ));
+        }
+    };
+    if let Err(error) = verify_membrane_proof(&agent_id) {
+        return Ok(CallbackResult::Fail(error.to_string()));
+    }
+    let dna = context.get_dna().expect("Callback called without DNA set!");
+    for zome_name in dna.zomes.keys() {
+        let wasm = match context.get_wasm(zome_name) {
+            Some(wasm) => wasm,
+            None => continue,
+        };
+        let params = AgentIdValidationArgs {
+            agent_id: agent_id.clone(),
+            validation_data: validation_data.clone(),
+        };
+        let call = ZomeFnCall::new(zome_name, None, "validate_agent", params);
+        match ribosome::run_dna(
+            &dna.name,
+            context.clone(),
+            wasm.code.clone(),
+            &call,
+            Some(call.clone().parameters.into_bytes()),
+        ) {
+            Ok(call_result) => {
+                if !call_result.is_null() {
+                    return Ok(CallbackResult::Fail(call_result.to_string()));
+                }
+            }
+            // zomes without membrane rules accept every agent
+            Err(HolochainError::NotImplemented(_)) => (),
+            Err(error) => return Ok(CallbackResult::Fail(error.to_string())),
+        }
+    }
+    Ok(CallbackResult::Pass)
+}
+
+/// A deletion is validated by the validation callback of the deleted entry's type,
+/// which gets the deleted entry together with EntryAction::Delete and the deleted entry's
+/// header in the validation data.
+/// Without the deleted entry at hand (i.e. on the nodes holding the Deletion entry itself)
+/// there is nothing to validate, the deletion only takes effect on the nodes holding the
+/// deleted entry, which validate it there.
+fn validate_deletion_entry(
+    validation_data: ValidationData,
+    context: Arc<Context>,
+) -> Result<CallbackResult, HolochainError> {
+    match validation_data.old_entry.clone() {
+        Some(old_entry) => match old_entry.entry_type() {
+            EntryType::App(app_entry_type) => Ok(validate_app_entry(
+                old_entry.clone(),
+                app_entry_type.clone(),
+                validation_data,
+                context,
+            )?),
+            _ => Ok(CallbackResult::Pass),
+        },
+        None => Ok(CallbackResult::Pass),
+    }
+}
+
 fn validate_app_entry(
     entry: Entry,
     app_entry_type: AppEntryType,
@@ -135,6 +210,13 @@ fn validate_app_entry(
     }
 
     let zome_name = zome_name.unwrap();
+
+    // Entries have to match the JSON Schema their type declares in the DNA
+    // before they get to the zome's validation callback
+    if let Err(fail_string) = context.entry_schemas()?.validate(&entry, &app_entry_type) {
+        return Ok(CallbackResult::Fail(fail_string));
+    }
+
     match context.get_wasm(&zome_name) {
         Some(wasm) => {
             let validation_call = build_validation_call(
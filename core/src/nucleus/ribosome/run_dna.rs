@@ -89,9 +89,26 @@ pub fn run_dna(
         .run_start(&mut NopExternals)
         .map_err(|_| HolochainError::RibosomeFailed("Module failed to start".to_string()))?;
 
+    // Optional callbacks (like validate_agent) are not exported by every zome,
+    // so calls to missing functions are told apart from failing ones
+    if wasm_instance.export_by_name(&zome_call.fn_name).is_none() {
+        return Err(HolochainError::NotImplemented(format!(
+            "Zome {} does not export function {}",
+            zome_call.zome_name, zome_call.fn_name
+        )));
+    }
+
     // write input arguments for module call in memory Buffer
     let input_parameters: Vec<_> = parameters.unwrap_or_default();
 
+    // Scope the context to this zome call, so that its commits end up in the bundle it opens.
+    // Callbacks run during a zome call (e.g. validation) stay in the scope of that call.
+    let context = if context.zome_call.is_some() {
+        context
+    } else {
+        Arc::new(context.with_zome_call(&zome_call))
+    };
+
     // instantiate runtime struct for passing external state data over wasm but not to wasm
     let mut runtime = Runtime {
         memory_manager: WasmPageManager::new(&wasm_instance),
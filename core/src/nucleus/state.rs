@@ -3,9 +3,9 @@ use holochain_core_types::{
     cas::content::Address, dna::Dna, error::HolochainError, json::JsonString,
     validation::ValidationPackage,
 };
-use snowflake;
 use std::collections::HashMap;
-#[derive(Clone, Debug, PartialEq)]
+use uuid::Uuid;
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum NucleusStatus {
     New,
     Initializing,
@@ -19,7 +19,9 @@ impl Default for NucleusStatus {
     }
 }
 
-pub type ValidationResult = Result<(), String>;
+/// Err(HolochainError::ValidationFailed) if the entry is invalid,
+/// any other error if it could not be validated, e.g. because of a missing dependency
+pub type ValidationResult = Result<(), HolochainError>;
 
 /// The state-slice for the Nucleus.
 /// Holds the dynamic parts of the DNA, i.e. zome calls and validation requests.
@@ -32,9 +34,8 @@ pub struct NucleusState {
     // @TODO should this use the standard ActionWrapper/ActionResponse format?
     // @see https://github.com/holochain/holochain-rust/issues/196
     pub zome_calls: HashMap<ZomeFnCall, Option<Result<JsonString, HolochainError>>>,
-    pub validation_results: HashMap<(snowflake::ProcessUniqueId, Address), ValidationResult>,
-    pub validation_packages:
-        HashMap<snowflake::ProcessUniqueId, Result<ValidationPackage, HolochainError>>,
+    pub validation_results: HashMap<(Uuid, Address), ValidationResult>,
+    pub validation_packages: HashMap<Uuid, Result<ValidationPackage, HolochainError>>,
 }
 
 impl NucleusState {
@@ -1,13 +1,19 @@
 use crate::action::ActionWrapper;
+use holochain_core_types::json::JsonString;
 use std::{
     sync::mpsc::{channel, sync_channel, Receiver, SyncSender},
     thread,
 };
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Signal {
     Internal(ActionWrapper),
-    User,
+    /// Emitted by a zome through `hdk::emit_signal`, for the UIs of the instance
+    User {
+        instance_id: String,
+        name: String,
+        payload: JsonString,
+    },
 }
 
 pub type SignalSender = SyncSender<Signal>;
@@ -1,29 +1,28 @@
 use crate::{
-    action::ActionWrapper,
+    action::{Action, ActionWrapper},
     agent::{
         chain_store::ChainStore,
         state::{AgentState, AgentStateSnapshot},
     },
     context::Context,
     dht::{dht_reducers::ENTRY_HEADER_ATTRIBUTE, dht_store::DhtStore},
-    network::state::NetworkState,
-    nucleus::state::NucleusState,
+    network::{actions::ActionResponse, state::NetworkState},
+    nucleus::state::{NucleusState, NucleusStatus},
 };
 use holochain_core_types::{
-    cas::{
-        content::{Address, AddressableContent},
-        storage::ContentAddressableStorage,
-    },
+    cas::content::{Address, AddressableContent, Content},
     chain_header::ChainHeader,
     dna::Dna,
     entry::{entry_type::EntryType, Entry},
     error::{HcResult, HolochainError},
+    json::JsonString,
 };
 use std::{
     collections::HashSet,
-    convert::TryInto,
-    sync::{Arc, RwLock},
+    convert::{TryFrom, TryInto},
+    sync::Arc,
 };
+use uuid::Uuid;
 
 /// The Store of the Holochain instance Object, according to Redux pattern.
 /// It's composed of all sub-module's state slices.
@@ -63,10 +62,8 @@ impl State {
         let cas = context.dht_storage.clone();
         let eav = context.eav_storage.clone();
 
-        fn get_dna(
-            agent_state: &Arc<AgentState>,
-            cas: Arc<RwLock<dyn ContentAddressableStorage>>,
-        ) -> HcResult<Dna> {
+        fn get_dna(agent_state: &Arc<AgentState>) -> HcResult<Dna> {
+            let cas = agent_state.chain_store().content_storage();
             let dna_entry_header = agent_state
                 .chain_store()
                 .iter_type(&agent_state.top_chain_header(), &EntryType::Dna)
@@ -90,7 +87,7 @@ impl State {
         }
 
         let mut nucleus_state = NucleusState::new();
-        nucleus_state.dna = get_dna(&agent_state, cas.clone()).ok();
+        nucleus_state.dna = get_dna(&agent_state).ok();
         State {
             nucleus: Arc::new(nucleus_state),
             agent: agent_state,
@@ -159,6 +156,79 @@ impl State {
         ))
     }
 
+    /// Restores the state a persisted StateSnapshot was taken of.
+    /// The source chain, the DHT shard and its metadata live in the instance's storages,
+    /// so only the top chain header, the nucleus status and the publishes that were still
+    /// pending need to be restored. Pending publishes get restored with their original
+    /// action ids and the error that kept them from succeeding, so they can be resumed.
+    pub fn try_from_snapshot(context: Arc<Context>, snapshot: StateSnapshot) -> HcResult<State> {
+        if snapshot.version > STATE_SNAPSHOT_VERSION {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "State snapshot has version {} but only versions up to {} are supported",
+                snapshot.version, STATE_SNAPSHOT_VERSION
+            )));
+        }
+        let mut state = match snapshot.agent {
+            Some(agent_snapshot) => State::new_with_agent(
+                context.clone(),
+                Arc::new(AgentState::new_with_top_chain_header(
+                    ChainStore::new(context.chain_storage.clone()),
+                    agent_snapshot.top_chain_header().clone(),
+                )),
+            ),
+            None => State::new(context.clone()),
+        };
+
+        let mut nucleus_state = (*state.nucleus).clone();
+        nucleus_state.status = match snapshot.nucleus_status {
+            // an initialization that got interrupted has to start over
+            NucleusStatus::Initializing => NucleusStatus::New,
+            status => status,
+        };
+        state.nucleus = Arc::new(nucleus_state);
+
+        let mut network_state = NetworkState::new();
+        for (id, address) in snapshot.pending_publishes {
+            network_state.actions.insert(
+                ActionWrapper::with_id(Action::Publish(address), id),
+                ActionResponse::Publish(Err(HolochainError::ErrorGeneric(
+                    "Publish got interrupted by a restart".to_string(),
+                ))),
+            );
+        }
+        state.network = Arc::new(network_state);
+
+        Ok(state)
+    }
+
+    /// Publish actions that did not succeed (yet), and for which no later publish
+    /// of the same address succeeded either.
+    pub fn pending_publishes(&self) -> Vec<ActionWrapper> {
+        let actions = &self.network.actions;
+        let published: HashSet<Address> = actions
+            .values()
+            .filter_map(|response| match response {
+                ActionResponse::Publish(Ok(address)) => Some(address.clone()),
+                _ => None,
+            })
+            .collect();
+        let mut pending: Vec<ActionWrapper> = actions
+            .iter()
+            .filter_map(
+                |(action_wrapper, response)| match (action_wrapper.action(), response) {
+                    (Action::Publish(address), ActionResponse::Publish(Err(_)))
+                        if !published.contains(address) =>
+                    {
+                        Some(action_wrapper.clone())
+                    }
+                    _ => None,
+                },
+            )
+            .collect();
+        pending.sort_by_key(|action_wrapper| action_wrapper.id().to_string());
+        pending
+    }
+
     /// Get all headers for an entry by first looking in the DHT meta store
     /// for header addresses, then resolving them with the DHT CAS
     pub fn get_headers(&self, entry_address: Address) -> Result<Vec<ChainHeader>, HolochainError> {
@@ -204,6 +274,59 @@ impl State {
     }
 }
 
+pub const STATE_SNAPSHOT_VERSION: u32 = 1;
+pub static STATE_SNAPSHOT_ADDRESS: &'static str = "StateSnapshot";
+
+/// The parts of the State that do not live in the instance's storages already
+/// and that a restarted instance needs to resume where it left off.
+/// The source chain, the DHT shard and its metadata are read back from the storages.
+/// Pending gets, validation package requests, direct messages and zome calls are not part of
+/// the snapshot: the futures waiting for their results don't survive a restart, and re-running
+/// a zome call would repeat its side effects. Validations of entries held for the DHT are not
+/// restored either, as their authors publish them again once they reconnect.
+/// The version gets bumped whenever the format changes so that old snapshots can be migrated
+/// and newer ones are rejected instead of being misread.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize, DefaultJson)]
+pub struct StateSnapshot {
+    pub version: u32,
+    pub agent: Option<AgentStateSnapshot>,
+    pub nucleus_status: NucleusStatus,
+    /// action ids and addresses of publishes that have not succeeded yet
+    pub pending_publishes: Vec<(Uuid, Address)>,
+}
+
+impl<'a> From<&'a State> for StateSnapshot {
+    fn from(state: &'a State) -> StateSnapshot {
+        StateSnapshot {
+            version: STATE_SNAPSHOT_VERSION,
+            agent: state.agent().top_chain_header().map(AgentStateSnapshot::new),
+            nucleus_status: state.nucleus().status(),
+            pending_publishes: state
+                .pending_publishes()
+                .into_iter()
+                .map(|action_wrapper| {
+                    let address = unwrap_to!(action_wrapper.action() => Action::Publish).clone();
+                    (action_wrapper.id().clone(), address)
+                })
+                .collect(),
+        }
+    }
+}
+
+impl AddressableContent for StateSnapshot {
+    fn content(&self) -> Content {
+        self.to_owned().into()
+    }
+
+    fn try_from_content(content: &Content) -> Result<Self, HolochainError> {
+        Self::try_from(content.to_owned())
+    }
+
+    fn address(&self) -> Address {
+        STATE_SNAPSHOT_ADDRESS.into()
+    }
+}
+
 pub fn test_store(context: Arc<Context>) -> State {
     State::new(context)
 }
@@ -1,5 +1,6 @@
 use crate::{
     context::{get_dna_and_agent, Context},
+    instance::dispatch_action,
     network::actions::initialize_network,
 };
 
@@ -15,11 +16,31 @@ pub async fn initialize(
     context: Arc<Context>,
 ) -> HcResult<Arc<Context>> {
     let instance_context = instance.initialize_context(context.clone());
+    let loading = dna.is_none();
     await!(get_dna_and_agent(&instance_context)
         .map_ok(|_| ())
         .or_else(
             |_| initialize_application(dna.unwrap_or(Dna::new()), &instance_context).map_ok(|_| ())
         ))?;
     await!(initialize_network::initialize_network(&instance_context))?;
+    if loading {
+        resume_pending_publishes(&instance_context);
+    }
     Ok(instance_context)
 }
+
+/// Publishes that were still pending when the loaded state got persisted are dispatched
+/// again with their original action ids, so their results replace the restored errors.
+fn resume_pending_publishes(context: &Arc<Context>) {
+    let pending = context
+        .state()
+        .map(|state| state.pending_publishes())
+        .unwrap_or_default();
+    for action_wrapper in pending {
+        context.log(format!(
+            "debug/application: resuming {:?}",
+            action_wrapper.action()
+        ));
+        dispatch_action(context.action_channel(), action_wrapper);
+    }
+}
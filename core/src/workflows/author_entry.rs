@@ -1,7 +1,8 @@
 use crate::{
-    agent::actions::commit::commit_entry,
+    agent::{actions::commit::commit_entry, can_publish_chain_entry},
     context::Context,
-    network::actions::publish::publish,
+    dht::actions::hold::hold_encrypted_entry,
+    network::{actions::publish::publish, entry_with_header::EncryptedEntryWithHeader},
     nucleus::actions::{
         build_validation_package::build_validation_package, validate::validate_entry,
     },
@@ -19,18 +20,85 @@ pub async fn author_entry<'a>(
     entry: &'a Entry,
     maybe_crud_link: Option<Address>,
     context: &'a Arc<Context>,
+) -> Result<Address, HolochainError> {
+    await!(author_entry_for_recipients(
+        entry,
+        maybe_crud_link,
+        Vec::new(),
+        context
+    ))
+}
+
+/// Authors an entry of a type the DNA declares encrypted, sealed for the given recipients
+/// (the addresses of their AgentId entries) and the author.
+/// Entries inside a bundle can only be sealed for the author, since they get published
+/// when the bundle gets closed.
+pub async fn author_encrypted_entry<'a>(
+    entry: &'a Entry,
+    recipients: Vec<Address>,
+    context: &'a Arc<Context>,
+) -> Result<Address, HolochainError> {
+    let dna = context
+        .get_dna()
+        .ok_or_else(|| HolochainError::ErrorGeneric("No DNA set".to_string()))?;
+    if !entry.entry_type().is_encrypted(&dna) {
+        return Err(HolochainError::ErrorGeneric(format!(
+            "Entry type {} is not declared encrypted",
+            entry.entry_type()
+        )));
+    }
+    if context
+        .state()
+        .unwrap()
+        .agent()
+        .bundle(&context.zome_call)
+        .is_some()
+    {
+        return Err(HolochainError::ErrorGeneric(
+            "Entries can't be sealed for recipients inside a bundle".to_string(),
+        ));
+    }
+    await!(author_entry_for_recipients(
+        entry, None, recipients, context
+    ))
+}
+
+async fn author_entry_for_recipients<'a>(
+    entry: &'a Entry,
+    maybe_crud_link: Option<Address>,
+    recipients: Vec<Address>,
+    context: &'a Arc<Context>,
 ) -> Result<Address, HolochainError> {
     let address = entry.address();
     context.log(format!(
         "debug/workflow/authoring_entry: {} with content: {:?}",
         address, entry
     ));
+
+    // 0. Inside a bundle the entry only gets staged.
+    // Validation and publishing happen when the bundle gets closed, see close_bundle_workflow.
+    if context
+        .state()
+        .unwrap()
+        .agent()
+        .bundle(&context.zome_call)
+        .is_some()
+    {
+        context.log(format!(
+            "debug/workflow/authoring_entry/{}: staging in bundle...",
+            address
+        ));
+        return await!(commit_entry(entry.clone(), maybe_crud_link, &context));
+    }
+
     // 1. Build the context needed for validation of the entry
     let validation_package = await!(build_validation_package(&entry, &context))?;
     let validation_data = ValidationData {
         package: validation_package,
         lifecycle: EntryLifecycle::Chain,
         action: EntryAction::Create,
+        old_entry: None,
+        old_entry_header: None,
     };
 
     // 2. Validate the entry
@@ -52,10 +120,19 @@ pub async fn author_entry<'a>(
         address
     ));
 
-    // 4. Publish the valid entry to DHT. This will call Hold to itself
-    //TODO: missing a general public/private sharing check here, for now just
-    // using the entry_type can_publish() function which isn't enough
-    if entry.entry_type().can_publish() {
+    // 4. Publish the valid entry to DHT, unless the DNA declares its type private.
+    // This will call Hold to itself
+    let dna = context
+        .get_dna()
+        .ok_or_else(|| HolochainError::ErrorGeneric("No DNA set".to_string()))?;
+    if can_publish_chain_entry(entry, &dna, &context) {
+        if entry.entry_type().is_encrypted(&dna) {
+            context.log(format!(
+                "debug/workflow/authoring_entry/{}: sealing...",
+                address
+            ));
+            await!(hold_sealed_entry(entry, &recipients, &context))?;
+        }
         context.log(format!(
             "debug/workflow/authoring_entry/{}: publishing...",
             address
@@ -74,11 +151,68 @@ pub async fn author_entry<'a>(
     Ok(addr)
 }
 
+/// Seals a committed entry of an encrypted type for the given recipients and the author
+/// and holds the sealed copy in the author's DHT shard, which is what gets published.
+pub(crate) async fn hold_sealed_entry<'a>(
+    entry: &'a Entry,
+    recipients: &'a [Address],
+    context: &'a Arc<Context>,
+) -> Result<Address, HolochainError> {
+    let header = context
+        .state()
+        .unwrap()
+        .agent()
+        .get_most_recent_header_for_entry(entry)
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Entry {} has no header in the source chain to be sealed with",
+                entry.address()
+            ))
+        })?;
+    let encrypted_entry = context.encrypt_entry(entry, recipients)?;
+    await!(hold_encrypted_entry(
+        EncryptedEntryWithHeader::new(encrypted_entry, header),
+        context.clone()
+    ))
+}
+
+/// The recipients the entry with the given address got sealed for, so that an update
+/// of it can be sealed for the same agents. Empty if the entry is not held encrypted.
+pub(crate) fn sealed_recipients(
+    context: &Arc<Context>,
+    entry_address: &Address,
+) -> Result<Vec<Address>, HolochainError> {
+    Ok(context
+        .state()
+        .unwrap()
+        .dht()
+        .get_encrypted_entry(entry_address)?
+        .map(|encrypted_entry| {
+            encrypted_entry
+                .keys()
+                .iter()
+                .map(|sealed_key| Address::from(sealed_key.recipient.clone()))
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
 #[cfg(test)]
 pub mod tests {
-    use super::author_entry;
-    use crate::nucleus::actions::tests::*;
-    use holochain_core_types::{entry::test_entry, json::JsonString};
+    use super::{author_encrypted_entry, author_entry};
+    use crate::{
+        nucleus::actions::tests::*, workflows::get_entry_result::get_entry_with_meta_workflow,
+    };
+    use holochain_core_types::{
+        cas::content::AddressableContent,
+        dna::entry_types::Sharing,
+        entry::{
+            entry_type::{AppEntryType, EntryType},
+            test_entry,
+        },
+        json::JsonString,
+        time::Timeout,
+    };
     use std::{thread, time};
 
     #[test]
@@ -122,4 +256,112 @@ pub mod tests {
             "{\"App\":[\"testEntryType\",\"\\\"test entry value\\\"\"]}".to_string(),
         );
     }
+
+    #[test]
+    #[cfg(not(windows))]
+    /// test that entries of types the DNA declares private stay in the author's source chain
+    fn test_commit_private_entry_without_dht_publish() {
+        let mut dna = test_dna();
+        dna.uuid = "test_commit_private_entry_without_dht_publish".to_string();
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&EntryType::App(AppEntryType::from("testEntryType")))
+            .unwrap()
+            .sharing = Sharing::Private;
+        let netname = Some("test_commit_private_entry_without_dht_publish, the network");
+        let (_instance1, context1) = instance_by_name("jill", dna.clone(), netname);
+        let (_instance2, context2) = instance_by_name("jack", dna, netname);
+
+        let entry_address = context1
+            .block_on(author_entry(&test_entry(), None, &context1))
+            .unwrap();
+        thread::sleep(time::Duration::from_millis(1000));
+
+        let json = context2
+            .state()
+            .unwrap()
+            .dht()
+            .content_storage()
+            .read()
+            .unwrap()
+            .fetch(&entry_address)
+            .expect("could not fetch from CAS");
+        assert_eq!(json, None);
+    }
+
+    #[test]
+    #[cfg(not(windows))]
+    /// test that entries of encrypted types only travel sealed and are readable by their recipients
+    fn test_commit_encrypted_entry_for_recipient() {
+        let mut dna = test_dna();
+        dna.uuid = "test_commit_encrypted_entry_for_recipient".to_string();
+        dna.zomes
+            .get_mut("test_zome")
+            .unwrap()
+            .entry_types
+            .get_mut(&EntryType::App(AppEntryType::from("testEntryType")))
+            .unwrap()
+            .sharing = Sharing::Encrypted;
+        let netname = Some("test_commit_encrypted_entry_for_recipient, the network");
+        let (_instance1, context1) = instance_by_name("jill", dna.clone(), netname);
+        let (_instance2, context2) = instance_by_name("jack", dna.clone(), netname);
+        let (_instance3, context3) = instance_by_name("joan", dna, netname);
+
+        let entry_address = context1
+            .block_on(author_encrypted_entry(
+                &test_entry(),
+                vec![context2.agent_id.address()],
+                &context1,
+            ))
+            .unwrap();
+
+        let mut maybe_entry_with_meta = None;
+        let mut tries = 0;
+        while maybe_entry_with_meta.is_none() && tries < 120 {
+            tries = tries + 1;
+            maybe_entry_with_meta = context2
+                .block_on(get_entry_with_meta_workflow(
+                    &context2,
+                    &entry_address,
+                    &Timeout::new(1000),
+                ))
+                .unwrap();
+            if maybe_entry_with_meta.is_none() {
+                thread::sleep(time::Duration::from_millis(1000));
+            }
+        }
+        assert_eq!(maybe_entry_with_meta.unwrap().entry, test_entry());
+
+        // the recipient's DHT shard only holds the sealed entry
+        let json = context2
+            .state()
+            .unwrap()
+            .dht()
+            .content_storage()
+            .read()
+            .unwrap()
+            .fetch(&entry_address)
+            .expect("could not fetch from CAS");
+        assert_eq!(json, None);
+
+        let maybe_entry_with_meta = context3
+            .block_on(get_entry_with_meta_workflow(
+                &context3,
+                &entry_address,
+                &Timeout::new(1000),
+            ))
+            .unwrap();
+        assert_eq!(maybe_entry_with_meta, None);
+    }
+
+    #[test]
+    /// test that only entries of types the DNA declares encrypted can be sealed
+    fn test_commit_encrypted_entry_needs_encrypted_type() {
+        let (_instance, context) = instance_by_name("jill", test_dna(), None);
+        assert!(context
+            .block_on(author_encrypted_entry(&test_entry(), Vec::new(), &context))
+            .is_err());
+    }
 }
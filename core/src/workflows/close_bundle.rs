@@ -0,0 +1,206 @@
+use crate::{
+    agent::{
+        actions::{bundle::close_bundle, update_entry::update_entry},
+        bundle::Bundle,
+        can_publish_chain_entry,
+    },
+    context::Context,
+    dht::actions::remove_entry::remove_entry,
+    network::actions::publish::publish,
+    nucleus::actions::{
+        build_validation_package::build_validation_package,
+        get_entry::{get_entry_from_agent, get_entry_from_dht},
+        validate::validate_entry,
+    },
+    workflows::author_entry::{hold_sealed_entry, sealed_recipients},
+};
+
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    entry::Entry,
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
+use holochain_wasm_utils::api_serialization::bundle::BundleOnClose;
+use std::sync::Arc;
+
+/// Closes the bundle of the zome call the context is scoped to.
+/// On BundleOnClose::Commit every staged entry gets validated in the order it was committed.
+/// Updates and deletions get validated as such, with the entry they modify.
+/// Only if all of them are valid, they get appended to the source chain at once and published,
+/// together with the DHT metadata of the updates and deletions.
+/// If any of them is invalid, the whole bundle gets discarded and the validation error is returned.
+pub async fn close_bundle_workflow<'a>(
+    on_close: BundleOnClose,
+    context: &'a Arc<Context>,
+) -> Result<Vec<Address>, HolochainError> {
+    if on_close == BundleOnClose::Discard {
+        context.log("debug/workflow/close_bundle: discarding bundle");
+        return await!(close_bundle(BundleOnClose::Discard, &context));
+    }
+
+    let bundle = context
+        .state()
+        .unwrap()
+        .agent()
+        .bundle(&context.zome_call)
+        .ok_or_else(|| HolochainError::ErrorGeneric("No bundle open".to_string()))?;
+    let staged_entries = bundle.staged_entries()?;
+
+    // 1. Validate all staged entries, the bundle is still open so that the validation
+    // packages get built from the staged chain
+    let mut modified_entries = Vec::new();
+    for (entry, header) in staged_entries.iter() {
+        context.log(format!(
+            "debug/workflow/close_bundle/{}: validating...",
+            entry.address()
+        ));
+        let (action, old_entry, old_entry_header) =
+            match modified_entry(&bundle, entry, header, &context) {
+                Ok(modified) => modified,
+                Err(error) => {
+                    await!(close_bundle(BundleOnClose::Discard, &context))?;
+                    return Err(error);
+                }
+            };
+        let validation_result = match await!(build_validation_package(entry, &context)) {
+            Ok(validation_package) => await!(validate_entry(
+                entry.clone(),
+                ValidationData {
+                    package: validation_package,
+                    lifecycle: EntryLifecycle::Chain,
+                    action: action.clone(),
+                    old_entry: old_entry.clone(),
+                    old_entry_header,
+                },
+                &context
+            )),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = validation_result {
+            context.log(format!(
+                "debug/workflow/close_bundle/{}: invalid, discarding bundle",
+                entry.address()
+            ));
+            await!(close_bundle(BundleOnClose::Discard, &context))?;
+            return Err(error);
+        }
+        modified_entries.push((action, old_entry));
+    }
+
+    // 2. Append all of them to the source chain
+    context.log("debug/workflow/close_bundle: committing bundle");
+    let addresses = await!(close_bundle(BundleOnClose::Commit, &context))?;
+
+    // 3. Publish the valid entries to DHT, unless the DNA declares their type private,
+    // and record updates and deletions in the DHT metadata of the entries they modify,
+    // unless those are private.
+    // Entries of encrypted types get sealed for their author,
+    // and updates also for the recipients of the entry they update.
+    let dna = context
+        .get_dna()
+        .ok_or_else(|| HolochainError::ErrorGeneric("No DNA set".to_string()))?;
+    for ((entry, _), (action, old_entry)) in staged_entries.iter().zip(modified_entries) {
+        let old_entry_published = old_entry
+            .as_ref()
+            .map(|old_entry| old_entry.entry_type().can_publish(&dna))
+            .unwrap_or(true);
+        if !old_entry_published || !can_publish_chain_entry(entry, &dna, &context) {
+            continue;
+        }
+        let recipients = match (&action, &old_entry) {
+            (EntryAction::Modify, Some(old_entry)) => {
+                sealed_recipients(&context, &old_entry.address())?
+            }
+            _ => Vec::new(),
+        };
+        match (action, old_entry) {
+            (EntryAction::Modify, Some(old_entry)) => {
+                await!(update_entry(
+                    &context,
+                    context.action_channel(),
+                    old_entry.address(),
+                    entry.address()
+                ))?;
+            }
+            (EntryAction::Delete, Some(old_entry)) => {
+                await!(remove_entry(
+                    &context,
+                    context.action_channel(),
+                    old_entry.address(),
+                    entry.address()
+                ))?;
+            }
+            _ => (),
+        }
+        if entry.entry_type().is_encrypted(&dna) {
+            await!(hold_sealed_entry(entry, &recipients, &context))?;
+        }
+        await!(publish(entry.address(), &context))?;
+    }
+    context.log(format!(
+        "debug/workflow/close_bundle: committed {} entries",
+        addresses.len()
+    ));
+    Ok(addresses)
+}
+
+/// What a staged entry does to the entry it modifies, if any, as it was validated
+/// when it got committed: updates and deletions name the header of the entry they
+/// modify in their crud link. That header and its entry are either staged themselves
+/// or committed before, to the source chain or by another agent to the DHT.
+fn modified_entry(
+    bundle: &Bundle,
+    entry: &Entry,
+    header: &ChainHeader,
+    context: &Arc<Context>,
+) -> Result<(EntryAction, Option<Entry>, Option<ChainHeader>), HolochainError> {
+    let old_header_address = match header.link_crud() {
+        Some(address) => address,
+        None => return Ok((EntryAction::Create, None, None)),
+    };
+    let action = match entry {
+        Entry::Deletion(_) => EntryAction::Delete,
+        _ => EntryAction::Modify,
+    };
+    let state = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Could not get state".to_string()))?;
+    let mut maybe_content = bundle
+        .scratch_chain()
+        .content_storage()
+        .read()?
+        .fetch(&old_header_address)?;
+    for storage in vec![
+        state.agent().chain_store().content_storage(),
+        state.dht().content_storage(),
+    ] {
+        if maybe_content.is_none() {
+            maybe_content = storage.read()?.fetch(&old_header_address)?;
+        }
+    }
+    let old_header = ChainHeader::try_from_content(&maybe_content.ok_or_else(|| {
+        HolochainError::ErrorGeneric(format!(
+            "Header {} modified by staged entry {} not found",
+            old_header_address,
+            entry.address()
+        ))
+    })?)?;
+    let old_entry_address = old_header.entry_address();
+    let old_entry = match bundle.get_entry(old_entry_address)? {
+        Some(old_entry) => Some(old_entry),
+        None => match get_entry_from_agent(context, old_entry_address)? {
+            Some(old_entry) => Some(old_entry),
+            None => get_entry_from_dht(context, old_entry_address)?,
+        },
+    }
+    .ok_or_else(|| {
+        HolochainError::ErrorGeneric(format!(
+            "Entry {} modified by staged entry {} not found",
+            old_entry_address,
+            entry.address()
+        ))
+    })?;
+    Ok((action, Some(old_entry), Some(old_header)))
+}
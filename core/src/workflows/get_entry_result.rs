@@ -1,4 +1,4 @@
-use crate::{context::Context, network, nucleus};
+use crate::{context::Context, network, nucleus, workflows::hold_warrant::verify_warrant};
 use holochain_core_types::{chain_header::ChainHeader, time::Timeout};
 
 use holochain_core_types::{
@@ -25,11 +25,55 @@ pub async fn get_entry_with_meta_workflow<'a>(
         return Ok(maybe_entry_with_meta);
     }
     // 2. No result, so try on the network
-    await!(network::actions::get_entry::get_entry(
+    let maybe_entry_with_meta = await!(network::actions::get_entry::get_entry(
         context.clone(),
         address.clone(),
         timeout.clone(),
-    ))
+    ))?;
+    // 3. Don't take a rejection reported by another node on trust
+    match maybe_entry_with_meta {
+        Some(entry_with_meta) if entry_with_meta.crud_status == CrudStatus::Rejected => {
+            await!(check_rejection(context, address, entry_with_meta)).map(Some)
+        }
+        result => Ok(result),
+    }
+}
+
+/// Checks the warrant a node backed its report of an entry as rejected with.
+/// A founded warrant confirms the rejection. If the entry turns out to be valid,
+/// the node tried to hide it and the entry gets reported as live instead.
+async fn check_rejection<'a>(
+    context: &'a Arc<Context>,
+    address: &'a Address,
+    entry_with_meta: EntryWithMeta,
+) -> Result<EntryWithMeta, HolochainError> {
+    let warrant = match entry_with_meta.warrant {
+        Some(ref warrant) if warrant.entry_address() == *address => warrant.clone(),
+        _ => {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Entry {} got reported as rejected without a warrant",
+                address
+            )));
+        }
+    };
+    if await!(verify_warrant(&warrant, context))? {
+        Ok(EntryWithMeta {
+            entry: warrant.entry().clone(),
+            ..entry_with_meta
+        })
+    } else {
+        context.log(format!(
+            "warn/workflow/get_entry: entry {} got reported as rejected but is valid",
+            address
+        ));
+        Ok(EntryWithMeta {
+            entry: warrant.entry().clone(),
+            crud_status: CrudStatus::Live,
+            maybe_crud_link: None,
+            crud_fork: Vec::new(),
+            warrant: None,
+        })
+    }
 }
 
 /// Get GetEntryResult workflow
@@ -76,9 +120,11 @@ pub async fn get_entry_result_workflow<'a>(
                 break;
             }
 
-            // Follow crud-link if possible
+            // Follow crud-link if possible.
+            // Rejected entries are reported as such, without following any of their links.
             if entry_with_meta.maybe_crud_link.is_some()
                 && entry_with_meta.crud_status != CrudStatus::Deleted
+                && entry_with_meta.crud_status != CrudStatus::Rejected
                 && args.options.status_request != StatusRequestKind::Initial
             {
                 maybe_address = Some(entry_with_meta.maybe_crud_link.unwrap());
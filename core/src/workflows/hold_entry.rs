@@ -1,16 +1,21 @@
 use crate::{
+    agent::verify_header_provenances,
     context::Context,
-    dht::actions::hold::hold_entry,
+    dht::actions::hold::{hold_encrypted_entry, hold_entry},
     network::{
-        actions::get_validation_package::get_validation_package, entry_with_header::EntryWithHeader,
+        actions::get_validation_package::get_validation_package,
+        entry_with_header::{EncryptedEntryWithHeader, EntryWithHeader},
     },
     nucleus::actions::validate::validate_entry,
+    workflows::hold_warrant::issue_warrant,
 };
 
 use holochain_core_types::{
-    cas::content::Address,
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    entry::{entry_type::EntryType, Entry},
     error::HolochainError,
-    validation::{EntryAction, EntryLifecycle, ValidationData},
+    validation::{EntryAction, EntryLifecycle, ValidationData, ValidationPackage},
 };
 use std::sync::Arc;
 
@@ -20,38 +25,244 @@ pub async fn hold_entry_workflow<'a>(
 ) -> Result<Address, HolochainError> {
     let EntryWithHeader { entry, header } = &entry_with_header;
 
+    // 0. Check that the header belongs to the entry
+    if entry.address() != *header.entry_address() {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Header is for entry {} but received entry {}",
+            header.entry_address(),
+            entry.address()
+        )));
+    }
+
+    // 0.1 Entries of encrypted types must only ever travel sealed
+    if context
+        .get_dna()
+        .map(|dna| entry.entry_type().is_encrypted(&dna))
+        .unwrap_or(false)
+    {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Entry {} is of an encrypted type but was not sealed",
+            entry.address()
+        )));
+    }
+
+    // 0.2 Headers we rejected before stay rejected, they failed validation definitively.
+    // The entry can still be held with another header.
+    if !context
+        .state()
+        .unwrap()
+        .dht()
+        .get_warrants(&header.address())?
+        .is_empty()
+    {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Header {} of entry {} has been rejected before",
+            header.address(),
+            entry.address()
+        )));
+    }
+
     // 1. Get validation package from source
     let maybe_validation_package = await!(get_validation_package(header.clone(), &context))?;
     let validation_package = maybe_validation_package
         .ok_or("Could not get validation package from source".to_string())?;
 
+    // 1.1 Check that the header got signed by its author(s) with the key
+    // that was current at the header's position in the source chain
+    verify_header_provenances(header, &validation_package)?;
+
     // 2. Create validation data struct
     let validation_data = ValidationData {
         package: validation_package,
         lifecycle: EntryLifecycle::Dht,
         action: EntryAction::Create,
+        old_entry: None,
+        old_entry_header: None,
     };
 
-    // 3. Validate the entry
-    await!(validate_entry(entry.clone(), validation_data, &context))?;
+    // 3. Check the author against the membrane and validate the entry.
+    // If either is invalid, record it as rejected and let the other holders know with a warrant.
+    // Errors that kept us from validating it, like a missing dependency, don't lead to a
+    // warrant, so the entry can still be held when it gets published again.
+    let validation_result =
+        match await!(validate_author(header, &validation_data.package, &context)) {
+            Ok(()) => await!(validate_entry(entry.clone(), validation_data, &context)).map(|_| ()),
+            Err(error) => Err(error),
+        };
+    match validation_result {
+        Err(HolochainError::ValidationFailed(reason)) => {
+            if let Err(error) = await!(issue_warrant(
+                entry_with_header.clone(),
+                reason.clone(),
+                &context
+            )) {
+                context.log(format!(
+                    "err/workflow/hold_entry: could not issue warrant for {}: {}",
+                    entry.address(),
+                    error
+                ));
+            }
+            return Err(HolochainError::ValidationFailed(reason));
+        }
+        result => result?,
+    };
 
-    // 3. If valid store the entry in the local DHT shard
+    // 4. If valid store the entry in the local DHT shard
     await!(hold_entry(entry_with_header, context))
 }
 
+/// Holds the sealed copy of an entry of an encrypted type.
+/// DHT nodes can't read the entry, so they can't run its validation callbacks.
+/// What they can check is that the envelope matches the header, that the DNA
+/// declares its type encrypted and that the header got signed by its author.
+pub async fn hold_encrypted_entry_workflow<'a>(
+    entry_with_header: EncryptedEntryWithHeader,
+    context: Arc<Context>,
+) -> Result<Address, HolochainError> {
+    let EncryptedEntryWithHeader {
+        encrypted_entry,
+        header,
+    } = &entry_with_header;
+
+    // 0. Check that the header belongs to the sealed entry
+    if encrypted_entry.entry_address() != header.entry_address()
+        || encrypted_entry.entry_type() != header.entry_type()
+    {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Header is for entry {} but received sealed entry {}",
+            header.entry_address(),
+            encrypted_entry.entry_address()
+        )));
+    }
+
+    // 0.1 Check that the DNA wants entries of this type sealed
+    let dna = context.get_dna().ok_or_else(|| {
+        HolochainError::ErrorGeneric("Could not get DNA to hold sealed entry".to_string())
+    })?;
+    if !encrypted_entry.entry_type().is_encrypted(&dna) {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Received sealed entry {} of type {} which is not encrypted",
+            encrypted_entry.entry_address(),
+            encrypted_entry.entry_type()
+        )));
+    }
+
+    // 1. Get validation package from source
+    let maybe_validation_package = await!(get_validation_package(header.clone(), &context))?;
+    let validation_package = maybe_validation_package
+        .ok_or("Could not get validation package from source".to_string())?;
+
+    // 2. Check that the header got signed by its author(s)
+    // and that the author passes the membrane
+    verify_header_provenances(header, &validation_package)?;
+    await!(validate_author(header, &validation_package, &context))?;
+
+    // 3. Store the sealed entry in the local DHT shard
+    await!(hold_encrypted_entry(entry_with_header, context))
+}
+
+/// Checks the author of the given header against the membrane rules of the DNA, i.e. runs
+/// the `validate_agent` callbacks on the AgentId entry the validation package came with.
+/// That has to be the entry of the newest of the package's `agent_id_headers`, which
+/// `verify_header_provenances` ties to the header's position in its source chain, so
+/// the check has to come after that one.
+/// AgentId entries themselves get checked against the membrane when they get validated,
+/// and only the DNA entry precedes the agent's initial AgentId.
+pub(crate) async fn validate_author<'a>(
+    header: &'a ChainHeader,
+    validation_package: &'a ValidationPackage,
+    context: &'a Arc<Context>,
+) -> Result<(), HolochainError> {
+    match header.entry_type() {
+        EntryType::AgentId | EntryType::Dna => return Ok(()),
+        _ => (),
+    }
+    let agent_id_header = validation_package.agent_id_headers.first().ok_or_else(|| {
+        HolochainError::ValidationFailed(format!(
+            "Header {} is not preceded by an AgentId of its author",
+            header.address()
+        ))
+    })?;
+    let author = match validation_package.author_agent_id {
+        Some(ref author) if author.address() == *agent_id_header.entry_address() => author,
+        _ => {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Header {} did not come with the AgentId of its author",
+                header.address()
+            )));
+        }
+    };
+    let mut package = ValidationPackage::only_header(agent_id_header.clone());
+    package.agent_id_headers = validation_package.agent_id_headers[1..].to_vec();
+    let validation_data = ValidationData {
+        package,
+        lifecycle: EntryLifecycle::Dht,
+        action: EntryAction::Create,
+        old_entry: None,
+        old_entry_header: None,
+    };
+    await!(validate_entry(
+        Entry::AgentId(author.clone()),
+        validation_data,
+        context
+    ))
+    .map(|_| ())
+    .map_err(|error| match error {
+        HolochainError::ValidationFailed(reason) => HolochainError::ValidationFailed(format!(
+            "Author of header {} does not pass the membrane: {}",
+            header.address(),
+            reason
+        )),
+        error => error,
+    })
+}
+
 #[cfg(test)]
-// too slow!
-#[cfg(feature = "broken-tests")]
 pub mod tests {
     use super::*;
-    use crate::{
-        network::test_utils::*, nucleus::actions::tests::*, workflows::author_entry::author_entry,
-    };
-    use futures::executor::block_on;
-    use holochain_core_types::entry::test_entry;
-    use test_utils::*;
+    use crate::nucleus::actions::{build_validation_package::build_validation_package, tests::*};
+    use holochain_core_types::agent::AgentId;
+
+    #[test]
+    /// Test that entries only pass if their author came with an AgentId that passes the membrane
+    fn test_validate_author() {
+        let (_instance, context) = instance(None);
+        let header = commit(test_entry_package_entry(), &context);
+        let validation_package = context
+            .block_on(build_validation_package(
+                &test_entry_package_entry(),
+                &context,
+            ))
+            .unwrap();
+        assert_eq!(
+            context.block_on(validate_author(&header, &validation_package, &context)),
+            Ok(())
+        );
+
+        let is_rejected = |package: &ValidationPackage| match context
+            .block_on(validate_author(&header, package, &context))
+        {
+            Err(HolochainError::ValidationFailed(_)) => true,
+            _ => false,
+        };
+        let mut package = validation_package.clone();
+        package.author_agent_id = None;
+        assert!(is_rejected(&package));
+        package.author_agent_id = Some(AgentId::generate_fake("mallory"));
+        assert!(is_rejected(&package));
+        // a membrane proof the author did not sign
+        let mut author = context.agent_id.clone();
+        author.membrane_proof = Some("invitation".to_string());
+        package.author_agent_id = Some(author);
+        assert!(is_rejected(&package));
+        let mut package = validation_package.clone();
+        package.agent_id_headers = Vec::new();
+        assert!(is_rejected(&package));
+    }
 
     #[test]
+    // too slow!
+    #[cfg(feature = "broken-tests")]
     /// Test that an invalid entry will be rejected by this workflow.
     ///
     /// This test simulates an attack where a node is changing its local copy of the DNA to
@@ -59,6 +270,10 @@ pub mod tests {
     ///
     /// hold_entry_workflow is then expected to fail in its validation step
     fn test_reject_invalid_entry_on_hold_workflow() {
+        use crate::{network::test_utils::*, workflows::author_entry::author_entry};
+        use holochain_core_types::entry::test_entry;
+        use test_utils::*;
+
         // Hacked DNA that regards everything as valid
         let hacked_dna =
             create_test_dna_with_wat("test_zome", "test_cap", Some(&test_wat_always_valid()));
@@ -1,10 +1,12 @@
 use crate::{
+    agent::verify_header_provenances,
     context::Context,
-    dht::actions::add_link::add_link,
+    dht::actions::{add_link::add_link, remove_link::remove_link},
     network::{
         actions::get_validation_package::get_validation_package, entry_with_header::EntryWithHeader,
     },
     nucleus::actions::validate::validate_entry,
+    workflows::hold_entry::validate_author,
 };
 
 use holochain_core_types::{
@@ -20,13 +22,14 @@ pub async fn hold_link_workflow<'a>(
 ) -> Result<(), HolochainError> {
     let EntryWithHeader { entry, header } = &entry_with_header;
 
-    let link_add = match entry {
-        Entry::LinkAdd(link_add) => link_add,
+    let link = match entry {
+        Entry::LinkAdd(link_add) => link_add.link().clone(),
+        Entry::LinkRemove(link_remove) => link_remove.link().clone(),
         _ => Err(HolochainError::ErrorGeneric(
-            "hold_link_workflow expects entry to be an Entry::LinkAdd".to_string(),
+            "hold_link_workflow expects entry to be an Entry::LinkAdd or Entry::LinkRemove"
+                .to_string(),
         ))?,
     };
-    let link = link_add.link().clone();
 
     context.log(format!("debug/workflow/hold_link: {:?}", link));
     // 1. Get validation package from source
@@ -38,11 +41,18 @@ pub async fn hold_link_workflow<'a>(
         .ok_or("Could not get validation package from source".to_string())?;
     context.log(format!("debug/workflow/hold_link: got validation package!"));
 
+    // 1.1 Check that the header got signed by its author(s)
+    // and that the author passes the membrane
+    verify_header_provenances(header, &validation_package)?;
+    await!(validate_author(header, &validation_package, &context))?;
+
     // 2. Create validation data struct
     let validation_data = ValidationData {
         package: validation_package,
         lifecycle: EntryLifecycle::Meta,
         action: EntryAction::Create,
+        old_entry: None,
+        old_entry_header: None,
     };
 
     // 3. Validate the entry
@@ -53,9 +63,17 @@ pub async fn hold_link_workflow<'a>(
     })?;
     context.log(format!("debug/workflow/hold_link: is valid!"));
 
-    // 3. If valid store the entry in the local DHT shard
-    await!(add_link(&link, &context))?;
-    context.log(format!("debug/workflow/hold_link: added! {:?}", link));
+    // 3. If valid store the link (or its removal) in the local DHT shard
+    match entry {
+        Entry::LinkRemove(_) => {
+            await!(remove_link(&link, &context))?;
+            context.log(format!("debug/workflow/hold_link: removed! {:?}", link));
+        }
+        _ => {
+            await!(add_link(&link, &context))?;
+            context.log(format!("debug/workflow/hold_link: added! {:?}", link));
+        }
+    }
     Ok(())
 }
 
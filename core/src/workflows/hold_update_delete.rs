@@ -0,0 +1,433 @@
+use crate::{
+    agent::{actions::update_entry::update_entry, verify_header_provenances},
+    context::Context,
+    dht::actions::{hold::hold_entry, remove_entry::remove_entry, resolve_conflict},
+    network::{
+        actions::get_validation_package::get_validation_package, entry_with_header::EntryWithHeader,
+    },
+    nucleus::{
+        actions::{
+            get_entry::{
+                get_entry_crud_links_from_dht, get_entry_crud_meta_from_dht, get_entry_from_dht,
+            },
+            validate::validate_entry,
+        },
+        ribosome::callback::{self, CallbackResult},
+    },
+    workflows::hold_entry::validate_author,
+};
+
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    chain_header::ChainHeader,
+    crud_status::CrudStatus,
+    dna::entry_types::ConflictResolution,
+    entry::{entry_type::EntryType, Entry},
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+};
+use holochain_wasm_utils::api_serialization::resolve_conflict::ConflictingUpdate;
+use std::{cmp::Ordering, sync::Arc};
+
+/// Validates an update or a deletion (`entry_with_header`) of an entry we hold
+/// and, if valid, records it in the CRUD metadata of that entry.
+/// Only the nodes holding the old entry have it and the header it got committed with at hand,
+/// so this is where updates and deletions get validated with EntryAction::Modify or
+/// EntryAction::Delete and the old entry in the validation data.
+/// Updates of an entry that already got updated concurrently get recorded next to the others
+/// and the conflict gets resolved with the strategy of the entry's type.
+pub async fn hold_update_delete_workflow<'a>(
+    old_entry_address: Address,
+    entry_with_header: EntryWithHeader,
+    context: Arc<Context>,
+) -> Result<(), HolochainError> {
+    let EntryWithHeader { entry, header } = &entry_with_header;
+
+    // 0. Check that the header belongs to the entry
+    if entry.address() != *header.entry_address() {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Header is for entry {} but received entry {}",
+            header.entry_address(),
+            entry.address()
+        )));
+    }
+
+    // 0.1 Check that the entry modifies the given entry
+    let action = match entry {
+        Entry::Deletion(deletion_entry)
+            if deletion_entry.clone().deleted_entry_address() == old_entry_address
+                && header.link_crud().is_some() =>
+        {
+            EntryAction::Delete
+        }
+        Entry::App(_, _) | Entry::AgentId(_) if header.link_crud().is_some() => EntryAction::Modify,
+        _ => {
+            return Err(HolochainError::ValidationFailed(format!(
+                "Entry {} is neither an update nor a deletion of entry {}",
+                entry.address(),
+                old_entry_address
+            )));
+        }
+    };
+
+    // 0.2 Nothing to do if we already recorded this modification, unless it still waits
+    // for the resolution of a conflict with a concurrent update.
+    // Concurrent updates of the same app entry get recorded side by side.
+    // An agent's key can only be rotated once from each key.
+    let is_update = match (&action, entry) {
+        (EntryAction::Modify, Entry::App(_, _)) => true,
+        _ => false,
+    };
+    let crud_links = get_entry_crud_links_from_dht(&context, &old_entry_address)?;
+    let crud_status = get_entry_crud_meta_from_dht(&context, old_entry_address.clone())?
+        .map(|(status, _)| status);
+    if crud_links.contains(&entry.address()) {
+        return match crud_status {
+            Some(CrudStatus::Locked) => {
+                await!(resolve_concurrent_updates(old_entry_address, &context))
+            }
+            _ => Ok(()),
+        };
+    }
+    match crud_status {
+        Some(CrudStatus::Live) => (),
+        Some(CrudStatus::Modified) | Some(CrudStatus::Locked) if is_update => (),
+        Some(_) => {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Entry {} has already been modified or deleted",
+                old_entry_address
+            )));
+        }
+        None => {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Entry {} is not held in the local DHT shard",
+                old_entry_address
+            )));
+        }
+    }
+
+    // 1. Get the old entry and the header it got committed with from the local DHT shard.
+    // Updates and deletions name that header in their crud link, and get validated
+    // with exactly that header.
+    let old_entry = get_entry_from_dht(&context, &old_entry_address)?.ok_or_else(|| {
+        HolochainError::ErrorGeneric(format!(
+            "Entry {} is not held in the local DHT shard",
+            old_entry_address
+        ))
+    })?;
+    let old_headers = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Could not get state".to_string()))?
+        .get_headers(old_entry_address.clone())?;
+    let old_entry_header = old_headers
+        .into_iter()
+        .find(|old_header| Some(old_header.address()) == header.link_crud())
+        .ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "Header {:?} of entry {} not found in the local DHT shard",
+                header.link_crud(),
+                old_entry_address
+            ))
+        })?;
+
+    // 2. Get validation package from source
+    let maybe_validation_package = await!(get_validation_package(header.clone(), &context))?;
+    let validation_package = maybe_validation_package
+        .ok_or("Could not get validation package from source".to_string())?;
+
+    // 2.1 Check that the header got signed by its author(s)
+    // and that the author passes the membrane
+    verify_header_provenances(header, &validation_package)?;
+    await!(validate_author(header, &validation_package, &context))?;
+
+    // 3. Create validation data struct
+    let validation_data = ValidationData {
+        package: validation_package,
+        lifecycle: EntryLifecycle::Meta,
+        action: action.clone(),
+        old_entry: Some(old_entry),
+        old_entry_header: Some(old_entry_header),
+    };
+
+    // 4. Validate the update or deletion
+    await!(validate_entry(entry.clone(), validation_data, &context))?;
+
+    // 5. If valid record it in the CRUD metadata of the old entry.
+    // We hold updates ourselves, so that their headers are at hand for resolving conflicts.
+    match action {
+        EntryAction::Modify => {
+            await!(hold_entry(entry_with_header.clone(), context.clone()))?;
+            await!(update_entry(
+                &context,
+                context.action_channel(),
+                old_entry_address.clone(),
+                entry.address()
+            ))?;
+            if crud_links.is_empty() {
+                Ok(())
+            } else {
+                await!(resolve_concurrent_updates(old_entry_address, &context))
+            }
+        }
+        _ => await!(remove_entry(
+            &context,
+            context.action_channel(),
+            old_entry_address,
+            entry.address()
+        )),
+    }
+}
+
+/// Picks the latest version of an entry that got updated concurrently with the conflict
+/// resolution strategy of the entry's type and records it in the entry's CRUD metadata,
+/// which unlocks the entry.
+/// Every node holding the entry picks the same update, since the strategies only depend on
+/// the competing updates, which get handed to them ordered by address.
+async fn resolve_concurrent_updates<'a>(
+    old_entry_address: Address,
+    context: &'a Arc<Context>,
+) -> Result<(), HolochainError> {
+    let old_entry = get_entry_from_dht(context, &old_entry_address)?.ok_or_else(|| {
+        HolochainError::ErrorGeneric(format!(
+            "Entry {} is not held in the local DHT shard",
+            old_entry_address
+        ))
+    })?;
+    let app_entry_type = match old_entry.entry_type() {
+        EntryType::App(app_entry_type) => app_entry_type,
+        _ => {
+            return Err(HolochainError::ErrorGeneric(format!(
+                "Entry {} is not an app entry and can't have concurrent updates",
+                old_entry_address
+            )));
+        }
+    };
+
+    // 1. Collect the competing updates with their headers.
+    // Agents committing the same update content share the entry, each with a header of its own,
+    // of which every node has to pick the same.
+    let state = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Could not get state".to_string()))?;
+    let mut updates = Vec::new();
+    for address in get_entry_crud_links_from_dht(context, &old_entry_address)? {
+        let entry = get_entry_from_dht(context, &address)?;
+        let header = latest_header(state.get_headers(address.clone())?);
+        match (entry, header) {
+            (Some(entry), Some(header)) => updates.push(ConflictingUpdate { entry, header }),
+            _ => {
+                return Err(HolochainError::ErrorGeneric(format!(
+                    "Update {} of entry {} is not held in the local DHT shard yet",
+                    address, old_entry_address
+                )));
+            }
+        }
+    }
+
+    // 2. Let the strategy of the entry's type pick the winner
+    let conflict_resolution = context
+        .get_dna()
+        .and_then(|dna| {
+            dna.get_entry_type_def(&String::from(app_entry_type.clone()))
+                .map(|entry_type_def| entry_type_def.conflict_resolution.clone())
+        })
+        .unwrap_or_default();
+    let callback_winner = match conflict_resolution {
+        ConflictResolution::LastWriterWins => None,
+        ConflictResolution::Callback => match callback::resolve_conflict::resolve_conflict(
+            context.clone(),
+            app_entry_type,
+            old_entry,
+            updates.clone(),
+        )? {
+            CallbackResult::ConflictWinner(winner) => Some(winner),
+            result => {
+                context.log(format!(
+                    "warn/workflow/resolve_conflict: falling back to last-writer-wins for {}: {:?}",
+                    old_entry_address, result
+                ));
+                None
+            }
+        },
+    };
+    let winner = match callback_winner {
+        Some(winner) => winner,
+        None => last_writer_wins(&updates).ok_or_else(|| {
+            HolochainError::ErrorGeneric(format!(
+                "No updates of entry {} to resolve",
+                old_entry_address
+            ))
+        })?,
+    };
+
+    // 3. Record the winner as the entry's latest version
+    context.log(format!(
+        "debug/workflow/resolve_conflict: {} wins over the concurrent updates of {}",
+        winner, old_entry_address
+    ));
+    await!(resolve_conflict::resolve_conflict(
+        context,
+        old_entry_address,
+        winner
+    ))
+    .map(|_| ())
+}
+
+/// The header with the latest timestamp.
+/// Ties, as well as timestamps that can't be compared, are decided by the higher header address.
+fn latest_header(headers: Vec<ChainHeader>) -> Option<ChainHeader> {
+    headers.into_iter().max_by(|a, b| {
+        a.timestamp()
+            .partial_cmp(b.timestamp())
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| a.address().cmp(&b.address()))
+    })
+}
+
+/// The update with the latest header timestamp wins.
+/// Ties, as well as timestamps that can't be compared, are decided by the higher entry address.
+fn last_writer_wins(updates: &[ConflictingUpdate]) -> Option<Address> {
+    updates
+        .iter()
+        .max_by(|a, b| {
+            a.header
+                .timestamp()
+                .partial_cmp(b.header.timestamp())
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| a.entry.address().cmp(&b.entry.address()))
+        })
+        .map(|update| update.entry.address())
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::instance::tests::test_context;
+    use holochain_core_types::{
+        chain_header::{test_provenances, ChainHeader},
+        entry::{
+            deletion_entry::DeletionEntry, entry_type::EntryType, test_entry, test_entry_a,
+            test_entry_b,
+        },
+        time::{test_iso_8601, Iso8601},
+    };
+
+    #[test]
+    /// Test that only deletions of the given entry and updates are accepted as modifications
+    fn test_reject_entry_not_modifying_old_entry() {
+        let context = test_context("alice", None);
+        let deletion = Entry::Deletion(DeletionEntry::new(test_entry_b().address()));
+        let header = ChainHeader::new(
+            &EntryType::Deletion,
+            &deletion.address(),
+            &test_provenances("sig"),
+            &None,
+            &None,
+            &Some(test_entry_b().address()),
+            &test_iso_8601(),
+        );
+        let entry_with_header = EntryWithHeader {
+            entry: deletion,
+            header,
+        };
+
+        let result = context.block_on(hold_update_delete_workflow(
+            test_entry().address(),
+            entry_with_header,
+            context.clone(),
+        ));
+        assert!(match result {
+            Err(HolochainError::ValidationFailed(_)) => true,
+            _ => false,
+        });
+
+        // deletions have to name the header of the deleted entry
+        let deletion = Entry::Deletion(DeletionEntry::new(test_entry().address()));
+        let header = ChainHeader::new(
+            &EntryType::Deletion,
+            &deletion.address(),
+            &test_provenances("sig"),
+            &None,
+            &None,
+            &None,
+            &test_iso_8601(),
+        );
+        let result = context.block_on(hold_update_delete_workflow(
+            test_entry().address(),
+            EntryWithHeader {
+                entry: deletion,
+                header,
+            },
+            context.clone(),
+        ));
+        assert!(match result {
+            Err(HolochainError::ValidationFailed(_)) => true,
+            _ => false,
+        });
+    }
+
+    fn test_update(entry: Entry, timestamp: &str) -> ConflictingUpdate {
+        let header = ChainHeader::new(
+            &entry.entry_type(),
+            &entry.address(),
+            &test_provenances("sig"),
+            &None,
+            &None,
+            &Some(test_entry().address()),
+            &Iso8601::from(timestamp),
+        );
+        ConflictingUpdate { entry, header }
+    }
+
+    #[test]
+    /// Test that last-writer-wins picks the same update no matter in which order it gets them
+    fn test_last_writer_wins() {
+        let early = test_update(test_entry_a(), "2018-10-11T03:23:38+00:00");
+        let late = test_update(test_entry_b(), "2018-10-11T03:24:38+00:00");
+        assert_eq!(
+            last_writer_wins(&[early.clone(), late.clone()]),
+            Some(test_entry_b().address())
+        );
+        assert_eq!(
+            last_writer_wins(&[late, early]),
+            Some(test_entry_b().address())
+        );
+
+        // without comparable timestamps the higher address wins
+        let a = test_update(test_entry_a(), "");
+        let b = test_update(test_entry_b(), "");
+        let higher_address = test_entry_a().address().max(test_entry_b().address());
+        assert_eq!(
+            last_writer_wins(&[a.clone(), b.clone()]),
+            Some(higher_address.clone())
+        );
+        assert_eq!(last_writer_wins(&[b, a]), Some(higher_address));
+        assert_eq!(last_writer_wins(&[]), None);
+    }
+
+    #[test]
+    /// Test that every node picks the same header for an update that several agents committed
+    fn test_latest_header() {
+        let early = test_update(test_entry_a(), "2018-10-11T03:23:38+00:00").header;
+        let late = test_update(test_entry_a(), "2018-10-11T03:24:38+00:00").header;
+        assert_eq!(
+            latest_header(vec![early.clone(), late.clone()]),
+            Some(late.clone())
+        );
+        assert_eq!(latest_header(vec![late.clone(), early]), Some(late));
+
+        let a = test_update(test_entry_a(), "").header;
+        let b = test_update(test_entry_b(), "").header;
+        let higher_address = if a.address() > b.address() {
+            a.clone()
+        } else {
+            b.clone()
+        };
+        assert_eq!(
+            latest_header(vec![a.clone(), b.clone()]),
+            Some(higher_address.clone())
+        );
+        assert_eq!(latest_header(vec![b, a]), Some(higher_address));
+        assert_eq!(latest_header(Vec::new()), None);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    agent::{verify_header_provenances, verify_signature},
+    context::Context,
+    dht::actions::hold::hold_warrant,
+    network::{
+        actions::{get_validation_package::get_validation_package, publish::publish_warrant},
+        entry_with_header::EntryWithHeader,
+    },
+    nucleus::actions::validate::validate_entry,
+    workflows::hold_entry::validate_author,
+};
+
+use holochain_core_types::{
+    cas::content::{Address, AddressableContent},
+    error::HolochainError,
+    validation::{EntryAction, EntryLifecycle, ValidationData},
+    warrant::Warrant,
+};
+use std::sync::Arc;
+
+/// Issues a warrant for an entry that failed validation on this node with the given header:
+/// the header gets recorded as rejected in the local DHT shard and the warrant,
+/// signed by our agent, gets published to the other nodes holding the entry.
+pub(crate) async fn issue_warrant<'a>(
+    entry_with_header: EntryWithHeader,
+    reason: String,
+    context: &'a Arc<Context>,
+) -> Result<Address, HolochainError> {
+    let EntryWithHeader { entry, header } = entry_with_header;
+    let signature = context.sign(Warrant::payload(&header, &reason))?;
+    let warrant = Warrant::new(
+        entry,
+        header,
+        reason,
+        context.agent_signing_key()?,
+        signature,
+    );
+    context.log(format!(
+        "debug/workflow/issue_warrant: rejecting {}",
+        warrant.entry_address()
+    ));
+    await!(hold_warrant(warrant.clone(), context.clone()))?;
+    await!(publish_warrant(warrant, context))
+}
+
+/// Checks a warrant another node published for an entry and records the warrant's header as
+/// rejected if the warrant is founded, i.e. if the entry fails validation on this node, too.
+/// A warrant for an entry that is valid gets refused, so a single dishonest node can't
+/// get valid entries rejected.
+pub async fn hold_warrant_workflow<'a>(
+    warrant: Warrant,
+    context: Arc<Context>,
+) -> Result<Address, HolochainError> {
+    // 0. Check that the warrant is for the entry of its header and got signed by its issuer
+    verify_warrant_signature(&warrant)?;
+
+    // 1. Nothing to do for a warrant we hold already,
+    // and no need to validate an entry with a header we rejected before
+    let held_warrants = context
+        .state()
+        .ok_or_else(|| HolochainError::ErrorGeneric("Could not get state".to_string()))?
+        .dht()
+        .get_warrants(&warrant.header().address())?;
+    if held_warrants.contains(&warrant) {
+        return Ok(warrant.address());
+    }
+    if !held_warrants.is_empty() {
+        return await!(hold_warrant(warrant, context));
+    }
+
+    // 2. Record the header as rejected if the entry is invalid with it
+    if await!(warrant_is_founded(&warrant, &context))? {
+        await!(hold_warrant(warrant, context))
+    } else {
+        Err(HolochainError::ValidationFailed(format!(
+            "Warrant for entry {} by {} is unfounded, the entry is valid",
+            warrant.entry_address(),
+            warrant.issuer()
+        )))
+    }
+}
+
+/// Checks a warrant without holding it: Ok(true) if it got signed by its issuer and
+/// the entry fails validation on this node, too, Ok(false) if the entry is valid.
+pub(crate) async fn verify_warrant<'a>(
+    warrant: &'a Warrant,
+    context: &'a Arc<Context>,
+) -> Result<bool, HolochainError> {
+    verify_warrant_signature(warrant)?;
+    await!(warrant_is_founded(warrant, context))
+}
+
+fn verify_warrant_signature(warrant: &Warrant) -> Result<(), HolochainError> {
+    let header = warrant.header();
+    if warrant.entry_address() != *header.entry_address() {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Header is for entry {} but warrant is for entry {}",
+            header.entry_address(),
+            warrant.entry_address()
+        )));
+    }
+    if !verify_signature(
+        warrant.issuer(),
+        warrant.signature(),
+        Warrant::payload(header, warrant.reason()),
+    )? {
+        return Err(HolochainError::ValidationFailed(format!(
+            "Signature of warrant for entry {} by {} is invalid",
+            warrant.entry_address(),
+            warrant.issuer()
+        )));
+    }
+    Ok(())
+}
+
+/// Validates the entry of a warrant ourselves. Errors that keep us from validating it,
+/// like a missing validation package, are returned instead of deciding either way.
+async fn warrant_is_founded<'a>(
+    warrant: &'a Warrant,
+    context: &'a Arc<Context>,
+) -> Result<bool, HolochainError> {
+    let header = warrant.header();
+
+    // 1. Get validation package from source
+    let maybe_validation_package = await!(get_validation_package(header.clone(), context))?;
+    let validation_package = maybe_validation_package
+        .ok_or("Could not get validation package from source".to_string())?;
+
+    // 2. Validate the entry
+    let validation_data = ValidationData {
+        package: validation_package.clone(),
+        lifecycle: EntryLifecycle::Dht,
+        action: EntryAction::Create,
+        old_entry: None,
+        old_entry_header: None,
+    };
+    let validation_result = match verify_header_provenances(header, &validation_package) {
+        Ok(()) => match await!(validate_author(header, &validation_package, context)) {
+            Ok(()) => await!(validate_entry(
+                warrant.entry().clone(),
+                validation_data,
+                context
+            ))
+            .map(|_| ()),
+            Err(error) => Err(error),
+        },
+        Err(error) => Err(error),
+    };
+
+    match validation_result {
+        Err(HolochainError::ValidationFailed(_)) => Ok(true),
+        Err(error) => Err(error),
+        Ok(()) => Ok(false),
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::instance::tests::test_context_with_state;
+    use holochain_core_types::{
+        chain_header::test_chain_header, entry::test_entry, signature::Signature,
+    };
+
+    #[test]
+    /// Test that warrants without a valid signature of their issuer get refused
+    /// and don't mark the entry as rejected
+    fn test_reject_warrant_with_invalid_signature() {
+        let context = test_context_with_state(None);
+        let warrant = Warrant::new(
+            test_entry(),
+            test_chain_header(),
+            "FAIL".to_string(),
+            Address::from("issuer"),
+            Signature::from("sig"),
+        );
+
+        let result = context.block_on(hold_warrant_workflow(warrant, context.clone()));
+        assert!(match result {
+            Err(HolochainError::ValidationFailed(_)) => true,
+            _ => false,
+        });
+        assert_eq!(
+            context
+                .state()
+                .unwrap()
+                .dht()
+                .get_warrants(&test_chain_header().address()),
+            Ok(Vec::new())
+        );
+    }
+}
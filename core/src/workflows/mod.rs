@@ -1,7 +1,10 @@
 pub mod application;
 pub mod author_entry;
+pub mod close_bundle;
 pub mod get_entry_result;
 pub mod handle_custom_direct_message;
 pub mod hold_entry;
 pub mod hold_link;
+pub mod hold_update_delete;
+pub mod hold_warrant;
 pub mod respond_validation_package_request;
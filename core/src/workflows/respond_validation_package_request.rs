@@ -30,9 +30,15 @@ pub async fn respond_validation_package_request(
     requested_entry_address: Address,
     context: Arc<Context>,
 ) {
-    let maybe_validation_package = match get_entry(&requested_entry_address, &context) {
-        Ok(entry) => await!(build_validation_package(&entry, &context)).ok(),
-        Err(_) => None,
+    // Private entries never leave this node, not even to get validated
+    let maybe_validation_package = match (
+        get_entry(&requested_entry_address, &context),
+        context.get_dna(),
+    ) {
+        (Ok(entry), Some(dna)) if entry.entry_type().can_publish(&dna) => {
+            await!(build_validation_package(&entry, &context)).ok()
+        }
+        _ => None,
     };
 
     let direct_message = DirectMessage::ValidationPackage(maybe_validation_package);
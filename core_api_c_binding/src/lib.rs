@@ -3,6 +3,7 @@ extern crate holochain_cas_implementations;
 extern crate holochain_conductor_api;
 extern crate holochain_core;
 extern crate holochain_core_types;
+extern crate holochain_dpki;
 extern crate holochain_net;
 
 use holochain_conductor_api::{context_builder::ContextBuilder, Holochain};
@@ -12,7 +13,8 @@ use holochain_core_types::{cas::content::Address, dna::Dna, error::HolochainErro
 use std::sync::Arc;
 
 use holochain_core::logger::Logger;
-use holochain_core_types::{agent::AgentId, dna::capabilities::CapabilityCall};
+use holochain_core_types::dna::capabilities::{CallSignature, CapabilityCall};
+use holochain_dpki::keypair::Keypair;
 use std::{
     ffi::{CStr, CString},
     os::raw::c_char,
@@ -57,9 +59,11 @@ pub unsafe extern "C" fn holochain_load(storage_path: CStrPtr) -> *mut Holochain
 }
 
 fn get_context(path: &String) -> Result<Context, HolochainError> {
-    let agent = AgentId::generate_fake("c_bob");
+    let keypair = Keypair::new_from_insecure_string("c_bob")?;
+    let agent = keypair.agent_id("c_bob")?;
     Ok(ContextBuilder::new()
         .with_agent(agent)
+        .with_agent_keypair(keypair)
         .with_file_storage(path.clone())?
         .spawn())
 }
@@ -110,7 +114,11 @@ pub unsafe extern "C" fn holochain_call(
 
     match holochain.call(
         zome.as_str(),
-        Some(CapabilityCall::new(Address::from(token.as_str()), None)),
+        Some(CapabilityCall::new(
+            Address::from(token.as_str()),
+            None,
+            CallSignature::default(),
+        )),
         function.as_str(),
         parameters.as_str(),
     ) {
@@ -5,6 +5,7 @@ use crate::{
     entry::Entry,
     error::HcResult,
     json::JsonString,
+    signature::{domain_payload, Signature, MEMBRANE_PROOF_DOMAIN},
 };
 
 use std::{convert::TryFrom, str};
@@ -84,6 +85,15 @@ pub struct AgentId {
     pub nick: String,
     /// the base64url encoded public identity string for this agent
     pub key: String,
+    /// proof that the agent may join the network (e.g. an invitation), which
+    /// the DNA's `validate_agent` callbacks check on genesis and on every holder
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub membrane_proof: Option<String>,
+    /// signature of the `membrane_proof_payload()` by this agent's key.
+    /// The address of an AgentId only covers the key, so without this signature
+    /// anybody passing the entry on could swap its membrane proof.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub membrane_proof_signature: Option<Signature>,
 }
 
 impl AgentId {
@@ -109,9 +119,29 @@ impl AgentId {
         AgentId {
             nick: nick.to_string(),
             key: key.render(),
+            membrane_proof: None,
+            membrane_proof_signature: None,
         }
     }
 
+    /// the same agent, presenting the given membrane proof when joining the network.
+    /// The proof gets signed when the AgentId entry is committed at genesis.
+    pub fn with_membrane_proof<S: Into<String>>(mut self, membrane_proof: S) -> Self {
+        self.membrane_proof = Some(membrane_proof.into());
+        self.membrane_proof_signature = None;
+        self
+    }
+
+    /// the data the agent signs to bind its membrane proof to its key, if it has a proof
+    pub fn membrane_proof_payload(&self) -> Option<String> {
+        self.membrane_proof.as_ref().map(|membrane_proof| {
+            domain_payload(
+                MEMBRANE_PROOF_DOMAIN,
+                &format!("{}:{}", self.key, membrane_proof),
+            )
+        })
+    }
+
     /// get a key buffer based on this agent's key (no correction)
     pub fn to_buffer(&self) -> KeyBuffer {
         let s = self.key.replace("-", "+").replace("_", "/");
@@ -255,4 +285,36 @@ mod tests {
             AgentId::try_from_content(&expected_content).unwrap(),
         );
     }
+
+    #[test]
+    /// the membrane proof is part of the content but not of the address
+    fn agent_with_membrane_proof_test() {
+        assert_eq!(test_agent_id().membrane_proof_payload(), None);
+        let agent_id = test_agent_id().with_membrane_proof("invitation");
+        // the payload the agent signs covers both its key and its proof
+        let payload = agent_id.membrane_proof_payload().unwrap();
+        assert!(payload.contains(&agent_id.key));
+        assert!(payload.contains("invitation"));
+        assert_ne!(
+            test_agent_id()
+                .with_membrane_proof("other invitation")
+                .membrane_proof_payload(),
+            Some(payload),
+        );
+
+        let mut signed_agent_id = agent_id.clone();
+        signed_agent_id.membrane_proof_signature = Some(Signature::from("signature"));
+        assert_eq!(
+            AgentId::try_from_content(&signed_agent_id.content()).unwrap(),
+            signed_agent_id,
+        );
+        assert_ne!(signed_agent_id.content(), agent_id.content());
+        // setting another proof drops the signature of the previous one
+        assert_eq!(
+            signed_agent_id
+                .with_membrane_proof("other invitation")
+                .membrane_proof_signature,
+            None
+        );
+    }
 }
@@ -99,6 +99,26 @@ impl AddressableContent for OtherExampleAddressableContent {
     }
 }
 
+/// Checks that the given content really belongs to the given address by hashing it again.
+/// Only addresses that are multihashes can be checked like that. Other addresses, like agent keys
+/// or the fixed address of agent state snapshots, get accepted as they are.
+pub fn verify_content(address: &Address, content: &Content) -> Result<(), HolochainError> {
+    match address.hash_type() {
+        Some(hash_type) => {
+            let actual_address = Address::encode_from_str(&String::from(content), hash_type);
+            if actual_address == *address {
+                Ok(())
+            } else {
+                Err(HolochainError::ErrorGeneric(format!(
+                    "Content stored under {} hashes to {}",
+                    address, actual_address
+                )))
+            }
+        }
+        None => Ok(()),
+    }
+}
+
 pub struct AddressableContentTestSuite;
 
 impl AddressableContentTestSuite {
@@ -172,12 +192,29 @@ impl AddressableContentTestSuite {
 pub mod tests {
     use crate::{
         cas::content::{
-            Address, AddressableContent, AddressableContentTestSuite, ExampleAddressableContent,
-            OtherExampleAddressableContent,
+            verify_content, Address, AddressableContent, AddressableContentTestSuite,
+            ExampleAddressableContent, OtherExampleAddressableContent,
         },
         json::{JsonString, RawString},
     };
 
+    #[test]
+    /// show that content only verifies against its own hash
+    fn verify_content_test() {
+        let content = JsonString::from(RawString::from("foo"));
+        let other_content = JsonString::from(RawString::from("bar"));
+        let address = ExampleAddressableContent::try_from_content(&content)
+            .unwrap()
+            .address();
+        assert_eq!(verify_content(&address, &content), Ok(()));
+        assert!(verify_content(&address, &other_content).is_err());
+        // addresses that are no hashes can't be verified
+        assert_eq!(
+            verify_content(&Address::from("AgentState"), &content),
+            Ok(())
+        );
+    }
+
     #[test]
     /// test the first example
     fn example_addressable_content_trait_test() {
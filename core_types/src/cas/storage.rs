@@ -34,6 +34,9 @@ pub trait ContentAddressableStorage: objekt::Clone + Send + Sync + Debug {
     /// AddressableContent::from_content() can be used to allow the compiler to infer the type
     /// @see the fetch implementation for ExampleCas in the cas module tests
     fn fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError>;
+    /// returns the Addresses of all content in the Store, in no particular order.
+    /// used to copy a whole store, e.g. when migrating to another implementation.
+    fn addresses(&self) -> Result<Vec<Address>, HolochainError>;
     //needed to find a way to compare two different CAS for partialord derives.
     //easiest solution was to just compare two ids which are based on uuids
     fn get_id(&self) -> Uuid;
@@ -83,6 +86,10 @@ impl ContentAddressableStorage for ExampleContentAddressableStorage {
         Ok(self.content.read()?.unthreadable_fetch(address)?)
     }
 
+    fn addresses(&self) -> Result<Vec<Address>, HolochainError> {
+        Ok(self.content.read()?.unthreadable_addresses())
+    }
+
     fn get_id(&self) -> Uuid {
         Uuid::new_v4()
     }
@@ -117,6 +124,10 @@ impl ExampleContentAddressableStorageContent {
     fn unthreadable_fetch(&self, address: &Address) -> Result<Option<Content>, HolochainError> {
         Ok(self.storage.get(address).cloned())
     }
+
+    fn unthreadable_addresses(&self) -> Vec<Address> {
+        self.storage.keys().cloned().collect()
+    }
 }
 
 // A struct for our test suite that infers a type of ContentAddressableStorage
@@ -189,6 +200,15 @@ where
                 Ok(Some(other_content.clone())),
                 cas.fetch(&other_content.address())
             );
+            assert_eq!(
+                vec![content.address(), other_content.address()]
+                    .into_iter()
+                    .collect::<BTreeSet<_>>(),
+                cas.addresses()
+                    .expect("could not list addresses")
+                    .into_iter()
+                    .collect::<BTreeSet<_>>()
+            );
         }
 
         // show consistent view on data across threads
@@ -392,6 +412,107 @@ impl EavTestSuite {
         }
     }
 
+    pub fn test_attribute_prefix<A, S>(mut eav_storage: S)
+    where
+        A: AddressableContent + Clone,
+        S: EntityAttributeValueStorage,
+    {
+        let one = A::try_from_content(&Content::from(RawString::from("foo")))
+            .expect("could not create AddressableContent from Content");
+        let two = A::try_from_content(&Content::from(RawString::from("bar")))
+            .expect("could not create AddressableContent from Content");
+
+        let mut add = |entity: &A, attribute: &str| {
+            let eav = EntityAttributeValueIndex::new(
+                &entity.address(),
+                &attribute.to_string(),
+                &two.address(),
+            )
+            .expect("could not create EAV");
+            eav_storage
+                .add_eavi(&eav)
+                .expect("could not add eav")
+                .expect("Could not get eavi option")
+        };
+        let one_a = add(&one, "prefix__a");
+        let one_b = add(&one, "prefix__b");
+        add(&one, "prefixed");
+        add(&one, "other");
+        let two_a = add(&two, "prefix__a");
+
+        let expected_one: BTreeSet<_> = vec![one_a.clone(), one_b.clone()].into_iter().collect();
+        assert_eq!(
+            expected_one,
+            eav_storage
+                .fetch_eavi_by_attribute_prefix(
+                    Some(one.address()),
+                    "prefix__",
+                    IndexQuery::default()
+                )
+                .expect("could not fetch eav"),
+        );
+
+        let expected_all: BTreeSet<_> = vec![one_a, one_b, two_a].into_iter().collect();
+        assert_eq!(
+            expected_all,
+            eav_storage
+                .fetch_eavi_by_attribute_prefix(None, "prefix__", IndexQuery::default())
+                .expect("could not fetch eav"),
+        );
+    }
+
+    pub fn test_limit<A, S>(mut eav_storage: S)
+    where
+        A: AddressableContent + Clone,
+        S: EntityAttributeValueStorage,
+    {
+        let one = A::try_from_content(&Content::from(RawString::from("foo")))
+            .expect("could not create AddressableContent from Content");
+        let attribute = "limit".to_string();
+
+        let eavis: Vec<EntityAttributeValueIndex> = (0..5)
+            .map(|i| {
+                let value = A::try_from_content(&Content::from(RawString::from(i.to_string())))
+                    .expect("could not create AddressableContent from Content");
+                let eav =
+                    EntityAttributeValueIndex::new(&one.address(), &attribute, &value.address())
+                        .expect("could not create EAV");
+                eav_storage
+                    .add_eavi(&eav)
+                    .expect("could not add eav")
+                    .expect("Could not get eavi option")
+            })
+            .collect();
+
+        // the limit keeps the first EAVIs in index order
+        let expected: BTreeSet<_> = eavis[0..2].iter().cloned().collect();
+        assert_eq!(
+            expected,
+            eav_storage
+                .fetch_eavi(
+                    Some(one.address()),
+                    Some(attribute.clone()),
+                    None,
+                    IndexQuery::default().with_limit(2)
+                )
+                .expect("could not fetch eav"),
+        );
+
+        // and applies after the range
+        let expected: BTreeSet<_> = eavis[1..3].iter().cloned().collect();
+        assert_eq!(
+            expected,
+            eav_storage
+                .fetch_eavi(
+                    Some(one.address()),
+                    Some(attribute.clone()),
+                    None,
+                    IndexQuery::new(eavis[1].index(), eavis[4].index()).with_limit(2)
+                )
+                .expect("could not fetch eav"),
+        );
+    }
+
     pub fn test_range<A, S>(mut eav_storage: S)
     where
         A: AddressableContent + Clone,
@@ -11,11 +11,16 @@ use crate::{
     },
     error::HolochainError,
     json::JsonString,
-    signature::Signature,
+    signature::{domain_payload, Signature, PROVENANCE_DOMAIN},
     time::{test_iso_8601, Iso8601},
 };
 use std::convert::TryInto;
 
+/// The string the authors of a header sign as its provenance
+pub fn provenance_payload(entry_address: &Address) -> String {
+    domain_payload(PROVENANCE_DOMAIN, &entry_address.to_string())
+}
+
 /// ChainHeader of a source chain "Item"
 /// The address of the ChainHeader is used as the Item's key in the source chain hash table
 /// ChainHeaders are linked to next header in chain and next header of same type in chain
@@ -1,4 +1,124 @@
-//! NOT YET AVAILABLE
+//! A ChainMigrate entry marks the point where an agent's source chain gets migrated,
+//! e.g. because the agent moves to a new version of the DNA.
+//! The old chain gets closed with a `MigrationKind::Close` entry pointing to the successor
+//! chain, and the new chain may be opened with a `MigrationKind::Open` entry pointing back.
+//! No entries can be committed on top of a chain that was closed.
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
-pub struct ChainMigrate {}
+use crate::{cas::content::Address, error::HolochainError, json::JsonString};
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DefaultJson)]
+pub enum MigrationKind {
+    /// This chain ends here and is continued by the referenced chain
+    Close,
+    /// This chain continues the referenced chain
+    Open,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DefaultJson)]
+pub struct ChainMigrate {
+    kind: MigrationKind,
+    /// address of the DNA of the referenced chain
+    dna_address: Address,
+    /// address of the agent on the referenced chain
+    agent_address: Address,
+    /// arbitrary data, e.g. the reason for the migration
+    data: String,
+}
+
+impl ChainMigrate {
+    pub fn new(
+        kind: MigrationKind,
+        dna_address: Address,
+        agent_address: Address,
+        data: String,
+    ) -> Self {
+        ChainMigrate {
+            kind,
+            dna_address,
+            agent_address,
+            data,
+        }
+    }
+
+    /// Closes the current chain, pointing to the given successor chain
+    pub fn close(dna_address: Address, agent_address: Address, data: String) -> Self {
+        ChainMigrate::new(MigrationKind::Close, dna_address, agent_address, data)
+    }
+
+    /// Opens a new chain, pointing to the given predecessor chain
+    pub fn open(dna_address: Address, agent_address: Address, data: String) -> Self {
+        ChainMigrate::new(MigrationKind::Open, dna_address, agent_address, data)
+    }
+
+    pub fn kind(&self) -> &MigrationKind {
+        &self.kind
+    }
+
+    pub fn dna_address(&self) -> &Address {
+        &self.dna_address
+    }
+
+    pub fn agent_address(&self) -> &Address {
+        &self.agent_address
+    }
+
+    pub fn data(&self) -> &String {
+        &self.data
+    }
+
+    /// Whether this entry closes the chain it got committed to
+    pub fn closes_chain(&self) -> bool {
+        self.kind == MigrationKind::Close
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{cas::content::AddressableContent, entry::Entry};
+    use std::convert::TryFrom;
+
+    pub fn test_chain_migrate() -> ChainMigrate {
+        ChainMigrate::close(
+            Address::from("QmSuccessorDna"),
+            Address::from("QmSuccessorAgent"),
+            String::from("upgrade"),
+        )
+    }
+
+    #[test]
+    fn chain_migrate_smoke_test() {
+        let chain_migrate = test_chain_migrate();
+        assert_eq!(chain_migrate.kind(), &MigrationKind::Close);
+        assert!(chain_migrate.closes_chain());
+        assert_eq!(
+            chain_migrate.dna_address(),
+            &Address::from("QmSuccessorDna")
+        );
+        assert_eq!(
+            chain_migrate.agent_address(),
+            &Address::from("QmSuccessorAgent")
+        );
+        assert_eq!(chain_migrate.data(), "upgrade");
+
+        let opening = ChainMigrate::open(
+            Address::from("QmPredecessorDna"),
+            Address::from("QmPredecessorAgent"),
+            String::new(),
+        );
+        assert!(!opening.closes_chain());
+    }
+
+    #[test]
+    fn chain_migrate_entry_round_trip() {
+        let entry = Entry::ChainMigrate(test_chain_migrate());
+        assert_eq!(
+            Entry::try_from(entry.content()).unwrap(),
+            Entry::ChainMigrate(test_chain_migrate())
+        );
+        assert_eq!(
+            ChainMigrate::try_from(JsonString::from(test_chain_migrate())).unwrap(),
+            test_chain_migrate()
+        );
+    }
+}
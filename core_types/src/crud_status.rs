@@ -19,6 +19,12 @@ use std::{convert::TryInto, str::FromStr};
 pub const STATUS_NAME: &str = "crud-status";
 /// The [EAV](../eav/index.html) attribute name utilized for storing metadata that indicates the address of an updated version of a given entry
 pub const LINK_NAME: &str = "crud-link";
+/// The DHT meta attribute name under which an update or deletion of a given entry gets published
+/// to the nodes holding it, so they can validate it
+pub const CRUD_NAME: &str = "crud";
+/// The [EAV](../eav/index.html) attribute name utilized for storing metadata that indicates which one of several
+/// concurrent updates of a given entry won the conflict resolution
+pub const RESOLVED_LINK_NAME: &str = "crud-link-resolved";
 
 /// Create a new [EAV](../eav/struct.EntityAttributeValue.html) with an entry address as the Entity, [STATUS_NAME](constant.STATUS_NAME.html) as the attribute
 /// and CrudStatus as the value.
@@ -40,6 +46,16 @@ pub fn create_crud_link_eav(from: &Address, to: &Address) -> HcResult<EntityAttr
     EntityAttributeValueIndex::new(from, &LINK_NAME.to_string(), to)
 }
 
+/// Create a new [EAV](../eav/struct.EntityAttributeValue.html) with an old entry address as the Entity,
+/// [RESOLVED_LINK_NAME](constant.RESOLVED_LINK_NAME.html) as the attribute and the address of the update that
+/// won the conflict resolution as the value
+pub fn create_crud_resolved_link_eav(
+    from: &Address,
+    to: &Address,
+) -> HcResult<EntityAttributeValueIndex> {
+    EntityAttributeValueIndex::new(from, &RESOLVED_LINK_NAME.to_string(), to)
+}
+
 /// the CRUD status of a Pair is stored using an EAV, NOT in the entry itself
 #[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize, DefaultJson)]
 #[serde(rename_all = "lowercase")]
@@ -48,7 +64,8 @@ pub enum CrudStatus {
     Rejected,
     Deleted,
     Modified,
-    /// CRDT resolution in progress
+    /// CRDT resolution in progress, i.e. the entry got updated concurrently
+    /// and it is not resolved yet which of the updates is its latest version
     Locked,
 }
 
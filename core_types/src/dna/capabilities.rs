@@ -1,6 +1,12 @@
 //! File holding all the structs for handling capabilities defined in DNA.
 
-use crate::cas::content::Address;
+use crate::{
+    cas::content::Address,
+    hash::HashString,
+    json::JsonString,
+    signature::{domain_payload, Signature, CALL_DOMAIN},
+};
+use multihash::Hash;
 use std::str::FromStr;
 
 //--------------------------------------------------------------------------------------------------
@@ -48,8 +54,39 @@ impl ReservedCapabilityNames {
 // CapabilityCall
 //--------------------------------------------------------------------------------------------------
 /// a struct to hold the signature of the call
-#[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
-pub struct CallSignature {}
+/// The caller signs the payload built by `CallSignature::payload`, which binds the signature
+/// to the called function, the call parameters and the capability token.
+/// The default (empty) signature marks an unsigned call, which only suffices for public functions.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct CallSignature {
+    signature: Signature,
+}
+
+impl CallSignature {
+    pub fn new(signature: Signature) -> Self {
+        CallSignature { signature }
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// The string a caller signs when calling `fn_name` with `parameters` using `token`
+    pub fn payload(fn_name: &str, parameters: &JsonString, token: &Address) -> String {
+        let parameters_hash =
+            HashString::encode_from_json_string(parameters.clone(), Hash::SHA2256);
+        domain_payload(
+            CALL_DOMAIN,
+            &format!("{}:{}:{}", fn_name, parameters_hash, token),
+        )
+    }
+}
+
+impl From<Signature> for CallSignature {
+    fn from(signature: Signature) -> Self {
+        CallSignature::new(signature)
+    }
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct CapabilityCall {
@@ -59,11 +96,11 @@ pub struct CapabilityCall {
 }
 
 impl CapabilityCall {
-    pub fn new(token: Address, caller: Option<Address>) -> Self {
+    pub fn new(token: Address, caller: Option<Address>, signature: CallSignature) -> Self {
         CapabilityCall {
             cap_token: token,
             caller,
-            signature: CallSignature {}, // FIXME
+            signature,
         }
     }
 }
@@ -135,6 +172,42 @@ mod tests {
         assert_eq!(cap.cap_type, CapabilityType::Transferable);
     }
 
+    #[test]
+    /// test that the signed payload changes with every part of the call
+    fn test_call_signature_payload() {
+        let payload = CallSignature::payload(
+            "test_fn",
+            &JsonString::from("{\"foo\":1}"),
+            &Address::from("test_token"),
+        );
+        assert!(payload.starts_with(&format!("{}test_fn:", CALL_DOMAIN)));
+        assert!(payload.ends_with(":test_token"));
+        assert_ne!(
+            payload,
+            CallSignature::payload(
+                "other_fn",
+                &JsonString::from("{\"foo\":1}"),
+                &Address::from("test_token"),
+            )
+        );
+        assert_ne!(
+            payload,
+            CallSignature::payload(
+                "test_fn",
+                &JsonString::from("{\"foo\":2}"),
+                &Address::from("test_token"),
+            )
+        );
+        assert_ne!(
+            payload,
+            CallSignature::payload(
+                "test_fn",
+                &JsonString::from("{\"foo\":1}"),
+                &Address::from("other_token"),
+            )
+        );
+    }
+
     #[test]
     /// test that ReservedCapabilityNames can be created from a canonical string
     fn test_capabilities_from_str() {
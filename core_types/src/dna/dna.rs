@@ -227,6 +227,17 @@ impl Dna {
         None
     }
 
+    /// Return the DNA property with the given key.
+    /// Keys starting with "/" are treated as JSON pointers into the properties
+    /// (e.g. "/settings/max_size"), all others as names of top level properties.
+    pub fn get_property(&self, key: &str) -> Option<&Value> {
+        if key.starts_with('/') {
+            self.properties.pointer(key)
+        } else {
+            self.properties.get(key)
+        }
+    }
+
     pub fn multihash(&self) -> Result<Vec<u8>, HolochainError> {
         let s = String::from(JsonString::from(self.to_owned()));
         multihash::encode(multihash::Hash::SHA2256, &s.into_bytes())
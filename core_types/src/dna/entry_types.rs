@@ -5,7 +5,11 @@ use entry::entry_type::EntryType;
 use error::HolochainError;
 use json::JsonString;
 use serde::{ser::SerializeMap, Deserialize, Deserializer, Serializer};
-use std::collections::BTreeMap;
+use serde_json::Value;
+use std::{
+    collections::BTreeMap,
+    hash::{Hash, Hasher},
+};
 
 /// Enum for Zome EntryType "sharing" property.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
@@ -36,6 +40,31 @@ impl Default for Sharing {
     }
 }
 
+/// Enum for Zome EntryType "conflict_resolution" property:
+/// how to pick the latest version of an entry that got updated concurrently.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
+pub enum ConflictResolution {
+    /// The update with the latest header timestamp wins
+    #[serde(rename = "last_writer_wins")]
+    LastWriterWins,
+    /// The zome's `resolve_conflict` callback picks the winning update
+    #[serde(rename = "callback")]
+    Callback,
+}
+
+impl ConflictResolution {
+    pub fn is_default(&self) -> bool {
+        *self == ConflictResolution::default()
+    }
+}
+
+impl Default for ConflictResolution {
+    /// Default zome entry_type conflict resolution is "last_writer_wins"
+    fn default() -> Self {
+        ConflictResolution::LastWriterWins
+    }
+}
+
 /// An individual object in a "links_to" array.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Hash)]
 pub struct LinksTo {
@@ -123,7 +152,7 @@ where
 }
 
 /// Represents an individual object in the "zome" "entry_types" array.
-#[derive(Default, Serialize, Deserialize, Clone, Debug, PartialEq, Hash, DefaultJson)]
+#[derive(Default, Serialize, Deserialize, Clone, Debug, DefaultJson)]
 pub struct EntryTypeDef {
     /// A description of this entry type.
     #[serde(default)]
@@ -140,6 +169,28 @@ pub struct EntryTypeDef {
     /// An array of link definitions for links pointing to entries of this type
     #[serde(default)]
     pub linked_from: Vec<LinkedFrom>,
+
+    /// How concurrent updates of entries of this type get resolved
+    #[serde(default, skip_serializing_if = "ConflictResolution::is_default")]
+    pub conflict_resolution: ConflictResolution,
+
+    /// A JSON Schema (draft 4) the values of app entries of this type have to match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<Value>,
+}
+
+impl Hash for EntryTypeDef {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let s = String::from(JsonString::from(self.to_owned()));
+        s.hash(state);
+    }
+}
+
+impl PartialEq for EntryTypeDef {
+    fn eq(&self, other: &EntryTypeDef) -> bool {
+        // need to guarantee that PartialEq and Hash always agree
+        JsonString::from(self.to_owned()) == JsonString::from(other.to_owned())
+    }
 }
 
 impl EntryTypeDef {
@@ -198,4 +249,35 @@ mod tests {
 
         assert_eq!(fixture, entry);
     }
+
+    #[test]
+    fn conflict_resolution_defaults_to_last_writer_wins() {
+        let entry: EntryTypeDef = serde_json::from_str(r#"{"description": "test"}"#).unwrap();
+        assert_eq!(
+            entry.conflict_resolution,
+            ConflictResolution::LastWriterWins
+        );
+        assert!(!serde_json::to_string(&entry)
+            .unwrap()
+            .contains("conflict_resolution"));
+
+        let entry: EntryTypeDef =
+            serde_json::from_str(r#"{"conflict_resolution": "callback"}"#).unwrap();
+        assert_eq!(entry.conflict_resolution, ConflictResolution::Callback);
+    }
+
+    #[test]
+    fn schema_is_optional() {
+        let entry: EntryTypeDef = serde_json::from_str(r#"{"description": "test"}"#).unwrap();
+        assert_eq!(entry.schema, None);
+        assert!(!serde_json::to_string(&entry).unwrap().contains("schema"));
+
+        let entry: EntryTypeDef =
+            serde_json::from_str(r#"{"schema": {"type": "object", "required": ["content"]}}"#)
+                .unwrap();
+        assert_eq!(
+            entry.schema,
+            Some(json!({"type": "object", "required": ["content"]}))
+        );
+    }
 }
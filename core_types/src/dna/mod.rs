@@ -305,6 +305,29 @@ pub mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn get_property_resolves_names_and_json_pointers() {
+        let dna = Dna::try_from(JsonString::from(
+            r#"{
+                "properties": {
+                    "str": "hello",
+                    "obj": {"a": 1, "list": [true, false]}
+                }
+            }"#,
+        ))
+        .unwrap();
+
+        assert_eq!(Some("hello"), dna.get_property("str").and_then(|v| v.as_str()));
+        assert_eq!(Some(1), dna.get_property("/obj/a").and_then(|v| v.as_i64()));
+        assert_eq!(
+            Some(false),
+            dna.get_property("/obj/list/1").and_then(|v| v.as_bool())
+        );
+        assert!(dna.get_property("obj").unwrap().is_object());
+        assert_eq!(None, dna.get_property("missing"));
+        assert_eq!(None, dna.get_property("/obj/missing"));
+    }
+
     #[test]
     fn parse_accepts_arbitrary_dna_properties() {
         let dna = Dna::try_from(JsonString::from(
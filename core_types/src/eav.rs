@@ -13,8 +13,9 @@ use chrono::offset::Utc;
 use objekt;
 use std::{
     cmp::Ordering,
-    collections::BTreeSet,
+    collections::{BTreeSet, HashMap},
     convert::TryInto,
+    ops::Bound,
     sync::{Arc, RwLock},
 };
 
@@ -55,9 +56,16 @@ impl PartialOrd for EntityAttributeValueIndex {
     }
 }
 
+/// EAVIs are ordered by index. EAVIs of different triples can share an index, e.g. in
+/// storages that only keep indexes unique per triple, so these get ordered by their triple
+/// to not collapse them in sets.
 impl Ord for EntityAttributeValueIndex {
     fn cmp(&self, other: &EntityAttributeValueIndex) -> Ordering {
-        self.index.cmp(&other.index())
+        self.index
+            .cmp(&other.index())
+            .then_with(|| self.entity.cmp(&other.entity))
+            .then_with(|| self.attribute.cmp(&other.attribute))
+            .then_with(|| self.value.cmp(&other.value))
     }
 }
 
@@ -65,6 +73,7 @@ impl Ord for EntityAttributeValueIndex {
 pub struct IndexQuery {
     start: Option<i64>,
     end: Option<i64>,
+    limit: Option<usize>,
 }
 
 impl IndexQuery {
@@ -76,12 +85,97 @@ impl IndexQuery {
         self.end.clone()
     }
 
+    pub fn limit(&self) -> Option<usize> {
+        self.limit.clone()
+    }
+
     pub fn new(start: i64, end: i64) -> IndexQuery {
         IndexQuery {
             start: Some(start),
             end: Some(end),
+            limit: None,
         }
     }
+
+    /// Restricts the query to the first `limit` matching EAVIs in index order.
+    pub fn with_limit(mut self, limit: usize) -> IndexQuery {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The same query without a limit,
+    /// for storages that have to filter the EAVIs they look up some more.
+    pub fn without_limit(mut self) -> IndexQuery {
+        self.limit = None;
+        self
+    }
+
+    /// The range of indexes a storage has to look at to answer this query.
+    /// Without a start, only the latest EAVI of each entity/attribute/value triple matches.
+    /// Telling if an EAVI is the latest requires looking at the EAVIs after it,
+    /// so in that case the end can not narrow down the lookup.
+    pub fn lookup_range(&self) -> (Bound<Index>, Bound<Index>) {
+        let start = self.start.map(Bound::Included).unwrap_or(Bound::Unbounded);
+        let end = self
+            .start
+            .and(self.end)
+            .map(Bound::Included)
+            .unwrap_or(Bound::Unbounded);
+        (start, end)
+    }
+
+    /// How many of the EAVIs matching the entity, attribute and value constraints a storage
+    /// needs to look up in index order, if it can stop early at all.
+    /// Only with both ends set no later EAVIs are needed to tell which ones are the latest,
+    /// so only then the limit can be applied while looking EAVIs up.
+    pub fn scan_limit(&self) -> Option<usize> {
+        self.start.and(self.end).and(self.limit)
+    }
+
+    /// Same as `lookup_range()` for storages that can't make use of ranges directly.
+    pub fn is_in_lookup_range(&self, index: Index) -> bool {
+        self.start.map(|start| start <= index).unwrap_or(true)
+            && self
+                .start
+                .and(self.end)
+                .map(|end| index <= end)
+                .unwrap_or(true)
+    }
+
+    /// Applies the query to EAVIs that match the entity, attribute and value constraints.
+    /// These have to include all matching EAVIs within `lookup_range()`.
+    /// Open ends of the range only let through the latest EAVI of each triple
+    /// and the result gets cut off at the limit.
+    pub fn apply(
+        &self,
+        eavis: BTreeSet<EntityAttributeValueIndex>,
+    ) -> BTreeSet<EntityAttributeValueIndex> {
+        let mut latest = HashMap::new();
+        for eavi in eavis.iter() {
+            latest.insert(
+                (eavi.entity(), eavi.attribute(), eavi.value()),
+                eavi.index(),
+            );
+        }
+        let is_latest = |eavi: &EntityAttributeValueIndex| {
+            latest.get(&(eavi.entity(), eavi.attribute(), eavi.value())) == Some(&eavi.index())
+        };
+        eavis
+            .iter()
+            .filter(|e| {
+                self.start
+                    .map(|start| start <= e.index())
+                    .unwrap_or_else(|| is_latest(e))
+            })
+            .filter(|e| {
+                self.end
+                    .map(|end| end >= e.index())
+                    .unwrap_or_else(|| is_latest(e))
+            })
+            .take(self.limit.unwrap_or(usize::max_value()))
+            .cloned()
+            .collect()
+    }
 }
 
 impl Default for IndexQuery {
@@ -89,10 +183,48 @@ impl Default for IndexQuery {
         IndexQuery {
             start: None,
             end: None,
+            limit: None,
+        }
+    }
+}
+
+/// Constraint on the attribute of the EAVIs a storage looks up.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AttributeFilter {
+    Any,
+    Exact(Attribute),
+    Prefix(String),
+}
+
+impl AttributeFilter {
+    pub fn matches(&self, attribute: &str) -> bool {
+        match self {
+            AttributeFilter::Any => true,
+            AttributeFilter::Exact(exact) => attribute == exact,
+            AttributeFilter::Prefix(prefix) => attribute.starts_with(prefix.as_str()),
+        }
+    }
+
+    /// The first matching attribute in lexicographical order.
+    /// All matching attributes follow it without gaps,
+    /// so sorted indexes can look them up by iterating from there while `matches()` holds.
+    pub fn range_start(&self) -> Attribute {
+        match self {
+            AttributeFilter::Any => Attribute::new(),
+            AttributeFilter::Exact(attribute) => attribute.clone(),
+            AttributeFilter::Prefix(prefix) => prefix.clone(),
         }
     }
 }
 
+impl From<Option<Attribute>> for AttributeFilter {
+    fn from(attribute: Option<Attribute>) -> AttributeFilter {
+        attribute
+            .map(AttributeFilter::Exact)
+            .unwrap_or(AttributeFilter::Any)
+    }
+}
+
 impl AddressableContent for EntityAttributeValueIndex {
     fn content(&self) -> Content {
         self.to_owned().into()
@@ -190,6 +322,7 @@ pub trait EntityAttributeValueStorage: objekt::Clone + Send + Sync + Debug {
     /// - Some(Entity) = requires the given entity (e.g. all a/v pairs for the entity)
     /// - Some(Attribute) = requires the given attribute (e.g. all links)
     /// - Some(Value) = requires the given value (e.g. all entities referencing an Address)
+    /// The index query restricts the result to a range of indexes and/or a maximum number of EAVIs.
     fn fetch_eavi(
         &self,
         entity: Option<Entity>,
@@ -197,6 +330,15 @@ pub trait EntityAttributeValueStorage: objekt::Clone + Send + Sync + Debug {
         value: Option<Value>,
         index_query: IndexQuery,
     ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError>;
+    /// Fetch the set of EntityAttributeValues whose attribute starts with the given prefix,
+    /// optionally restricted to the given entity (e.g. all links of an entry regardless of their tag).
+    /// The index query applies as in `fetch_eavi()`.
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError>;
 }
 
 clone_trait_object!(EntityAttributeValueStorage);
@@ -211,6 +353,25 @@ impl ExampleEntityAttributeValueStorage {
             storage: Arc::new(RwLock::new(BTreeSet::new())),
         }
     }
+
+    fn fetch(
+        &self,
+        entity: Option<Entity>,
+        attribute: AttributeFilter,
+        value: Option<Value>,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        let map = self.storage.read()?;
+        let filtered = map
+            .iter()
+            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.entity(), entity.as_ref()))
+            .filter(|e| attribute.matches(&e.attribute()))
+            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.value(), value.as_ref()))
+            .cloned()
+            .collect::<BTreeSet<EntityAttributeValueIndex>>();
+
+        Ok(index_query.apply(filtered))
+    }
 }
 
 pub fn increment_key_till_no_collision(
@@ -250,46 +411,21 @@ impl EntityAttributeValueStorage for ExampleEntityAttributeValueStorage {
         value: Option<Value>,
         index_query: IndexQuery,
     ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
-        let map = self.storage.read()?;
-        let new_map = map.clone();
-        let filtered = new_map
-            .into_iter()
-            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.entity(), entity.as_ref()))
-            .filter(|e| {
-                EntityAttributeValueIndex::filter_on_eav(&e.attribute(), attribute.as_ref())
-            })
-            .filter(|e| EntityAttributeValueIndex::filter_on_eav(&e.value(), value.as_ref()))
-            .filter(|e| {
-                index_query
-                    .start()
-                    .map(|start| {
-                        println!("start {:?}", e.index().clone());
-                        println!("start condition {:?}", start.clone() <= e.index());
-                        start <= e.index()
-                    })
-                    .unwrap_or_else(|| {
-                        let latest = get_latest(e.clone(), map.clone())
-                            .unwrap_or(EntityAttributeValueIndex::default());
-                        latest.index() == e.index()
-                    })
-            })
-            .filter(|e| {
-                index_query
-                    .end()
-                    .map(|end| {
-                        println!("end {:?}", e.index().clone());
-                        println!("start condition {:?}", end.clone() >= e.index());
-                        end >= e.index()
-                    })
-                    .unwrap_or_else(|| {
-                        let latest = get_latest(e.clone(), map.clone())
-                            .unwrap_or(EntityAttributeValueIndex::default());
-                        latest.index() == e.index()
-                    })
-            })
-            .collect::<BTreeSet<EntityAttributeValueIndex>>();
+        self.fetch(entity, attribute.into(), value, index_query)
+    }
 
-        Ok(filtered)
+    fn fetch_eavi_by_attribute_prefix(
+        &self,
+        entity: Option<Entity>,
+        attribute_prefix: &str,
+        index_query: IndexQuery,
+    ) -> Result<BTreeSet<EntityAttributeValueIndex>, HolochainError> {
+        self.fetch(
+            entity,
+            AttributeFilter::Prefix(attribute_prefix.to_string()),
+            None,
+            index_query,
+        )
     }
 }
 
@@ -465,6 +601,59 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn example_eav_attribute_prefix() {
+        EavTestSuite::test_attribute_prefix::<
+            ExampleAddressableContent,
+            ExampleEntityAttributeValueStorage,
+        >(test_eav_storage());
+    }
+
+    #[test]
+    fn example_eav_limit() {
+        EavTestSuite::test_limit::<ExampleAddressableContent, ExampleEntityAttributeValueStorage>(
+            test_eav_storage(),
+        );
+    }
+
+    #[test]
+    /// show that open ends of an index query can't narrow down the lookup unless there is a start
+    fn index_query_lookup_range() {
+        assert_eq!(
+            IndexQuery::default().lookup_range(),
+            (Bound::Unbounded, Bound::Unbounded)
+        );
+        assert_eq!(
+            IndexQuery::new(1, 5).lookup_range(),
+            (Bound::Included(1), Bound::Included(5))
+        );
+        let end_only = IndexQuery {
+            start: None,
+            end: Some(5),
+            limit: None,
+        };
+        assert_eq!(
+            end_only.lookup_range(),
+            (Bound::Unbounded, Bound::Unbounded)
+        );
+        assert!(end_only.is_in_lookup_range(6));
+        assert!(!IndexQuery::new(1, 5).is_in_lookup_range(6));
+    }
+
+    #[test]
+    /// show that storages can only stop looking up EAVIs at the limit if both ends are set
+    fn index_query_scan_limit() {
+        assert_eq!(IndexQuery::new(1, 5).scan_limit(), None);
+        assert_eq!(IndexQuery::new(1, 5).with_limit(2).scan_limit(), Some(2));
+        assert_eq!(IndexQuery::default().with_limit(2).scan_limit(), None);
+        let start_only = IndexQuery {
+            start: Some(1),
+            end: None,
+            limit: Some(2),
+        };
+        assert_eq!(start_only.scan_limit(), None);
+    }
+
     #[test]
     /// show AddressableContent implementation
     fn addressable_content_test() {
@@ -537,5 +726,4 @@ pub mod tests {
         )
         .is_err());
     }
-
 }
@@ -1,10 +1,11 @@
 use crate::{
     cas::content::{Address, AddressableContent},
-    dna::capabilities::{CallSignature, CapabilityType},
+    dna::capabilities::CapabilityType,
     entry::Entry,
     error::HolochainError,
     json::JsonString,
 };
+use uuid::Uuid;
 
 pub type CapTokenValue = Address;
 
@@ -24,22 +25,33 @@ impl CapToken {
 }
 
 /// System entry to hold a capabilities granted by the callee
+/// The grant gives access to the functions of the zome capability named `cap_name`.
+/// The random `nonce` makes every grant's token unique, so tokens can't be guessed
+/// from the capability and assignees and granting again after a revocation
+/// does not bring back the revoked token.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, DefaultJson)]
 pub struct CapTokenGrant {
+    cap_name: String,
     assignees: Option<Vec<Address>>,
+    nonce: String,
 }
 
 impl CapTokenGrant {
-    fn new(assignees: Option<Vec<Address>>) -> Self {
-        CapTokenGrant { assignees }
+    fn new(cap_name: &str, assignees: Option<Vec<Address>>) -> Self {
+        CapTokenGrant {
+            cap_name: cap_name.to_string(),
+            assignees,
+            nonce: Uuid::new_v4().to_string(),
+        }
     }
 
     pub fn create(
+        cap_name: &str,
         cap_type: CapabilityType,
         assignees: Option<Vec<Address>>,
     ) -> Result<Self, HolochainError> {
         let assignees = CapTokenGrant::valid(cap_type, assignees)?;
-        Ok(CapTokenGrant::new(assignees))
+        Ok(CapTokenGrant::new(cap_name, assignees))
     }
 
     // internal check that type and assignees are valid for create
@@ -88,17 +100,18 @@ impl CapTokenGrant {
         }
     }
 
+    pub fn cap_name(&self) -> String {
+        self.cap_name.clone()
+    }
+
     pub fn assignees(&self) -> Option<Vec<Address>> {
         self.assignees.clone()
     }
 
-    /// verifies that this grant is valid for a given requester and token value
-    pub fn verify(
-        &self,
-        token: CapTokenValue,
-        from: Option<Address>,
-        _message: &CallSignature,
-    ) -> bool {
+    /// verifies that this grant is valid for a given requester and token value.
+    /// This does not check the call's signature, which needs to be verified against
+    /// the requester's key by the caller of this function.
+    pub fn verify(&self, token: CapTokenValue, from: Option<Address>) -> bool {
         let cap_type = self.cap_type();
         if cap_type == CapabilityType::Public {
             return true;
@@ -111,8 +124,6 @@ impl CapTokenGrant {
             return false;
         }
 
-        // TODO: CallSignature check against Address
-
         match self.cap_type() {
             CapabilityType::Public => true,
             CapabilityType::Transferable => true,
@@ -134,14 +145,15 @@ pub mod tests {
 
     #[test]
     fn test_new_cap_token_grant_entry() {
-        let grant = CapTokenGrant::new(None);
+        let grant = CapTokenGrant::new("foo", None);
         assert_eq!(grant.cap_type(), CapabilityType::Public);
-        let grant = CapTokenGrant::new(Some(Vec::new()));
+        let grant = CapTokenGrant::new("foo", Some(Vec::new()));
         assert_eq!(grant.cap_type(), CapabilityType::Transferable);
         let test_address = Address::new();
-        let grant = CapTokenGrant::new(Some(vec![test_address.clone()]));
+        let grant = CapTokenGrant::new("foo", Some(vec![test_address.clone()]));
         assert_eq!(grant.cap_type(), CapabilityType::Assigned);
-        assert_eq!(grant.assignees().unwrap()[0], test_address)
+        assert_eq!(grant.assignees().unwrap()[0], test_address);
+        assert_eq!(grant.cap_name(), "foo");
     }
 
     #[test]
@@ -161,28 +173,35 @@ pub mod tests {
 
     #[test]
     fn test_create_cap_token_grant_entry() {
-        let maybe_grant = CapTokenGrant::create(CapabilityType::Public, None);
+        let maybe_grant = CapTokenGrant::create("foo", CapabilityType::Public, None);
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Public);
 
-        let maybe_grant = CapTokenGrant::create(CapabilityType::Transferable, Some(Vec::new()));
+        let maybe_grant =
+            CapTokenGrant::create("foo", CapabilityType::Transferable, Some(Vec::new()));
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Transferable);
 
         let test_address = Address::new();
 
-        let maybe_grant =
-            CapTokenGrant::create(CapabilityType::Public, Some(vec![test_address.clone()]));
+        let maybe_grant = CapTokenGrant::create(
+            "foo",
+            CapabilityType::Public,
+            Some(vec![test_address.clone()]),
+        );
         assert!(maybe_grant.is_err());
-        let maybe_grant = CapTokenGrant::create(CapabilityType::Transferable, None);
+        let maybe_grant = CapTokenGrant::create("foo", CapabilityType::Transferable, None);
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Transferable);
 
-        let maybe_grant =
-            CapTokenGrant::create(CapabilityType::Assigned, Some(vec![test_address.clone()]));
+        let maybe_grant = CapTokenGrant::create(
+            "foo",
+            CapabilityType::Assigned,
+            Some(vec![test_address.clone()]),
+        );
         assert!(maybe_grant.is_ok());
         let grant = maybe_grant.unwrap();
         assert_eq!(grant.cap_type(), CapabilityType::Assigned);
@@ -193,56 +212,49 @@ pub mod tests {
     fn test_cap_grant_verify() {
         let test_address1 = Address::from("some identity");
         let test_address2 = Address::from("some other identity");
-        let test_call_signature = &CallSignature {};
 
-        let grant = CapTokenGrant::create(CapabilityType::Public, None).unwrap();
+        let grant = CapTokenGrant::create("foo", CapabilityType::Public, None).unwrap();
         let token = grant.token();
-        assert!(grant.verify(token.clone(), None, test_call_signature));
-        assert!(grant.verify(
-            token.clone(),
-            Some(test_address1.clone()),
-            test_call_signature
-        ));
-        assert!(grant.verify(Address::from("Bad Token"), None, test_call_signature));
-
-        let grant = CapTokenGrant::create(CapabilityType::Transferable, None).unwrap();
+        assert!(grant.verify(token.clone(), None));
+        assert!(grant.verify(token.clone(), Some(test_address1.clone())));
+        assert!(grant.verify(Address::from("Bad Token"), None));
+
+        let grant = CapTokenGrant::create("foo", CapabilityType::Transferable, None).unwrap();
         let token = grant.token();
-        assert!(!grant.verify(token.clone(), None, test_call_signature));
-        assert!(grant.verify(
-            token.clone(),
-            Some(test_address1.clone()),
-            test_call_signature
-        ));
-        assert!(grant.verify(
-            token.clone(),
-            Some(test_address2.clone()),
-            test_call_signature
-        ));
-        assert!(!grant.verify(
-            Address::from("Bad Token"),
-            Some(test_address1.clone()),
-            test_call_signature
-        ));
-
-        let grant =
-            CapTokenGrant::create(CapabilityType::Assigned, Some(vec![test_address1.clone()]))
-                .unwrap();
+        assert!(!grant.verify(token.clone(), None));
+        assert!(grant.verify(token.clone(), Some(test_address1.clone())));
+        assert!(grant.verify(token.clone(), Some(test_address2.clone())));
+        assert!(!grant.verify(Address::from("Bad Token"), Some(test_address1.clone())));
+
+        let grant = CapTokenGrant::create(
+            "foo",
+            CapabilityType::Assigned,
+            Some(vec![test_address1.clone()]),
+        )
+        .unwrap();
         let token = grant.token();
-        assert!(!grant.verify(token.clone(), None, test_call_signature));
-        assert!(grant.verify(
-            token.clone(),
-            Some(test_address1.clone()),
-            test_call_signature
-        ));
-        assert!(!grant.verify(
-            token.clone(),
-            Some(test_address2.clone()),
-            test_call_signature
-        ));
-        assert!(!grant.verify(
-            Address::from("Bad Token"),
-            Some(test_address1.clone()),
-            test_call_signature
-        ));
+        assert!(!grant.verify(token.clone(), None));
+        assert!(grant.verify(token.clone(), Some(test_address1.clone())));
+        assert!(!grant.verify(token.clone(), Some(test_address2.clone())));
+        assert!(!grant.verify(Address::from("Bad Token"), Some(test_address1.clone())));
+
+        // grants for different capabilities have different tokens
+        let other_grant = CapTokenGrant::create(
+            "bar",
+            CapabilityType::Assigned,
+            Some(vec![test_address1.clone()]),
+        )
+        .unwrap();
+        assert!(!other_grant.verify(token.clone(), Some(test_address1.clone())));
+
+        // granting the same capability again yields a new token
+        let same_grant = CapTokenGrant::create(
+            "foo",
+            CapabilityType::Assigned,
+            Some(vec![test_address1.clone()]),
+        )
+        .unwrap();
+        assert_ne!(same_grant.token(), token);
+        assert!(!same_grant.verify(token.clone(), Some(test_address1.clone())));
     }
 }
@@ -0,0 +1,147 @@
+//! An EncryptedEntry is what leaves an agent's node for an entry of a type the DNA
+//! declares with `"sharing": "encrypted"`.
+//! The entry gets encrypted with a random symmetric key, which is sealed separately
+//! for every recipient agent with a key derived (`sodium::kx`) from the author's and the
+//! recipient's encryption keys. DHT nodes only ever hold the cipher text next to the header
+//! of the plain text entry and can not read it unless they are one of the recipients.
+
+use crate::{
+    cas::content::{Address, AddressableContent, Content},
+    crud_status::CrudStatus,
+    entry::entry_type::EntryType,
+    error::HolochainError,
+    json::JsonString,
+};
+use std::convert::TryInto;
+
+/// The symmetric key of an EncryptedEntry, sealed for one recipient
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SealedKey {
+    /// public keys of the recipient, as rendered by KeyBuffer
+    pub recipient: String,
+    /// nonce and cipher text of the symmetric key, base64 encoded
+    pub cipher: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DefaultJson)]
+pub struct EncryptedEntry {
+    /// address of the plain text entry, i.e. the entry address of its header
+    entry_address: Address,
+    entry_type: EntryType,
+    /// public keys of the author at the time the entry got sealed
+    author: String,
+    keys: Vec<SealedKey>,
+    /// nonce and cipher text of the entry, base64 encoded
+    cipher: String,
+}
+
+impl EncryptedEntry {
+    pub fn new(
+        entry_address: Address,
+        entry_type: EntryType,
+        author: String,
+        keys: Vec<SealedKey>,
+        cipher: String,
+    ) -> Self {
+        EncryptedEntry {
+            entry_address,
+            entry_type,
+            author,
+            keys,
+            cipher,
+        }
+    }
+
+    pub fn entry_address(&self) -> &Address {
+        &self.entry_address
+    }
+
+    pub fn entry_type(&self) -> &EntryType {
+        &self.entry_type
+    }
+
+    pub fn author(&self) -> &String {
+        &self.author
+    }
+
+    pub fn keys(&self) -> &Vec<SealedKey> {
+        &self.keys
+    }
+
+    pub fn cipher(&self) -> &String {
+        &self.cipher
+    }
+
+    /// The symmetric key sealed for the recipient with the given public keys, if any
+    pub fn key_for(&self, recipient: &str) -> Option<&SealedKey> {
+        self.keys.iter().find(|key| key.recipient == recipient)
+    }
+}
+
+impl AddressableContent for EncryptedEntry {
+    fn content(&self) -> Content {
+        self.to_owned().into()
+    }
+
+    fn try_from_content(content: &Content) -> Result<Self, HolochainError> {
+        content.to_owned().try_into()
+    }
+}
+
+/// An EncryptedEntry as a DHT node hands it out, together with the metadata
+/// it holds for the address of the plain text entry.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DefaultJson)]
+pub struct EncryptedEntryWithMeta {
+    pub encrypted_entry: EncryptedEntry,
+    pub crud_status: CrudStatus,
+    pub maybe_crud_link: Option<Address>,
+    /// The competing updates if the entry got updated concurrently, empty otherwise
+    #[serde(default)]
+    pub crud_fork: Vec<Address>,
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::entry::{entry_type::test_entry_type, test_entry};
+
+    pub fn test_encrypted_entry() -> EncryptedEntry {
+        EncryptedEntry::new(
+            test_entry().address(),
+            test_entry_type(),
+            "alice".to_string(),
+            vec![
+                SealedKey {
+                    recipient: "alice".to_string(),
+                    cipher: "a-key".to_string(),
+                },
+                SealedKey {
+                    recipient: "bob".to_string(),
+                    cipher: "b-key".to_string(),
+                },
+            ],
+            "cipher".to_string(),
+        )
+    }
+
+    #[test]
+    fn encrypted_entry_round_trip() {
+        let encrypted_entry = test_encrypted_entry();
+        assert_eq!(
+            EncryptedEntry::try_from_content(&encrypted_entry.content()),
+            Ok(encrypted_entry.clone())
+        );
+        // the cipher text is stored under its own address, not the plain text entry's
+        assert_ne!(encrypted_entry.address(), test_entry().address());
+    }
+
+    #[test]
+    fn finds_key_for_recipient() {
+        let encrypted_entry = test_encrypted_entry();
+        assert_eq!(
+            encrypted_entry.key_for("bob").map(|key| key.cipher.clone()),
+            Some("b-key".to_string())
+        );
+        assert_eq!(encrypted_entry.key_for("carol"), None);
+    }
+}
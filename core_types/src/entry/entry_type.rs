@@ -1,3 +1,4 @@
+use dna::{entry_types::Sharing, Dna};
 use error::HolochainError;
 use json::JsonString;
 use std::{
@@ -90,33 +91,37 @@ impl EntryType {
         !self.is_app()
     }
 
-    pub fn can_publish(&self) -> bool {
-        /*
-                let dna = context
-            .state()
-            .expect("context must have a State.")
-            .nucleus()
-            .dna()
-            .expect("context.state must hold DNA in order to commit an app entry.");
-        let maybe_def = dna.get_entry_type_def(&entry.entry_type().to_string());
-        if maybe_def.is_none() {
-            // TODO #439 - Log the error. Once we have better logging.
-            return None;
-        }
-        let entry_type_def = maybe_def.unwrap();
-
-        // app entry type must be publishable
-        if !entry_type_def.sharing.clone().can_publish() {
-            return None;
-        }
-            */
+    /// Whether entries of this type get published to the DHT.
+    /// App entries follow the sharing of their entry type definition in the given DNA,
+    /// so entries of types the DNA does not define don't get published.
+    pub fn can_publish(&self, dna: &Dna) -> bool {
         match self {
             EntryType::Dna => false,
             EntryType::CapTokenGrant => false,
+            EntryType::App(app_entry_type) => {
+                let name = String::from(app_entry_type.clone());
+                EntryType::has_valid_app_name(&name)
+                    && dna
+                        .get_entry_type_def(&name)
+                        .map(|entry_type_def| entry_type_def.sharing.clone().can_publish())
+                        .unwrap_or(false)
+            }
             _ => true,
         }
     }
 
+    /// Whether entries of this type only get published encrypted for a set of recipients,
+    /// because the DNA declares the type with `Sharing::Encrypted`.
+    pub fn is_encrypted(&self, dna: &Dna) -> bool {
+        match self {
+            EntryType::App(app_entry_type) => dna
+                .get_entry_type_def(&String::from(app_entry_type.clone()))
+                .map(|entry_type_def| entry_type_def.sharing == Sharing::Encrypted)
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
     /// Checks entry_type_name is valid
     pub fn has_valid_app_name(entry_type_name: &str) -> bool {
         // TODO #445 - do a real regex test instead
@@ -217,6 +222,7 @@ pub fn test_entry_type_b() -> EntryType {
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use dna::{entry_types::EntryTypeDef, zome::Zome};
 
     pub fn test_types() -> Vec<EntryType> {
         vec![
@@ -281,12 +287,52 @@ pub mod tests {
 
     #[test]
     fn can_publish_test() {
+        let mut dna = Dna::new();
+        let mut zome = Zome::default();
+        zome.entry_types.insert(
+            EntryType::App(AppEntryType::from("foo")),
+            EntryTypeDef::new(),
+        );
+        let mut private_entry_type_def = EntryTypeDef::new();
+        private_entry_type_def.sharing = Sharing::Private;
+        zome.entry_types.insert(
+            EntryType::App(AppEntryType::from("bar")),
+            private_entry_type_def,
+        );
+        dna.zomes.insert("zome".to_string(), zome);
+
         for t in test_types() {
             match t {
-                EntryType::Dna => assert!(!t.can_publish()),
-                EntryType::CapTokenGrant => assert!(!t.can_publish()),
-                _ => assert!(t.can_publish()),
+                EntryType::Dna => assert!(!t.can_publish(&dna)),
+                EntryType::CapTokenGrant => assert!(!t.can_publish(&dna)),
+                _ => assert!(t.can_publish(&dna)),
             }
         }
+        assert!(!EntryType::App(AppEntryType::from("bar")).can_publish(&dna));
+        assert!(!EntryType::App(AppEntryType::from("baz")).can_publish(&dna));
+    }
+
+    #[test]
+    fn is_encrypted_test() {
+        let mut dna = Dna::new();
+        let mut zome = Zome::default();
+        zome.entry_types.insert(
+            EntryType::App(AppEntryType::from("foo")),
+            EntryTypeDef::new(),
+        );
+        let mut encrypted_entry_type_def = EntryTypeDef::new();
+        encrypted_entry_type_def.sharing = Sharing::Encrypted;
+        zome.entry_types.insert(
+            EntryType::App(AppEntryType::from("secret")),
+            encrypted_entry_type_def,
+        );
+        dna.zomes.insert("zome".to_string(), zome);
+
+        let secret = EntryType::App(AppEntryType::from("secret"));
+        assert!(secret.is_encrypted(&dna));
+        assert!(secret.can_publish(&dna));
+        assert!(!EntryType::App(AppEntryType::from("foo")).is_encrypted(&dna));
+        assert!(!EntryType::App(AppEntryType::from("baz")).is_encrypted(&dna));
+        assert!(!EntryType::AgentId.is_encrypted(&dna));
     }
 }
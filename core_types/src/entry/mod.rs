@@ -5,6 +5,7 @@
 
 pub mod cap_entries;
 pub mod deletion_entry;
+pub mod encrypted_entry;
 pub mod entry_type;
 
 use self::{
@@ -25,6 +26,7 @@ use multihash::Hash;
 use serde::{ser::SerializeTuple, Deserialize, Deserializer, Serializer};
 use snowflake;
 use std::convert::TryFrom;
+use warrant::Warrant;
 
 pub type AppEntryValue = JsonString;
 
@@ -135,6 +137,13 @@ pub struct EntryWithMeta {
     pub entry: Entry,
     pub crud_status: CrudStatus,
     pub maybe_crud_link: Option<Address>,
+    /// The competing updates if the entry got updated concurrently, empty otherwise
+    #[serde(default)]
+    pub crud_fork: Vec<Address>,
+    /// The warrant of the node that rejected the entry if its crud-status is Rejected,
+    /// so that getters can check the rejection instead of taking it on trust
+    #[serde(default)]
+    pub warrant: Option<Warrant>,
 }
 
 /// dummy entry value
@@ -5,6 +5,7 @@ pub enum DnaError {
     ZomeNotFound(String),
     CapabilityNotFound(String),
     ZomeFunctionNotFound(String),
+    InvalidEntrySchema(String),
 }
 
 impl Error for DnaError {}
@@ -15,6 +16,7 @@ impl fmt::Display for DnaError {
             DnaError::ZomeNotFound(err_msg) => err_msg,
             DnaError::CapabilityNotFound(err_msg) => err_msg,
             DnaError::ZomeFunctionNotFound(err_msg) => err_msg,
+            DnaError::InvalidEntrySchema(err_msg) => err_msg,
         };
         write!(f, "{}", msg)
     }
@@ -298,6 +298,10 @@ mod tests {
                 HolochainError::Dna(DnaError::ZomeFunctionNotFound(String::from("foo"))),
                 "foo",
             ),
+            (
+                HolochainError::Dna(DnaError::InvalidEntrySchema(String::from("foo"))),
+                "foo",
+            ),
             (HolochainError::IoError(String::from("foo")), "foo"),
             (
                 HolochainError::SerializationError(String::from("foo")),
@@ -153,6 +153,7 @@ pub enum RibosomeErrorCode {
     NotAnAllocation                 = 8 << 32,
     ZeroSizedAllocation             = 9 << 32,
     UnknownEntryType                = 10 << 32,
+    CapabilityCheckFailed           = 11 << 32,
 }
 
 #[rustfmt::skip]
@@ -169,6 +170,7 @@ impl RibosomeErrorCode {
             NotAnAllocation                 => "Not an allocation",
             ZeroSizedAllocation             => "Zero-sized allocation",
             UnknownEntryType                => "Unknown entry type",
+            CapabilityCheckFailed           => "Capability check failed",
         }
     }
 }
@@ -187,7 +189,7 @@ impl From<HolochainError> for RibosomeErrorCode {
                 RibosomeErrorCode::ArgumentDeserializationFailed
             }
             HolochainError::InvalidOperationOnSysEntry => RibosomeErrorCode::UnknownEntryType,
-            HolochainError::CapabilityCheckFailed => RibosomeErrorCode::Unspecified,
+            HolochainError::CapabilityCheckFailed => RibosomeErrorCode::CapabilityCheckFailed,
             HolochainError::ValidationFailed(_) => RibosomeErrorCode::CallbackFailed,
             HolochainError::Ribosome(e) => e,
             HolochainError::RibosomeFailed(_) => RibosomeErrorCode::CallbackFailed,
@@ -223,6 +225,7 @@ impl RibosomeErrorCode {
             8 => NotAnAllocation,
             9 => ZeroSizedAllocation,
             10 => UnknownEntryType,
+            11 => CapabilityCheckFailed,
             1 | _ => Unspecified,
         }
     }
@@ -273,6 +276,7 @@ impl FromStr for RibosomeErrorCode {
             "Not an allocation" => Ok(RibosomeErrorCode::NotAnAllocation),
             "Zero-sized allocation" => Ok(RibosomeErrorCode::ZeroSizedAllocation),
             "Unknown entry type" => Ok(RibosomeErrorCode::UnknownEntryType),
+            "Capability check failed" => Ok(RibosomeErrorCode::CapabilityCheckFailed),
             _ => Err(HolochainError::ErrorGeneric(String::from(
                 "Unknown RibosomeErrorCode",
             ))),
@@ -297,7 +301,7 @@ pub mod tests {
 
     #[test]
     fn error_conversion() {
-        for code in 1..=10 {
+        for code in 1..=11 {
             let mut err = RibosomeErrorCode::from_code_int(code);
 
             let err_str = err.as_str().to_owned();
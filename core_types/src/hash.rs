@@ -6,8 +6,8 @@ use crate::{
     error::error::HolochainError,
     json::{default_try_from_json, JsonString},
 };
-use multihash::{encode, Hash};
-use rust_base58::ToBase58;
+use multihash::{decode, encode, Hash};
+use rust_base58::{FromBase58, ToBase58};
 use std::{convert::TryFrom, fmt};
 
 // HashString newtype for String
@@ -66,6 +66,12 @@ impl HashString {
     pub fn encode_from_json_string(json_string: JsonString, hash_type: Hash) -> HashString {
         HashString::encode_from_str(&String::from(json_string), hash_type)
     }
+
+    /// the hash type if this is a b58 encoded multihash, None for other strings like agent keys
+    pub fn hash_type(&self) -> Option<Hash> {
+        let bytes = self.0.from_base58().ok()?;
+        decode(&bytes).ok().map(|multihash| multihash.alg)
+    }
 }
 
 #[cfg(test)]
@@ -111,6 +117,18 @@ pub mod tests {
         assert_eq!(test_hash(), HashString::from(test_entry().address()),);
     }
 
+    #[test]
+    /// show that only multihashes have a hash type
+    fn hash_type_test() {
+        assert_eq!(test_hash().hash_type(), Some(Hash::SHA2256));
+        assert_eq!(
+            HashString::encode_from_str("foo", Hash::SHA2512).hash_type(),
+            Some(Hash::SHA2512)
+        );
+        assert_eq!(HashString::from("AgentState").hash_type(), None);
+        assert_eq!(HashString::new().hash_type(), None);
+    }
+
     #[test]
     /// mimics tests from legacy golang holochain core hashing bytes
     fn bytes_to_b58_known_golang() {
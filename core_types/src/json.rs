@@ -76,6 +76,12 @@ impl From<u128> for JsonString {
     }
 }
 
+impl From<bool> for JsonString {
+    fn from(b: bool) -> JsonString {
+        default_to_json(b)
+    }
+}
+
 impl TryFrom<JsonString> for u32 {
     type Error = HolochainError;
     fn try_from(j: JsonString) -> Result<Self, Self::Error> {
@@ -90,6 +96,13 @@ impl TryFrom<JsonString> for u64 {
     }
 }
 
+impl TryFrom<JsonString> for bool {
+    type Error = HolochainError;
+    fn try_from(j: JsonString) -> Result<Self, Self::Error> {
+        default_try_from_json(j)
+    }
+}
+
 impl From<serde_json::Value> for JsonString {
     fn from(v: serde_json::Value) -> JsonString {
         JsonString::from(v.to_string())
@@ -378,6 +391,13 @@ pub mod tests {
         );
     }
 
+    #[test]
+    /// show From<bool> for JsonString and TryFrom<JsonString> for bool
+    fn json_bool_round_trip_test() {
+        assert_eq!(String::from("true"), String::from(JsonString::from(true)),);
+        assert_eq!(false, bool::try_from(JsonString::from(false)).unwrap(),);
+    }
+
     #[test]
     /// show From<&str> and From<String> for RawString
     fn raw_from_string_test() {
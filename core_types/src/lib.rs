@@ -51,3 +51,4 @@ pub mod link;
 pub mod signature;
 pub mod time;
 pub mod validation;
+pub mod warrant;
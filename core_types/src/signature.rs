@@ -2,10 +2,28 @@
 //! a way of providing cryptographically verifiable proof of a given agent
 //! as having been the author of a given data entry.
 
+/// Domain tags that get prefixed to every payload an agent signs, one per purpose,
+/// so that a signature made for one purpose can never pass as one made for another.
+/// Payloads signed through `hdk::sign`
+pub const SIGN_DOMAIN: &str = "hc:sign:";
+/// Entry addresses signed as the provenance of a chain header
+pub const PROVENANCE_DOMAIN: &str = "hc:provenance:";
+/// Capability calls signed by their caller
+pub const CALL_DOMAIN: &str = "hc:call:";
+/// Membrane proofs signed together with the agent's key
+pub const MEMBRANE_PROOF_DOMAIN: &str = "hc:membrane-proof:";
+/// Warrants signed by the node that issued them
+pub const WARRANT_DOMAIN: &str = "hc:warrant:";
+
+/// The string to sign for the given payload in the given domain
+pub fn domain_payload(domain: &str, payload: &str) -> String {
+    format!("{}{}", domain, payload)
+}
+
 /// Signature is meant in the classic cryptographic sense,
 /// as a string which can be validated as having been signed
 /// by the private key associated with a given public key
-#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 pub struct Signature(String);
 
 impl From<&'static str> for Signature {
@@ -13,3 +31,15 @@ impl From<&'static str> for Signature {
         Signature(s.to_owned())
     }
 }
+
+impl From<String> for Signature {
+    fn from(s: String) -> Signature {
+        Signature(s)
+    }
+}
+
+impl From<Signature> for String {
+    fn from(s: Signature) -> String {
+        s.0
+    }
+}
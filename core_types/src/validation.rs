@@ -4,8 +4,8 @@
 
 extern crate serde_json;
 use crate::{
-    cas::content::Address, chain_header::ChainHeader, entry::Entry, error::HolochainError,
-    json::JsonString,
+    agent::AgentId, cas::content::Address, chain_header::ChainHeader, entry::Entry,
+    error::HolochainError, json::JsonString,
 };
 use chain_header::test_chain_header;
 
@@ -15,6 +15,21 @@ pub struct ValidationPackage {
     pub source_chain_entries: Option<Vec<Entry>>,
     pub source_chain_headers: Option<Vec<ChainHeader>>,
     pub custom: Option<String>,
+    /// The headers of all AgentId entries that precede the entry in the source chain,
+    /// newest first. Since the address of an AgentId entry is the agent's key,
+    /// these determine which key was current at the entry's position in the chain.
+    #[serde(default)]
+    pub agent_id_headers: Vec<ChainHeader>,
+    /// The headers that precede the entry in the source chain back to the newest AgentId entry,
+    /// or to the start of the chain if there is none, newest first.
+    /// These tie the entry to its position in the chain, so that none of the agent's key
+    /// rotations can be left out of `agent_id_headers`.
+    #[serde(default)]
+    pub headers_since_agent_id: Vec<ChainHeader>,
+    /// The AgentId entry of the newest of `agent_id_headers`, including the membrane proof,
+    /// so that the nodes holding the entry can check its author against the DNA's membrane.
+    #[serde(default)]
+    pub author_agent_id: Option<AgentId>,
 }
 
 impl ValidationPackage {
@@ -24,6 +39,9 @@ impl ValidationPackage {
             source_chain_entries: None,
             source_chain_headers: None,
             custom: None,
+            agent_id_headers: Vec::new(),
+            headers_since_agent_id: Vec::new(),
+            author_agent_id: None,
         }
     }
 }
@@ -60,6 +78,14 @@ pub struct ValidationData {
     pub lifecycle: EntryLifecycle,
     /// Does the entry get committed, modified or deleted?
     pub action: EntryAction,
+    /// The entry that gets modified or deleted, if the action is
+    /// EntryAction::Modify or EntryAction::Delete.
+    #[serde(default)]
+    pub old_entry: Option<Entry>,
+    /// The header the modified or deleted entry got committed with.
+    /// Its provenances tell who authored the original entry.
+    #[serde(default)]
+    pub old_entry_header: Option<ChainHeader>,
 }
 
 impl Default for ValidationData {
@@ -70,9 +96,14 @@ impl Default for ValidationData {
                 source_chain_entries: None,
                 source_chain_headers: None,
                 custom: None,
+                agent_id_headers: Vec::new(),
+                headers_since_agent_id: Vec::new(),
+                author_agent_id: None,
             },
             lifecycle: EntryLifecycle::default(),
             action: EntryAction::default(),
+            old_entry: None,
+            old_entry_header: None,
         }
     }
 }
@@ -87,6 +118,16 @@ impl ValidationData {
             .map(|(addr, _)| addr.clone())
             .collect()
     }
+
+    /// The list of authors that have signed the modified or deleted entry.
+    /// Empty if there is no old entry, i.e. when the entry gets created.
+    pub fn old_entry_sources(&self) -> Vec<Address> {
+        self.old_entry_header
+            .iter()
+            .flat_map(|header| header.provenances().iter())
+            .map(|(addr, _)| addr.clone())
+            .collect()
+    }
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
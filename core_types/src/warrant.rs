@@ -0,0 +1,135 @@
+//! A Warrant is what a DHT node publishes when an entry it was asked to hold fails validation.
+//! It carries the rejected entry together with its header, so that other nodes can check the
+//! claim by validating the entry themselves, and is signed by the agent that issued it,
+//! which makes the issuer accountable for it.
+
+use crate::{
+    cas::content::{Address, AddressableContent, Content},
+    chain_header::ChainHeader,
+    entry::Entry,
+    error::HolochainError,
+    hash::HashString,
+    json::JsonString,
+    signature::{domain_payload, Signature, WARRANT_DOMAIN},
+};
+use multihash::Hash;
+use std::convert::TryInto;
+
+/// The [EAV](../eav/index.html) attribute name under which warrants get stored for the address
+/// of the header they reject, as well as the attribute of the DHT metadata they get published as
+pub const WARRANT_NAME: &str = "warrant";
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, DefaultJson)]
+pub struct Warrant {
+    entry: Entry,
+    header: ChainHeader,
+    /// the fail string of the validation
+    reason: String,
+    /// signing key of the agent that issued the warrant
+    issuer: Address,
+    /// signature of the issuer over the warrant's payload
+    signature: Signature,
+}
+
+impl Warrant {
+    pub fn new(
+        entry: Entry,
+        header: ChainHeader,
+        reason: String,
+        issuer: Address,
+        signature: Signature,
+    ) -> Self {
+        Warrant {
+            entry,
+            header,
+            reason,
+            issuer,
+            signature,
+        }
+    }
+
+    /// The string the issuer of a warrant signs: the addresses of the rejected
+    /// entry and its header and the reason of the rejection.
+    pub fn payload(header: &ChainHeader, reason: &str) -> String {
+        let reason_hash = HashString::encode_from_str(reason, Hash::SHA2256);
+        domain_payload(
+            WARRANT_DOMAIN,
+            &format!(
+                "{}:{}:{}",
+                header.entry_address(),
+                header.address(),
+                reason_hash
+            ),
+        )
+    }
+
+    pub fn entry(&self) -> &Entry {
+        &self.entry
+    }
+
+    pub fn header(&self) -> &ChainHeader {
+        &self.header
+    }
+
+    pub fn reason(&self) -> &String {
+        &self.reason
+    }
+
+    pub fn issuer(&self) -> &Address {
+        &self.issuer
+    }
+
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Address of the rejected entry
+    pub fn entry_address(&self) -> Address {
+        self.entry.address()
+    }
+}
+
+impl AddressableContent for Warrant {
+    fn content(&self) -> Content {
+        self.to_owned().into()
+    }
+
+    fn try_from_content(content: &Content) -> Result<Self, HolochainError> {
+        content.to_owned().try_into()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::{chain_header::test_chain_header, entry::test_entry};
+
+    pub fn test_warrant() -> Warrant {
+        Warrant::new(
+            test_entry(),
+            test_chain_header(),
+            "FAIL".to_string(),
+            Address::from("issuer"),
+            Signature::from("sig"),
+        )
+    }
+
+    #[test]
+    fn warrant_round_trip() {
+        let warrant = test_warrant();
+        assert_eq!(
+            Warrant::try_from_content(&warrant.content()),
+            Ok(warrant.clone())
+        );
+        assert_eq!(warrant.entry_address(), test_entry().address());
+    }
+
+    #[test]
+    fn payload_depends_on_reason() {
+        let header = test_chain_header();
+        assert_ne!(
+            Warrant::payload(&header, "FAIL"),
+            Warrant::payload(&header, "other reason")
+        );
+    }
+}
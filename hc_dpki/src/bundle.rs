@@ -1,8 +1,13 @@
+use serde_derive::{Deserialize, Serialize};
+
 /// This struct is the bundle for the Key pairs. i.e. signing and encryption keys
 ///
 /// The bundle_type tells if the bundle is a RootSeed bundle | DeviceSeed bundle | DevicePINSeed Bundle | ApplicationKeys Bundle
 ///
 /// the data includes a base64 encoded string of the ReturnBundleData Struct that was created by combining all the keys in one SecBuf
+///
+/// KeyBundles get stored as JSON in key files
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct KeyBundle {
     pub bundle_type: String,
     pub hint: String,
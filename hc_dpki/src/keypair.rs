@@ -1,9 +1,18 @@
 use crate::{
     bundle,
-    holochain_sodium::{kx, secbuf::SecBuf, sign},
+    holochain_sodium::{aead, hash, kx, random::random_secbuf, secbuf::SecBuf, sign},
     util::{self, PwHashConfig},
 };
-use holochain_core_types::{agent::KeyBuffer, error::HolochainError};
+use holochain_core_types::{
+    agent::{AgentId, KeyBuffer},
+    cas::content::AddressableContent,
+    entry::{
+        encrypted_entry::{EncryptedEntry, SealedKey},
+        Entry,
+    },
+    error::HolochainError,
+    json::JsonString,
+};
 use rustc_serialize::json;
 use std::str;
 
@@ -23,6 +32,8 @@ const BUNDLE_DATA_LEN_MISALIGN: usize = 1 // version byte
 
 pub const BUNDLE_DATA_LEN: usize = ((BUNDLE_DATA_LEN_MISALIGN + 8 - 1) / 8) * 8;
 
+const SYMMETRIC_SECRET_BYTES: usize = 32;
+
 impl Keypair {
     /// derive the pairs from a 32 byte seed buffer
     ///
@@ -43,6 +54,18 @@ impl Keypair {
         })
     }
 
+    /// derive the pairs from the sha256 hash of the given string
+    /// anyone who knows the string can re-derive the private keys,
+    /// so this is only meant for test and development agents
+    ///
+    /// @param {string} s - the string to derive the seed from
+    pub fn new_from_insecure_string(s: &str) -> Result<Self, HolochainError> {
+        let mut input = SecBuf::with_insecure_from_string(s.to_string());
+        let mut seed = SecBuf::with_insecure(SEEDSIZE);
+        hash::sha256(&mut input, &mut seed)?;
+        Keypair::new_from_seed(&mut seed)
+    }
+
     /// get the keypair identifier string
     ///
     /// @return {string}
@@ -50,6 +73,16 @@ impl Keypair {
         return self.pub_keys.clone();
     }
 
+    /// build the AgentId that is backed by this keypair
+    ///
+    /// @param {string} nick - the nickname of the agent
+    pub fn agent_id(&self, nick: &str) -> Result<AgentId, HolochainError> {
+        Ok(AgentId::new(
+            nick,
+            &KeyBuffer::with_corrected(&self.pub_keys)?,
+        ))
+    }
+
     /// generate an encrypted persistence bundle
     ///
     /// @param {SecBuf} passphrase - the encryption passphrase
@@ -172,170 +205,193 @@ impl Keypair {
         Ok(verified)
     }
 
-    // /// encrypt arbitrary data to be readale by potentially multiple recipients
-    // ///
-    // /// @param {array<string>} recipientIds - multiple recipient identifier strings
-    // ///
-    // /// @param {Buffer} data - the data to encrypt
-    // ///
-    // /// @param {Buffer} out - Empty vec[secBuf]
-    // pub fn encrypt(
-    //     &mut self,
-    //     recipient_id: Vec<&String>,
-    //     data: &mut SecBuf,
-    //     out: &mut Vec<SecBuf>,
-    // ) -> Result<(), HolochainError> {
-    //     let mut sym_secret = SecBuf::with_secure(32);
-    //     random_secbuf(&mut sym_secret);
-    //
-    //     let mut server_rx = SecBuf::with_insecure(kx::SESSIONKEYBYTES);
-    //     let mut server_tx = SecBuf::with_insecure(kx::SESSIONKEYBYTES);
-    //
-    //     let pub_keys = &mut self.pub_keys;
-    //     let mut sign_pub = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
-    //     let mut enc_pub = SecBuf::with_insecure(kx::PUBLICKEYBYTES);
-    //     util::decode_id(pub_keys.to_string(), &mut sign_pub, &mut enc_pub)?;
-    //
-    //     for client_pk in recipient_id {
-    //         let mut client_sign_pub = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
-    //         let mut client_enc_pub = SecBuf::with_insecure(kx::PUBLICKEYBYTES);
-    //
-    //         util::decode_id(
-    //             client_pk.to_string(),
-    //             &mut client_sign_pub,
-    //             &mut client_enc_pub,
-    //         )?;
-    //
-    //         kx::server_session(
-    //             &mut enc_pub,
-    //             &mut self.enc_priv,
-    //             &mut client_enc_pub,
-    //             &mut server_rx,
-    //             &mut server_tx,
-    //         )?;
-    //
-    //         let mut nonce = SecBuf::with_insecure(16);
-    //         random_secbuf(&mut nonce);
-    //         let mut cipher = SecBuf::with_insecure(sym_secret.len() + aead::ABYTES);
-    //
-    //         aead::enc(
-    //             &mut sym_secret,
-    //             &mut server_tx,
-    //             None,
-    //             &mut nonce,
-    //             &mut cipher,
-    //         )?;
-    //         out.push(nonce);
-    //         out.push(cipher);
-    //     }
-    //
-    //     let mut nonce = SecBuf::with_insecure(16);
-    //     random_secbuf(&mut nonce);
-    //     let mut cipher = SecBuf::with_insecure(data.len() + aead::ABYTES);
-    //     let mut data = data;
-    //     aead::enc(&mut data, &mut sym_secret, None, &mut nonce, &mut cipher)?;
-    //     out.push(nonce);
-    //     out.push(cipher);
-    //     Ok(())
-    // }
-    //
-    // /// attempt to decrypt the cipher buffer (assuming it was targeting us)
-    // ///
-    // /// @param {string} sourceId - identifier string of who encrypted this data
-    // ///
-    // /// @param {Buffer} cipher - the encrypted data
-    // ///
-    // /// @return {Result<SecBuf,String>} - the decrypted data
-    // pub fn decrypt(
-    //     &mut self,
-    //     source_id: String,
-    //     cipher_bundle: &mut Vec<SecBuf>,
-    // ) -> Result<SecBuf, HolochainError> {
-    //     let mut source_sign_pub = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
-    //     let mut source_enc_pub = SecBuf::with_insecure(kx::PUBLICKEYBYTES);
-    //     util::decode_id(source_id, &mut source_sign_pub, &mut source_enc_pub)?;
-    //
-    //     let client_pub_keys = &self.pub_keys;
-    //     let mut client_sign_pub = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
-    //     let mut client_enc_pub = SecBuf::with_insecure(kx::PUBLICKEYBYTES);
-    //     util::decode_id(
-    //         client_pub_keys.to_string(),
-    //         &mut client_sign_pub,
-    //         &mut client_enc_pub,
-    //     )?;
-    //     let mut client_enc_priv = &mut self.enc_priv;
-    //
-    //     let mut client_rx = SecBuf::with_insecure(kx::SESSIONKEYBYTES);
-    //     let mut client_tx = SecBuf::with_insecure(kx::SESSIONKEYBYTES);
-    //     kx::client_session(
-    //         &mut client_enc_pub,
-    //         &mut client_enc_priv,
-    //         &mut source_enc_pub,
-    //         &mut client_rx,
-    //         &mut client_tx,
-    //     )?;
-    //
-    //     let mut sys_secret_check: Option<SecBuf> = None;
-    //
-    //     while cipher_bundle.len() != 2 {
-    //         let mut nonce: Vec<_> = cipher_bundle.splice(..1, vec![]).collect();
-    //         let mut cipher: Vec<_> = cipher_bundle.splice(..1, vec![]).collect();
-    //         let mut nonce = &mut nonce[0];
-    //         let mut cipher = &mut cipher[0];
-    //         let mut sys_secret = SecBuf::with_insecure(cipher.len() - aead::ABYTES);
-    //
-    //         match aead::dec(
-    //             &mut sys_secret,
-    //             &mut client_rx,
-    //             None,
-    //             &mut nonce,
-    //             &mut cipher,
-    //         ) {
-    //             Ok(_) => {
-    //                 if util::check_if_wrong_secbuf(&mut sys_secret) {
-    //                     sys_secret_check = Some(sys_secret);
-    //                     break;
-    //                 } else {
-    //                     sys_secret_check = None;
-    //                 }
-    //             }
-    //             Err(_) => {
-    //                 sys_secret_check = None;
-    //             }
-    //         };
-    //     }
-    //
-    //     let mut cipher: Vec<_> = cipher_bundle
-    //         .splice(cipher_bundle.len() - 1.., vec![])
-    //         .collect();
-    //     let mut nonce: Vec<_> = cipher_bundle
-    //         .splice(cipher_bundle.len() - 1.., vec![])
-    //         .collect();
-    //     let mut nonce = &mut nonce[0];
-    //     let mut cipher = &mut cipher[0];
-    //     let mut decrypeted_message = SecBuf::with_insecure(cipher.len() - aead::ABYTES);
-    //
-    //     if let Some(mut secret) = sys_secret_check {
-    //         aead::dec(
-    //             &mut decrypeted_message,
-    //             &mut secret,
-    //             None,
-    //             &mut nonce,
-    //             &mut cipher,
-    //         )?;
-    //         Ok(decrypeted_message)
-    //     } else {
-    //         Err(HolochainError::new(
-    //             &"could not decrypt - not a recipient?".to_string(),
-    //         ))
-    //     }
-    // }
+    /// encrypt an entry to be readable by potentially multiple recipients
+    /// the entry gets encrypted with a random symmetric secret, which gets sealed
+    /// for each recipient with the session key of our and the recipient's encryption keys.
+    /// we are always one of the recipients, so the author can read its entries back
+    ///
+    /// @param {Entry} entry - the entry to encrypt
+    ///
+    /// @param {array<string>} recipient_ids - multiple recipient identifier strings
+    pub fn encrypt_entry(
+        &mut self,
+        entry: &Entry,
+        recipient_ids: &[String],
+    ) -> Result<EncryptedEntry, HolochainError> {
+        let mut sym_secret = SecBuf::with_secure(SYMMETRIC_SECRET_BYTES);
+        random_secbuf(&mut sym_secret);
+
+        let mut recipients = vec![self.pub_keys.clone()];
+        for recipient_id in recipient_ids {
+            if !recipients.contains(recipient_id) {
+                recipients.push(recipient_id.clone());
+            }
+        }
+
+        let mut keys = Vec::new();
+        for recipient in recipients {
+            let mut session_key = self.session_key(&recipient, true)?;
+            let cipher = {
+                let sym_secret = sym_secret.read_lock();
+                seal(&mut session_key, &sym_secret[..], recipient.as_bytes())?
+            };
+            keys.push(SealedKey { recipient, cipher });
+        }
+
+        let content = String::from(entry.content());
+        let cipher = seal(
+            &mut sym_secret,
+            content.as_bytes(),
+            String::from(entry.address()).as_bytes(),
+        )?;
+        Ok(EncryptedEntry::new(
+            entry.address(),
+            entry.entry_type(),
+            self.pub_keys.clone(),
+            keys,
+            cipher,
+        ))
+    }
+
+    /// attempt to decrypt an encrypted entry (assuming it was targeting us)
+    ///
+    /// @param {EncryptedEntry} encrypted_entry - the entry as sealed by its author
+    ///
+    /// @return {Result<Option<Entry>,HolochainError>} - the decrypted entry,
+    /// None if we are not one of its recipients
+    pub fn decrypt_entry(
+        &mut self,
+        encrypted_entry: &EncryptedEntry,
+    ) -> Result<Option<Entry>, HolochainError> {
+        let sealed_key = match encrypted_entry.key_for(&self.pub_keys) {
+            Some(sealed_key) => sealed_key,
+            None => return Ok(None),
+        };
+        let mut session_key = self.session_key(encrypted_entry.author(), false)?;
+        let sym_secret = open(
+            &mut session_key,
+            &sealed_key.cipher,
+            self.pub_keys.as_bytes(),
+        )?;
+        let mut sym_secret = secbuf_from(&sym_secret)?;
+        let content = open(
+            &mut sym_secret,
+            encrypted_entry.cipher(),
+            String::from(encrypted_entry.entry_address().clone()).as_bytes(),
+        )?;
+        let content = String::from_utf8(content)
+            .map_err(|error| HolochainError::ErrorGeneric(error.to_string()))?;
+        let entry = Entry::try_from_content(&JsonString::from(content))?;
+        if entry.address() != *encrypted_entry.entry_address() {
+            return Err(HolochainError::ErrorGeneric(
+                "Decrypted entry does not match its address".to_string(),
+            ));
+        }
+        Ok(Some(entry))
+    }
+
+    /// derive the key we share with the other party from the encryption keys
+    /// the author of an encrypted entry is the kx server, its recipients are the clients,
+    /// so that the author's tx key equals the recipient's rx key
+    ///
+    /// @param {string} other_id - identifier string of the other party
+    ///
+    /// @param {bool} as_author - whether we seal (true) or open (false)
+    fn session_key(&mut self, other_id: &str, as_author: bool) -> Result<SecBuf, HolochainError> {
+        let mut sign_pub = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
+        let mut enc_pub = SecBuf::with_insecure(kx::PUBLICKEYBYTES);
+        util::decode_id(self.pub_keys.clone(), &mut sign_pub, &mut enc_pub)?;
+        let mut other_sign_pub = SecBuf::with_insecure(sign::PUBLICKEYBYTES);
+        let mut other_enc_pub = SecBuf::with_insecure(kx::PUBLICKEYBYTES);
+        util::decode_id(
+            other_id.to_string(),
+            &mut other_sign_pub,
+            &mut other_enc_pub,
+        )?;
+
+        let mut rx = SecBuf::with_secure(kx::SESSIONKEYBYTES);
+        let mut tx = SecBuf::with_secure(kx::SESSIONKEYBYTES);
+        if as_author {
+            kx::server_session(
+                &mut enc_pub,
+                &mut self.enc_priv,
+                &mut other_enc_pub,
+                &mut rx,
+                &mut tx,
+            )?;
+            Ok(tx)
+        } else {
+            kx::client_session(
+                &mut enc_pub,
+                &mut self.enc_priv,
+                &mut other_enc_pub,
+                &mut rx,
+                &mut tx,
+            )?;
+            Ok(rx)
+        }
+    }
+}
+
+fn secbuf_from(bytes: &[u8]) -> Result<SecBuf, HolochainError> {
+    let mut buf = SecBuf::with_insecure(bytes.len());
+    buf.write(0, bytes)?;
+    Ok(buf)
+}
+
+/// encrypt the message with the secret and a random nonce
+/// the additional data gets authenticated but not stored
+///
+/// @return {string} - nonce and cipher text, base64 encoded
+fn seal(secret: &mut SecBuf, message: &[u8], adata: &[u8]) -> Result<String, HolochainError> {
+    let mut message = secbuf_from(message)?;
+    let mut adata = secbuf_from(adata)?;
+    let mut nonce = SecBuf::with_insecure(aead::NONCEBYTES);
+    random_secbuf(&mut nonce);
+    let mut cipher = SecBuf::with_insecure(message.len() + aead::ABYTES);
+    aead::enc(
+        &mut message,
+        secret,
+        Some(&mut adata),
+        &mut nonce,
+        &mut cipher,
+    )?;
+    let nonce = nonce.read_lock().to_vec();
+    let cipher = cipher.read_lock().to_vec();
+    Ok(base64::encode(&[nonce, cipher].concat()))
+}
+
+/// reverse `seal()`, failing if the cipher text was not sealed with the secret
+/// and the same additional data
+fn open(secret: &mut SecBuf, sealed: &str, adata: &[u8]) -> Result<Vec<u8>, HolochainError> {
+    let bytes = base64::decode(sealed)?;
+    if bytes.len() < aead::NONCEBYTES + aead::ABYTES {
+        return Err(HolochainError::ErrorGeneric(
+            "Sealed data is too short".to_string(),
+        ));
+    }
+    let (nonce, cipher) = bytes.split_at(aead::NONCEBYTES);
+    let mut nonce = secbuf_from(nonce)?;
+    let mut cipher = secbuf_from(cipher)?;
+    let mut adata = secbuf_from(adata)?;
+    let mut message = SecBuf::with_insecure(cipher.len() - aead::ABYTES);
+    aead::dec(
+        &mut message,
+        secret,
+        Some(&mut adata),
+        &mut nonce,
+        &mut cipher,
+    )
+    .map_err(|_| HolochainError::ErrorGeneric("Could not decrypt sealed data".to_string()))?;
+    let message = message.read_lock().to_vec();
+    Ok(message)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::holochain_sodium::{pwhash, random::random_secbuf};
+    use crate::holochain_sodium::pwhash;
+    use holochain_core_types::entry::test_entry;
 
     const TEST_CONFIG: Option<PwHashConfig> = Some(PwHashConfig(
         pwhash::OPSLIMIT_INTERACTIVE,
@@ -385,140 +441,58 @@ mod tests {
         assert_eq!(0, check);
     }
 
-    // #[test]
-    // fn it_should_encode_n_decode_data() {
-    //     let mut seed = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed);
-    //     let mut keypair_main = Keypair::new_from_seed(&mut seed).unwrap();
-    //
-    //     let mut seed_1 = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed_1);
-    //     let mut keypair_1 = Keypair::new_from_seed(&mut seed_1).unwrap();
-    //
-    //     let mut message = SecBuf::with_insecure(16);
-    //     random_secbuf(&mut message);
-    //
-    //     let recipient_id = vec![&keypair_1.pub_keys];
-    //
-    //     let mut out = Vec::new();
-    //     keypair_main
-    //         .encrypt(recipient_id, &mut message, &mut out)
-    //         .unwrap();
-    //
-    //     match keypair_1.decrypt(keypair_main.pub_keys, &mut out) {
-    //         Ok(mut dm) => {
-    //             let message = message.read_lock();
-    //             let dm = dm.read_lock();
-    //             assert_eq!(format!("{:?}", *message), format!("{:?}", *dm));
-    //         }
-    //         Err(_) => {
-    //             assert!(false);
-    //         }
-    //     };
-    // }
-    //
-    // #[test]
-    // fn it_should_encode_n_decode_data_for_multiple_users2() {
-    //     let mut seed = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed);
-    //     let mut keypair_main = Keypair::new_from_seed(&mut seed).unwrap();
-    //
-    //     let mut seed_1 = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed_1);
-    //     let keypair_1 = Keypair::new_from_seed(&mut seed_1).unwrap();
-    //
-    //     let mut seed_2 = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed_2);
-    //     let mut keypair_2 = Keypair::new_from_seed(&mut seed_2).unwrap();
-    //
-    //     let mut message = SecBuf::with_insecure(16);
-    //     random_secbuf(&mut message);
-    //
-    //     let recipient_id = vec![&keypair_1.pub_keys, &keypair_2.pub_keys];
-    //
-    //     let mut out = Vec::new();
-    //     keypair_main
-    //         .encrypt(recipient_id, &mut message, &mut out)
-    //         .unwrap();
-    //
-    //     match keypair_2.decrypt(keypair_main.pub_keys, &mut out) {
-    //         Ok(mut dm) => {
-    //             let message = message.read_lock();
-    //             let dm = dm.read_lock();
-    //             assert_eq!(format!("{:?}", *message), format!("{:?}", *dm));
-    //         }
-    //         Err(_) => {
-    //             assert!(false);
-    //         }
-    //     };
-    // }
-    //
-    // #[test]
-    // fn it_should_encode_n_decode_data_for_multiple_users1() {
-    //     let mut seed = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed);
-    //     let mut keypair_main = Keypair::new_from_seed(&mut seed).unwrap();
-    //
-    //     let mut seed_1 = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed_1);
-    //     let mut keypair_1 = Keypair::new_from_seed(&mut seed_1).unwrap();
-    //
-    //     let mut seed_2 = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed_2);
-    //     let keypair_2 = Keypair::new_from_seed(&mut seed_2).unwrap();
-    //
-    //     let mut message = SecBuf::with_insecure(16);
-    //     random_secbuf(&mut message);
-    //
-    //     let recipient_id = vec![&keypair_1.pub_keys, &keypair_2.pub_keys];
-    //
-    //     let mut out = Vec::new();
-    //     keypair_main
-    //         .encrypt(recipient_id, &mut message, &mut out)
-    //         .unwrap();
-    //
-    //     match keypair_1.decrypt(keypair_main.pub_keys, &mut out) {
-    //         Ok(mut dm) => {
-    //             println!("Decrypted Message: {:?}", dm);
-    //             let message = message.read_lock();
-    //             let dm = dm.read_lock();
-    //             assert_eq!(format!("{:?}", *message), format!("{:?}", *dm));
-    //         }
-    //         Err(_) => {
-    //             println!("Error");
-    //             assert!(false);
-    //         }
-    //     };
-    // }
-    //
-    // #[test]
-    // fn it_should_with_fail_when_wrong_key_used_to_decrypt() {
-    //     let mut seed = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed);
-    //     let mut keypair_main = Keypair::new_from_seed(&mut seed).unwrap();
-    //
-    //     let mut seed_1 = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed_1);
-    //     let keypair_1 = Keypair::new_from_seed(&mut seed_1).unwrap();
-    //
-    //     let mut seed_2 = SecBuf::with_insecure(SEEDSIZE);
-    //     random_secbuf(&mut seed_2);
-    //     let mut keypair_2 = Keypair::new_from_seed(&mut seed_2).unwrap();
-    //
-    //     let mut message = SecBuf::with_insecure(16);
-    //     random_secbuf(&mut message);
-    //
-    //     let recipient_id = vec![&keypair_1.pub_keys];
-    //
-    //     let mut out = Vec::new();
-    //     keypair_main
-    //         .encrypt(recipient_id, &mut message, &mut out)
-    //         .unwrap();
-    //
-    //     keypair_2
-    //         .decrypt(keypair_main.pub_keys, &mut out)
-    //         .expect_err("should have failed");
-    // }
+    #[test]
+    fn it_should_encrypt_and_decrypt_entry_for_recipients() {
+        let mut alice = Keypair::new_from_insecure_string("alice").unwrap();
+        let mut bob = Keypair::new_from_insecure_string("bob").unwrap();
+        let mut carol = Keypair::new_from_insecure_string("carol").unwrap();
+
+        let entry = test_entry();
+        let encrypted_entry = alice.encrypt_entry(&entry, &[bob.get_id()]).unwrap();
+        assert_eq!(*encrypted_entry.entry_address(), entry.address());
+        assert_eq!(encrypted_entry.keys().len(), 2);
+        assert!(!encrypted_entry.cipher().contains("test entry value"));
+
+        assert_eq!(
+            alice.decrypt_entry(&encrypted_entry),
+            Ok(Some(entry.clone()))
+        );
+        assert_eq!(bob.decrypt_entry(&encrypted_entry), Ok(Some(entry)));
+        assert_eq!(carol.decrypt_entry(&encrypted_entry), Ok(None));
+    }
+
+    #[test]
+    fn it_should_fail_to_decrypt_with_a_key_sealed_for_someone_else() {
+        let mut alice = Keypair::new_from_insecure_string("alice").unwrap();
+        let mut bob = Keypair::new_from_insecure_string("bob").unwrap();
+        let mut carol = Keypair::new_from_insecure_string("carol").unwrap();
+
+        let encrypted_entry = alice.encrypt_entry(&test_entry(), &[bob.get_id()]).unwrap();
+        // carol claims bob's sealed key as her own
+        let bobs_key = encrypted_entry.key_for(&bob.get_id()).unwrap().clone();
+        let forged_entry = EncryptedEntry::new(
+            encrypted_entry.entry_address().clone(),
+            encrypted_entry.entry_type().clone(),
+            encrypted_entry.author().clone(),
+            vec![SealedKey {
+                recipient: carol.get_id(),
+                cipher: bobs_key.cipher,
+            }],
+            encrypted_entry.cipher().clone(),
+        );
+        assert!(carol.decrypt_entry(&forged_entry).is_err());
+    }
+
+    #[test]
+    fn it_should_derive_the_same_keys_from_the_same_string() {
+        let mut keypair_1 = Keypair::new_from_insecure_string("alice").unwrap();
+        let mut keypair_2 = Keypair::new_from_insecure_string("alice").unwrap();
+        let mut keypair_3 = Keypair::new_from_insecure_string("bob").unwrap();
+
+        assert_eq!(keypair_1.get_id(), keypair_2.get_id());
+        assert_ne!(keypair_1.get_id(), keypair_3.get_id());
+        assert_eq!(keypair_1.agent_id("alice").unwrap().key, keypair_1.get_id());
+    }
 
     #[test]
     fn it_should_get_from_bundle() {
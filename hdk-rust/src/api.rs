@@ -8,22 +8,28 @@ use crate::{
 };
 use holochain_core_types::{
     cas::content::Address,
-    dna::capabilities::CapabilityCall,
-    entry::Entry,
+    dna::capabilities::{CallSignature, CapabilityCall, CapabilityType},
+    entry::{cap_entries::CapTokenGrant, Entry},
     error::{RibosomeEncodedAllocation, RibosomeEncodingBits, ZomeApiInternalResult},
     time::Timeout,
 };
 pub use holochain_wasm_utils::api_serialization::validation::*;
 use holochain_wasm_utils::{
     api_serialization::{
+        bundle::{CloseBundleArgs, StartBundleArgs},
+        capabilities::{CapabilityGrantsResult, GrantCapabilityArgs},
+        emit_signal::EmitSignalArgs,
         get_entry::{
             EntryHistory, GetEntryArgs, GetEntryOptions, GetEntryResult, GetEntryResultType,
             StatusRequestKind,
         },
         get_links::{GetLinksArgs, GetLinksOptions, GetLinksResult},
         link_entries::LinkEntriesArgs,
+        property::PropertyArgs,
         send::{SendArgs, SendOptions},
-        QueryArgs, QueryArgsNames, QueryArgsOptions, QueryResult, UpdateEntryArgs, ZomeFnCallArgs,
+        sign::{SignArgs, VerifySignatureArgs},
+        CommitEncryptedEntryArgs, QueryArgs, QueryArgsNames, QueryArgsOptions, QueryResult,
+        UpdateEntryArgs, ZomeFnCallArgs,
     },
     holochain_core_types::{
         hash::HashString,
@@ -54,8 +60,8 @@ lazy_static! {
   /// The identity string used when the chain was first initialized.
   pub static ref AGENT_ID_STR: &'static str = &GLOBALS.agent_id_str;
 
-  /// The hash of your public key.
-  /// This is your node address on the DHT.
+  /// The hash of your initial public key.
+  /// This is your node address on the DHT and does not change when the key gets rotated.
   /// It can be used for node-to-node messaging with `send` and `receive` functions.
   pub static ref AGENT_ADDRESS: &'static Address = &GLOBALS.agent_address;
 
@@ -177,10 +183,7 @@ impl Default for GetEntryMask {
 //}
 
 /// Allowed input for close_bundle()
-pub enum BundleOnClose {
-    Commit,
-    Discard,
-}
+pub use holochain_wasm_utils::api_serialization::bundle::BundleOnClose;
 
 //--------------------------------------------------------------------------------------------------
 // API FUNCTIONS
@@ -200,6 +203,18 @@ pub enum Dispatch {
     Query,
     Send,
     Sleep,
+    Sign,
+    VerifySignature,
+    Property,
+    RemoveLink,
+    StartBundle,
+    CloseBundle,
+    UpdateAgent,
+    GrantCapability,
+    RevokeCapability,
+    ListCapabilityGrants,
+    CommitEncryptedEntry,
+    EmitSignal,
 }
 
 impl Dispatch {
@@ -236,6 +251,18 @@ impl Dispatch {
                 Dispatch::Query => hc_query,
                 Dispatch::Send => hc_send,
                 Dispatch::Sleep => hc_sleep,
+                Dispatch::Sign => hc_sign,
+                Dispatch::VerifySignature => hc_verify_signature,
+                Dispatch::Property => hc_property,
+                Dispatch::RemoveLink => hc_remove_link,
+                Dispatch::StartBundle => hc_start_bundle,
+                Dispatch::CloseBundle => hc_close_bundle,
+                Dispatch::UpdateAgent => hc_update_agent,
+                Dispatch::GrantCapability => hc_grant_capability,
+                Dispatch::RevokeCapability => hc_revoke_capability,
+                Dispatch::ListCapabilityGrants => hc_list_capability_grants,
+                Dispatch::CommitEncryptedEntry => hc_commit_encrypted_entry,
+                Dispatch::EmitSignal => hc_emit_signal,
             })(encoded_input)
         };
 
@@ -268,6 +295,9 @@ impl Dispatch {
 /// on the same agent in the same conductor.
 /// Arguments for the called function are passed as `JsonString`.
 /// Returns the value that's returned by the given function as a json str.
+/// Calls into this instance are signed with the agent's key. Unless the called function is
+/// public, the callee checks `cap_token` against the `CapTokenGrant` committed under that
+/// address and fails the call with a capability check error if it is not granted to this agent.
 /// # Examples
 /// In order to utilize `call`, you must have at least two separate Zomes.
 /// Here are two Zome examples, where one performs a `call` into the other.
@@ -309,11 +339,35 @@ impl Dispatch {
 /// # #[no_mangle]
 /// # pub fn hc_sleep(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
+/// # pub fn hc_sign(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_verify_signature(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_property(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
 /// # pub fn hc_debug(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_get_links(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_link_entries(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_emit_signal(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_commit_encrypted_entry(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_grant_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_revoke_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_list_capability_grants(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_update_agent(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_close_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_start_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_remove_link(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 ///
 /// # fn main() {
 ///
@@ -388,11 +442,35 @@ impl Dispatch {
 /// # #[no_mangle]
 /// # pub fn hc_sleep(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
+/// # pub fn hc_sign(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_verify_signature(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_property(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
 /// # pub fn hc_debug(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_get_links(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_link_entries(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_emit_signal(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_commit_encrypted_entry(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_grant_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_revoke_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_list_capability_grants(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_update_agent(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_close_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_start_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_remove_link(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 ///
 /// # fn main() {
 ///
@@ -441,7 +519,12 @@ pub fn call<S: Into<String>>(
     Dispatch::Call.with_input(ZomeFnCallArgs {
         instance_handle: instance_handle.into(),
         zome_name: zome_name.into(),
-        cap: Some(CapabilityCall::new(Address::from(cap_token.into()), None)),
+        // the caller and signature get filled in by the ribosome on behalf of this agent
+        cap: Some(CapabilityCall::new(
+            Address::from(cap_token.into()),
+            None,
+            CallSignature::default(),
+        )),
         fn_name: fn_name.into(),
         fn_args: String::from(fn_args),
     })
@@ -524,6 +607,19 @@ pub fn commit_entry(entry: &Entry) -> ZomeApiResult<Address> {
     Dispatch::CommitEntry.with_input(entry)
 }
 
+/// Commits an entry of a type the DNA declares with `"sharing": "encrypted"` to your local
+/// source chain and publishes it sealed for the given recipients, which are addressed by
+/// their agent addresses (`AGENT_ADDRESS`). The author is always one of the recipients.
+/// DHT nodes hold the sealed entry without being able to read it, for every other agent
+/// `get_entry` on its address returns None.
+/// Entries of encrypted types committed with `commit_entry` are sealed for their author only.
+pub fn commit_encrypted_entry(entry: &Entry, recipients: Vec<Address>) -> ZomeApiResult<Address> {
+    Dispatch::CommitEncryptedEntry.with_input(CommitEncryptedEntryArgs {
+        entry: entry.clone(),
+        recipients,
+    })
+}
+
 /// Retrieves latest version of an entry from the local chain or the DHT, by looking it up using
 /// the specified address.
 /// Returns None if no entry exists at the specified address or
@@ -549,7 +645,7 @@ pub fn commit_entry(entry: &Entry) -> ZomeApiResult<Address> {
 pub fn get_entry(address: &Address) -> ZomeApiResult<Option<Entry>> {
     let entry_result = get_entry_result(address, GetEntryOptions::default())?;
 
-    let entry = if !entry_result.found() {
+    let entry = if !entry_result.found() || entry_result.rejected() {
         None
     } else {
         entry_result.latest()
@@ -601,6 +697,8 @@ pub fn get_entry_result(
 /// Consumes three values, two of which are the addresses of entries, and one of which is a string that defines a
 /// relationship between them, called a `tag`. Later, lists of entries can be looked up by using [get_links](fn.get_links.html). Entries
 /// can only be looked up in the direction from the `base`, which is the first argument, to the `target`.
+/// A link that got removed with [remove_link](fn.remove_link.html) stays removed: linking the same
+/// `base`, `target` and `tag` again does not bring it back.
 /// # Examples
 /// ```rust
 /// # #![feature(try_from)]
@@ -666,13 +764,57 @@ pub fn link_entries<S: Into<String>>(
     })
 }
 
-/// NOT YET AVAILABLE
-// Returns a DNA property, which are defined by the DNA developer.
-// They are custom values that are defined in the DNA file
-// that can be used in the zome code for defining configurable behaviors.
-// (e.g. Name, Language, Description, Author, etc.).
-pub fn property<S: Into<String>>(_name: S) -> ZomeApiResult<String> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Removes a link between two entries that was previously created with [link_entries](fn.link_entries.html).
+/// This commits a `LinkRemove` entry carrying the same `base`, `target` and `tag`, which marks
+/// the link as deleted in the DHT. Removed links are no longer returned by [get_links](fn.get_links.html)
+/// unless they are explicitly requested with `LinksStatusRequestKind::Deleted` or `LinksStatusRequestKind::All`.
+/// The removal is permanent: DHT nodes keep the tombstone of the link, no matter in which order
+/// they receive additions and removals, so calling [link_entries](fn.link_entries.html) with the
+/// same `base`, `target` and `tag` again does not revive it. Link with another tag instead.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::cas::content::Address;
+/// # use hdk::error::ZomeApiResult;
+/// # fn main() {
+/// pub fn handle_unfollow(follower: Address, followed: Address) -> ZomeApiResult<()> {
+///     hdk::remove_link(&follower, &followed, "follows")
+/// }
+/// # }
+/// ```
+pub fn remove_link<S: Into<String>>(
+    base: &Address,
+    target: &Address,
+    tag: S,
+) -> Result<(), ZomeApiError> {
+    Dispatch::RemoveLink.with_input(LinkEntriesArgs {
+        base: base.clone(),
+        target: target.clone(),
+        tag: tag.into(),
+    })
+}
+
+/// Returns a DNA property, which are defined by the DNA developer.
+/// They are custom values that are defined in the DNA file
+/// that can be used in the zome code for defining configurable behaviors.
+/// (e.g. Name, Language, Description, Author, etc.).
+/// The key is either the name of a top level property or, if it starts with "/",
+/// a JSON pointer into the properties (e.g. "/limits/max_post_length").
+/// Returns the JSON of the property's value or an error if the DNA does not define it.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # use hdk::error::ZomeApiResult;
+/// # use hdk::holochain_core_types::json::JsonString;
+/// # fn main() {
+/// pub fn handle_get_max_post_length() -> ZomeApiResult<JsonString> {
+///     hdk::property("/limits/max_post_length")
+/// }
+/// # }
+/// ```
+pub fn property<S: Into<String>>(name: S) -> ZomeApiResult<JsonString> {
+    Dispatch::Property.with_input(PropertyArgs { key: name.into() })
 }
 
 /// Reconstructs an address of the given entry data.
@@ -719,18 +861,55 @@ pub fn entry_address(entry: &Entry) -> ZomeApiResult<Address> {
     Dispatch::EntryAddress.with_input(entry)
 }
 
-/// NOT YET AVAILABLE
-pub fn sign<S: Into<String>>(_doc: S) -> ZomeApiResult<String> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Signs a string payload using the agent's private key.
+/// Returns the signature as a base64 encoded string.
+/// The payload gets signed with the domain tag `hc:sign:` in front of it, which keeps zomes
+/// from signing payloads the agent signs for other purposes, like header provenances or
+/// capability calls. [verify_signature](fn.verify_signature.html) expects this tag.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # use hdk::error::ZomeApiResult;
+/// # fn main() {
+/// pub fn handle_sign_message(message: String) -> ZomeApiResult<String> {
+///     hdk::sign(message)
+/// }
+/// # }
+/// ```
+pub fn sign<S: Into<String>>(payload: S) -> ZomeApiResult<String> {
+    Dispatch::Sign.with_input(SignArgs {
+        payload: payload.into(),
+    })
 }
 
-/// NOT YET AVAILABLE
+/// Verifies that the given signature was made over the given payload with the private key
+/// that belongs to the given public key (which is an agent address).
+/// Returns `false` for signatures that are malformed or don't match.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # use hdk::error::ZomeApiResult;
+/// # use hdk::holochain_core_types::cas::content::Address;
+/// # fn main() {
+/// pub fn handle_check_receipt(
+///     signature: String,
+///     receipt: String,
+///     signer: Address,
+/// ) -> ZomeApiResult<bool> {
+///     hdk::verify_signature(signature, receipt, signer.to_string())
+/// }
+/// # }
+/// ```
 pub fn verify_signature<S: Into<String>>(
-    _signature: S,
-    _data: S,
-    _pub_key: S,
+    signature: S,
+    payload: S,
+    pub_key: S,
 ) -> ZomeApiResult<bool> {
-    Err(ZomeApiError::FunctionNotImplemented)
+    Dispatch::VerifySignature.with_input(VerifySignatureArgs {
+        pub_key: pub_key.into(),
+        signature: signature.into(),
+        payload: payload.into(),
+    })
 }
 
 /// Commit an entry to your local source chain that "updates" a previous entry, meaning when getting
@@ -746,9 +925,40 @@ pub fn update_entry(new_entry: Entry, address: &Address) -> ZomeApiResult<Addres
     })
 }
 
-/// NOT YET AVAILABLE
+/// Rotates the agent's key.
+/// A fresh keypair gets generated and its public key committed as a new `AgentId` entry,
+/// which updates the previous `AgentId` entry. This commit is still signed with the old key,
+/// every subsequent one with the new key. The agent keeps its initial address as identity
+/// on the DHT, which validates each header against the key that was current at its position
+/// in the source chain.
+/// Returns the address of the new `AgentId` entry.
+///
+/// To retire a source chain altogether (e.g. when moving to a new version of the DNA),
+/// commit a closing `ChainMigrate` entry instead. No entries can be committed after it.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::{
+/// #     cas::content::Address, chain_migrate::ChainMigrate, entry::Entry,
+/// # };
+/// # use hdk::error::ZomeApiResult;
+/// # fn main() {
+/// pub fn handle_rotate_key() -> ZomeApiResult<Address> {
+///     hdk::update_agent()
+/// }
+///
+/// pub fn handle_migrate(new_dna: Address, new_agent: Address) -> ZomeApiResult<Address> {
+///     hdk::commit_entry(&Entry::ChainMigrate(ChainMigrate::close(
+///         new_dna,
+///         new_agent,
+///         "upgrade to v2".into(),
+///     )))
+/// }
+/// # }
+/// ```
 pub fn update_agent() -> ZomeApiResult<Address> {
-    Err(ZomeApiError::FunctionNotImplemented)
+    Dispatch::UpdateAgent.with_input(JsonString::empty_object())
 }
 
 /// Commit a DeletionEntry to your local source chain that marks an entry as 'deleted' by setting
@@ -998,11 +1208,35 @@ pub fn query_result(
 /// # #[no_mangle]
 /// # pub fn hc_sleep(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
+/// # pub fn hc_sign(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_verify_signature(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_property(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
 /// # pub fn hc_debug(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_get_links(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_link_entries(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_emit_signal(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_commit_encrypted_entry(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_grant_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_revoke_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_list_capability_grants(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_update_agent(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_close_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_start_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_remove_link(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 ///
 /// # fn main() {
 /// fn handle_send_message(to_agent: Address, message: String) -> ZomeApiResult<String> {
@@ -1043,14 +1277,95 @@ pub fn send(to_agent: Address, payload: String, timeout: Timeout) -> ZomeApiResu
     })
 }
 
-/// NOT YET AVAILABLE
-pub fn start_bundle(_timeout: usize, _user_param: serde_json::Value) -> ZomeApiResult<()> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Opens a bundle, which makes all following commits of the current zome function call
+/// all-or-nothing.
+/// Entries committed (and links created) while the bundle is open are only staged and are
+/// neither validated, appended to the source chain nor published until the bundle gets closed
+/// with [close_bundle](fn.close_bundle.html). The same holds for updates and removals of entries,
+/// including entries staged in the bundle.
+/// Each zome function call can have one bundle open at a time. Commits of other, concurrent
+/// calls are not affected by it, but if they extend the source chain before the bundle gets
+/// closed, committing the bundle fails. If the bundle is still open after `timeout`
+/// milliseconds, all of its staged commits get discarded.
+/// `user_param` is arbitrary data that gets stored with the bundle.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::{cas::content::Address, entry::Entry};
+/// # use hdk::{api::BundleOnClose, error::ZomeApiResult};
+/// # fn main() {
+/// pub fn handle_post_with_tag(post: Entry, tag: Entry) -> ZomeApiResult<Address> {
+///     hdk::start_bundle(10000, "post with tag".into())?;
+///     let post_address = hdk::commit_entry(&post)?;
+///     let tag_address = hdk::commit_entry(&tag)?;
+///     hdk::link_entries(&tag_address, &post_address, "tagged")?;
+///     // either all three commits end up on the source chain or none of them
+///     hdk::close_bundle(BundleOnClose::Commit)?;
+///     Ok(post_address)
+/// }
+/// # }
+/// ```
+pub fn start_bundle(timeout: usize, user_param: serde_json::Value) -> ZomeApiResult<()> {
+    Dispatch::StartBundle.with_input(StartBundleArgs {
+        timeout: Timeout::new(timeout),
+        user_param,
+    })
+}
+
+/// Closes the bundle that was opened with [start_bundle](fn.start_bundle.html).
+/// With `BundleOnClose::Commit` all staged entries get validated in the order they were committed.
+/// If all of them are valid they get appended to the source chain at once and published,
+/// otherwise the whole bundle gets discarded and the validation error is returned.
+/// With `BundleOnClose::Discard` all staged entries get dropped.
+pub fn close_bundle(action: BundleOnClose) -> ZomeApiResult<()> {
+    Dispatch::CloseBundle.with_input(CloseBundleArgs { action })
+}
+
+/// Grants access to the functions of the capability `cap_name` of the calling zome by
+/// committing a `CapTokenGrant` entry to the agent's source chain.
+/// Grants are private entries and never get published.
+/// Returns the token that callers have to pass to [call](fn.call.html), which is the address
+/// of the grant entry. `Assigned` grants need a list of assignees and can only be used by
+/// these agents, `Transferable` grants can be used by anybody who knows the token.
+/// # Examples
+/// ```rust
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use holochain_core_types::{cas::content::Address, dna::capabilities::CapabilityType};
+/// # use hdk::error::ZomeApiResult;
+/// # fn main() {
+/// pub fn handle_share_with(agent: Address) -> ZomeApiResult<Address> {
+///     hdk::grant_capability("share", CapabilityType::Assigned, Some(vec![agent]))
+/// }
+/// # }
+/// ```
+pub fn grant_capability<S: Into<String>>(
+    cap_name: S,
+    cap_type: CapabilityType,
+    assignees: Option<Vec<Address>>,
+) -> ZomeApiResult<Address> {
+    Dispatch::GrantCapability.with_input(GrantCapabilityArgs {
+        cap_name: cap_name.into(),
+        cap_type,
+        assignees,
+    })
+}
+
+/// Revokes the grant with the given token that was made with
+/// [grant_capability](fn.grant_capability.html), by committing a deletion of the grant entry
+/// to the source chain. Calls using the token fail from then on.
+/// Like the grant, the deletion does not get published.
+pub fn revoke_capability(token: &Address) -> ZomeApiResult<()> {
+    Dispatch::RevokeCapability.with_input(token.to_owned())
 }
 
-/// NOT YET AVAILABLE
-pub fn close_bundle(_action: BundleOnClose) -> ZomeApiResult<()> {
-    Err(ZomeApiError::FunctionNotImplemented)
+/// Returns all grants on the agent's source chain that were not revoked, newest first.
+/// The token of a grant is available through `CapTokenGrant::token()`.
+pub fn list_capability_grants() -> ZomeApiResult<Vec<CapTokenGrant>> {
+    let result: CapabilityGrantsResult =
+        Dispatch::ListCapabilityGrants.with_input(JsonString::empty_object())?;
+    Ok(result.grants)
 }
 
 /// Lets the DNA runtime sleep for the given duration.
@@ -1077,3 +1392,34 @@ pub fn sleep(duration: Duration) -> ZomeApiResult<()> {
     // return Ok(()) unconditionally instead of the "error" from success
     Ok(())
 }
+
+/// Emits a signal to the UIs connected to the running instance, e.g. to tell them about
+/// new data without having them poll for it.
+/// The conductor pushes the signal, tagged with the id of the instance, to all websocket
+/// clients of interfaces that expose the instance.
+/// The payload can be anything that converts into a JsonString.
+/// # Examples
+/// ```rust
+/// # #[macro_use]
+/// # extern crate hdk;
+/// # extern crate holochain_core_types;
+/// # use hdk::error::ZomeApiResult;
+/// # use holochain_core_types::json::{JsonString, RawString};
+///
+/// # fn main() {
+/// pub fn handle_receive_message(message: String) -> ZomeApiResult<()> {
+///     // ...
+///     hdk::emit_signal("new_message", JsonString::from(RawString::from(message)))
+/// }
+///
+/// # }
+/// ```
+pub fn emit_signal<S: Into<String>, J: Into<JsonString>>(name: S, payload: J) -> ZomeApiResult<()> {
+    let _: ZomeApiResult<()> = Dispatch::EmitSignal.with_input(EmitSignalArgs {
+        name: name.into(),
+        payload: String::from(payload.into()),
+    });
+    // internally returns RibosomeEncodedValue::Success which is a zero length allocation
+    // return Ok(()) unconditionally instead of the "error" from success
+    Ok(())
+}
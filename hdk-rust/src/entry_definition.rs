@@ -64,22 +64,29 @@ pub struct ValidatingLinkDefinition {
 
 /// The `entry` macro is a helper for creating `ValidatingEntryType` definitions
 /// for use within the [define_zome](macro.define_zome.html) macro.
-/// It has 7 component parts:
+/// It has 9 component parts:
 /// 1. name: `name` is simply the descriptive name of the entry type, such as "post", or "user".
 ///      It is what must be given as the `entry_type_name` argument when calling [commit_entry](fn.commit_entry.html) and the other data read/write functions.
 /// 2. description: `description` is something that is primarily for human readers of your code, just describe this entry type
 /// 3. sharing: `sharing` defines what distribution over the DHT, or not, occurs with entries of this type, possible values
 ///      are defined in the [Sharing](../core_types/entry/dna/zome/entry_types/enum.Sharing.html) enum
 /// 4. native_type: `native_type` references a given Rust struct, which provides a clear schema for entries of this type.
-/// 5. validation_package: `validation_package` is a special identifier, which declares which data is required from peers
+/// 5. conflict_resolution (optional): `conflict_resolution` defines which update wins when an entry of this type
+///      gets updated concurrently, possible values are defined in the
+///      [ConflictResolution](../core_types/entry/dna/zome/entry_types/enum.ConflictResolution.html) enum.
+///      Defaults to last-writer-wins, `ConflictResolution::Callback` lets the zome's `resolve_conflict` callback decide.
+/// 6. schema (optional): `schema` is a JSON Schema (draft 4), given as a `serde_json::Value`, that the entries of
+///      this type have to match. Holochain checks it before calling `validation`, both when committing an entry
+///      and on every node that holds it, and fails validation with the parts of the entry that don't match.
+/// 7. validation_package: `validation_package` is a special identifier, which declares which data is required from peers
 ///      when attempting to validate entries of this type.
 ///      Possible values are found within [ValidationPackageDefinition](enum.ValidationPackageDefinition.html)
-/// 6. validation: `validation` is a callback function which will be called any time that a
+/// 8. validation: `validation` is a callback function which will be called any time that a
 ///      (DHT) node processes or stores this entry, triggered through actions such as [commit_entry](fn.commit_entry.html), [update_entry](fn.update_entry.html), [remove_entry](fn.remove_entry.html).
 ///      It always expects two arguments, the first of which is the entry attempting to be validated,
 ///      the second is the validation `context`, which offers a variety of metadata useful for validation.
 ///      See [ValidationData](struct.ValidationData.html) for more details.
-/// 7. links: `links` is a vector of link definitions represented by `ValidatingLinkDefinition`.
+/// 9. links: `links` is a vector of link definitions represented by `ValidatingLinkDefinition`.
 ///     Links can be defined with the `link!` macro or, more concise, with either the `to!` or `from!` macro,
 ///     to define an association pointing from this entry type to another, or one that points back from
 ///     the other entry type to this one.
@@ -157,6 +164,8 @@ macro_rules! entry {
         description: $description:expr,
         sharing: $sharing:expr,
         $(native_type: $native_type:ty,)*
+        $(conflict_resolution: $conflict_resolution:expr,)*
+        $(schema: $schema:expr,)*
 
         validation_package: || $package_creator:expr,
         validation: | $entry:ident : $entry_type:ty, $validation_data:ident : hdk::ValidationData | $entry_validation:expr
@@ -174,6 +183,12 @@ macro_rules! entry {
             let mut entry_type = hdk::holochain_core_types::dna::entry_types::EntryTypeDef::new();
             entry_type.description = String::from($description);
             entry_type.sharing = $sharing;
+            $(
+                entry_type.conflict_resolution = $conflict_resolution;
+            )*
+            $(
+                entry_type.schema = Some($schema);
+            )*
 
             $($(
                 match $link_expr.link_type {
@@ -53,4 +53,18 @@ extern "C" {
     pub(crate) fn hc_close_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
 
     pub(crate) fn hc_sleep(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
+
+    pub(crate) fn hc_remove_link(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
+
+    pub(crate) fn hc_update_agent(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
+
+    pub(crate) fn hc_grant_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
+
+    pub(crate) fn hc_revoke_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
+
+    pub(crate) fn hc_list_capability_grants(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
+
+    pub(crate) fn hc_commit_encrypted_entry(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
+
+    pub(crate) fn hc_emit_signal(_: RibosomeEncodingBits) -> RibosomeEncodingBits;
 }
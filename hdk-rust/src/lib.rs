@@ -40,7 +40,9 @@ pub mod globals;
 pub mod init_globals;
 pub mod macros;
 
-pub use holochain_wasm_utils::api_serialization::{validation::*, THIS_INSTANCE};
+pub use holochain_wasm_utils::api_serialization::{
+    resolve_conflict::*, validation::*, THIS_INSTANCE,
+};
 
 pub mod meta;
 
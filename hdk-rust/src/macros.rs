@@ -40,7 +40,7 @@ macro_rules! load_string {
 
 /// Every Zome must utilize the `define_zome`
 /// macro in the main library file in their Zome.
-/// The `define_zome` macro has 4 component parts:
+/// The `define_zome` macro has 6 component parts:
 /// 1. entries: an array of [ValidatingEntryType](entry_definition/struct.ValidatingEntryType.html) as returned by using the [entry](macro.entry.html) macro
 /// 2. genesis: `genesis` is a callback called by Holochain to every Zome implemented within a DNA.
 ///     It gets called when a new agent is initializing an instance of the DNA for the first time, and
@@ -48,7 +48,17 @@ macro_rules! load_string {
 /// 3. receive (optional): `receive` is a callback called by Holochain when another agent on a hApp has initiated a node-to-node direct message.
 ///     That node-to-node message is initiated via the [**send** function of the API](api/fn.send.html), which is where you can read further about use of `send` and `receive`.
 ///     `receive` is optional to include, based on whether you use `send` anywhere in the code.
-/// 4. functions: `functions` is divided up into `capabilities`, which specify who can access those functions.
+/// 4. validate_agent (optional): `validate_agent` is the membrane rule of the DNA. Holochain calls it in every Zome that
+///     defines it with the AgentId entry of an agent joining the network (which carries the agent's `membrane_proof`
+///     from the conductor's instance configuration) and its validation data, both at genesis and on every node holding
+///     the entry. It should return `Ok` to let the agent in or an `Err` with the reason for rejecting it.
+/// 5. resolve_conflict (optional): `resolve_conflict` gets called on the nodes holding an entry of a type with
+///     `conflict_resolution: ConflictResolution::Callback` when that entry got updated concurrently. It gets the
+///     entry type name, the old entry and the competing updates with their headers
+///     ([ConflictResolutionArgs](struct.ConflictResolutionArgs.html)) and should return `Ok` with the address of the
+///     update that wins, which becomes the entry's latest version. With an `Err`, or an address that is not one
+///     of the updates, Holochain falls back to last-writer-wins.
+/// 6. functions: `functions` is divided up into `capabilities`, which specify who can access those functions.
 ///     `functions` must be a tree structure where the first children are `capabilities`
 ///     and the children of those `capabilities` are actual function definitions.
 /// # Examples
@@ -75,7 +85,7 @@ macro_rules! load_string {
 /// use hdk::error::ZomeApiResult;
 /// use holochain_core_types::{
 ///     cas::content::Address,
-///     dna::entry_types::Sharing,
+///     dna::entry_types::{ConflictResolution, Sharing},
 /// };
 /// # use holochain_core_types::error::RibosomeEncodingBits;
 /// # // Adding empty functions so that the cfg(test) build can link.
@@ -98,6 +108,12 @@ macro_rules! load_string {
 /// # #[no_mangle]
 /// # pub fn hc_sleep(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
+/// # pub fn hc_sign(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_verify_signature(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_property(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
 /// # pub fn hc_debug(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_call(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
@@ -105,6 +121,24 @@ macro_rules! load_string {
 /// # pub fn hc_get_links(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # #[no_mangle]
 /// # pub fn hc_link_entries(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_emit_signal(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_commit_encrypted_entry(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_grant_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_revoke_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_list_capability_grants(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_update_agent(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_close_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_start_bundle(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
+/// # #[no_mangle]
+/// # pub fn hc_remove_link(_: RibosomeEncodingBits) -> RibosomeEncodingBits { RibosomeEncodedValue::Success.into() }
 /// # fn main() {
 ///
 /// #[derive(Serialize, Deserialize, Debug, DefaultJson)]
@@ -129,6 +163,7 @@ macro_rules! load_string {
 ///             description: "",
 ///             sharing: Sharing::Public,
 ///             native_type: Post,
+///             conflict_resolution: ConflictResolution::Callback,
 ///
 ///             validation_package: || {
 ///                 hdk::ValidationPackageDefinition::ChainFull
@@ -150,6 +185,19 @@ macro_rules! load_string {
 ///       format!("Received: {}", payload)
 ///     }
 ///
+///     validate_agent: |agent_id, _validation_data| {
+///         // only let in agents who were invited
+///         (agent_id.membrane_proof == Some(String::from("invited")))
+///             .ok_or_else(|| String::from("Agent was not invited"))
+///     }
+///
+///     resolve_conflict: |conflict| {
+///         // let the first of the concurrent updates win
+///         conflict.updates.first()
+///             .map(|update| update.header.entry_address().clone())
+///             .ok_or_else(|| String::from("No updates to choose from"))
+///     }
+///
 ///     functions: [
 ///             // the name of this function, "post_address" is the
 ///             // one to give while performing a `call` method to this function.
@@ -188,6 +236,18 @@ macro_rules! define_zome {
             }
         )*
 
+        $(
+            validate_agent : |$validate_agent_id_param:ident, $validate_agent_data_param:ident| {
+                $validate_agent_expr:expr
+            }
+        )*
+
+        $(
+            resolve_conflict : |$resolve_conflict_param:ident| {
+                $resolve_conflict_expr:expr
+            }
+        )*
+
         functions : [
             $(
                         $zome_function_name:ident : {
@@ -275,6 +335,78 @@ macro_rules! define_zome {
             }
         )*
 
+        $(
+            #[no_mangle]
+            pub extern "C" fn validate_agent(encoded_allocation_of_input: hdk::holochain_core_types::error::RibosomeEncodingBits) -> hdk::holochain_core_types::error::RibosomeEncodingBits {
+                let maybe_allocation = $crate::holochain_wasm_utils::memory::allocation::WasmAllocation::try_from_ribosome_encoding(encoded_allocation_of_input);
+                let allocation = match maybe_allocation {
+                    Ok(allocation) => allocation,
+                    Err(allocation_error) => return hdk::holochain_core_types::error::RibosomeEncodedValue::from(allocation_error).into(),
+                };
+                let init = $crate::global_fns::init_global_memory(allocation);
+                if init.is_err() {
+                    return $crate::holochain_wasm_utils::memory::ribosome::return_code_for_allocation_result(
+                        init
+                    ).into();
+                }
+
+                // Deserialize input
+                let input: $crate::AgentIdValidationArgs = load_json!(encoded_allocation_of_input);
+
+                fn execute(
+                    agent_id: $crate::holochain_core_types::agent::AgentId,
+                    validation_data: $crate::ValidationData,
+                ) -> Result<(), String> {
+                    let $validate_agent_id_param = agent_id;
+                    let $validate_agent_data_param = validation_data;
+                    $validate_agent_expr
+                }
+
+                match execute(input.agent_id, input.validation_data) {
+                    Ok(()) => hdk::holochain_core_types::error::RibosomeEncodedValue::Success.into(),
+                    Err(fail_string) => $crate::holochain_wasm_utils::memory::ribosome::return_code_for_allocation_result(
+                        $crate::global_fns::write_json(fail_string)
+                    ).into(),
+                }
+            }
+        )*
+
+        $(
+            #[no_mangle]
+            pub extern "C" fn resolve_conflict(encoded_allocation_of_input: hdk::holochain_core_types::error::RibosomeEncodingBits) -> hdk::holochain_core_types::error::RibosomeEncodingBits {
+                let maybe_allocation = $crate::holochain_wasm_utils::memory::allocation::WasmAllocation::try_from_ribosome_encoding(encoded_allocation_of_input);
+                let allocation = match maybe_allocation {
+                    Ok(allocation) => allocation,
+                    Err(allocation_error) => return hdk::holochain_core_types::error::RibosomeEncodedValue::from(allocation_error).into(),
+                };
+                let init = $crate::global_fns::init_global_memory(allocation);
+                if init.is_err() {
+                    return $crate::holochain_wasm_utils::memory::ribosome::return_code_for_allocation_result(
+                        init
+                    ).into();
+                }
+
+                // Deserialize input
+                let input: $crate::ConflictResolutionArgs = load_json!(encoded_allocation_of_input);
+
+                fn execute(
+                    conflict: $crate::ConflictResolutionArgs,
+                ) -> Result<$crate::holochain_core_types::cas::content::Address, String> {
+                    let $resolve_conflict_param = conflict;
+                    $resolve_conflict_expr
+                }
+
+                match execute(input) {
+                    Ok(winner) => $crate::holochain_wasm_utils::memory::ribosome::return_code_for_allocation_result(
+                        $crate::global_fns::write_json(winner)
+                    ).into(),
+                    Err(fail_string) => $crate::holochain_wasm_utils::memory::ribosome::return_code_for_allocation_result(
+                        $crate::global_fns::write_json(fail_string)
+                    ).into(),
+                }
+            }
+        )*
+
         use $crate::holochain_core_types::dna::capabilities::Capability;
         use std::collections::HashMap;
 
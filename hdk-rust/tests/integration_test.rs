@@ -20,7 +20,7 @@ use holochain_core_types::{
     cas::content::{Address, AddressableContent},
     crud_status::CrudStatus,
     dna::{
-        capabilities::{Capability, CapabilityCall, CapabilityType},
+        capabilities::{CallSignature, Capability, CapabilityCall, CapabilityType},
         entry_types::{EntryTypeDef, LinksTo},
         fn_declarations::FnDeclaration,
         zome::{ZomeCapabilities, ZomeFnDeclarations},
@@ -138,6 +138,41 @@ pub fn hc_sleep(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
     RibosomeEncodedValue::Success.into()
 }
 
+#[no_mangle]
+pub fn hc_remove_link(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
+    RibosomeEncodedValue::Success.into()
+}
+
+#[no_mangle]
+pub fn hc_update_agent(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
+    RibosomeEncodedValue::Success.into()
+}
+
+#[no_mangle]
+pub fn hc_grant_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
+    RibosomeEncodedValue::Success.into()
+}
+
+#[no_mangle]
+pub fn hc_revoke_capability(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
+    RibosomeEncodedValue::Success.into()
+}
+
+#[no_mangle]
+pub fn hc_list_capability_grants(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
+    RibosomeEncodedValue::Success.into()
+}
+
+#[no_mangle]
+pub fn hc_commit_encrypted_entry(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
+    RibosomeEncodedValue::Success.into()
+}
+
+#[no_mangle]
+pub fn hc_emit_signal(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
+    RibosomeEncodedValue::Success.into()
+}
+
 #[no_mangle]
 pub fn zome_setup(_: RibosomeEncodingBits) -> RibosomeEncodingBits {
     RibosomeEncodedValue::Success.into()
@@ -191,6 +226,8 @@ fn example_valid_entry_result() -> GetEntryResult {
         entry: entry.clone(),
         crud_status: CrudStatus::Live,
         maybe_crud_link: None,
+        crud_fork: Vec::new(),
+        warrant: None,
     };
     GetEntryResult::new(StatusRequestKind::Latest, Some((entry_with_meta, vec![])))
 }
@@ -227,6 +264,8 @@ fn start_holochain_instance<T: Into<String>>(
         "links_roundtrip_create",
         "links_roundtrip_get",
         "links_roundtrip_get_and_load",
+        "links_roundtrip_remove",
+        "links_roundtrip_get_with_status",
         "link_validation",
         "check_query",
         "check_app_entry_address",
@@ -238,9 +277,19 @@ fn start_holochain_instance<T: Into<String>>(
         "remove_modified_entry_ok",
         "send_message",
         "sleep",
+        "sign",
+        "verify_signature",
+        "get_property",
+        "commit_bundle",
+        "update_agent",
+        "grant_and_revoke_capability",
     ]);
     let mut dna = create_test_dna_with_defs("test_zome", defs, &wasm);
     dna.uuid = uuid.into();
+    dna.properties = json!({
+        "answer": 42,
+        "limits": {"max_post_length": 280}
+    });
 
     // TODO: construct test DNA using the auto-generated JSON feature
     // The code below is fragile!
@@ -287,7 +336,11 @@ fn start_holochain_instance<T: Into<String>>(
 fn make_test_call(hc: &mut Holochain, fn_name: &str, params: &str) -> HolochainResult<JsonString> {
     hc.call(
         "test_zome",
-        Some(CapabilityCall::new(Address::from("test_token"), None)),
+        Some(CapabilityCall::new(
+            Address::from("test_token"),
+            None,
+            CallSignature::default(),
+        )),
         fn_name,
         params,
     )
@@ -795,3 +848,226 @@ fn sleep_smoke_test() {
     let result = make_test_call(&mut hc, "sleep", r#"{}"#);
     assert!(result.is_ok(), "result = {:?}", result);
 }
+
+#[test]
+fn can_sign_and_verify_signature() {
+    let (mut hc, _) = start_holochain_instance("can_sign_and_verify_signature", "alice");
+    let (agent_id, _) = test_agent_and_keypair("alice");
+    let agent_address = agent_id.address().to_string();
+
+    let result = make_test_call(&mut hc, "sign", r#"{"payload": "receipt"}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    let signature: ZomeApiResult<String> = serde_json::from_str(&result.unwrap().to_string())
+        .expect("sign should return a ZomeApiResult<String>");
+    let signature = signature.expect("sign should succeed");
+
+    for (payload, expected) in vec![("receipt", true), ("forged receipt", false)] {
+        let params = json!({
+            "signature": signature,
+            "payload": payload,
+            "pub_key": agent_address,
+        });
+        let result = make_test_call(&mut hc, "verify_signature", &params.to_string());
+        let expected: ZomeApiResult<bool> = Ok(expected);
+        assert_eq!(result.unwrap(), JsonString::from(expected));
+    }
+}
+
+#[test]
+fn can_get_dna_properties() {
+    let (mut hc, _) = start_holochain_instance("can_get_dna_properties", "alice");
+
+    for (key, expected) in vec![
+        ("answer", Ok(JsonString::from("42"))),
+        ("/limits/max_post_length", Ok(JsonString::from("280"))),
+        ("/limits", Ok(JsonString::from("{\"max_post_length\":280}"))),
+    ] {
+        let params = json!({ "key": key }).to_string();
+        let result = make_test_call(&mut hc, "get_property", &params);
+        let expected: ZomeApiResult<JsonString> = expected;
+        assert_eq!(result.unwrap(), JsonString::from(expected));
+    }
+
+    let result = make_test_call(&mut hc, "get_property", r#"{"key": "missing"}"#);
+    assert!(result.is_ok(), "result = {:?}", result);
+    assert!(result.unwrap().to_string().contains("not found"));
+}
+
+/// Polls get_links with the given status request until it returns the expected addresses
+/// (in any order), since links get held asynchronously.
+fn wait_for_links(
+    hc: &mut Holochain,
+    base: &Address,
+    status_request: &str,
+    mut expected: Vec<Address>,
+) {
+    expected.sort();
+    let mut addresses = Vec::new();
+    for _ in 0..10 {
+        let result = make_test_call(
+            hc,
+            "links_roundtrip_get_with_status",
+            &format!(
+                r#"{{"address": "{}", "status_request": "{}"}}"#,
+                base, status_request
+            ),
+        )
+        .expect("get_links call should succeed");
+        let links: Result<GetLinksResult, HolochainError> =
+            serde_json::from_str(&String::from(result)).expect("should be a GetLinksResult");
+        addresses = links.expect("get_links should succeed").addresses().clone();
+        addresses.sort();
+        if addresses == expected {
+            break;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+    assert_eq!(addresses, expected);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn can_remove_links() {
+    let (mut hc, _) = start_holochain_instance("can_remove_links", "alice");
+    let result = make_test_call(&mut hc, "links_roundtrip_create", r#"{}"#);
+    let maybe_address: Result<Address, String> =
+        serde_json::from_str(&String::from(result.unwrap())).unwrap();
+    let entry_address = maybe_address.unwrap();
+    let entry_address_2 = Address::from("QmdQVqSuqbrEJWC8Va85PSwrcPfAB3EpG5h83C3Vrj62hN");
+    let entry_address_3 = Address::from("QmPn1oj8ANGtxS5sCGdKBdSBN63Bb6yBkmWrLc9wFRYPtJ");
+
+    wait_for_links(
+        &mut hc,
+        &entry_address,
+        "Live",
+        vec![entry_address_2.clone(), entry_address_3.clone()],
+    );
+
+    let result = make_test_call(
+        &mut hc,
+        "links_roundtrip_remove",
+        &format!(
+            r#"{{"base": "{}", "target": "{}"}}"#,
+            entry_address, entry_address_2
+        ),
+    );
+    assert!(result.is_ok(), "result = {:?}", result);
+
+    wait_for_links(
+        &mut hc,
+        &entry_address,
+        "Live",
+        vec![entry_address_3.clone()],
+    );
+    wait_for_links(
+        &mut hc,
+        &entry_address,
+        "Deleted",
+        vec![entry_address_2.clone()],
+    );
+    wait_for_links(
+        &mut hc,
+        &entry_address,
+        "All",
+        vec![entry_address_2, entry_address_3],
+    );
+}
+
+/// Calls commit_bundle and returns the addresses of the entries committed in the bundle
+fn commit_bundle(
+    hc: &mut Holochain,
+    stuffs: Vec<&str>,
+    commit: bool,
+) -> ZomeApiResult<Vec<Address>> {
+    let result = make_test_call(
+        hc,
+        "commit_bundle",
+        &json!({"stuffs": stuffs, "commit": commit}).to_string(),
+    )
+    .expect("commit_bundle call should succeed");
+    serde_json::from_str(&String::from(result)).expect("should be a ZomeApiResult")
+}
+
+fn get_entry(hc: &mut Holochain, address: &Address) -> Option<Entry> {
+    let result = make_test_call(
+        hc,
+        "check_get_entry",
+        &json!({ "entry_address": address }).to_string(),
+    )
+    .expect("check_get_entry call should succeed");
+    let maybe_entry: ZomeApiResult<Option<Entry>> =
+        serde_json::from_str(&String::from(result)).expect("should be a ZomeApiResult");
+    maybe_entry.expect("get_entry should succeed")
+}
+
+#[test]
+#[cfg(not(windows))]
+fn can_commit_and_discard_bundles() {
+    let (mut hc, _) = start_holochain_instance("can_commit_and_discard_bundles", "alice");
+
+    // Discarded bundles leave no trace
+    let addresses = commit_bundle(&mut hc, vec!["discarded1", "discarded2"], false)
+        .expect("discarding a bundle should succeed");
+    assert_eq!(addresses.len(), 2);
+    for address in addresses.iter() {
+        assert_eq!(get_entry(&mut hc, address), None);
+    }
+
+    // Committed bundles end up on the chain and in the DHT as a whole
+    let addresses = commit_bundle(&mut hc, vec!["bundled1", "bundled2"], true)
+        .expect("committing a valid bundle should succeed");
+    assert_eq!(addresses.len(), 2);
+    for address in addresses.iter() {
+        assert!(get_entry(&mut hc, address).is_some());
+    }
+
+    // A single invalid entry discards the whole bundle
+    let result = commit_bundle(&mut hc, vec!["valid_in_invalid_bundle", "FAIL"], true);
+    assert!(result.is_err(), "result = {:?}", result);
+    let valid_entry = Entry::App(
+        test_app_entry_type().into(),
+        EntryStruct {
+            stuff: "valid_in_invalid_bundle".into(),
+        }
+        .into(),
+    );
+    assert_eq!(get_entry(&mut hc, &valid_entry.address()), None);
+}
+
+#[test]
+#[cfg(not(windows))]
+fn can_rotate_agent_key() {
+    let (mut hc, _) = start_holochain_instance("can_rotate_agent_key", "alice");
+
+    let result = make_test_call(
+        &mut hc,
+        "update_agent",
+        r#"{"stuff": "signed with the new key"}"#,
+    )
+    .expect("update_agent call should succeed");
+    let addresses: ZomeApiResult<Vec<Address>> =
+        serde_json::from_str(&String::from(result)).expect("should be a ZomeApiResult");
+    let addresses = addresses.expect("update_agent should succeed");
+    assert_eq!(addresses.len(), 2);
+
+    // Both the new AgentId entry and the entry committed after the rotation
+    // got accepted by the DHT
+    match get_entry(&mut hc, &addresses[0]) {
+        Some(Entry::AgentId(agent_id)) => assert_eq!(agent_id.address(), addresses[0]),
+        other => panic!("expected new AgentId entry, got {:?}", other),
+    }
+    assert!(get_entry(&mut hc, &addresses[1]).is_some());
+}
+
+#[test]
+fn can_grant_and_revoke_capabilities() {
+    let (mut hc, _) = start_holochain_instance("can_grant_and_revoke_capabilities", "alice");
+
+    let result = make_test_call(
+        &mut hc,
+        "grant_and_revoke_capability",
+        r#"{"assignee": "bob"}"#,
+    )
+    .expect("grant_and_revoke_capability call should succeed");
+    assert_eq!(result, JsonString::from(r#"{"Ok":[1,0]}"#));
+}
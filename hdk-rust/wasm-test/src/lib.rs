@@ -17,17 +17,18 @@ use handle_crud::{
     handle_remove_entry_ok, handle_remove_modified_entry_ok, handle_update_entry_ok,
 };
 use hdk::{
+    api::BundleOnClose,
     error::{ZomeApiError, ZomeApiResult},
 };
 use holochain_wasm_utils::{
     api_serialization::{
         get_entry::{GetEntryOptions, GetEntryResult},
-        get_links::GetLinksResult,
+        get_links::{GetLinksOptions, GetLinksResult, LinksStatusRequestKind},
         query::{ QueryArgsNames, QueryArgsOptions, QueryResult },
     },
     holochain_core_types::{
         cas::content::{Address, AddressableContent},
-        dna::entry_types::Sharing,
+        dna::{capabilities::CapabilityType, entry_types::Sharing},
         entry::{
             entry_type::{AppEntryType, EntryType},
             AppEntryValue, Entry,
@@ -200,6 +201,24 @@ fn handle_links_roundtrip_get_and_load(
     hdk::get_links_and_load(&address, "test-tag")
 }
 
+fn handle_links_roundtrip_remove(base: Address, target: Address) -> ZomeApiResult<()> {
+    hdk::remove_link(&base, &target, "test-tag")
+}
+
+fn handle_links_roundtrip_get_with_status(
+    address: Address,
+    status_request: LinksStatusRequestKind,
+) -> ZomeApiResult<GetLinksResult> {
+    hdk::get_links_with_options(
+        &address,
+        "test-tag",
+        GetLinksOptions {
+            status_request,
+            ..Default::default()
+        },
+    )
+}
+
 fn handle_check_query() -> ZomeApiResult<Vec<Address>> {
     println!("handle_check_query");
     fn err(s: &str) -> ZomeApiResult<Vec<Address>> {
@@ -427,6 +446,62 @@ fn handle_sleep() -> ZomeApiResult<()> {
     hdk::sleep(Duration::from_millis(10))
 }
 
+fn handle_sign(payload: String) -> ZomeApiResult<String> {
+    hdk::sign(payload)
+}
+
+fn handle_verify_signature(
+    signature: String,
+    payload: String,
+    pub_key: String,
+) -> ZomeApiResult<bool> {
+    hdk::verify_signature(signature, payload, pub_key)
+}
+
+fn handle_get_property(key: String) -> ZomeApiResult<JsonString> {
+    hdk::property(key)
+}
+
+/// Commit one entry per given stuff inside a bundle
+/// and close the bundle with either Commit or Discard
+fn handle_commit_bundle(stuffs: Vec<String>, commit: bool) -> ZomeApiResult<Vec<Address>> {
+    hdk::start_bundle(10000, serde_json::Value::from(stuffs.len()))?;
+    let mut addresses = Vec::new();
+    for stuff in stuffs {
+        let entry = Entry::App("testEntryType".into(), EntryStruct { stuff }.into());
+        addresses.push(hdk::commit_entry(&entry)?);
+    }
+    hdk::close_bundle(if commit {
+        BundleOnClose::Commit
+    } else {
+        BundleOnClose::Discard
+    })?;
+    Ok(addresses)
+}
+
+/// Rotate the agent's key and commit an entry signed with the new key.
+/// Returns the addresses of the new AgentId entry and of the committed entry.
+fn handle_update_agent(stuff: String) -> ZomeApiResult<Vec<Address>> {
+    let agent_address = hdk::update_agent()?;
+    let entry = Entry::App("testEntryType".into(), EntryStruct { stuff }.into());
+    let entry_address = hdk::commit_entry(&entry)?;
+    Ok(vec![agent_address, entry_address])
+}
+
+/// Grant access to the test capability to the given agent and revoke it again.
+/// Returns the numbers of active grants after granting and after revoking.
+fn handle_grant_and_revoke_capability(assignee: Address) -> ZomeApiResult<Vec<usize>> {
+    let token =
+        hdk::grant_capability("test_cap", CapabilityType::Assigned, Some(vec![assignee]))?;
+    let granted = hdk::list_capability_grants()?;
+    if granted.iter().all(|grant| grant.token() != token) {
+        return Err(ZomeApiError::Internal("grant is not listed".to_string()));
+    }
+    hdk::revoke_capability(&token)?;
+    let remaining = hdk::list_capability_grants()?;
+    Ok(vec![granted.len(), remaining.len()])
+}
+
 define_zome! {
     entries: [
         entry!(
@@ -593,6 +668,18 @@ define_zome! {
             handler: handle_links_roundtrip_get_and_load
         }
 
+        links_roundtrip_remove: {
+            inputs: |base: Address, target: Address|,
+            outputs: |result: ZomeApiResult<()>|,
+            handler: handle_links_roundtrip_remove
+        }
+
+        links_roundtrip_get_with_status: {
+            inputs: |address: Address, status_request: LinksStatusRequestKind|,
+            outputs: |result: ZomeApiResult<GetLinksResult>|,
+            handler: handle_links_roundtrip_get_with_status
+        }
+
         link_validation: {
             inputs: |stuff1: String, stuff2: String|,
             outputs: |result: JsonString|,
@@ -664,6 +751,42 @@ define_zome! {
             outputs: |response: ZomeApiResult<()>|,
             handler: handle_sleep
         }
+
+        sign: {
+            inputs: |payload: String|,
+            outputs: |signature: ZomeApiResult<String>|,
+            handler: handle_sign
+        }
+
+        verify_signature: {
+            inputs: |signature: String, payload: String, pub_key: String|,
+            outputs: |valid: ZomeApiResult<bool>|,
+            handler: handle_verify_signature
+        }
+
+        get_property: {
+            inputs: |key: String|,
+            outputs: |property: ZomeApiResult<JsonString>|,
+            handler: handle_get_property
+        }
+
+        commit_bundle: {
+            inputs: |stuffs: Vec<String>, commit: bool|,
+            outputs: |addresses: ZomeApiResult<Vec<Address>>|,
+            handler: handle_commit_bundle
+        }
+
+        update_agent: {
+            inputs: |stuff: String|,
+            outputs: |addresses: ZomeApiResult<Vec<Address>>|,
+            handler: handle_update_agent
+        }
+
+        grant_and_revoke_capability: {
+            inputs: |assignee: Address|,
+            outputs: |grant_counts: ZomeApiResult<Vec<usize>>|,
+            handler: handle_grant_and_revoke_capability
+        }
     ]
 
     capabilities: {}
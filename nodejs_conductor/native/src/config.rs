@@ -65,6 +65,7 @@ fn make_config(instance_data: Vec<InstanceData>, logger: LoggerConfiguration) ->
                 name: agent_name.clone(),
                 public_address: agent_key.key,
                 key_file: format!("fake/key/{}", agent_name),
+                test_agent: Some(true),
             };
             config
         });
@@ -79,6 +80,7 @@ fn make_config(instance_data: Vec<InstanceData>, logger: LoggerConfiguration) ->
             agent: agent_id,
             dna: dna_id,
             storage: StorageConfiguration::Memory,
+            membrane_proof: None,
         };
         instance_configs.push(instance);
     }
@@ -19,7 +19,7 @@ use holochain_core::{
 };
 use holochain_core_types::{
     cas::content::{Address, AddressableContent},
-    dna::capabilities::CapabilityCall,
+    dna::capabilities::{CallSignature, CapabilityCall},
     entry::Entry,
 };
 use holochain_node_test_waiter::waiter::{CallBlockingTask, ControlMsg, MainBackgroundTask};
@@ -160,6 +160,7 @@ declare_types! {
                 let cap = Some(CapabilityCall::new(
                     Address::from(""), //FIXME
                     None,
+                    CallSignature::default(),
                 ));
                 let instance_arc = tc.conductor.instances().get(&instance_id)
                     .expect(&format!("No instance with id: {}", instance_id));
@@ -8,12 +8,14 @@ id = "test/agent/1"
 name = "Holo Tester 1"
 key_file = "holo_tester.key"
 public_address = "sandwich--------------------------------------------------------------------------AAAEqzh28L"
+test_agent = true
 
 [[agents]]
 id = "test/agent/2"
 name = "Holo Tester 2"
 key_file = "holo_tester.key"
 public_address = "sandwich--------------------------------------------------------------------------AAAEqzh28L"
+test_agent = true
 
 [[dnas]]
 id = "test/dna"
@@ -5,5 +5,6 @@ extern crate holochain_conductor_api;
 extern crate holochain_core;
 extern crate holochain_core_types;
 extern crate holochain_net;
+extern crate holochain_wasm_utils;
 
 pub mod waiter;
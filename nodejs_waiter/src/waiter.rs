@@ -6,6 +6,7 @@ use holochain_core::{
     signal::{Signal, SignalReceiver},
 };
 use holochain_core_types::entry::Entry;
+use holochain_wasm_utils::api_serialization::bundle::BundleOnClose;
 use neon::{context::Context, prelude::*};
 use std::{
     cell::RefCell,
@@ -159,6 +160,8 @@ pub struct Waiter {
     current: Option<ZomeFnCall>,
     sender_rx: Receiver<ControlSender>,
     num_instances: usize,
+    /// id and staged entries of the currently open commit bundle
+    open_bundle: Option<(String, Vec<Entry>)>,
 }
 
 impl Waiter {
@@ -168,6 +171,7 @@ impl Waiter {
             current: None,
             sender_rx,
             num_instances,
+            open_bundle: None,
         }
     }
 
@@ -180,6 +184,7 @@ impl Waiter {
         match sig {
             Signal::Internal(ref aw) => {
                 let aw = aw.clone();
+                let committed_entries = self.track_bundles(aw.action());
                 match (self.current_checker(), aw.action().clone()) {
                     // Pair every `ExecuteZomeFunction` with one `ReturnZomeFunctionResult`
                     (_, Action::ExecuteZomeFunction(call)) => match self.sender_rx.try_recv() {
@@ -199,42 +204,52 @@ impl Waiter {
                         }
                     },
 
-                    (Some(checker), Action::Commit((committed_entry, _))) => {
-                        match committed_entry.clone() {
-                            // Pair every `Commit` with N `Hold`s
-                            Entry::App(_, _) => {
-                                // TODO: is there a possiblity that this can get messed up if the same
-                                // entry is committed multiple times?
-                                checker.add(num_instances, move |aw| match aw.action() {
-                                    Action::Hold(EntryWithHeader { entry, header: _ }) => {
-                                        *entry == committed_entry
-                                    }
-                                    _ => false,
-                                });
-                            }
-                            // Pair every `LinkAdd` with N `Hold`s and N `AddLink`s
-                            Entry::LinkAdd(link_add) => {
-                                checker.add(num_instances, move |aw| match aw.action() {
-                                    Action::Hold(EntryWithHeader { entry, header: _ }) => {
-                                        *entry == committed_entry
-                                    }
-                                    _ => false,
-                                });
-                                checker.add(num_instances, move |aw| {
-                                    *aw.action() == Action::AddLink(link_add.clone().link().clone())
-                                });
-                            }
-                            // Pair every `LinkRemove` with N `Hold`s
-                            Entry::LinkRemove(_link_remove) => {
-                                checker.add(num_instances, move |aw| match aw.action() {
-                                    Action::Hold(EntryWithHeader { entry, header: _ }) => {
-                                        *entry == committed_entry
-                                    }
-                                    _ => false,
-                                });
-                                println!("warn/waiter: LinkRemove not implemented!");
+                    // Commits only have side effects once they leave a bundle
+                    (Some(checker), Action::Commit(_))
+                    | (Some(checker), Action::CloseBundle(_)) => {
+                        for committed_entry in committed_entries {
+                            match committed_entry.clone() {
+                                // Pair every `Commit` with N `Hold`s
+                                Entry::App(_, _) => {
+                                    // TODO: is there a possiblity that this can get messed up if the same
+                                    // entry is committed multiple times?
+                                    checker.add(num_instances, move |aw| match aw.action() {
+                                        Action::Hold(EntryWithHeader { entry, header: _ }) => {
+                                            *entry == committed_entry
+                                        }
+                                        _ => false,
+                                    });
+                                }
+                                // Pair every `LinkAdd` with N `Hold`s and N `AddLink`s
+                                Entry::LinkAdd(link_add) => {
+                                    checker.add(num_instances, move |aw| match aw.action() {
+                                        Action::Hold(EntryWithHeader { entry, header: _ }) => {
+                                            *entry == committed_entry
+                                        }
+                                        _ => false,
+                                    });
+                                    checker.add(num_instances, move |aw| {
+                                        *aw.action()
+                                            == Action::AddLink(link_add.clone().link().clone())
+                                    });
+                                }
+                                // Pair every `LinkRemove` with N `Hold`s and N `RemoveLink`s
+                                Entry::LinkRemove(link_remove) => {
+                                    checker.add(num_instances, move |aw| match aw.action() {
+                                        Action::Hold(EntryWithHeader { entry, header: _ }) => {
+                                            *entry == committed_entry
+                                        }
+                                        _ => false,
+                                    });
+                                    checker.add(num_instances, move |aw| {
+                                        *aw.action()
+                                            == Action::RemoveLink(
+                                                link_remove.clone().link().clone(),
+                                            )
+                                    });
+                                }
+                                _ => (),
                             }
-                            _ => (),
                         }
                     }
 
@@ -270,6 +285,37 @@ impl Waiter {
         };
     }
 
+    /// Keeps track of commits that happen inside a bundle.
+    /// Returns the entries whose commit is final with this action:
+    /// the committed entry for a `Commit` outside of a bundle,
+    /// and all staged entries for a `CloseBundle` that commits the open bundle.
+    fn track_bundles(&mut self, action: &Action) -> Vec<Entry> {
+        match action {
+            Action::StartBundle(bundle) => {
+                self.open_bundle = Some((bundle.id().clone(), Vec::new()));
+                Vec::new()
+            }
+            Action::Commit((entry, _)) => match self.open_bundle {
+                Some((_, ref mut staged_entries)) => {
+                    staged_entries.push(entry.clone());
+                    Vec::new()
+                }
+                None => vec![entry.clone()],
+            },
+            Action::CloseBundle((id, on_close)) => match self.open_bundle.take() {
+                Some((open_id, staged_entries)) if open_id == *id => match on_close {
+                    BundleOnClose::Commit => staged_entries,
+                    BundleOnClose::Discard => Vec::new(),
+                },
+                maybe_other_bundle => {
+                    self.open_bundle = maybe_other_bundle;
+                    Vec::new()
+                }
+            },
+            _ => Vec::new(),
+        }
+    }
+
     fn run_checks(&mut self, aw: &ActionWrapper) {
         let size = self.checkers.len();
         self.checkers.retain(|_, checker| checker.run_checks(aw));
@@ -368,9 +414,12 @@ impl Task for MainBackgroundTask {
 #[cfg(test)]
 mod tests {
     use super::{Action::*, *};
-    use holochain_core::nucleus::ExecuteZomeFnResponse;
+    use holochain_core::{agent::bundle::Bundle, nucleus::ExecuteZomeFnResponse};
     use holochain_core_types::{
-        chain_header::test_chain_header, entry::Entry, json::JsonString, link::link_add::LinkAdd,
+        chain_header::test_chain_header,
+        entry::Entry,
+        json::JsonString,
+        link::{link_add::LinkAdd, link_remove::LinkRemove},
     };
     use std::sync::mpsc::sync_channel;
 
@@ -612,6 +661,80 @@ mod tests {
         assert_eq!(waiter.checkers.len(), 0);
     }
 
+    #[test]
+    fn can_await_link_removals() {
+        let (mut waiter, sender_tx) = test_waiter();
+        let call = zf_call("c1");
+        let link_remove = LinkRemove::new(
+            &"base".to_string().into(),
+            &"target".to_string().into(),
+            "tag",
+        );
+        let entry = Entry::LinkRemove(link_remove.clone());
+        let entry_wh = mk_entry_wh(entry.clone());
+
+        let control_rx = test_register(&sender_tx);
+        waiter.process_signal(sig(ExecuteZomeFunction(call.clone())));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+
+        // this adds two actions to await
+        waiter.process_signal(sig(Commit((entry.clone(), None))));
+        assert_eq!(num_conditions(&waiter, &call), 3);
+
+        waiter.process_signal(sig(Hold(entry_wh)));
+        assert_eq!(num_conditions(&waiter, &call), 2);
+
+        waiter.process_signal(sig(RemoveLink(link_remove.link().clone())));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+
+        expect_final(control_rx, || {
+            waiter.process_signal(sig(ReturnZomeFunctionResult(zf_response(call.clone()))))
+        });
+        assert_eq!(waiter.checkers.len(), 0);
+    }
+
+    #[test]
+    fn can_await_bundled_commits() {
+        let (mut waiter, sender_tx) = test_waiter();
+        let entry_1 = mk_entry("t1", "x");
+        let entry_2 = mk_entry("t2", "y");
+        let discarded_entry = mk_entry("t3", "z");
+        let call = zf_call("c1");
+
+        let control_rx = test_register(&sender_tx);
+        waiter.process_signal(sig(ExecuteZomeFunction(call.clone())));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+
+        // discarded commits are never held
+        waiter.process_signal(sig(StartBundle(Bundle::new("b1".into(), "".into()))));
+        waiter.process_signal(sig(Commit((discarded_entry.clone(), None))));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+        waiter.process_signal(sig(CloseBundle(("b1".into(), BundleOnClose::Discard))));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+
+        // committed bundles are awaited as a whole
+        waiter.process_signal(sig(StartBundle(Bundle::new("b2".into(), "".into()))));
+        waiter.process_signal(sig(Commit((entry_1.clone(), None))));
+        waiter.process_signal(sig(Commit((entry_2.clone(), None))));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+
+        // closing a bundle that is not open anymore has no effect
+        waiter.process_signal(sig(CloseBundle(("b1".into(), BundleOnClose::Discard))));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+
+        waiter.process_signal(sig(CloseBundle(("b2".into(), BundleOnClose::Commit))));
+        assert_eq!(num_conditions(&waiter, &call), 3);
+
+        waiter.process_signal(sig(Hold(mk_entry_wh(entry_1))));
+        waiter.process_signal(sig(Hold(mk_entry_wh(entry_2))));
+        assert_eq!(num_conditions(&waiter, &call), 1);
+
+        expect_final(control_rx, || {
+            waiter.process_signal(sig(ReturnZomeFunctionResult(zf_response(call.clone()))))
+        });
+        assert_eq!(waiter.checkers.len(), 0);
+    }
+
     #[test]
     fn can_await_registered_and_unregistered_zome_calls() {
         let (mut waiter, sender_tx) = test_waiter();
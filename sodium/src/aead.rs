@@ -72,6 +72,9 @@ pub fn enc(
 /// @param {Buffer} nonce - sometimes called initialization vector (iv)
 ///
 /// @param {Buffer} cipher - the cipher text
+///
+/// Returns an error if the cipher text can't be authenticated, i.e. it was tampered with or
+/// encrypted with another secret, nonce or auth data. The output buffer is zeroed then.
 pub fn dec(
     decrypted_message: &mut SecBuf,
     secret: &mut SecBuf,
@@ -95,7 +98,7 @@ pub fn dec(
     let nonce = nonce.read_lock();
     let secret = secret.read_lock();
 
-    unsafe {
+    let result = unsafe {
         rust_sodium_sys::crypto_aead_xchacha20poly1305_ietf_decrypt(
             raw_ptr_char!(decrypted_message),
             std::ptr::null_mut(),
@@ -106,7 +109,12 @@ pub fn dec(
             my_ad_len,
             raw_ptr_char_immut!(nonce),
             raw_ptr_char_immut!(secret),
-        );
+        )
+    };
+    if result != 0 {
+        return Err(SodiumError::new(
+            "Could not decrypt cipher text, it failed authentication",
+        ));
     }
     Ok(())
 }
@@ -186,7 +194,7 @@ mod tests {
             &mut nonce,
             &mut cipher,
         )
-        .unwrap();;
+        .unwrap();
         let message = message.read_lock();
         let decrypted_message = decrypted_message.read_lock();
         assert_eq!(
@@ -220,17 +228,17 @@ mod tests {
             &mut nonce,
             &mut cipher,
         )
-        .unwrap();;
+        .unwrap();
         let dec_len = cip_len - ABYTES;
         let mut decrypted_message = SecBuf::with_insecure(dec_len);
-        dec(
+        assert!(dec(
             &mut decrypted_message,
             &mut secret,
             Some(&mut adata1),
             &mut nonce,
             &mut cipher,
         )
-        .unwrap();;
+        .is_err());
         let decrypted_message = decrypted_message.read_lock();
         assert_eq!(
             "[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]",
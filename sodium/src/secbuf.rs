@@ -7,7 +7,8 @@ use super::check_init;
 use crate::error::SodiumError;
 
 /// a trait for structures that can be used as a backing store for SecBuf
-pub trait Bufferable {
+/// backing stores need to be Send so that keys can be handed to other threads
+pub trait Bufferable: Send {
     fn new(s: usize) -> Box<Bufferable>
     where
         Self: Sized;
@@ -118,6 +119,10 @@ impl Bufferable for SodiumBuf {
     }
 }
 
+/// the sodium_malloc-ed memory is exclusively owned by this struct,
+/// so it is safe to move it to another thread
+unsafe impl Send for SodiumBuf {}
+
 impl Drop for SodiumBuf {
     fn drop(&mut self) {
         unsafe {
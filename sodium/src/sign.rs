@@ -4,6 +4,7 @@ use crate::error::SodiumError;
 
 pub const PUBLICKEYBYTES: usize = rust_sodium_sys::crypto_sign_PUBLICKEYBYTES as usize;
 pub const SECRETKEYBYTES: usize = rust_sodium_sys::crypto_sign_SECRETKEYBYTES as usize;
+pub const BYTES: usize = rust_sodium_sys::crypto_sign_BYTES as usize;
 
 /// Generate a signing keypair from a seed buffer
 ///
@@ -2,6 +2,7 @@ extern crate holochain_cas_implementations;
 extern crate holochain_conductor_api;
 extern crate holochain_core;
 extern crate holochain_core_types;
+extern crate holochain_dpki;
 extern crate holochain_net;
 extern crate serde_json;
 extern crate tempfile;
@@ -18,7 +19,7 @@ use holochain_core_types::{
     agent::AgentId,
     cas::content::Address,
     dna::{
-        capabilities::{Capability, CapabilityCall, CapabilityType},
+        capabilities::{CallSignature, Capability, CapabilityCall, CapabilityType},
         entry_types::{EntryTypeDef, LinkedFrom, LinksTo},
         fn_declarations::FnDeclaration,
         wasm::DnaWasm,
@@ -28,6 +29,7 @@ use holochain_core_types::{
     entry::entry_type::{AppEntryType, EntryType},
     json::JsonString,
 };
+use holochain_dpki::keypair::Keypair;
 use holochain_net::p2p_config::P2pConfig;
 
 use std::{
@@ -169,17 +171,28 @@ pub fn create_test_dna_with_defs(
     dna
 }
 
+/// create an agent with a keypair derived from its name so that it can sign its chain
+pub fn test_agent_and_keypair(agent_name: &str) -> (AgentId, Keypair) {
+    let keypair = Keypair::new_from_insecure_string(agent_name)
+        .expect("Could not derive keypair for test agent");
+    let agent = keypair
+        .agent_id(agent_name)
+        .expect("Could not create AgentId from test keypair");
+    (agent, keypair)
+}
+
 #[cfg_attr(tarpaulin, skip)]
 pub fn test_context_and_logger_with_network_name(
     agent_name: &str,
     network_name: Option<&str>,
 ) -> (Arc<Context>, Arc<Mutex<TestLogger>>) {
-    let agent = AgentId::generate_fake(agent_name);
+    let (agent, keypair) = test_agent_and_keypair(agent_name);
     let logger = test_logger();
     (
         Arc::new({
             let mut builder = ContextBuilder::new()
                 .with_agent(agent)
+                .with_agent_keypair(keypair)
                 .with_logger(logger.clone())
                 .with_file_storage(tempdir().unwrap().path().to_str().unwrap())
                 .expect("Tempdir must be accessible");
@@ -232,6 +245,7 @@ pub fn hc_setup_and_call_zome_fn<J: Into<JsonString>>(wasm_path: &str, fn_name:
         Some(CapabilityCall::new(
             Address::from("test_token"),
             None,
+            CallSignature::default(),
         )),
         fn_name,
         &String::from(params.into()),
@@ -240,10 +254,11 @@ pub fn hc_setup_and_call_zome_fn<J: Into<JsonString>>(wasm_path: &str, fn_name:
 
 /// create a test context and TestLogger pair so we can use the logger in assertions
 pub fn create_test_context(agent_name: &str) -> Arc<Context> {
-    let agent = AgentId::generate_fake(agent_name);
+    let (agent, keypair) = test_agent_and_keypair(agent_name);
     Arc::new(
         ContextBuilder::new()
             .with_agent(agent)
+            .with_agent_keypair(keypair)
             .with_file_storage(tempdir().unwrap().path().to_str().unwrap())
             .expect("Tempdir must be accessible")
             .spawn(),
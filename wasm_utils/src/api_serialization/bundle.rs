@@ -0,0 +1,24 @@
+use holochain_core_types::{error::HolochainError, json::*, time::Timeout};
+use serde_json::Value;
+
+/// Struct for input data received when Zome API function start_bundle() is invoked
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct StartBundleArgs {
+    /// Time after which an open bundle gets discarded automatically
+    pub timeout: Timeout,
+    /// Arbitrary data the zome wants to associate with the bundle
+    pub user_param: Value,
+}
+
+/// Allowed input for close_bundle()
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub enum BundleOnClose {
+    Commit,
+    Discard,
+}
+
+/// Struct for input data received when Zome API function close_bundle() is invoked
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct CloseBundleArgs {
+    pub action: BundleOnClose,
+}
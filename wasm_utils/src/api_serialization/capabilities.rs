@@ -0,0 +1,21 @@
+use holochain_core_types::{
+    cas::content::Address, dna::capabilities::CapabilityType, entry::cap_entries::CapTokenGrant,
+    error::HolochainError, json::*,
+};
+
+/// Struct for input data received when Zome API function grant_capability() is invoked
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct GrantCapabilityArgs {
+    /// name of the capability of the calling zome the grant gives access to
+    pub cap_name: String,
+    pub cap_type: CapabilityType,
+    /// agents the grant is assigned to, only used for `CapabilityType::Assigned`
+    pub assignees: Option<Vec<Address>>,
+}
+
+/// Result of the Zome API function list_capability_grants()
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct CapabilityGrantsResult {
+    /// all grants of the agent's source chain that were not revoked, newest first
+    pub grants: Vec<CapTokenGrant>,
+}
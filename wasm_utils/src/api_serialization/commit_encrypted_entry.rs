@@ -0,0 +1,8 @@
+use holochain_core_types::{cas::content::Address, entry::Entry, error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function commit_encrypted_entry() is invoked
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct CommitEncryptedEntryArgs {
+    pub entry: Entry,
+    pub recipients: Vec<Address>,
+}
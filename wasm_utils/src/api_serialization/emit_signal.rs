@@ -0,0 +1,9 @@
+use holochain_core_types::{error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function emit_signal() is invoked
+#[derive(Deserialize, Clone, PartialEq, Debug, Serialize, DefaultJson)]
+pub struct EmitSignalArgs {
+    pub name: String,
+    /// JSON the signal carries to the instance's UIs
+    pub payload: String,
+}
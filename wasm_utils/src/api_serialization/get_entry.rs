@@ -108,12 +108,17 @@ impl GetEntryResultItem {
 pub struct EntryHistory {
     pub items: Vec<GetEntryResultItem>,
     pub crud_links: HashMap<Address, Address>,
+    /// The competing updates of the entries in the history that got updated concurrently.
+    /// The history follows the update that won the conflict resolution.
+    #[serde(default)]
+    pub forks: HashMap<Address, Vec<Address>>,
 }
 impl EntryHistory {
     pub fn new() -> Self {
         EntryHistory {
             items: Vec::new(),
             crud_links: HashMap::new(),
+            forks: HashMap::new(),
         }
     }
 
@@ -122,7 +127,10 @@ impl EntryHistory {
         let item = GetEntryResultItem::new(Some((entry_with_meta, headers)));
         self.items.push(item);
         if let Some(new_address) = entry_with_meta.maybe_crud_link.clone() {
-            self.crud_links.insert(address, new_address);
+            self.crud_links.insert(address.clone(), new_address);
+        }
+        if !entry_with_meta.crud_fork.is_empty() {
+            self.forks.insert(address, entry_with_meta.crud_fork.clone());
         }
     }
 }
@@ -166,6 +174,19 @@ impl GetEntryResult {
         }
     }
 
+    /// true if the (latest) entry found failed validation on the DHT.
+    /// Rejected entries are reported with the crud-status Rejected instead of being
+    /// fetched from another node that might still hand them out.
+    pub fn rejected(&self) -> bool {
+        let item = match self.result {
+            GetEntryResultType::Single(ref item) => Some(item),
+            GetEntryResultType::All(ref history) => history.items.last(),
+        };
+        item.and_then(|item| item.meta.as_ref())
+            .map(|meta| meta.crud_status == CrudStatus::Rejected)
+            .unwrap_or(false)
+    }
+
     /// clears the entry result to be equivalent to not found
     pub fn clear(&mut self) {
         match self.result {
@@ -230,6 +251,8 @@ mod tests {
                 entry: test_entry(),
                 crud_status: CrudStatus::Live,
                 maybe_crud_link: None,
+                crud_fork: Vec::new(),
+                warrant: None,
             },
             vec![test_chain_header()],
         );
@@ -246,6 +269,8 @@ mod tests {
                 entry: test_entry_a(),
                 crud_status: CrudStatus::Modified,
                 maybe_crud_link: None,
+                crud_fork: Vec::new(),
+                warrant: None,
             },
             vec![test_chain_header()],
         );
@@ -254,6 +279,8 @@ mod tests {
                 entry: test_entry_b(),
                 crud_status: CrudStatus::Live,
                 maybe_crud_link: None,
+                crud_fork: Vec::new(),
+                warrant: None,
             },
             vec![test_chain_header()],
         );
@@ -261,6 +288,52 @@ mod tests {
         assert_eq!(result.latest(), Some(test_entry_b()));
     }
 
+    #[test]
+    fn test_get_entry_history_fork() {
+        let mut result = GetEntryResult::new(StatusRequestKind::All, None);
+        result.push(
+            &EntryWithMeta {
+                entry: test_entry(),
+                crud_status: CrudStatus::Modified,
+                maybe_crud_link: Some(test_entry_b().address()),
+                crud_fork: vec![test_entry_a().address(), test_entry_b().address()],
+                warrant: None,
+            },
+            vec![test_chain_header()],
+        );
+        match result.result {
+            GetEntryResultType::All(history) => {
+                assert_eq!(
+                    history.crud_links.get(&test_entry().address()),
+                    Some(&test_entry_b().address())
+                );
+                assert_eq!(
+                    history.forks.get(&test_entry().address()),
+                    Some(&vec![test_entry_a().address(), test_entry_b().address()])
+                );
+            }
+            _ => panic!("expected an entry history"),
+        }
+    }
+
+    #[test]
+    fn test_get_entry_rejected() {
+        let mut result = GetEntryResult::new(StatusRequestKind::Latest, None);
+        assert!(!result.rejected());
+        result.push(
+            &EntryWithMeta {
+                entry: test_entry(),
+                crud_status: CrudStatus::Rejected,
+                maybe_crud_link: None,
+                crud_fork: Vec::new(),
+                warrant: None,
+            },
+            vec![test_chain_header()],
+        );
+        assert!(result.found());
+        assert!(result.rejected());
+    }
+
     #[test]
     fn test_clear() {
         let mut result = GetEntryResult::new(StatusRequestKind::All, None);
@@ -269,6 +342,8 @@ mod tests {
                 entry: test_entry(),
                 crud_status: CrudStatus::Live,
                 maybe_crud_link: None,
+                crud_fork: Vec::new(),
+                warrant: None,
             },
             vec![test_chain_header()],
         );
@@ -282,6 +357,8 @@ mod tests {
                 entry: test_entry(),
                 crud_status: CrudStatus::Live,
                 maybe_crud_link: None,
+                crud_fork: Vec::new(),
+                warrant: None,
             },
             vec![test_chain_header()],
         );
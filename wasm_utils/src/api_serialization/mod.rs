@@ -9,13 +9,22 @@ mod call;
 ///
 /// For the case of HDK-rust we can use the exact same types by
 /// importing this module.
+pub mod bundle;
+pub mod capabilities;
+mod commit_encrypted_entry;
+pub mod emit_signal;
 pub mod get_entry;
 pub mod get_links;
 pub mod link_entries;
+pub mod property;
 pub mod query;
+pub mod resolve_conflict;
 pub mod send;
+pub mod sign;
 mod update_entry;
 pub mod validation;
 mod zome_api_globals;
 
-pub use self::{call::*, query::*, update_entry::*, zome_api_globals::*};
+pub use self::{
+    call::*, commit_encrypted_entry::*, query::*, update_entry::*, zome_api_globals::*,
+};
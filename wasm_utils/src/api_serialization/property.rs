@@ -0,0 +1,7 @@
+use holochain_core_types::{error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function property() is invoked
+#[derive(Deserialize, Default, Debug, Serialize, DefaultJson)]
+pub struct PropertyArgs {
+    pub key: String,
+}
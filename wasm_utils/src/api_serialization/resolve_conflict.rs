@@ -0,0 +1,19 @@
+use holochain_core_types::{
+    chain_header::ChainHeader, entry::Entry, error::HolochainError, json::*,
+};
+
+/// One of several concurrent updates of an entry, together with the header it got committed with
+#[derive(Deserialize, Debug, Serialize, DefaultJson, Clone, PartialEq)]
+pub struct ConflictingUpdate {
+    pub entry: Entry,
+    pub header: ChainHeader,
+}
+
+/// Struct for input data of the resolve_conflict callback, which picks the update
+/// that counts as the latest version of an entry that got updated concurrently
+#[derive(Deserialize, Debug, Serialize, DefaultJson, Clone)]
+pub struct ConflictResolutionArgs {
+    pub entry_type: String,
+    pub old_entry: Entry,
+    pub updates: Vec<ConflictingUpdate>,
+}
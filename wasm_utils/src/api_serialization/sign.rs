@@ -0,0 +1,15 @@
+use holochain_core_types::{error::HolochainError, json::*};
+
+/// Struct for input data received when Zome API function sign() is invoked
+#[derive(Deserialize, Default, Debug, Serialize, DefaultJson)]
+pub struct SignArgs {
+    pub payload: String,
+}
+
+/// Struct for input data received when Zome API function verify_signature() is invoked
+#[derive(Deserialize, Default, Debug, Serialize, DefaultJson)]
+pub struct VerifySignatureArgs {
+    pub pub_key: String,
+    pub signature: String,
+    pub payload: String,
+}
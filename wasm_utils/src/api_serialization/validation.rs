@@ -1,4 +1,5 @@
 use holochain_core_types::{
+    agent::AgentId,
     entry::{entry_type::EntryType, Entry},
     error::HolochainError,
     json::*,
@@ -33,3 +34,9 @@ pub struct LinkValidationArgs {
     pub direction: LinkDirection,
     pub validation_data: ValidationData,
 }
+
+#[derive(Deserialize, Debug, Serialize, DefaultJson, Clone)]
+pub struct AgentIdValidationArgs {
+    pub agent_id: AgentId,
+    pub validation_data: ValidationData,
+}